@@ -31,10 +31,11 @@ fn main() {
 
             for (i, obj) in annotation.objects.iter().enumerate() {
                 println!(
-                    "  Object {}: category={}, points={}, properties={}",
+                    "  Object {}: category={}, shape={}, points={}, properties={}",
                     i,
                     obj.category,
-                    obj.polygon.len(),
+                    obj.shape.kind().as_str(),
+                    obj.shape.vertices().len(),
                     obj.properties.len()
                 );
             }
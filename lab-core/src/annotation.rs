@@ -1,12 +1,15 @@
-use crate::geometry::{Point, Polygon};
+use crate::geometry::{MultiPolygon, Point, Polygon};
+use crate::migrate::ANNOTATION_VERSION;
+use crate::orientation::Orientation;
+use crate::shape::Shape;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Annotation data for a single image
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Annotation {
-    /// Format version
+    /// Schema version, see [`crate::migrate`]
     pub version: String,
 
     /// User agent that created this annotation
@@ -18,10 +21,18 @@ pub struct Annotation {
     /// Last modification timestamp
     pub last_modified: DateTime<Utc>,
 
+    /// Size and content hash of the image this annotation was saved for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<ImageInfo>,
+
     /// Region of Interest polygons
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub rois: Vec<Vec<Point>>,
 
+    /// Image-level properties (property_id -> list of values with confidence)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub properties: HashMap<String, Vec<PropertyValueWithConfidence>>,
+
     /// Annotated objects
     pub objects: Vec<Object>,
 }
@@ -31,11 +42,13 @@ impl Annotation {
     pub fn new(user_agent: impl Into<String>) -> Self {
         let now = Utc::now();
         Self {
-            version: "2.0".to_string(),
+            version: ANNOTATION_VERSION.to_string(),
             user_agent: user_agent.into(),
             created_at: now,
             last_modified: now,
+            image: None,
             rois: Vec::new(),
+            properties: HashMap::new(),
             objects: Vec::new(),
         }
     }
@@ -72,6 +85,214 @@ impl Annotation {
         self.last_modified = Utc::now();
     }
 
+    /// Move all coordinates into the upright frame of an image with `orientation`
+    ///
+    /// The current frame is the one recorded in [`Annotation::image`], or the
+    /// raw pixels if none is recorded; [`Orientation::Normal`] converts to
+    /// raw coordinates. Returns whether anything moved.
+    pub fn reorient_to(&mut self, orientation: Orientation) -> bool {
+        let recorded = self
+            .image
+            .as_ref()
+            .map(|info| info.orientation)
+            .unwrap_or_default();
+        if recorded == orientation {
+            return false;
+        }
+        recorded.inverse().apply_annotation(self);
+        orientation.apply_annotation(self);
+        if let Some(info) = &mut self.image {
+            info.orientation = orientation;
+        }
+        true
+    }
+
+    /// Set an image-level property value
+    pub fn set_property(&mut self, property_id: i32, value: i32, confidence: f32) {
+        let key = property_id.to_string();
+        self.properties.insert(
+            key,
+            vec![PropertyValueWithConfidence::new(value, confidence)],
+        );
+        self.last_modified = Utc::now();
+    }
+
+    /// Get an image-level property value
+    pub fn get_property(&self, property_id: i32) -> Option<&PropertyValueWithConfidence> {
+        let key = property_id.to_string();
+        self.properties.get(&key).and_then(|values| values.first())
+    }
+
+    /// All values of an image-level property, empty if not set
+    pub fn property_values(&self, property_id: i32) -> &[PropertyValueWithConfidence] {
+        self.properties
+            .get(&property_id.to_string())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Select or deselect one value of a multi-label image-level property
+    ///
+    /// Returns whether the value is selected afterwards.
+    pub fn toggle_property_value(&mut self, property_id: i32, value: i32, confidence: f32) -> bool {
+        let selected = toggle_value(&mut self.properties, property_id, value, confidence);
+        self.last_modified = Utc::now();
+        selected
+    }
+
+    /// Remove an image-level property
+    pub fn remove_property(&mut self, property_id: i32) {
+        let key = property_id.to_string();
+        if self.properties.remove(&key).is_some() {
+            self.last_modified = Utc::now();
+        }
+    }
+
+    /// Stamp provenance of the objects added or changed since `previous`
+    ///
+    /// New objects without provenance are recorded as created manually by
+    /// `user`; objects whose content changed are recorded as modified.
+    pub fn update_provenance(&mut self, previous: &Annotation, user: &str) {
+        for obj in &mut self.objects {
+            match previous.find_object(obj.id) {
+                None if obj.provenance.is_none() => {
+                    obj.provenance = Some(Provenance::created(user, ObjectSource::Manual));
+                }
+                Some(old) if !obj.same_content(old) => obj.mark_modified(user),
+                _ => {}
+            }
+        }
+    }
+
+    /// Merge an object with the objects of its category that overlap or touch it
+    ///
+    /// The object takes the combined region and the others are removed;
+    /// their IDs are returned. Only closed shapes take part.
+    pub fn merge_objects(&mut self, id: i32) -> Vec<i32> {
+        let Some(target) = self.find_object(id).filter(|obj| obj.shape.is_closed()) else {
+            return Vec::new();
+        };
+        let category = target.category;
+        let mut region = normalized_region(&target.shape);
+
+        let mut merged = Vec::new();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for obj in &self.objects {
+                if obj.id == id
+                    || obj.category != category
+                    || !obj.shape.is_closed()
+                    || merged.contains(&obj.id)
+                {
+                    continue;
+                }
+                let other = normalized_region(&obj.shape);
+                if !bounds_touch(&region, &other) {
+                    continue;
+                }
+                // Overlapping or touching parts fuse into fewer parts
+                let union = region.union(&other);
+                if union.polygons.len() < region.polygons.len() + other.polygons.len() {
+                    region = union;
+                    merged.push(obj.id);
+                    changed = true;
+                }
+            }
+        }
+        if merged.is_empty() {
+            return merged;
+        }
+
+        let Some(target) = self.find_object_mut(id) else {
+            return Vec::new();
+        };
+        let Some(shape) = target.shape.with_region(region) else {
+            return Vec::new();
+        };
+        target.shape = shape;
+        self.objects.retain(|obj| !merged.contains(&obj.id));
+        self.touch();
+        merged
+    }
+
+    /// Cut the region of an object out of every other object it overlaps
+    ///
+    /// Objects left without area are removed. Returns the IDs of the changed
+    /// and removed objects. Only closed shapes take part.
+    pub fn subtract_object(&mut self, id: i32) -> Vec<i32> {
+        let Some(cutter) = self
+            .find_object(id)
+            .filter(|obj| obj.shape.is_closed())
+            .map(|obj| obj.shape.to_multi_polygon())
+        else {
+            return Vec::new();
+        };
+        self.reshape_objects(|obj| {
+            if obj.id == id {
+                return None;
+            }
+            let region = obj.shape.to_multi_polygon();
+            if !bounds_touch(&region, &cutter) || region.intersection(&cutter).is_empty() {
+                return None;
+            }
+            Some(region.difference(&cutter))
+        })
+    }
+
+    /// Clip one object, or all objects if `id` is None, to the ROIs
+    ///
+    /// Objects entirely outside the ROIs are removed. Returns the IDs of the
+    /// changed and removed objects; nothing changes if there are no ROIs.
+    /// Only closed shapes take part.
+    pub fn clip_to_rois(&mut self, id: Option<i32>) -> Vec<i32> {
+        let rois = MultiPolygon::new(
+            self.rois
+                .iter()
+                .filter(|roi| roi.len() >= 3)
+                .map(|roi| Polygon::new(roi.clone()))
+                .collect(),
+        );
+        if rois.is_empty() {
+            return Vec::new();
+        }
+        self.reshape_objects(|obj| {
+            if id.is_some_and(|id| id != obj.id) {
+                return None;
+            }
+            let region = obj.shape.to_multi_polygon();
+            if region.difference(&rois).is_empty() {
+                return None;
+            }
+            Some(region.intersection(&rois))
+        })
+    }
+
+    /// Replace the region of closed objects for which `reshape` returns one,
+    /// removing those left empty
+    fn reshape_objects<F>(&mut self, mut reshape: F) -> Vec<i32>
+    where
+        F: FnMut(&Object) -> Option<MultiPolygon>,
+    {
+        let mut changed = Vec::new();
+        let mut removed = Vec::new();
+        for obj in self.objects.iter_mut().filter(|obj| obj.shape.is_closed()) {
+            let Some(region) = reshape(obj) else {
+                continue;
+            };
+            match obj.shape.with_region(region) {
+                Some(shape) => obj.shape = shape,
+                None => removed.push(obj.id),
+            }
+            changed.push(obj.id);
+        }
+        if !changed.is_empty() {
+            self.objects.retain(|obj| !removed.contains(&obj.id));
+            self.touch();
+        }
+        changed
+    }
+
     /// Get the next available object ID
     pub fn next_object_id(&self) -> i32 {
         self.objects
@@ -83,8 +304,24 @@ impl Annotation {
     }
 }
 
+/// Region of a shape with overlapping and self-intersecting parts resolved
+fn normalized_region(shape: &Shape) -> MultiPolygon {
+    shape.to_multi_polygon().union(&MultiPolygon::default())
+}
+
+/// Whether the bounding boxes of two regions overlap or touch
+fn bounds_touch(a: &MultiPolygon, b: &MultiPolygon) -> bool {
+    match (a.bounding_box(), b.bounding_box()) {
+        (Some((min_a, max_a)), Some((min_b, max_b))) => {
+            min_a.x <= max_b.x && min_b.x <= max_a.x && min_a.y <= max_b.y && min_b.y <= max_a.y
+        }
+        _ => false,
+    }
+}
+
 /// An annotated object in an image
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "ObjectRecord")]
 pub struct Object {
     /// Unique ID within this annotation
     pub id: i32,
@@ -95,31 +332,83 @@ pub struct Object {
     /// Confidence score (0.0 to 1.0), typically 1.0 for manual annotations
     pub confidence: f32,
 
-    /// Object shape as a polygon
-    pub polygon: Vec<Point>,
+    /// Object shape
+    pub shape: Shape,
 
     /// Object properties (property_id -> list of values with confidence)
     #[serde(default)]
     pub properties: HashMap<String, Vec<PropertyValueWithConfidence>>,
+
+    /// Keypoints in the order declared by the category
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keypoints: Vec<Keypoint>,
+
+    /// Who created and changed the object, and its review state
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+}
+
+/// On-disk form of [`Object`], also accepting the legacy `polygon` field
+#[derive(Deserialize)]
+struct ObjectRecord {
+    id: i32,
+    category: i32,
+    confidence: f32,
+    #[serde(default)]
+    shape: Option<Shape>,
+    #[serde(default)]
+    polygon: Option<Vec<Point>>,
+    #[serde(default)]
+    properties: HashMap<String, Vec<PropertyValueWithConfidence>>,
+    #[serde(default)]
+    keypoints: Vec<Keypoint>,
+    #[serde(default)]
+    provenance: Option<Provenance>,
+}
+
+impl From<ObjectRecord> for Object {
+    fn from(record: ObjectRecord) -> Self {
+        let shape = record
+            .shape
+            .unwrap_or_else(|| Shape::polygon(record.polygon.unwrap_or_default()));
+        Self {
+            id: record.id,
+            category: record.category,
+            confidence: record.confidence,
+            shape,
+            properties: record.properties,
+            keypoints: record.keypoints,
+            provenance: record.provenance,
+        }
+    }
 }
 
 impl Object {
-    /// Create a new object
+    /// Create a new polygon object
     pub fn new(id: i32, category: i32, polygon: Vec<Point>) -> Self {
+        Self::with_shape(id, category, Shape::polygon(polygon))
+    }
+
+    /// Create a new object with the given shape
+    pub fn with_shape(id: i32, category: i32, shape: Shape) -> Self {
         Self {
             id,
             category,
             confidence: 1.0,
-            polygon,
+            shape,
             properties: HashMap::new(),
+            keypoints: Vec::new(),
+            provenance: None,
         }
     }
 
     /// Set a property value
     pub fn set_property(&mut self, property_id: i32, value: i32, confidence: f32) {
         let key = property_id.to_string();
-        self.properties
-            .insert(key, vec![PropertyValueWithConfidence { value, confidence }]);
+        self.properties.insert(
+            key,
+            vec![PropertyValueWithConfidence::new(value, confidence)],
+        );
     }
 
     /// Get a property value
@@ -128,28 +417,448 @@ impl Object {
         self.properties.get(&key).and_then(|values| values.first())
     }
 
+    /// All values of a property, empty if not set
+    pub fn property_values(&self, property_id: i32) -> &[PropertyValueWithConfidence] {
+        self.properties
+            .get(&property_id.to_string())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Select or deselect one value of a multi-label property
+    ///
+    /// Returns whether the value is selected afterwards.
+    pub fn toggle_property_value(&mut self, property_id: i32, value: i32, confidence: f32) -> bool {
+        toggle_value(&mut self.properties, property_id, value, confidence)
+    }
+
+    /// Set a typed property value
+    pub fn set_property_data(&mut self, property_id: i32, data: PropertyData, confidence: f32) {
+        let key = property_id.to_string();
+        self.properties.insert(
+            key,
+            vec![PropertyValueWithConfidence::with_data(data, confidence)],
+        );
+    }
+
     /// Remove a property
     pub fn remove_property(&mut self, property_id: i32) {
         let key = property_id.to_string();
         self.properties.remove(&key);
     }
 
-    /// Convert polygon to Polygon type
+    /// Set the keypoint at `index`, padding missing ones as not labeled
+    pub fn set_keypoint(&mut self, index: usize, keypoint: Keypoint) {
+        if self.keypoints.len() <= index {
+            self.keypoints.resize(index + 1, Keypoint::default());
+        }
+        self.keypoints[index] = keypoint;
+    }
+
+    /// Index of the first unlabeled keypoint among the first `count`
+    pub fn next_unlabeled_keypoint(&self, count: usize) -> Option<usize> {
+        (0..count).find(|&i| {
+            self.keypoints
+                .get(i)
+                .is_none_or(|kp| !kp.visibility.is_labeled())
+        })
+    }
+
+    /// Number of labeled keypoints
+    pub fn num_labeled_keypoints(&self) -> usize {
+        self.keypoints
+            .iter()
+            .filter(|kp| kp.visibility.is_labeled())
+            .count()
+    }
+
+    /// Convert the object outline to Polygon type
     pub fn as_polygon(&self) -> Polygon {
-        Polygon::new(self.polygon.clone())
+        self.shape.to_polygon()
+    }
+
+    /// Whether two objects are the same apart from their provenance
+    pub fn same_content(&self, other: &Object) -> bool {
+        self.id == other.id
+            && self.category == other.category
+            && self.confidence == other.confidence
+            && self.shape == other.shape
+            && self.properties == other.properties
+            && self.keypoints == other.keypoints
+    }
+
+    /// Record that `user` changed this object, creating provenance if missing
+    pub fn mark_modified(&mut self, user: &str) {
+        match &mut self.provenance {
+            Some(provenance) => provenance.modified(user),
+            None => {
+                let mut provenance = Provenance::default();
+                provenance.modified(user);
+                self.provenance = Some(provenance);
+            }
+        }
+    }
+}
+
+/// Where an object came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObjectSource {
+    /// Drawn by hand
+    #[default]
+    Manual,
+    /// Read from another dataset format
+    Imported,
+    /// Predicted by a model
+    Model,
+}
+
+impl ObjectSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Manual => "manual",
+            Self::Imported => "imported",
+            Self::Model => "model",
+        }
+    }
+}
+
+/// Review state of an object
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewState {
+    #[default]
+    Unreviewed,
+    Approved,
+    Rejected,
+}
+
+impl ReviewState {
+    pub fn all() -> [Self; 3] {
+        [Self::Unreviewed, Self::Approved, Self::Rejected]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unreviewed => "unreviewed",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+        }
+    }
+}
+
+/// Per-object record of who created and changed it, and its review state
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Provenance {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_by: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_at: Option<DateTime<Utc>>,
+
+    #[serde(default)]
+    pub source: ObjectSource,
+
+    #[serde(default)]
+    pub review: ReviewState,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reviewed_by: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reviewed_at: Option<DateTime<Utc>>,
+}
+
+impl Provenance {
+    /// Provenance of an object just created by `user`
+    pub fn created(user: &str, source: ObjectSource) -> Self {
+        Self {
+            created_by: Some(user.to_string()),
+            created_at: Some(Utc::now()),
+            source,
+            ..Self::default()
+        }
+    }
+
+    /// Record a change by `user`; a changed object needs to be reviewed again
+    pub fn modified(&mut self, user: &str) {
+        self.modified_by = Some(user.to_string());
+        self.modified_at = Some(Utc::now());
+        self.review = ReviewState::Unreviewed;
+        self.reviewed_by = None;
+        self.reviewed_at = None;
+    }
+
+    /// Record a review decision by `user`
+    pub fn reviewed(&mut self, review: ReviewState, user: &str) {
+        self.review = review;
+        if review == ReviewState::Unreviewed {
+            self.reviewed_by = None;
+            self.reviewed_at = None;
+        } else {
+            self.reviewed_by = Some(user.to_string());
+            self.reviewed_at = Some(Utc::now());
+        }
+    }
+}
+
+/// Size and content hash of an annotated image
+///
+/// Recorded on save so that exporters need not decode the image and a
+/// replaced image file can be told apart from the one that was annotated.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageInfo {
+    /// Width of the upright image
+    pub width: u32,
+
+    /// Height of the upright image
+    pub height: u32,
+
+    /// EXIF orientation applied to the stored pixels to show them upright
+    ///
+    /// Coordinates are in the upright frame of this orientation; files
+    /// without it were annotated on the raw pixels.
+    #[serde(default, skip_serializing_if = "Orientation::is_normal")]
+    pub orientation: Orientation,
+
+    /// Hex SHA-1 of the image file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha1: Option<String>,
+}
+
+impl ImageInfo {
+    /// Info for an upright image of the given size and file content
+    pub fn new(width: u32, height: u32, content: &[u8]) -> Self {
+        Self {
+            width,
+            height,
+            orientation: Orientation::Normal,
+            sha1: Some(content_hash(content)),
+        }
+    }
+
+    pub fn with_orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    /// Whether the recorded hash, if any, matches `content`
+    pub fn matches_content(&self, content: &[u8]) -> bool {
+        self.sha1
+            .as_ref()
+            .is_none_or(|sha1| sha1.eq_ignore_ascii_case(&content_hash(content)))
+    }
+}
+
+/// Hex SHA-1 of an image file's content
+pub fn content_hash(content: &[u8]) -> String {
+    use sha1::{Digest, Sha1};
+    Sha1::digest(content)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Add `value` to a property's values, or remove it if already present
+fn toggle_value(
+    properties: &mut HashMap<String, Vec<PropertyValueWithConfidence>>,
+    property_id: i32,
+    value: i32,
+    confidence: f32,
+) -> bool {
+    let key = property_id.to_string();
+    let values = properties.entry(key.clone()).or_default();
+    let selected = if let Some(index) = values
+        .iter()
+        .position(|v| v.data.is_none() && v.value == value)
+    {
+        values.remove(index);
+        false
+    } else {
+        values.push(PropertyValueWithConfidence::new(value, confidence));
+        true
+    };
+    if values.is_empty() {
+        properties.remove(&key);
+    }
+    selected
+}
+
+/// Visibility of a keypoint, following the COCO convention
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeypointVisibility {
+    /// Not labeled (COCO v=0)
+    #[default]
+    NotLabeled,
+    /// Labeled but not visible (COCO v=1)
+    Occluded,
+    /// Labeled and visible (COCO v=2)
+    Visible,
+}
+
+impl KeypointVisibility {
+    pub fn all() -> [Self; 3] {
+        [Self::Visible, Self::Occluded, Self::NotLabeled]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NotLabeled => "not_labeled",
+            Self::Occluded => "occluded",
+            Self::Visible => "visible",
+        }
+    }
+
+    pub fn is_labeled(&self) -> bool {
+        *self != Self::NotLabeled
+    }
+
+    /// COCO visibility flag
+    pub fn to_coco(&self) -> u8 {
+        match self {
+            Self::NotLabeled => 0,
+            Self::Occluded => 1,
+            Self::Visible => 2,
+        }
+    }
+
+    /// Parse a COCO visibility flag
+    pub fn from_coco(flag: u8) -> Self {
+        match flag {
+            0 => Self::NotLabeled,
+            1 => Self::Occluded,
+            _ => Self::Visible,
+        }
+    }
+}
+
+/// A keypoint of an object, in normalized coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Keypoint {
+    pub x: f32,
+    pub y: f32,
+    pub visibility: KeypointVisibility,
+}
+
+impl Keypoint {
+    pub fn new(position: Point, visibility: KeypointVisibility) -> Self {
+        Self {
+            x: position.x,
+            y: position.y,
+            visibility,
+        }
+    }
+
+    pub fn position(&self) -> Point {
+        Point::new(self.x, self.y)
     }
 }
 
 /// Property value with confidence score
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "PropertyValueRecord", into = "PropertyValueRecord")]
 pub struct PropertyValueWithConfidence {
-    /// Property value ID
+    /// Property value ID, or a special value ID
+    ///
+    /// Unused when `data` holds a typed value.
     pub value: i32,
 
+    /// Value of a typed (text, numeric or boolean) property
+    pub data: Option<PropertyData>,
+
     /// Confidence score (0.0 to 1.0)
     pub confidence: f32,
 }
 
+/// On-disk form of [`PropertyValueWithConfidence`], omitting the ID of typed values
+#[derive(Clone, Serialize, Deserialize)]
+struct PropertyValueRecord {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    data: Option<PropertyData>,
+    confidence: f32,
+}
+
+impl From<PropertyValueRecord> for PropertyValueWithConfidence {
+    fn from(record: PropertyValueRecord) -> Self {
+        Self {
+            value: record.value.unwrap_or_default(),
+            data: record.data,
+            confidence: record.confidence,
+        }
+    }
+}
+
+impl From<PropertyValueWithConfidence> for PropertyValueRecord {
+    fn from(value: PropertyValueWithConfidence) -> Self {
+        Self {
+            value: value.data.is_none().then_some(value.value),
+            data: value.data,
+            confidence: value.confidence,
+        }
+    }
+}
+
+impl PropertyValueWithConfidence {
+    /// An enumerated or special value
+    pub fn new(value: i32, confidence: f32) -> Self {
+        Self {
+            value,
+            data: None,
+            confidence,
+        }
+    }
+
+    /// A typed value
+    pub fn with_data(data: PropertyData, confidence: f32) -> Self {
+        Self {
+            value: 0,
+            data: Some(data),
+            confidence,
+        }
+    }
+}
+
+/// Value of a typed property
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PropertyData {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl PropertyData {
+    /// Numeric value of integer and float data
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Int(value) => Some(*value as f64),
+            Self::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for PropertyData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bool(value) => write!(f, "{}", value),
+            Self::Int(value) => write!(f, "{}", value),
+            Self::Float(value) => write!(f, "{}", value),
+            Self::Text(value) => write!(f, "{:?}", value),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -158,7 +867,7 @@ mod tests {
     #[test]
     fn test_annotation_creation() {
         let mut annotation = Annotation::new("test-tool");
-        assert_eq!(annotation.version, "2.0");
+        assert_eq!(annotation.version, ANNOTATION_VERSION);
         assert_eq!(annotation.objects.len(), 0);
 
         let obj = Object::new(
@@ -186,6 +895,151 @@ mod tests {
         assert_eq!(prop.confidence, 1.0);
     }
 
+    #[test]
+    fn test_update_provenance() {
+        let mut previous = Annotation::new("test");
+        previous.add_object(Object::new(0, 1, vec![Point::new(0.1, 0.1)]));
+        previous.add_object(Object::new(1, 1, vec![Point::new(0.2, 0.2)]));
+
+        let mut current = previous.clone();
+        current.objects[0].category = 2;
+        current.add_object(Object::new(2, 1, vec![Point::new(0.3, 0.3)]));
+        current.update_provenance(&previous, "alice");
+
+        let modified = current.objects[0].provenance.as_ref().unwrap();
+        assert_eq!(modified.modified_by.as_deref(), Some("alice"));
+        assert!(modified.created_by.is_none());
+        assert!(current.objects[1].provenance.is_none());
+        let created = current.objects[2].provenance.as_ref().unwrap();
+        assert_eq!(created.created_by.as_deref(), Some("alice"));
+        assert_eq!(created.source, ObjectSource::Manual);
+
+        // Review state is reset by later changes
+        let reviewed = current.clone();
+        current.objects[2]
+            .provenance
+            .as_mut()
+            .unwrap()
+            .reviewed(ReviewState::Approved, "bob");
+        current.update_provenance(&reviewed, "alice");
+        assert_eq!(
+            current.objects[2].provenance.as_ref().unwrap().review,
+            ReviewState::Approved
+        );
+        let approved = current.clone();
+        current.objects[2].confidence = 0.5;
+        current.update_provenance(&approved, "carol");
+        let provenance = current.objects[2].provenance.as_ref().unwrap();
+        assert_eq!(provenance.review, ReviewState::Unreviewed);
+        assert_eq!(provenance.modified_by.as_deref(), Some("carol"));
+
+        let yaml = serde_yaml::to_string(&current).unwrap();
+        let parsed: Annotation = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed, current);
+    }
+
+    #[test]
+    fn test_merge_subtract_clip() {
+        let square = |x0: f32, y0: f32, x1: f32, y1: f32| {
+            Shape::from_corners(Point::new(x0, y0), Point::new(x1, y1))
+        };
+        let mut annotation = Annotation::new("test");
+        annotation.add_object(Object::with_shape(0, 1, square(0.1, 0.1, 0.3, 0.3)));
+        annotation.add_object(Object::with_shape(1, 1, square(0.3, 0.1, 0.5, 0.3)));
+        annotation.add_object(Object::with_shape(2, 2, square(0.2, 0.2, 0.4, 0.4)));
+        annotation.add_object(Object::with_shape(3, 1, square(0.7, 0.7, 0.9, 0.9)));
+
+        // Adjacent object of the same category is absorbed
+        assert_eq!(annotation.merge_objects(0), vec![1]);
+        let merged = annotation.find_object(0).unwrap();
+        assert!((merged.shape.area() - 0.08).abs() < 1e-5);
+        assert_eq!(merged.shape.vertices().len(), 4);
+        assert_eq!(annotation.objects.len(), 3);
+
+        // Cutting object 2 out of the merged one leaves a notch
+        assert_eq!(annotation.subtract_object(2), vec![0]);
+        let cut = annotation.find_object(0).unwrap();
+        assert!((cut.shape.area() - 0.06).abs() < 1e-5);
+        assert_eq!(cut.shape.vertices().len(), 8);
+
+        // Without ROIs nothing is clipped
+        assert!(annotation.clip_to_rois(None).is_empty());
+        annotation.rois.push(vec![
+            Point::new(0.0, 0.0),
+            Point::new(0.3, 0.0),
+            Point::new(0.3, 0.6),
+            Point::new(0.0, 0.6),
+        ]);
+        assert_eq!(annotation.clip_to_rois(Some(2)), vec![2]);
+        assert!((annotation.find_object(2).unwrap().shape.area() - 0.02).abs() < 1e-5);
+        assert_eq!(annotation.clip_to_rois(None), vec![0, 3]);
+        assert!((annotation.find_object(0).unwrap().shape.area() - 0.03).abs() < 1e-5);
+        assert!(annotation.find_object(3).is_none());
+    }
+
+    #[test]
+    fn test_multi_label_properties() {
+        let mut obj = Object::new(0, 1, vec![]);
+        assert!(obj.property_values(0).is_empty());
+
+        assert!(obj.toggle_property_value(0, 1, 1.0));
+        assert!(obj.toggle_property_value(0, 2, 0.6));
+        assert_eq!(obj.property_values(0).len(), 2);
+        assert_eq!(obj.get_property(0).unwrap().value, 1);
+        assert_eq!(obj.property_values(0)[1].confidence, 0.6);
+
+        assert!(!obj.toggle_property_value(0, 1, 1.0));
+        assert_eq!(obj.property_values(0).len(), 1);
+        assert!(!obj.toggle_property_value(0, 2, 1.0));
+        assert!(!obj.properties.contains_key("0"));
+    }
+
+    #[test]
+    fn test_typed_properties() {
+        let mut obj = Object::new(0, 1, vec![]);
+        obj.set_property_data(0, PropertyData::Text("AB123".to_string()), 1.0);
+        obj.set_property_data(1, PropertyData::Int(42), 1.0);
+        obj.set_property_data(2, PropertyData::Float(0.25), 0.8);
+        obj.set_property_data(3, PropertyData::Bool(true), 1.0);
+        obj.set_property(4, -1, 1.0);
+
+        let yaml = serde_yaml::to_string(&obj).unwrap();
+        let parsed: Object = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed.properties, obj.properties);
+        assert_eq!(
+            parsed
+                .get_property(1)
+                .unwrap()
+                .data
+                .as_ref()
+                .and_then(|d| d.as_f64()),
+            Some(42.0)
+        );
+        assert_eq!(parsed.get_property(4).unwrap().value, -1);
+
+        // Strings that look like other types stay text
+        let value: PropertyValueWithConfidence =
+            serde_yaml::from_str("{data: '42', confidence: 1.0}").unwrap();
+        assert_eq!(value.data, Some(PropertyData::Text("42".to_string())));
+    }
+
+    #[test]
+    fn test_image_properties() {
+        let mut annotation = Annotation::new("test");
+        let yaml = serde_yaml::to_string(&annotation).unwrap();
+        assert!(!yaml.contains("properties"));
+
+        annotation.set_property(2, 1, 1.0);
+        assert_eq!(annotation.get_property(2).unwrap().value, 1);
+
+        let yaml = serde_yaml::to_string(&annotation).unwrap();
+        let parsed: Annotation = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed.get_property(2), annotation.get_property(2));
+
+        annotation.remove_property(2);
+        assert!(annotation.get_property(2).is_none());
+    }
+
     #[test]
     fn test_next_object_id() {
         let mut annotation = Annotation::new("test");
@@ -238,4 +1092,89 @@ objects: []
         assert_eq!(annotation.rois.len(), 2);
         assert_eq!(annotation.rois[0].len(), 3);
     }
+
+    #[test]
+    fn test_object_keypoints() {
+        let mut obj = Object::new(0, 1, vec![]);
+        assert_eq!(obj.next_unlabeled_keypoint(3), Some(0));
+
+        obj.set_keypoint(
+            1,
+            Keypoint::new(Point::new(0.5, 0.5), KeypointVisibility::Occluded),
+        );
+        assert_eq!(obj.keypoints.len(), 2);
+        assert_eq!(obj.keypoints[0].visibility, KeypointVisibility::NotLabeled);
+        assert_eq!(obj.next_unlabeled_keypoint(3), Some(0));
+        assert_eq!(obj.num_labeled_keypoints(), 1);
+
+        obj.set_keypoint(
+            0,
+            Keypoint::new(Point::new(0.4, 0.4), KeypointVisibility::Visible),
+        );
+        assert_eq!(obj.next_unlabeled_keypoint(3), Some(2));
+        assert_eq!(obj.next_unlabeled_keypoint(2), None);
+
+        let yaml = serde_yaml::to_string(&obj).unwrap();
+        assert!(yaml.contains("visibility: occluded"));
+        let parsed: Object = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed.keypoints, obj.keypoints);
+    }
+
+    #[test]
+    fn test_object_legacy_polygon() {
+        let yaml = r#"
+id: 3
+category: 1
+confidence: 1.0
+polygon:
+  - {x: 0.1, y: 0.1}
+  - {x: 0.2, y: 0.1}
+  - {x: 0.2, y: 0.2}
+"#;
+
+        let obj: Object = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(obj.shape.kind(), crate::shape::ShapeKind::Polygon);
+        assert_eq!(obj.shape.vertices().len(), 3);
+    }
+
+    #[test]
+    fn test_object_shape_roundtrip() {
+        let obj = Object::with_shape(
+            0,
+            1,
+            Shape::Ellipse {
+                center: Point::new(0.5, 0.5),
+                radius_x: 0.2,
+                radius_y: 0.1,
+            },
+        );
+
+        let yaml = serde_yaml::to_string(&obj).unwrap();
+        assert!(!yaml.contains("polygon"));
+        let parsed: Object = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed, obj);
+    }
+
+    #[test]
+    fn test_image_info() {
+        assert_eq!(
+            content_hash(b"abc"),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+
+        let mut annotation = Annotation::new("test");
+        let yaml = serde_yaml::to_string(&annotation).unwrap();
+        assert!(!yaml.contains("image"));
+
+        annotation.image = Some(ImageInfo::new(640, 480, b"abc"));
+        let yaml = serde_yaml::to_string(&annotation).unwrap();
+        let parsed: Annotation = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed, annotation);
+
+        let info = parsed.image.unwrap();
+        assert!(info.matches_content(b"abc"));
+        assert!(!info.matches_content(b"abd"));
+        let unhashed = ImageInfo { sha1: None, ..info };
+        assert!(unhashed.matches_content(b"abd"));
+    }
 }
@@ -0,0 +1,421 @@
+//! Colors used in project metadata
+//!
+//! Accepted spellings:
+//! - hex: `#RGB`, `#RGBA`, `#RRGGBB`, `#RRGGBBAA`
+//! - CSS/X11 names, case-insensitive, ignoring spaces, `_` and `-` (`BROWN`, `light_gray`)
+//! - functional: `rgb(255, 0, 0)`, `rgba(255, 0, 0, 0.5)`, `rgb(100% 0% 0% / 50%)`
+
+use crate::{Error, Result};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// An RGBA color with 8 bits per channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+
+    /// Read from a spelling that could not be parsed, see [`Color::UNREADABLE`]
+    unreadable: bool,
+}
+
+impl Color {
+    /// Gray stand-in for colors that could not be parsed when deserializing
+    ///
+    /// Metadata with a misspelled color still loads; [`crate::Meta::validate`]
+    /// reports where the color was.
+    pub const UNREADABLE: Self = Self {
+        r: 128,
+        g: 128,
+        b: 128,
+        a: 255,
+        unreadable: true,
+    };
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::rgba(r, g, b, 255)
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self {
+            r,
+            g,
+            b,
+            a,
+            unreadable: false,
+        }
+    }
+
+    /// Whether this color stands in for a spelling that could not be parsed
+    pub fn is_unreadable(&self) -> bool {
+        self.unreadable
+    }
+
+    /// Parse any of the accepted spellings
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        let color = if let Some(hex) = text.strip_prefix('#') {
+            parse_hex(hex)
+        } else if let Some(args) = functional_args(text) {
+            parse_rgb_args(args)
+        } else {
+            find_named(text)
+        };
+        color.ok_or_else(|| Error::InvalidData(format!("unknown color {:?}", text)))
+    }
+
+    /// Red, green and blue components as an array
+    pub fn to_rgb(&self) -> [u8; 3] {
+        [self.r, self.g, self.b]
+    }
+
+    /// `#RRGGBB`, or `#RRGGBBAA` for translucent colors
+    pub fn to_hex(&self) -> String {
+        if self.a == 255 {
+            format!("#{:02X}{:02X}{:02X}", self.r, self.g, self.b)
+        } else {
+            format!("#{:02X}{:02X}{:02X}{:02X}", self.r, self.g, self.b, self.a)
+        }
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl FromStr for Color {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+/// Written back in hex form; the original spelling is not kept
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+/// Unknown spellings become [`Color::UNREADABLE`] rather than an error
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Ok(Color::parse(&text).unwrap_or(Color::UNREADABLE))
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize| u8::from_str_radix(hex.get(i..i + 1)?, 16).ok();
+    let pair = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    match hex.len() {
+        3 | 4 => {
+            let short = |i: usize| digit(i).map(|d| d * 17);
+            let a = if hex.len() == 4 { short(3)? } else { 255 };
+            Some(Color::rgba(short(0)?, short(1)?, short(2)?, a))
+        }
+        6 | 8 => {
+            let a = if hex.len() == 8 { pair(6)? } else { 255 };
+            Some(Color::rgba(pair(0)?, pair(2)?, pair(4)?, a))
+        }
+        _ => None,
+    }
+}
+
+/// Arguments of `rgb(...)` or `rgba(...)`
+fn functional_args(text: &str) -> Option<&str> {
+    let lower = text.to_ascii_lowercase();
+    let start = if lower.starts_with("rgba(") {
+        5
+    } else if lower.starts_with("rgb(") {
+        4
+    } else {
+        return None;
+    };
+    text.get(start..)?.strip_suffix(')')
+}
+
+/// `r, g, b[, a]` or `r g b[ / a]`, channels as 0-255 or percentages
+fn parse_rgb_args(args: &str) -> Option<Color> {
+    let (args, slash_alpha) = match args.split_once('/') {
+        Some((channels, alpha)) => (channels, Some(alpha.trim())),
+        None => (args, None),
+    };
+    let parts: Vec<&str> = if args.contains(',') {
+        args.split(',').map(str::trim).collect()
+    } else {
+        args.split_whitespace().collect()
+    };
+    let (channels, alpha) = match (parts.as_slice(), slash_alpha) {
+        ([r, g, b], alpha) => ([*r, *g, *b], alpha),
+        ([r, g, b, a], None) => ([*r, *g, *b], Some(*a)),
+        _ => return None,
+    };
+
+    let channel = |value: &str| -> Option<u8> {
+        let scaled = match value.strip_suffix('%') {
+            Some(percent) => percent.trim().parse::<f32>().ok()? * 2.55,
+            None => value.parse::<f32>().ok()?,
+        };
+        (scaled.is_finite() && (0.0..=255.0).contains(&scaled)).then(|| scaled.round() as u8)
+    };
+    let alpha = match alpha {
+        None => 255,
+        Some(value) => {
+            let fraction = match value.strip_suffix('%') {
+                Some(percent) => percent.trim().parse::<f32>().ok()? / 100.0,
+                None => value.parse::<f32>().ok()?,
+            };
+            if !(0.0..=1.0).contains(&fraction) {
+                return None;
+            }
+            (fraction * 255.0).round() as u8
+        }
+    };
+    Some(Color::rgba(
+        channel(channels[0])?,
+        channel(channels[1])?,
+        channel(channels[2])?,
+        alpha,
+    ))
+}
+
+fn find_named(name: &str) -> Option<Color> {
+    let key: String = name
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '_' | '-'))
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    if key == "transparent" {
+        return Some(Color::rgba(0, 0, 0, 0));
+    }
+    NAMED_COLORS
+        .binary_search_by(|(name, _)| name.cmp(&key.as_str()))
+        .ok()
+        .map(|index| {
+            let rgb = NAMED_COLORS[index].1;
+            Color::rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+        })
+}
+
+/// CSS Color Module Level 4 named colors (which follow X11), sorted by name
+const NAMED_COLORS: &[(&str, u32)] = &[
+    ("aliceblue", 0xF0F8FF),
+    ("antiquewhite", 0xFAEBD7),
+    ("aqua", 0x00FFFF),
+    ("aquamarine", 0x7FFFD4),
+    ("azure", 0xF0FFFF),
+    ("beige", 0xF5F5DC),
+    ("bisque", 0xFFE4C4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xFFEBCD),
+    ("blue", 0x0000FF),
+    ("blueviolet", 0x8A2BE2),
+    ("brown", 0xA52A2A),
+    ("burlywood", 0xDEB887),
+    ("cadetblue", 0x5F9EA0),
+    ("chartreuse", 0x7FFF00),
+    ("chocolate", 0xD2691E),
+    ("coral", 0xFF7F50),
+    ("cornflowerblue", 0x6495ED),
+    ("cornsilk", 0xFFF8DC),
+    ("crimson", 0xDC143C),
+    ("cyan", 0x00FFFF),
+    ("darkblue", 0x00008B),
+    ("darkcyan", 0x008B8B),
+    ("darkgoldenrod", 0xB8860B),
+    ("darkgray", 0xA9A9A9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xA9A9A9),
+    ("darkkhaki", 0xBDB76B),
+    ("darkmagenta", 0x8B008B),
+    ("darkolivegreen", 0x556B2F),
+    ("darkorange", 0xFF8C00),
+    ("darkorchid", 0x9932CC),
+    ("darkred", 0x8B0000),
+    ("darksalmon", 0xE9967A),
+    ("darkseagreen", 0x8FBC8F),
+    ("darkslateblue", 0x483D8B),
+    ("darkslategray", 0x2F4F4F),
+    ("darkslategrey", 0x2F4F4F),
+    ("darkturquoise", 0x00CED1),
+    ("darkviolet", 0x9400D3),
+    ("deeppink", 0xFF1493),
+    ("deepskyblue", 0x00BFFF),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1E90FF),
+    ("firebrick", 0xB22222),
+    ("floralwhite", 0xFFFAF0),
+    ("forestgreen", 0x228B22),
+    ("fuchsia", 0xFF00FF),
+    ("gainsboro", 0xDCDCDC),
+    ("ghostwhite", 0xF8F8FF),
+    ("gold", 0xFFD700),
+    ("goldenrod", 0xDAA520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xADFF2F),
+    ("grey", 0x808080),
+    ("honeydew", 0xF0FFF0),
+    ("hotpink", 0xFF69B4),
+    ("indianred", 0xCD5C5C),
+    ("indigo", 0x4B0082),
+    ("ivory", 0xFFFFF0),
+    ("khaki", 0xF0E68C),
+    ("lavender", 0xE6E6FA),
+    ("lavenderblush", 0xFFF0F5),
+    ("lawngreen", 0x7CFC00),
+    ("lemonchiffon", 0xFFFACD),
+    ("lightblue", 0xADD8E6),
+    ("lightcoral", 0xF08080),
+    ("lightcyan", 0xE0FFFF),
+    ("lightgoldenrodyellow", 0xFAFAD2),
+    ("lightgray", 0xD3D3D3),
+    ("lightgreen", 0x90EE90),
+    ("lightgrey", 0xD3D3D3),
+    ("lightpink", 0xFFB6C1),
+    ("lightsalmon", 0xFFA07A),
+    ("lightseagreen", 0x20B2AA),
+    ("lightskyblue", 0x87CEFA),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xB0C4DE),
+    ("lightyellow", 0xFFFFE0),
+    ("lime", 0x00FF00),
+    ("limegreen", 0x32CD32),
+    ("linen", 0xFAF0E6),
+    ("magenta", 0xFF00FF),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66CDAA),
+    ("mediumblue", 0x0000CD),
+    ("mediumorchid", 0xBA55D3),
+    ("mediumpurple", 0x9370DB),
+    ("mediumseagreen", 0x3CB371),
+    ("mediumslateblue", 0x7B68EE),
+    ("mediumspringgreen", 0x00FA9A),
+    ("mediumturquoise", 0x48D1CC),
+    ("mediumvioletred", 0xC71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xF5FFFA),
+    ("mistyrose", 0xFFE4E1),
+    ("moccasin", 0xFFE4B5),
+    ("navajowhite", 0xFFDEAD),
+    ("navy", 0x000080),
+    ("oldlace", 0xFDF5E6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6B8E23),
+    ("orange", 0xFFA500),
+    ("orangered", 0xFF4500),
+    ("orchid", 0xDA70D6),
+    ("palegoldenrod", 0xEEE8AA),
+    ("palegreen", 0x98FB98),
+    ("paleturquoise", 0xAFEEEE),
+    ("palevioletred", 0xDB7093),
+    ("papayawhip", 0xFFEFD5),
+    ("peachpuff", 0xFFDAB9),
+    ("peru", 0xCD853F),
+    ("pink", 0xFFC0CB),
+    ("plum", 0xDDA0DD),
+    ("powderblue", 0xB0E0E6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xFF0000),
+    ("rosybrown", 0xBC8F8F),
+    ("royalblue", 0x4169E1),
+    ("saddlebrown", 0x8B4513),
+    ("salmon", 0xFA8072),
+    ("sandybrown", 0xF4A460),
+    ("seagreen", 0x2E8B57),
+    ("seashell", 0xFFF5EE),
+    ("sienna", 0xA0522D),
+    ("silver", 0xC0C0C0),
+    ("skyblue", 0x87CEEB),
+    ("slateblue", 0x6A5ACD),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xFFFAFA),
+    ("springgreen", 0x00FF7F),
+    ("steelblue", 0x4682B4),
+    ("tan", 0xD2B48C),
+    ("teal", 0x008080),
+    ("thistle", 0xD8BFD8),
+    ("tomato", 0xFF6347),
+    ("turquoise", 0x40E0D0),
+    ("violet", 0xEE82EE),
+    ("wheat", 0xF5DEB3),
+    ("white", 0xFFFFFF),
+    ("whitesmoke", 0xF5F5F5),
+    ("yellow", 0xFFFF00),
+    ("yellowgreen", 0x9ACD32),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(Color::parse("#800080").unwrap(), Color::rgb(128, 0, 128));
+        assert_eq!(
+            Color::parse("#ff000080").unwrap(),
+            Color::rgba(255, 0, 0, 128)
+        );
+        assert_eq!(Color::parse("#F0F").unwrap(), Color::rgb(255, 0, 255));
+        assert_eq!(
+            Color::parse("#F0F8").unwrap(),
+            Color::rgba(255, 0, 255, 136)
+        );
+        assert_eq!(Color::parse("BROWN").unwrap(), Color::rgb(165, 42, 42));
+        assert_eq!(
+            Color::parse("Light_Gray").unwrap(),
+            Color::rgb(211, 211, 211)
+        );
+        assert_eq!(Color::parse("transparent").unwrap().a, 0);
+        assert_eq!(
+            Color::parse("rgb(255, 128, 0)").unwrap(),
+            Color::rgb(255, 128, 0)
+        );
+        assert_eq!(
+            Color::parse("rgba(0, 0, 255, 0.5)").unwrap(),
+            Color::rgba(0, 0, 255, 128)
+        );
+        assert_eq!(
+            Color::parse("rgb(100% 0% 0% / 50%)").unwrap(),
+            Color::rgba(255, 0, 0, 128)
+        );
+
+        for bad in [
+            "BRWN",
+            "#12345",
+            "#GGGGGG",
+            "rgb(256, 0, 0)",
+            "rgb(1, 2)",
+            "",
+        ] {
+            assert!(Color::parse(bad).is_err(), "{:?} should not parse", bad);
+        }
+        assert!(NAMED_COLORS.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn test_color_serde() {
+        let colors: Vec<Color> = serde_yaml::from_str("[YELLOW, '#00ff0080']").unwrap();
+        assert_eq!(colors[0].to_hex(), "#FFFF00");
+        let yaml = serde_yaml::to_string(&colors).unwrap();
+        assert_eq!(yaml, "- '#FFFF00'\n- '#00FF0080'\n");
+
+        let color = serde_yaml::from_str::<Color>("BRWN").unwrap();
+        assert!(color.is_unreadable());
+        assert!(!colors[0].is_unreadable());
+    }
+}
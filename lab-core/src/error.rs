@@ -1,3 +1,4 @@
+use crate::diagnostic::Diagnostics;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -8,6 +9,9 @@ pub enum Error {
     #[error("YAML serialization error: {0}")]
     Yaml(#[from] serde_yaml::Error),
 
+    #[error("JSON serialization error: {0}")]
+    Json(#[from] serde_json::Error),
+
     #[error("Invalid data: {0}")]
     InvalidData(String),
 
@@ -16,6 +20,15 @@ pub enum Error {
 
     #[error("Export error: {0}")]
     Export(String),
+
+    #[error("Import error: {0}")]
+    Import(String),
+
+    #[error("Unsupported annotation version {found} (newest supported is {supported})")]
+    UnsupportedVersion { found: String, supported: String },
+
+    #[error("Invalid metadata:\n{0}")]
+    InvalidMeta(Diagnostics),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,162 @@
+use crate::export::{image_attributes, Exporter};
+use crate::{Annotation, Meta, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Image classification manifest exporter
+///
+/// Writes the image-level properties of each image as a JSON manifest,
+/// together with the declared properties and their possible values.
+pub struct ClassificationExporter;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ClassificationManifest {
+    properties: Vec<ManifestProperty>,
+    images: Vec<ManifestImage>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ManifestProperty {
+    id: i32,
+    name: String,
+    values: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ManifestImage {
+    file_name: String,
+    width: u32,
+    height: u32,
+    /// Property name -> value name, typed value or list of values
+    labels: BTreeMap<String, serde_json::Value>,
+}
+
+fn manifest_image(
+    image_path: &str,
+    annotation: &Annotation,
+    meta: &Meta,
+    width: u32,
+    height: u32,
+) -> ManifestImage {
+    let file_name = std::path::Path::new(image_path)
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown.jpg")
+        .to_string();
+    ManifestImage {
+        file_name,
+        width,
+        height,
+        labels: image_attributes(annotation, meta),
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string_pretty(value)
+        .map_err(|e| crate::Error::Export(format!("JSON serialization error: {}", e)))
+}
+
+impl Exporter for ClassificationExporter {
+    fn export_annotation(
+        &self,
+        annotation: &Annotation,
+        meta: &Meta,
+        image_path: &str,
+        image_width: u32,
+        image_height: u32,
+    ) -> Result<String> {
+        to_json(&manifest_image(
+            image_path,
+            annotation,
+            meta,
+            image_width,
+            image_height,
+        ))
+    }
+
+    fn export_batch(
+        &self,
+        annotations: &[(String, Annotation, u32, u32)],
+        meta: &Meta,
+    ) -> Result<String> {
+        let properties = meta
+            .image_properties
+            .iter()
+            .map(|prop| ManifestProperty {
+                id: prop.id,
+                name: prop.name.clone(),
+                values: meta
+                    .find_property_type_by_name(&prop.property_type)
+                    .map(|pt| pt.values.iter().map(|v| v.name.clone()).collect())
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        let images = annotations
+            .iter()
+            .map(|(image_path, annotation, width, height)| {
+                manifest_image(image_path, annotation, meta, *width, *height)
+            })
+            .collect();
+
+        to_json(&ClassificationManifest { properties, images })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classification_export() {
+        let meta: Meta = serde_yaml::from_str(
+            r##"
+id: 1
+name: test
+description: test
+shape: {title_style: 1, thickness: 2}
+roi: {color: "#800080"}
+categories: []
+property_types:
+  - id: 0
+    name: weather
+    description: Weather
+    values:
+      - {id: 0, name: sunny, description: Sunny, hotkey: "1", color: YELLOW, sign: S}
+      - {id: 1, name: rainy, description: Rainy, hotkey: "2", color: BLUE, sign: R}
+property_special_values:
+  - {id: -1, name: pending, description: Pending, hotkey: q, color: WHITE, sign: P}
+image_properties:
+  - {id: 0, name: weather, type: weather}
+"##,
+        )
+        .unwrap();
+
+        let mut tagged = Annotation::new("test");
+        tagged.set_property(0, 1, 1.0);
+        let mut pending = Annotation::new("test");
+        pending.set_property(0, -1, 1.0);
+        let annotations = vec![
+            ("images/a.jpg".to_string(), tagged, 640, 480),
+            ("images/b.jpg".to_string(), pending, 640, 480),
+            (
+                "images/c.jpg".to_string(),
+                Annotation::new("test"),
+                640,
+                480,
+            ),
+        ];
+
+        let json = ClassificationExporter
+            .export_batch(&annotations, &meta)
+            .unwrap();
+        let manifest: ClassificationManifest = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(manifest.properties[0].values, vec!["sunny", "rainy"]);
+        assert_eq!(manifest.images.len(), 3);
+        assert_eq!(manifest.images[0].file_name, "a.jpg");
+        assert_eq!(manifest.images[0].labels["weather"], "rainy");
+        assert_eq!(manifest.images[1].labels["weather"], "pending");
+        assert!(manifest.images[2].labels.is_empty());
+    }
+}
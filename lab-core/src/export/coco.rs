@@ -1,7 +1,8 @@
-use crate::export::Exporter;
-use crate::{Annotation, Meta, Result};
+use crate::export::{image_attributes, object_attributes, Exporter};
+use crate::{Annotation, Meta, Result, Shape, ShapeKind};
 use chrono::Datelike;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// COCO JSON format exporter
 pub struct CocoExporter;
@@ -39,6 +40,9 @@ struct CocoImage {
     file_name: String,
     license: i32,
     date_captured: String,
+    /// Image-level properties by property name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    attributes: BTreeMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -46,10 +50,26 @@ struct CocoAnnotation {
     id: i32,
     image_id: i32,
     category_id: i32,
-    segmentation: Vec<Vec<f32>>,
+    segmentation: CocoSegmentation,
     area: f32,
     bbox: Vec<f32>, // [x, y, width, height]
     iscrowd: i32,
+    /// Flattened [x, y, v, ...] in pixels, for categories with keypoints
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    keypoints: Option<Vec<f32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    num_keypoints: Option<usize>,
+    /// Object properties by property name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    attributes: BTreeMap<String, serde_json::Value>,
+}
+
+/// Polygon rings in pixels, or uncompressed RLE for masks
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum CocoSegmentation {
+    Polygons(Vec<Vec<f32>>),
+    Rle { size: [u32; 2], counts: Vec<u32> },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -57,6 +77,11 @@ struct CocoCategory {
     id: i32,
     name: String,
     supercategory: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    keypoints: Vec<String>,
+    /// Skeleton edges with 1-based keypoint indices
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    skeleton: Vec<[usize; 2]>,
 }
 
 impl Exporter for CocoExporter {
@@ -115,47 +140,82 @@ impl Exporter for CocoExporter {
                     .created_at
                     .format("%Y-%m-%d %H:%M:%S")
                     .to_string(),
+                attributes: image_attributes(annotation, meta),
             });
 
             for obj in &annotation.objects {
-                if obj.polygon.is_empty() {
-                    continue;
-                }
+                let keypoint_count = meta
+                    .find_category(obj.category)
+                    .map(|c| c.keypoints.len())
+                    .unwrap_or(0);
 
-                // Calculate bounding box
-                let mut min_x = f32::MAX;
-                let mut min_y = f32::MAX;
-                let mut max_x = f32::MIN;
-                let mut max_y = f32::MIN;
-
-                for point in &obj.polygon {
-                    min_x = min_x.min(point.x);
-                    min_y = min_y.min(point.y);
-                    max_x = max_x.max(point.x);
-                    max_y = max_y.max(point.y);
+                // Points have no extent and cannot be expressed as boxes
+                if obj.shape.kind() == ShapeKind::Point {
+                    continue;
                 }
+                let Some((min, max)) = obj.shape.bounding_box() else {
+                    continue;
+                };
 
                 // Convert to pixel coordinates
-                let x = min_x * *width as f32;
-                let y = min_y * *height as f32;
-                let w = (max_x - min_x) * *width as f32;
-                let h = (max_y - min_y) * *height as f32;
-
-                // Convert polygon to COCO segmentation format
-                let mut segmentation = Vec::new();
-                for point in &obj.polygon {
-                    segmentation.push(point.x * *width as f32);
-                    segmentation.push(point.y * *height as f32);
-                }
+                let x = min.x * *width as f32;
+                let y = min.y * *height as f32;
+                let w = (max.x - min.x) * *width as f32;
+                let h = (max.y - min.y) * *height as f32;
+
+                // Convert the closed outline to COCO segmentation format, one
+                // polygon per part with holes bridged into the outer ring;
+                // masks are exported as RLE at the image size and open
+                // shapes only carry a bounding box
+                let (segmentation, area) = if let Shape::Mask(mask) = &obj.shape {
+                    let mask = mask.resized(*width, *height)?;
+                    let rle = CocoSegmentation::Rle {
+                        size: [mask.height(), mask.width()],
+                        counts: mask.counts().to_vec(),
+                    };
+                    (rle, mask.pixel_count() as f32)
+                } else if obj.shape.is_closed() {
+                    let mut rings = Vec::new();
+                    for points in obj.shape.simple_rings() {
+                        let mut ring = Vec::new();
+                        for point in points {
+                            ring.push(point.x * *width as f32);
+                            ring.push(point.y * *height as f32);
+                        }
+                        rings.push(ring);
+                    }
+                    let area = obj.shape.area() * *width as f32 * *height as f32;
+                    (CocoSegmentation::Polygons(rings), area)
+                } else {
+                    (CocoSegmentation::Polygons(Vec::new()), w * h)
+                };
+
+                let keypoints = (keypoint_count > 0).then(|| {
+                    let mut flat = Vec::with_capacity(keypoint_count * 3);
+                    for index in 0..keypoint_count {
+                        match obj.keypoints.get(index) {
+                            Some(kp) if kp.visibility.is_labeled() => {
+                                flat.push(kp.x * *width as f32);
+                                flat.push(kp.y * *height as f32);
+                                flat.push(kp.visibility.to_coco() as f32);
+                            }
+                            _ => flat.extend([0.0, 0.0, 0.0]),
+                        }
+                    }
+                    flat
+                });
 
                 coco_annotations.push(CocoAnnotation {
                     id: annotation_id,
                     image_id: image_id as i32,
                     category_id: obj.category,
-                    segmentation: vec![segmentation],
-                    area: w * h,
+                    segmentation,
+                    area,
                     bbox: vec![x, y, w, h],
                     iscrowd: 0,
+                    num_keypoints: keypoints.as_ref().map(|_| obj.num_labeled_keypoints()),
+                    keypoints,
+                    attributes: object_attributes(obj, meta),
                 });
 
                 annotation_id += 1;
@@ -169,6 +229,8 @@ impl Exporter for CocoExporter {
                 id: cat.id,
                 name: cat.name.clone(),
                 supercategory: "object".to_string(),
+                keypoints: cat.keypoints.clone(),
+                skeleton: cat.skeleton.iter().map(|[a, b]| [a + 1, b + 1]).collect(),
             })
             .collect();
 
@@ -190,9 +252,10 @@ impl Exporter for CocoExporter {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::annotation::Object;
+    use crate::annotation::{Keypoint, KeypointVisibility, Object};
     use crate::geometry::Point;
-    use crate::meta::{Category, RoiConfig, ShapeConfig};
+    use crate::import::test_meta;
+    use crate::shape::Shape;
 
     #[test]
     fn test_coco_batch_export() {
@@ -200,7 +263,7 @@ mod tests {
 
         let obj = Object::new(
             0,
-            0,
+            1,
             vec![
                 Point::new(0.1, 0.1),
                 Point::new(0.5, 0.1),
@@ -210,30 +273,9 @@ mod tests {
         );
         annotation.add_object(obj);
 
-        let meta = Meta {
-            id: 1,
-            name: "TestDataset".to_string(),
-            description: "Test COCO export".to_string(),
-            shape: ShapeConfig {
-                title_style: 1,
-                thickness: 2,
-                auto_save: true,
-                vertex_radius: 10.0,
-            },
-            roi: RoiConfig {
-                color: "#800080".to_string(),
-            },
-            categories: vec![Category {
-                id: 0,
-                name: "person".to_string(),
-                description: "Person".to_string(),
-                hotkey: "1".to_string(),
-                color: "#FF0000".to_string(),
-                properties: vec![],
-            }],
-            property_types: vec![],
-            property_special_values: vec![],
-        };
+        // No category has keypoints
+        let mut meta = test_meta();
+        meta.categories[0].keypoints.clear();
 
         let batch = vec![("test.jpg".to_string(), annotation, 1000, 1000)];
 
@@ -243,5 +285,151 @@ mod tests {
         assert!(result.contains("\"images\""));
         assert!(result.contains("\"annotations\""));
         assert!(result.contains("\"categories\""));
+        assert!(!result.contains("\"keypoints\""));
+        assert!(!result.contains("\"attributes\""));
+    }
+
+    #[test]
+    fn test_coco_keypoints_export() {
+        let mut annotation = Annotation::new("test");
+        let mut obj = Object::with_shape(
+            0,
+            1,
+            Shape::from_corners(Point::new(0.1, 0.1), Point::new(0.9, 0.9)),
+        );
+        obj.set_keypoint(
+            1,
+            Keypoint::new(Point::new(0.5, 0.25), KeypointVisibility::Visible),
+        );
+        annotation.add_object(obj);
+
+        let mut meta = test_meta();
+        meta.categories[0].skeleton = vec![[0, 1]];
+
+        let batch = vec![("test.jpg".to_string(), annotation, 200, 100)];
+        let result = CocoExporter.export_batch(&batch, &meta).unwrap();
+        let dataset: CocoDataset = serde_json::from_str(&result).unwrap();
+
+        let ann = &dataset.annotations[0];
+        assert_eq!(
+            ann.keypoints.as_deref(),
+            Some(&[0.0, 0.0, 0.0, 100.0, 25.0, 2.0][..])
+        );
+        assert_eq!(ann.num_keypoints, Some(1));
+        assert_eq!(dataset.categories[0].skeleton, vec![[1, 2]]);
+    }
+
+    #[test]
+    fn test_coco_multi_polygon_export() {
+        let square = |min: f32, max: f32| {
+            vec![
+                Point::new(min, min),
+                Point::new(max, min),
+                Point::new(max, max),
+                Point::new(min, max),
+            ]
+        };
+        let mut shape = Shape::polygon(square(0.0, 0.4));
+        assert!(shape.add_hole(square(0.1, 0.2)));
+        assert!(shape.add_part(square(0.6, 0.8)));
+
+        let mut annotation = Annotation::new("test");
+        annotation.add_object(Object::with_shape(0, 2, shape));
+
+        let meta = test_meta();
+
+        let batch = vec![("test.jpg".to_string(), annotation, 100, 100)];
+        let result = CocoExporter.export_batch(&batch, &meta).unwrap();
+        let dataset: CocoDataset = serde_json::from_str(&result).unwrap();
+
+        // One ring per part, the hole bridged into the first one
+        let ann = &dataset.annotations[0];
+        let CocoSegmentation::Polygons(rings) = &ann.segmentation else {
+            panic!("expected polygon segmentation");
+        };
+        assert_eq!(rings.len(), 2);
+        assert_eq!(rings[0].len(), (4 + 4 + 2) * 2);
+        assert!((ann.area - 1900.0).abs() < 1e-2);
+        assert!((ann.bbox[2] - 80.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_coco_mask_export() {
+        let mut mask = crate::Mask::new(10, 10).unwrap();
+        mask.paint_stroke(Point::new(0.3, 0.3), Point::new(0.3, 0.3), 1.0, true);
+
+        let mut annotation = Annotation::new("test");
+        annotation.add_object(Object::with_shape(0, 2, Shape::Mask(mask)));
+
+        let meta = test_meta();
+
+        // The mask is resampled to the exported image size
+        let batch = vec![("test.jpg".to_string(), annotation, 20, 20)];
+        let result = CocoExporter.export_batch(&batch, &meta).unwrap();
+        assert!(result.contains("\"size\""));
+        let dataset: CocoDataset = serde_json::from_str(&result).unwrap();
+
+        let ann = &dataset.annotations[0];
+        let CocoSegmentation::Rle { size, counts } = &ann.segmentation else {
+            panic!("expected RLE segmentation");
+        };
+        assert_eq!(*size, [20, 20]);
+        assert_eq!(counts.iter().sum::<u32>(), 400);
+        assert_eq!(ann.area, 16.0);
+        assert_eq!(ann.bbox, vec![4.0, 4.0, 4.0, 4.0]);
+    }
+
+    #[test]
+    fn test_coco_attributes_export() {
+        let meta: Meta = serde_yaml::from_str(
+            r##"
+id: 1
+name: test
+description: test
+shape: {title_style: 1, thickness: 2}
+roi: {color: "#800080"}
+categories:
+  - id: 0
+    name: car
+    description: Car
+    hotkey: "1"
+    color: "#FF0000"
+    properties:
+      - {id: 0, name: plate, type: plate}
+      - {id: 1, name: occlusion, type: occlusion}
+      - {id: 2, name: color, type: color}
+property_types:
+  - {id: 0, name: plate, description: Plate, kind: string}
+  - {id: 1, name: occlusion, description: Occlusion, kind: float, min: 0.0, max: 1.0}
+  - id: 2
+    name: color
+    description: Color
+    values:
+      - {id: 0, name: red, description: Red, hotkey: "1", color: RED, sign: R}
+property_special_values: []
+"##,
+        )
+        .unwrap();
+
+        let mut annotation = Annotation::new("test");
+        let mut obj = Object::with_shape(
+            0,
+            0,
+            Shape::from_corners(Point::new(0.1, 0.1), Point::new(0.5, 0.5)),
+        );
+        obj.set_property_data(0, crate::PropertyData::Text("AB123".to_string()), 1.0);
+        obj.set_property_data(1, crate::PropertyData::Float(0.25), 1.0);
+        obj.set_property(2, 0, 1.0);
+        annotation.add_object(obj);
+
+        let batch = vec![("test.jpg".to_string(), annotation, 100, 100)];
+        let json = CocoExporter.export_batch(&batch, &meta).unwrap();
+        let dataset: CocoDataset = serde_json::from_str(&json).unwrap();
+
+        let attributes = &dataset.annotations[0].attributes;
+        assert_eq!(attributes["plate"], "AB123");
+        assert_eq!(attributes["occlusion"], 0.25);
+        assert_eq!(attributes["color"], "red");
+        assert!(dataset.images[0].attributes.is_empty());
     }
 }
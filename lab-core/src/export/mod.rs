@@ -1,6 +1,10 @@
-use crate::{Annotation, Error, Meta, Result};
+use crate::meta::CategoryProperty;
+use crate::{Annotation, Error, Meta, Object, PropertyValueWithConfidence, Result};
+use std::collections::{BTreeMap, HashMap};
 
+pub mod classification;
 pub mod coco;
+pub mod segmentation;
 pub mod voc;
 pub mod yolo;
 
@@ -11,8 +15,14 @@ pub enum ExportFormat {
     Voc,
     /// YOLO TXT format
     Yolo,
+    /// YOLO segmentation TXT format (polygons)
+    YoloSeg,
+    /// YOLO oriented bounding box TXT format
+    YoloObb,
     /// COCO JSON format
     Coco,
+    /// Image-level classification manifest (JSON)
+    Classification,
 }
 
 /// Trait for exporting annotations to different formats
@@ -39,3 +49,55 @@ pub trait Exporter {
         ))
     }
 }
+
+/// Property values by property name, for exporting as attributes
+///
+/// Enumerated and special values are exported by name and typed values as
+/// JSON scalars; a property holding several values becomes an array.
+/// Properties not listed in `declared` are skipped.
+pub fn property_attributes(
+    properties: &HashMap<String, Vec<PropertyValueWithConfidence>>,
+    declared: &[CategoryProperty],
+    meta: &Meta,
+) -> BTreeMap<String, serde_json::Value> {
+    let mut attributes = BTreeMap::new();
+    for prop in declared {
+        let Some(values) = properties.get(&prop.id.to_string()) else {
+            continue;
+        };
+        let mut items: Vec<serde_json::Value> = values
+            .iter()
+            .filter_map(|v| match &v.data {
+                Some(data) => serde_json::to_value(data).ok(),
+                None => meta
+                    .property_value_name(prop, v.value)
+                    .map(|name| name.to_string().into()),
+            })
+            .collect();
+        let value = match items.len() {
+            0 => continue,
+            1 => items.remove(0),
+            _ => items.into(),
+        };
+        attributes.insert(prop.name.clone(), value);
+    }
+    attributes
+}
+
+/// Image-level property values by property name
+pub(crate) fn image_attributes(
+    annotation: &Annotation,
+    meta: &Meta,
+) -> BTreeMap<String, serde_json::Value> {
+    property_attributes(&annotation.properties, &meta.image_properties, meta)
+}
+
+/// Property values of an object by property name
+pub(crate) fn object_attributes(
+    object: &Object,
+    meta: &Meta,
+) -> BTreeMap<String, serde_json::Value> {
+    meta.find_category(object.category)
+        .map(|category| property_attributes(&object.properties, &category.properties, meta))
+        .unwrap_or_default()
+}
@@ -0,0 +1,341 @@
+use crate::meta::Category;
+use crate::{Annotation, Error, Mask, Meta, Result};
+
+/// Order in which overlapping objects are painted; later objects cover
+/// earlier ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DrawOrder {
+    /// Objects in annotation order
+    #[default]
+    Annotation,
+    /// Larger objects first, so small objects stay visible on top
+    LargestFirst,
+    /// Objects by the order of their categories in `Meta.categories`
+    Category,
+}
+
+/// Settings for rendering annotations into segmentation maps
+///
+/// Categories become classes in the order of `Meta.categories`, numbered
+/// from 0 up while skipping the background and ignore values. With the
+/// defaults that gives classes `1..=254`, as in Pascal VOC where 0 is the
+/// background and 255 marks pixels to ignore. Instances are numbered the
+/// same way.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentationOptions {
+    pub draw_order: DrawOrder,
+
+    /// Categories left out; their pixels stay background
+    pub excluded_categories: Vec<i32>,
+
+    /// Value of pixels covered by no object
+    pub background: u8,
+
+    /// Value of pixels outside every ROI, or None to treat them as
+    /// background; images without ROIs are covered entirely
+    pub ignore: Option<u8>,
+}
+
+impl Default for SegmentationOptions {
+    fn default() -> Self {
+        Self {
+            draw_order: DrawOrder::Annotation,
+            excluded_categories: Vec::new(),
+            background: 0,
+            ignore: Some(255),
+        }
+    }
+}
+
+impl SegmentationOptions {
+    /// Whether a value marks the background or ignored pixels
+    pub fn is_reserved(&self, value: u8) -> bool {
+        value == self.background || self.ignore == Some(value)
+    }
+
+    /// Values left for classes and instances, in order
+    fn label_values(&self) -> Vec<u8> {
+        (0..=255)
+            .filter(|&value| !self.is_reserved(value))
+            .collect()
+    }
+
+    /// Categories rendered as classes, with their values
+    ///
+    /// Fails if more categories are included than an 8-bit map can hold.
+    pub fn classes<'a>(&self, meta: &'a Meta) -> Result<Vec<(u8, &'a Category)>> {
+        let categories: Vec<&Category> = meta
+            .categories
+            .iter()
+            .filter(|category| !self.excluded_categories.contains(&category.id))
+            .collect();
+        let values = self.label_values();
+        if categories.len() > values.len() {
+            return Err(Error::Export(format!(
+                "{} categories do not fit in an 8-bit class map, at most {} can be exported",
+                categories.len(),
+                values.len()
+            )));
+        }
+        Ok(values.into_iter().zip(categories).collect())
+    }
+
+    /// Palette of the class map: category colors, black background and
+    /// white ignored pixels
+    pub fn class_palette(&self, meta: &Meta) -> Result<Vec<[u8; 3]>> {
+        let mut palette = vec![[0, 0, 0]; 256];
+        for (value, category) in self.classes(meta)? {
+            palette[value as usize] = category.color.to_rgb();
+        }
+        if let Some(ignore) = self.ignore {
+            palette[ignore as usize] = [255, 255, 255];
+        }
+        palette[self.background as usize] = [0, 0, 0];
+        Ok(palette)
+    }
+
+    /// Palette of the instance map: the Pascal VOC color map, with a black
+    /// background and white ignored pixels
+    pub fn instance_palette(&self) -> Vec<[u8; 3]> {
+        let mut palette: Vec<[u8; 3]> = (0..=255).map(voc_color).collect();
+        if let Some(ignore) = self.ignore {
+            palette[ignore as usize] = [255, 255, 255];
+        }
+        palette[self.background as usize] = [0, 0, 0];
+        palette
+    }
+}
+
+/// Class and instance maps of an image, one byte per pixel in row order
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentationMaps {
+    pub width: u32,
+    pub height: u32,
+
+    /// Class index of each pixel
+    pub classes: Vec<u8>,
+
+    /// Instance number of each pixel, numbering rendered objects in
+    /// annotation order like classes; background pixels are
+    /// `options.background`
+    pub instances: Vec<u8>,
+}
+
+/// Render the objects of an annotation into class and instance maps
+///
+/// Closed shapes and masks are rasterized at the given pixel size; points
+/// and polylines have no area and are skipped.
+pub fn render_segmentation(
+    annotation: &Annotation,
+    meta: &Meta,
+    width: u32,
+    height: u32,
+    options: &SegmentationOptions,
+) -> Result<SegmentationMaps> {
+    let size = Mask::check_size(width, height)?;
+    let mut maps = SegmentationMaps {
+        width,
+        height,
+        classes: vec![options.background; size],
+        instances: vec![options.background; size],
+    };
+
+    let classes = options.classes(meta)?;
+    let mut objects = Vec::new();
+    for object in &annotation.objects {
+        let Some(&(class, _)) = classes.iter().find(|(_, c)| c.id == object.category) else {
+            continue;
+        };
+        let Some(mask) = object.shape.to_mask(width, height) else {
+            continue;
+        };
+        if mask.is_empty() {
+            continue;
+        }
+        objects.push((class, mask));
+    }
+    let instances = options.label_values();
+    if objects.len() > instances.len() {
+        return Err(Error::Export(format!(
+            "{} objects do not fit in an 8-bit instance map",
+            objects.len()
+        )));
+    }
+
+    let mut order: Vec<usize> = (0..objects.len()).collect();
+    match options.draw_order {
+        DrawOrder::Annotation => {}
+        DrawOrder::LargestFirst => {
+            order.sort_by_key(|&index| std::cmp::Reverse(objects[index].1.pixel_count()))
+        }
+        DrawOrder::Category => order.sort_by_key(|&index| objects[index].0),
+    }
+    for index in order {
+        let (class, mask) = &objects[index];
+        for pixel in pixels(mask) {
+            maps.classes[pixel] = *class;
+            maps.instances[pixel] = instances[index];
+        }
+    }
+
+    if let Some(ignore) = options.ignore {
+        if !annotation.rois.is_empty() {
+            let mut inside = vec![false; size];
+            for roi in &annotation.rois {
+                let mask = Mask::from_rings(std::slice::from_ref(roi), width, height)?;
+                for pixel in pixels(&mask) {
+                    inside[pixel] = true;
+                }
+            }
+            for (pixel, inside) in inside.into_iter().enumerate() {
+                if !inside {
+                    maps.classes[pixel] = ignore;
+                    maps.instances[pixel] = ignore;
+                }
+            }
+        }
+    }
+    Ok(maps)
+}
+
+/// Row-order indices of the set pixels of a mask
+fn pixels(mask: &Mask) -> impl Iterator<Item = usize> + '_ {
+    let width = mask.width() as usize;
+    mask.foreground_runs()
+        .into_iter()
+        .flat_map(move |(x, start, end)| (start..end).map(move |y| y as usize * width + x as usize))
+}
+
+/// Color of an index in the Pascal VOC color map
+fn voc_color(index: u8) -> [u8; 3] {
+    let mut color = [0u8; 3];
+    let mut value = index;
+    for bit in (0..8).rev() {
+        for (channel, c) in color.iter_mut().enumerate() {
+            *c |= ((value >> channel) & 1) << bit;
+        }
+        value >>= 3;
+    }
+    color
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::annotation::Object;
+    use crate::geometry::Point;
+    use crate::Shape;
+
+    fn square(min: f32, max: f32) -> Vec<Point> {
+        vec![
+            Point::new(min, min),
+            Point::new(max, min),
+            Point::new(max, max),
+            Point::new(min, max),
+        ]
+    }
+
+    #[test]
+    fn test_render_segmentation() {
+        let meta = crate::import::test_meta();
+        let mut annotation = Annotation::new("test");
+        annotation.add_object(Object::new(0, 2, square(0.0, 0.5)));
+        annotation.add_object(Object::new(1, 1, square(0.25, 0.75)));
+        annotation.add_object(Object::with_shape(
+            2,
+            1,
+            Shape::Point {
+                position: Point::new(0.9, 0.9),
+            },
+        ));
+
+        let options = SegmentationOptions::default();
+        let maps = render_segmentation(&annotation, &meta, 4, 4, &options).unwrap();
+        // cat (class 1) drawn over dog (class 2) where they overlap
+        assert_eq!(
+            maps.classes,
+            [2, 2, 0, 0, 2, 1, 1, 0, 0, 1, 1, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(maps.instances[0], 1);
+        assert_eq!(maps.instances[5], 2);
+
+        let options = SegmentationOptions {
+            draw_order: DrawOrder::Category,
+            ..Default::default()
+        };
+        let maps = render_segmentation(&annotation, &meta, 4, 4, &options).unwrap();
+        assert_eq!(maps.classes[5], 2);
+
+        // Outside the ROI is ignored
+        annotation.rois.push(square(0.0, 0.5));
+        let maps = render_segmentation(&annotation, &meta, 4, 4, &options).unwrap();
+        assert_eq!(maps.classes[..4], [2, 2, 255, 255]);
+        assert_eq!(maps.instances[15], 255);
+
+        // Classes and instances are numbered around a non-zero background
+        annotation.rois.clear();
+        let options = SegmentationOptions {
+            background: 1,
+            ignore: None,
+            ..Default::default()
+        };
+        let maps = render_segmentation(&annotation, &meta, 4, 4, &options).unwrap();
+        assert_eq!(
+            maps.classes,
+            [2, 2, 1, 1, 2, 0, 0, 1, 1, 0, 0, 1, 1, 1, 1, 1]
+        );
+        assert_eq!((maps.instances[0], maps.instances[5]), (0, 2));
+        assert_eq!(maps.instances[15], 1);
+    }
+
+    #[test]
+    fn test_palettes() {
+        let meta = crate::import::test_meta();
+        let options = SegmentationOptions {
+            excluded_categories: vec![1],
+            ..Default::default()
+        };
+        let palette = options.class_palette(&meta).unwrap();
+        assert_eq!((palette[0], palette[1]), ([0, 0, 0], [0, 255, 0]));
+        assert_eq!(palette[255], [255, 255, 255]);
+
+        let options = SegmentationOptions {
+            background: 1,
+            ignore: Some(0),
+            ..Default::default()
+        };
+        let palette = options.class_palette(&meta).unwrap();
+        assert_eq!(
+            &palette[..4],
+            [[255, 255, 255], [0, 0, 0], [255, 0, 0], [0, 255, 0]]
+        );
+
+        let palette = options.instance_palette();
+        assert_eq!(
+            &palette[..4],
+            [[255, 255, 255], [0, 0, 0], [0, 128, 0], [128, 128, 0]]
+        );
+    }
+
+    #[test]
+    fn test_too_many_classes() {
+        let mut meta = crate::import::test_meta();
+        let category = meta.categories[1].clone();
+        for id in 3..256 {
+            meta.categories.push(crate::meta::Category {
+                id,
+                ..category.clone()
+            });
+        }
+        // 255 categories, one more than fits between 0 and 255
+        let options = SegmentationOptions::default();
+        assert!(options.classes(&meta).is_err());
+
+        let options = SegmentationOptions {
+            ignore: None,
+            ..Default::default()
+        };
+        let classes = options.classes(&meta).unwrap();
+        assert_eq!(classes.last().unwrap().0, 255);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::export::Exporter;
-use crate::{Annotation, Meta, Result};
+use crate::{Annotation, Meta, Result, ShapeKind};
 use std::path::Path;
 
 /// Pascal VOC XML format exporter
@@ -41,22 +41,14 @@ impl Exporter for VocExporter {
         xml.push_str("  <segmented>0</segmented>\n");
 
         for obj in &annotation.objects {
-            if obj.polygon.is_empty() {
+            // Points have no extent and cannot be expressed as boxes
+            if obj.shape.kind() == ShapeKind::Point {
                 continue;
             }
-
-            // Calculate bounding box
-            let mut min_x = f32::MAX;
-            let mut min_y = f32::MAX;
-            let mut max_x = f32::MIN;
-            let mut max_y = f32::MIN;
-
-            for point in &obj.polygon {
-                min_x = min_x.min(point.x);
-                min_y = min_y.min(point.y);
-                max_x = max_x.max(point.x);
-                max_y = max_y.max(point.y);
-            }
+            let Some((min, max)) = obj.shape.bounding_box() else {
+                continue;
+            };
+            let (min_x, min_y, max_x, max_y) = (min.x, min.y, max.x, max.y);
 
             // Convert to pixel coordinates
             let xmin = (min_x * image_width as f32) as i32;
@@ -95,7 +87,7 @@ mod tests {
     use super::*;
     use crate::annotation::Object;
     use crate::geometry::Point;
-    use crate::meta::{Category, RoiConfig, ShapeConfig};
+    use crate::import::test_meta;
 
     #[test]
     fn test_voc_export() {
@@ -103,7 +95,7 @@ mod tests {
 
         let obj = Object::new(
             0,
-            0,
+            1,
             vec![
                 Point::new(0.1, 0.1),
                 Point::new(0.5, 0.1),
@@ -113,30 +105,7 @@ mod tests {
         );
         annotation.add_object(obj);
 
-        let meta = Meta {
-            id: 1,
-            name: "TestDataset".to_string(),
-            description: "test".to_string(),
-            shape: ShapeConfig {
-                title_style: 1,
-                thickness: 2,
-                auto_save: true,
-                vertex_radius: 10.0,
-            },
-            roi: RoiConfig {
-                color: "#800080".to_string(),
-            },
-            categories: vec![Category {
-                id: 0,
-                name: "person".to_string(),
-                description: "Person".to_string(),
-                hotkey: "1".to_string(),
-                color: "#FF0000".to_string(),
-                properties: vec![],
-            }],
-            property_types: vec![],
-            property_special_values: vec![],
-        };
+        let meta = test_meta();
 
         let exporter = VocExporter;
         let result = exporter
@@ -144,7 +113,7 @@ mod tests {
             .unwrap();
 
         assert!(result.contains("<annotation>"));
-        assert!(result.contains("<name>person</name>"));
+        assert!(result.contains("<name>cat</name>"));
         assert!(result.contains("<width>1000</width>"));
         assert!(result.contains("<xmin>100</xmin>"));
     }
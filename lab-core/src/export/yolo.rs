@@ -1,11 +1,108 @@
 use crate::export::Exporter;
-use crate::{Annotation, Meta, Result};
+use crate::geometry::min_area_rect;
+use crate::{Annotation, Meta, Point, Result, Shape, ShapeKind};
+use serde::Serialize;
+
+/// Contiguous YOLO class indices for the categories of a project
+///
+/// YOLO expects classes numbered from 0 without gaps, while category IDs
+/// may be sparse or negative. Classes follow the order of
+/// `Meta.categories`; excluded categories get no class and their objects
+/// are left out of the export.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct YoloClasses {
+    /// Category ID and name of each class, by index
+    classes: Vec<(i32, String)>,
+}
+
+impl YoloClasses {
+    /// Classes for every category of `meta` not listed in `excluded`
+    pub fn new(meta: &Meta, excluded: &[i32]) -> Self {
+        let classes = meta
+            .categories
+            .iter()
+            .filter(|category| !excluded.contains(&category.id))
+            .map(|category| (category.id, category.name.clone()))
+            .collect();
+        Self { classes }
+    }
+
+    /// Classes exported by default: every category of `meta` except
+    /// placeholders with negative IDs, such as `pending`
+    pub fn with_defaults(meta: &Meta) -> Self {
+        let placeholders: Vec<i32> = meta
+            .categories
+            .iter()
+            .filter(|category| category.id < 0)
+            .map(|category| category.id)
+            .collect();
+        Self::new(meta, &placeholders)
+    }
+
+    /// Class index of a category
+    pub fn index(&self, category: i32) -> Option<usize> {
+        self.classes.iter().position(|(id, _)| *id == category)
+    }
+
+    /// Category ID of a class index
+    pub fn category(&self, index: usize) -> Option<i32> {
+        self.classes.get(index).map(|(id, _)| *id)
+    }
+
+    /// Class names, by index
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.classes.iter().map(|(_, name)| name.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.classes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty()
+    }
+
+    /// Contents of `classes.txt`, one name per line
+    pub fn classes_txt(&self) -> String {
+        let mut content = String::new();
+        for name in self.names() {
+            content.push_str(name);
+            content.push('\n');
+        }
+        content
+    }
+
+    /// Contents of an Ultralytics `data.yaml`, with the image folders of
+    /// each split relative to the dataset root
+    pub fn data_yaml(&self, train: &str, val: &str, test: Option<&str>) -> Result<String> {
+        let data = DataYaml {
+            train,
+            val,
+            test,
+            nc: self.len(),
+            names: self.names().collect(),
+        };
+        Ok(serde_yaml::to_string(&data)?)
+    }
+}
+
+#[derive(Serialize)]
+struct DataYaml<'a> {
+    train: &'a str,
+    val: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    test: Option<&'a str>,
+    nc: usize,
+    names: Vec<&'a str>,
+}
 
 /// YOLO format exporter
 ///
 /// YOLO format: <class_id> <x_center> <y_center> <width> <height>
 /// All coordinates are normalized (0.0 to 1.0)
-pub struct YoloExporter;
+pub struct YoloExporter {
+    pub classes: YoloClasses,
+}
 
 impl Exporter for YoloExporter {
     fn export_annotation(
@@ -19,22 +116,17 @@ impl Exporter for YoloExporter {
         let mut lines = Vec::new();
 
         for obj in &annotation.objects {
-            if obj.polygon.is_empty() {
+            // Points have no extent and cannot be expressed as boxes
+            if obj.shape.kind() == ShapeKind::Point {
                 continue;
             }
-
-            // Calculate bounding box from polygon
-            let mut min_x = f32::MAX;
-            let mut min_y = f32::MAX;
-            let mut max_x = f32::MIN;
-            let mut max_y = f32::MIN;
-
-            for point in &obj.polygon {
-                min_x = min_x.min(point.x);
-                min_y = min_y.min(point.y);
-                max_x = max_x.max(point.x);
-                max_y = max_y.max(point.y);
-            }
+            let Some(class) = self.classes.index(obj.category) else {
+                continue;
+            };
+            let Some((min, max)) = obj.shape.bounding_box() else {
+                continue;
+            };
+            let (min_x, min_y, max_x, max_y) = (min.x, min.y, max.x, max.y);
 
             let width = max_x - min_x;
             let height = max_y - min_y;
@@ -44,7 +136,7 @@ impl Exporter for YoloExporter {
             // YOLO format: class_id x_center y_center width height
             lines.push(format!(
                 "{} {:.6} {:.6} {:.6} {:.6}",
-                obj.category, x_center, y_center, width, height
+                class, x_center, y_center, width, height
             ));
         }
 
@@ -52,12 +144,145 @@ impl Exporter for YoloExporter {
     }
 }
 
+/// YOLO segmentation format exporter
+///
+/// YOLO-seg format: <class_id> <x1> <y1> <x2> <y2> ... (normalized polygon)
+/// Each part of a multi-polygon or mask becomes a line of its own, with
+/// holes joined to the outer ring. Points and polylines have no area and
+/// are skipped.
+pub struct YoloSegExporter {
+    pub classes: YoloClasses,
+}
+
+impl Exporter for YoloSegExporter {
+    fn export_annotation(
+        &self,
+        annotation: &Annotation,
+        _meta: &Meta,
+        _image_path: &str,
+        _image_width: u32,
+        _image_height: u32,
+    ) -> Result<String> {
+        let mut lines = Vec::new();
+
+        for obj in &annotation.objects {
+            if matches!(obj.shape.kind(), ShapeKind::Point | ShapeKind::Polyline) {
+                continue;
+            }
+            let Some(class) = self.classes.index(obj.category) else {
+                continue;
+            };
+            for ring in obj.shape.simple_rings() {
+                if ring.len() < 3 {
+                    continue;
+                }
+                lines.push(format_line(class, &ring));
+            }
+        }
+
+        Ok(lines.join("\n"))
+    }
+}
+
+/// YOLO oriented bounding box format exporter
+///
+/// YOLO-OBB format: <class_id> <x1> <y1> <x2> <y2> <x3> <y3> <x4> <y4>
+/// The corners are those of the smallest rotated rectangle around the
+/// shape, found in pixels so that it stays a rectangle in the image.
+pub struct YoloObbExporter {
+    pub classes: YoloClasses,
+}
+
+impl Exporter for YoloObbExporter {
+    fn export_annotation(
+        &self,
+        annotation: &Annotation,
+        _meta: &Meta,
+        _image_path: &str,
+        image_width: u32,
+        image_height: u32,
+    ) -> Result<String> {
+        let mut lines = Vec::new();
+
+        for obj in &annotation.objects {
+            if obj.shape.kind() == ShapeKind::Point {
+                continue;
+            }
+            let Some(class) = self.classes.index(obj.category) else {
+                continue;
+            };
+            let corners = match &obj.shape {
+                Shape::RotatedBox { corners } => Some(*corners),
+                shape => rotated_bounds(shape, image_width, image_height),
+            };
+            if let Some(corners) = corners {
+                lines.push(format_line(class, &corners));
+            }
+        }
+
+        Ok(lines.join("\n"))
+    }
+}
+
+/// Smallest rotated rectangle around a shape, measured in pixels
+fn rotated_bounds(shape: &Shape, image_width: u32, image_height: u32) -> Option<[Point; 4]> {
+    let (width, height) = (image_width.max(1) as f32, image_height.max(1) as f32);
+    let pixels: Vec<Point> = shape
+        .rings()
+        .iter()
+        .flatten()
+        .map(|p| Point::new(p.x * width, p.y * height))
+        .collect();
+    let corners = min_area_rect(&pixels)?;
+    Some(corners.map(|p| Point::new(p.x / width, p.y / height)))
+}
+
+/// Class index followed by normalized coordinates
+fn format_line(class: usize, points: &[Point]) -> String {
+    let mut line = class.to_string();
+    for p in points {
+        line.push_str(&format!(" {:.6} {:.6}", p.x, p.y));
+    }
+    line
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::annotation::Object;
     use crate::geometry::Point;
 
+    #[test]
+    fn test_yolo_classes() {
+        let meta = crate::import::test_meta();
+        let classes = YoloClasses::new(&meta, &[]);
+        assert_eq!((classes.index(2), classes.category(0)), (Some(1), Some(1)));
+        assert_eq!(classes.index(-1), None);
+        assert_eq!(classes.classes_txt(), "cat\ndog\n");
+        let data = classes.data_yaml("images", "images", None).unwrap();
+        let data: serde_yaml::Value = serde_yaml::from_str(&data).unwrap();
+        assert_eq!(data["nc"], 2);
+        assert_eq!(data["names"][1], "dog");
+
+        let classes = YoloClasses::new(&meta, &[1]);
+        assert_eq!((classes.len(), classes.index(2)), (1, Some(0)));
+
+        // Placeholders are left out unless asked for
+        let mut meta = meta;
+        meta.categories.insert(
+            0,
+            crate::meta::Category {
+                id: -1,
+                name: "pending".to_string(),
+                ..meta.categories[0].clone()
+            },
+        );
+        let classes = YoloClasses::with_defaults(&meta);
+        assert_eq!((classes.index(-1), classes.index(1)), (None, Some(0)));
+        let classes = YoloClasses::new(&meta, &[]);
+        assert_eq!(classes.index(-1), Some(0));
+    }
+
     #[test]
     fn test_yolo_export() {
         let mut annotation = Annotation::new("test");
@@ -65,7 +290,7 @@ mod tests {
         // Create a simple polygon object
         let obj = Object::new(
             0,
-            0,
+            2,
             vec![
                 Point::new(0.1, 0.1),
                 Point::new(0.5, 0.1),
@@ -74,31 +299,80 @@ mod tests {
             ],
         );
         annotation.add_object(obj);
+        // Objects of excluded or unknown categories are left out
+        annotation.add_object(Object::new(
+            1,
+            7,
+            vec![
+                Point::new(0.1, 0.1),
+                Point::new(0.5, 0.1),
+                Point::new(0.5, 0.5),
+            ],
+        ));
 
-        let meta = Meta {
-            id: 1,
-            name: "test".to_string(),
-            description: "test".to_string(),
-            shape: crate::meta::ShapeConfig {
-                title_style: 1,
-                thickness: 2,
-                auto_save: true,
-                vertex_radius: 10.0,
-            },
-            roi: crate::meta::RoiConfig {
-                color: "#800080".to_string(),
-            },
-            categories: vec![],
-            property_types: vec![],
-            property_special_values: vec![],
+        let meta = crate::import::test_meta();
+        let exporter = YoloExporter {
+            classes: YoloClasses::new(&meta, &[]),
         };
+        let result = exporter
+            .export_annotation(&annotation, &meta, "test.jpg", 1920, 1080)
+            .unwrap();
 
-        let exporter = YoloExporter;
+        // Expected: class_id=1, x_center=0.3, y_center=0.3, width=0.4, height=0.4
+        assert_eq!(result, "1 0.300000 0.300000 0.400000 0.400000");
+
+        annotation.add_object(Object::with_shape(
+            2,
+            2,
+            Shape::Point {
+                position: Point::new(0.5, 0.5),
+            },
+        ));
+        let exporter = YoloSegExporter {
+            classes: YoloClasses::new(&meta, &[1]),
+        };
         let result = exporter
             .export_annotation(&annotation, &meta, "test.jpg", 1920, 1080)
             .unwrap();
+        assert_eq!(
+            result,
+            "0 0.100000 0.100000 0.500000 0.100000 0.500000 0.500000 0.100000 0.500000"
+        );
+    }
+
+    #[test]
+    fn test_yolo_obb_export() {
+        let mut annotation = Annotation::new("test");
+        // A diamond in a 200x100 image: its rotated bounds are itself
+        annotation.add_object(Object::new(
+            0,
+            2,
+            vec![
+                Point::new(0.5, 0.0),
+                Point::new(0.75, 0.5),
+                Point::new(0.5, 1.0),
+                Point::new(0.25, 0.5),
+            ],
+        ));
 
-        // Expected: class_id=0, x_center=0.3, y_center=0.3, width=0.4, height=0.4
-        assert!(result.contains("0 0.300000 0.300000 0.400000 0.400000"));
+        let meta = crate::import::test_meta();
+        let exporter = YoloObbExporter {
+            classes: YoloClasses::new(&meta, &[]),
+        };
+        let result = exporter
+            .export_annotation(&annotation, &meta, "a.jpg", 200, 100)
+            .unwrap();
+        let values: Vec<f32> = result
+            .split_whitespace()
+            .map(|v| v.parse().unwrap())
+            .collect();
+        assert_eq!(values.len(), 9);
+        assert_eq!(values[0], 1.0);
+        let mut corners: Vec<(i32, i32)> = values[1..]
+            .chunks(2)
+            .map(|c| ((c[0] * 100.0).round() as i32, (c[1] * 100.0).round() as i32))
+            .collect();
+        corners.sort();
+        assert_eq!(corners, [(25, 50), (50, 0), (50, 100), (75, 50)]);
     }
 }
@@ -0,0 +1,678 @@
+//! Boolean operations on polygons with holes
+//!
+//! The boundaries of both operands are overlaid: every edge is split where it
+//! meets another edge, a piece is kept if the region on one side of it belongs
+//! to the result and the region on the other side does not, and the kept
+//! pieces are linked into rings with the result on their left. Each operand is
+//! read like [`MultiPolygon::contains`] (even-odd within a part, any part
+//! counts), so self-intersecting and overlapping input is accepted.
+
+use super::{MultiPolygon, Point, Polygon};
+use std::collections::HashMap;
+
+/// Set operation combining two regions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BooleanOp {
+    /// Inside either operand
+    Union,
+    /// Inside both operands
+    Intersection,
+    /// Inside the first operand but not the second
+    Difference,
+    /// Inside exactly one operand
+    Xor,
+}
+
+impl BooleanOp {
+    fn apply(self, a: bool, b: bool) -> bool {
+        match self {
+            Self::Union => a || b,
+            Self::Intersection => a && b,
+            Self::Difference => a && !b,
+            Self::Xor => a != b,
+        }
+    }
+}
+
+type Vec2 = [f64; 2];
+
+/// Tolerance for merging vertices and snapping them onto edges, relative to
+/// the extent of the input; coordinates are single precision
+const MERGE_TOLERANCE: f64 = 1e-6;
+
+/// Combine two sets of polygons
+pub(crate) fn overlay(a: &[Polygon], b: &[Polygon], op: BooleanOp) -> MultiPolygon {
+    let first = Operand::new(a, 0);
+    let second = Operand::new(b, first.rings().count());
+    let operands = [first, second];
+    let rings: Vec<&[Vec2]> = operands.iter().flat_map(Operand::rings).collect();
+
+    let Some(extent) = extent(rings.iter().copied().flatten().copied()) else {
+        return MultiPolygon::default();
+    };
+    let tolerance = extent * MERGE_TOLERANCE;
+
+    let mut segments: Vec<Segment> = rings
+        .iter()
+        .enumerate()
+        .flat_map(|(ring, points)| {
+            (0..points.len())
+                .map(move |i| Segment::new(ring, [points[i], points[(i + 1) % points.len()]]))
+        })
+        .collect();
+    split_segments(&mut segments, tolerance);
+
+    let mut vertices = VertexSet::new(tolerance);
+    let mut pieces: Vec<Piece> = Vec::new();
+    let mut lookup = HashMap::new();
+    for segment in &segments {
+        for [from, to] in segment.pieces() {
+            let (from, to) = (vertices.insert(from), vertices.insert(to));
+            if from == to {
+                continue;
+            }
+            let index = *lookup
+                .entry((from.min(to), from.max(to)))
+                .or_insert_with(|| {
+                    pieces.push(Piece {
+                        from,
+                        to,
+                        rings: Vec::new(),
+                    });
+                    pieces.len() - 1
+                });
+            pieces[index].rings.push(segment.ring);
+        }
+    }
+    let overlay = Overlay {
+        points: vertices.points,
+        pieces,
+        ring_count: rings.len(),
+    };
+
+    // Keep pieces separating the result from the rest, result on the left
+    let inside = |p: Vec2| {
+        op.apply(
+            operands[0].contains(&overlay, p),
+            operands[1].contains(&overlay, p),
+        )
+    };
+    let mut edges = Vec::new();
+    for (index, piece) in overlay.pieces.iter().enumerate() {
+        let [p, q] = overlay.segment(piece);
+        let Some(normal) = left_normal(p, q) else {
+            continue;
+        };
+        let mid = midpoint(p, q);
+        // Stay closer to the piece than to any other piece
+        let offset = clearance(
+            mid,
+            overlay
+                .pieces
+                .iter()
+                .enumerate()
+                .filter(|&(other, _)| other != index)
+                .map(|(_, other)| overlay.segment(other)),
+        )
+        .min(norm(sub(q, p)))
+            / 2.0;
+        let left = inside([mid[0] + normal[0] * offset, mid[1] + normal[1] * offset]);
+        let right = inside([mid[0] - normal[0] * offset, mid[1] - normal[1] * offset]);
+        match (left, right) {
+            (true, false) => edges.push((piece.from, piece.to)),
+            (false, true) => edges.push((piece.to, piece.from)),
+            _ => {}
+        }
+    }
+
+    let rings = link_rings(&edges, &overlay.points);
+    assemble(rings, tolerance)
+}
+
+/// Rings of one operand, grouped by part
+struct Operand {
+    parts: Vec<Vec<Vec<Vec2>>>,
+    /// Index of the first ring among the rings of both operands
+    first_ring: usize,
+}
+
+impl Operand {
+    fn new(polygons: &[Polygon], first_ring: usize) -> Self {
+        let parts = polygons
+            .iter()
+            .map(|polygon| {
+                polygon
+                    .rings()
+                    .map(|ring| clean_ring(ring.iter().map(|p| [p.x as f64, p.y as f64])))
+                    .filter(|ring| ring.len() >= 3)
+                    .collect::<Vec<_>>()
+            })
+            .filter(|rings| !rings.is_empty())
+            .collect();
+        Self { parts, first_ring }
+    }
+
+    fn rings(&self) -> impl Iterator<Item = &[Vec2]> {
+        self.parts.iter().flatten().map(Vec::as_slice)
+    }
+
+    /// Inside the outer ring and outside the holes of any part, measured on the
+    /// split and merged pieces so that the test agrees with the overlay
+    fn contains(&self, overlay: &Overlay, point: Vec2) -> bool {
+        let crossings = overlay.crossings(point);
+        let mut ring = self.first_ring;
+        self.parts.iter().any(|rings| {
+            let first = ring;
+            ring += rings.len();
+            crossings[first] && !crossings[first + 1..ring].iter().any(|&inside| inside)
+        })
+    }
+}
+
+/// A split edge shared by one or more input rings
+struct Piece {
+    from: usize,
+    to: usize,
+    /// Rings the piece belongs to, once per occurrence
+    rings: Vec<usize>,
+}
+
+/// The pieces of all input edges over merged vertices
+struct Overlay {
+    points: Vec<Vec2>,
+    pieces: Vec<Piece>,
+    ring_count: usize,
+}
+
+impl Overlay {
+    fn segment(&self, piece: &Piece) -> [Vec2; 2] {
+        [self.points[piece.from], self.points[piece.to]]
+    }
+
+    /// Whether `point` is inside each input ring (even-odd)
+    fn crossings(&self, point: Vec2) -> Vec<bool> {
+        let mut inside = vec![false; self.ring_count];
+        for piece in &self.pieces {
+            let [p, q] = self.segment(piece);
+            if (p[1] > point[1]) != (q[1] > point[1])
+                && point[0] < (q[0] - p[0]) * (point[1] - p[1]) / (q[1] - p[1]) + p[0]
+            {
+                for &ring in &piece.rings {
+                    inside[ring] = !inside[ring];
+                }
+            }
+        }
+        inside
+    }
+}
+
+/// An input edge and the points where other edges meet it
+struct Segment {
+    ring: usize,
+    from: Vec2,
+    to: Vec2,
+    /// (parameter along the edge, point)
+    splits: Vec<(f64, Vec2)>,
+}
+
+impl Segment {
+    fn new(ring: usize, [from, to]: [Vec2; 2]) -> Self {
+        Self {
+            ring,
+            from,
+            to,
+            splits: Vec::new(),
+        }
+    }
+
+    fn direction(&self) -> Vec2 {
+        sub(self.to, self.from)
+    }
+
+    /// Parameter of the point on the edge closest to `point`, if within `tolerance`
+    fn locate(&self, point: Vec2, tolerance: f64) -> Option<f64> {
+        let d = self.direction();
+        let t = (dot(sub(point, self.from), d) / dot(d, d)).clamp(0.0, 1.0);
+        let closest = [self.from[0] + t * d[0], self.from[1] + t * d[1]];
+        (norm(sub(point, closest)) <= tolerance).then_some(t)
+    }
+
+    /// The edge cut at its split points
+    fn pieces(&self) -> Vec<[Vec2; 2]> {
+        let mut splits = self.splits.clone();
+        splits.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut points = vec![self.from];
+        points.extend(splits.into_iter().map(|(_, point)| point));
+        points.push(self.to);
+        points.windows(2).map(|w| [w[0], w[1]]).collect()
+    }
+}
+
+/// Record every point where two edges cross, touch or overlap on both edges
+fn split_segments(segments: &mut [Segment], tolerance: f64) {
+    let bounds: Vec<[f64; 4]> = segments
+        .iter()
+        .map(|s| {
+            [
+                s.from[0].min(s.to[0]) - tolerance,
+                s.from[1].min(s.to[1]) - tolerance,
+                s.from[0].max(s.to[0]) + tolerance,
+                s.from[1].max(s.to[1]) + tolerance,
+            ]
+        })
+        .collect();
+
+    for i in 0..segments.len() {
+        for j in i + 1..segments.len() {
+            let (bi, bj) = (bounds[i], bounds[j]);
+            if bi[0] > bj[2] || bj[0] > bi[2] || bi[1] > bj[3] || bj[1] > bi[3] {
+                continue;
+            }
+            for (on_i, t, point) in intersections(&segments[i], &segments[j], tolerance) {
+                let target = if on_i { i } else { j };
+                segments[target].splits.push((t, point));
+            }
+        }
+    }
+}
+
+/// Points to split `s1` (true) or `s2` (false) at, with their parameter
+///
+/// An endpoint lying on the other edge (within `tolerance`) splits that edge at
+/// the endpoint itself, which also covers collinear overlaps; otherwise a
+/// proper crossing splits both edges at the computed point.
+fn intersections(s1: &Segment, s2: &Segment, tolerance: f64) -> Vec<(bool, f64, Vec2)> {
+    let (d1, d2) = (s1.direction(), s2.direction());
+    let (len1, len2) = (norm(d1), norm(d2));
+    if len1 <= tolerance || len2 <= tolerance {
+        return Vec::new();
+    }
+
+    let mut splits = Vec::new();
+    let mut touching = false;
+    for (on_first, segment, others) in [(true, s1, [s2.from, s2.to]), (false, s2, [s1.from, s1.to])]
+    {
+        for point in others {
+            let Some(t) = segment.locate(point, tolerance) else {
+                continue;
+            };
+            touching = true;
+            let eps = tolerance / norm(segment.direction());
+            if t > eps && t < 1.0 - eps {
+                splits.push((on_first, t, point));
+            }
+        }
+    }
+    if touching {
+        return splits;
+    }
+
+    let denom = cross(d1, d2);
+    if denom == 0.0 {
+        return splits;
+    }
+    let offset = sub(s2.from, s1.from);
+    let t = cross(offset, d2) / denom;
+    let u = cross(offset, d1) / denom;
+    if t > 0.0 && t < 1.0 && u > 0.0 && u < 1.0 {
+        let point = [s1.from[0] + t * d1[0], s1.from[1] + t * d1[1]];
+        splits.push((true, t, point));
+        splits.push((false, u, point));
+    }
+    splits
+}
+
+/// Vertices merged within a tolerance, looked up through a grid
+struct VertexSet {
+    points: Vec<Vec2>,
+    grid: HashMap<(i64, i64), Vec<usize>>,
+    tolerance: f64,
+}
+
+impl VertexSet {
+    fn new(tolerance: f64) -> Self {
+        Self {
+            points: Vec::new(),
+            grid: HashMap::new(),
+            tolerance,
+        }
+    }
+
+    fn cell(&self, point: Vec2) -> (i64, i64) {
+        let size = self.tolerance * 4.0;
+        (
+            (point[0] / size).floor() as i64,
+            (point[1] / size).floor() as i64,
+        )
+    }
+
+    fn insert(&mut self, point: Vec2) -> usize {
+        let (cx, cy) = self.cell(point);
+        for x in cx - 1..=cx + 1 {
+            for y in cy - 1..=cy + 1 {
+                let Some(ids) = self.grid.get(&(x, y)) else {
+                    continue;
+                };
+                if let Some(&id) = ids
+                    .iter()
+                    .find(|&&id| norm(sub(self.points[id], point)) <= self.tolerance)
+                {
+                    return id;
+                }
+            }
+        }
+        let id = self.points.len();
+        self.points.push(point);
+        self.grid.entry((cx, cy)).or_default().push(id);
+        id
+    }
+}
+
+/// Link directed edges into closed rings
+///
+/// Where several rings touch at a vertex, the sharpest left turn is taken so
+/// each ring bounds a single face and touching rings stay separate.
+fn link_rings(edges: &[(usize, usize)], points: &[Vec2]) -> Vec<Vec<Vec2>> {
+    let mut outgoing: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (index, &(from, _)) in edges.iter().enumerate() {
+        outgoing.entry(from).or_default().push(index);
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut rings = Vec::new();
+    for start in 0..edges.len() {
+        if used[start] {
+            continue;
+        }
+        let mut ring = Vec::new();
+        let mut current = start;
+        loop {
+            used[current] = true;
+            let (from, to) = edges[current];
+            ring.push(points[from]);
+
+            let incoming = sub(points[to], points[from]);
+            let next = outgoing
+                .get(&to)
+                .into_iter()
+                .flatten()
+                .copied()
+                .filter(|&e| !used[e] || e == start)
+                .max_by(|&x, &y| {
+                    let turn = |e: usize| {
+                        let (f, t) = edges[e];
+                        let out = sub(points[t], points[f]);
+                        cross(incoming, out).atan2(dot(incoming, out))
+                    };
+                    turn(x).total_cmp(&turn(y))
+                });
+            match next {
+                Some(next) if next != start => current = next,
+                _ => break,
+            }
+        }
+        let ring = clean_ring(ring);
+        if ring.len() >= 3 {
+            rings.push(ring);
+        }
+    }
+    rings
+}
+
+/// Sort rings into outer boundaries (result on the left, positive area) and
+/// holes, each hole going to the smallest outer ring around it
+fn assemble(rings: Vec<Vec<Vec2>>, tolerance: f64) -> MultiPolygon {
+    let min_area = tolerance * tolerance;
+    let edges: Vec<[Vec2; 2]> = rings
+        .iter()
+        .flat_map(|ring| (0..ring.len()).map(|i| [ring[i], ring[(i + 1) % ring.len()]]))
+        .collect();
+
+    let mut outers: Vec<(f64, Vec<Vec2>, Vec<Vec<Vec2>>)> = Vec::new();
+    let mut holes = Vec::new();
+    for ring in rings {
+        let ring_area = signed_area(&ring);
+        if ring_area > min_area {
+            outers.push((ring_area, ring, Vec::new()));
+        } else if ring_area < -min_area {
+            holes.push(ring);
+        }
+    }
+    // Largest parts first
+    outers.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    for hole in holes {
+        // A probe just left of a hole edge lies in the surrounding region
+        let (p, q) = (hole[0], hole[1]);
+        let Some(normal) = left_normal(p, q) else {
+            continue;
+        };
+        let mid = midpoint(p, q);
+        let offset = clearance(mid, edges.iter().copied().filter(|&e| e != [p, q]))
+            .min(norm(sub(q, p)))
+            / 2.0;
+        let probe = [mid[0] + normal[0] * offset, mid[1] + normal[1] * offset];
+        if let Some(outer) = outers
+            .iter_mut()
+            .filter(|(_, ring, _)| ring_contains(ring, probe))
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+        {
+            outer.2.push(hole);
+        }
+    }
+
+    let to_points = |ring: Vec<Vec2>| ring.into_iter().map(to_point).collect::<Vec<_>>();
+    let polygons = outers
+        .into_iter()
+        .map(|(_, ring, holes)| {
+            Polygon::with_holes(to_points(ring), holes.into_iter().map(to_points).collect())
+        })
+        .filter(Polygon::is_valid)
+        .collect();
+    MultiPolygon::new(polygons)
+}
+
+/// Drop repeated, closing and collinear vertices
+fn clean_ring(points: impl IntoIterator<Item = Vec2>) -> Vec<Vec2> {
+    let mut ring: Vec<Vec2> = Vec::new();
+    for point in points {
+        if ring.last() != Some(&point) {
+            ring.push(point);
+        }
+    }
+    while ring.len() > 1 && ring.first() == ring.last() {
+        ring.pop();
+    }
+
+    let mut changed = true;
+    while changed && ring.len() >= 3 {
+        changed = false;
+        let n = ring.len();
+        for i in 0..n {
+            let (prev, point, next) = (ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]);
+            let (d1, d2) = (sub(point, prev), sub(next, point));
+            let scale = norm(d1) * norm(d2);
+            if scale == 0.0 || (cross(d1, d2).abs() <= 1e-12 * scale && dot(d1, d2) > 0.0) {
+                ring.remove(i);
+                changed = true;
+                break;
+            }
+        }
+    }
+    ring
+}
+
+fn extent(points: impl Iterator<Item = Vec2>) -> Option<f64> {
+    let mut bounds: Option<[f64; 4]> = None;
+    for p in points {
+        let b = bounds.get_or_insert([p[0], p[1], p[0], p[1]]);
+        b[0] = b[0].min(p[0]);
+        b[1] = b[1].min(p[1]);
+        b[2] = b[2].max(p[0]);
+        b[3] = b[3].max(p[1]);
+    }
+    bounds.map(|b| (b[2] - b[0]).max(b[3] - b[1]).max(1e-6))
+}
+
+/// Even-odd point in ring test
+fn ring_contains(ring: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+    let mut j = ring.len() - 1;
+    for i in 0..ring.len() {
+        let (pi, pj) = (ring[i], ring[j]);
+        if (pi[1] > point[1]) != (pj[1] > point[1])
+            && point[0] < (pj[0] - pi[0]) * (point[1] - pi[1]) / (pj[1] - pi[1]) + pi[0]
+        {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+fn signed_area(ring: &[Vec2]) -> f64 {
+    let n = ring.len();
+    (0..n)
+        .map(|i| cross(ring[i], ring[(i + 1) % n]))
+        .sum::<f64>()
+        / 2.0
+}
+
+/// Distance from `point` to the closest of `segments`
+fn clearance(point: Vec2, segments: impl Iterator<Item = [Vec2; 2]>) -> f64 {
+    segments
+        .map(|[p, q]| {
+            let d = sub(q, p);
+            let t = (dot(sub(point, p), d) / dot(d, d)).clamp(0.0, 1.0);
+            norm(sub(point, [p[0] + t * d[0], p[1] + t * d[1]]))
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+fn midpoint(p: Vec2, q: Vec2) -> Vec2 {
+    [(p[0] + q[0]) / 2.0, (p[1] + q[1]) / 2.0]
+}
+
+fn left_normal(p: Vec2, q: Vec2) -> Option<Vec2> {
+    let d = sub(q, p);
+    let length = norm(d);
+    (length > 0.0).then(|| [-d[1] / length, d[0] / length])
+}
+
+fn to_point(p: Vec2) -> Point {
+    Point::new(p[0] as f32, p[1] as f32)
+}
+
+fn sub(a: Vec2, b: Vec2) -> Vec2 {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+fn dot(a: Vec2, b: Vec2) -> f64 {
+    a[0] * b[0] + a[1] * b[1]
+}
+
+fn cross(a: Vec2, b: Vec2) -> f64 {
+    a[0] * b[1] - a[1] * b[0]
+}
+
+fn norm(a: Vec2) -> f64 {
+    dot(a, a).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x0: f32, y0: f32, x1: f32, y1: f32) -> Polygon {
+        Polygon::new(vec![
+            Point::new(x0, y0),
+            Point::new(x1, y0),
+            Point::new(x1, y1),
+            Point::new(x0, y1),
+        ])
+    }
+
+    fn assert_area(result: &MultiPolygon, parts: usize, area: f32) {
+        assert_eq!(result.polygons.len(), parts, "{:?}", result);
+        assert!(
+            (result.area() - area).abs() < 1e-5,
+            "{} != {}",
+            result.area(),
+            area
+        );
+    }
+
+    #[test]
+    fn test_overlapping_squares() {
+        let a = rect(0.0, 0.0, 2.0, 2.0);
+        let b = rect(1.0, 1.0, 3.0, 3.0);
+
+        let union = a.union(&b);
+        assert_area(&union, 1, 7.0);
+        assert_eq!(union.polygons[0].points.len(), 8);
+        let intersection = a.intersection(&b);
+        assert_area(&intersection, 1, 1.0);
+        assert_eq!(intersection.polygons[0].points.len(), 4);
+        assert_area(&a.difference(&b), 1, 3.0);
+        assert_area(&a.xor(&b), 2, 6.0);
+    }
+
+    #[test]
+    fn test_shared_edges_and_containment() {
+        // Adjacent squares merge into one rectangle without a seam
+        let merged = rect(0.0, 0.0, 1.0, 1.0).union(&rect(1.0, 0.0, 2.0, 1.0));
+        assert_area(&merged, 1, 2.0);
+        assert_eq!(merged.polygons[0].points.len(), 4);
+        assert!(rect(0.0, 0.0, 1.0, 1.0)
+            .intersection(&rect(1.0, 0.0, 2.0, 1.0))
+            .is_empty());
+
+        // Cutting out an inner square leaves a hole
+        let outer = rect(0.0, 0.0, 4.0, 4.0);
+        let inner = rect(1.0, 1.0, 2.0, 2.0);
+        let donut = outer.difference(&inner);
+        assert_area(&donut, 1, 15.0);
+        assert_eq!(donut.polygons[0].holes.len(), 1);
+        assert_area(&outer.union(&inner), 1, 16.0);
+        assert!(inner.difference(&outer).is_empty());
+
+        // Identical operands
+        assert_area(&inner.union(&inner), 1, 1.0);
+        assert!(inner.xor(&inner).is_empty());
+
+        // Squares touching at a corner stay separate parts
+        assert_area(
+            &rect(0.0, 0.0, 1.0, 1.0).union(&rect(1.0, 1.0, 2.0, 2.0)),
+            2,
+            2.0,
+        );
+    }
+
+    #[test]
+    fn test_holes_and_multi_polygons() {
+        let donut = Polygon::with_holes(
+            rect(0.0, 0.0, 4.0, 4.0).points,
+            vec![rect(1.0, 1.0, 3.0, 3.0).points],
+        );
+        // A bar across the hole fills part of it
+        let bar = rect(0.0, 1.5, 4.0, 2.5);
+        let union = donut.union(&bar);
+        assert_area(&union, 1, 12.0 + 2.0);
+        assert_eq!(union.polygons[0].holes.len(), 2);
+        assert_area(&donut.intersection(&bar), 2, 2.0);
+
+        let parts = MultiPolygon::new(vec![rect(0.0, 0.0, 1.0, 1.0), rect(2.0, 0.0, 3.0, 1.0)]);
+        let clip = MultiPolygon::new(vec![rect(0.5, 0.0, 2.5, 0.5)]);
+        assert_area(&parts.intersection(&clip), 2, 0.5);
+        assert_area(&parts.difference(&clip), 2, 1.5);
+
+        // Self-intersecting bow tie read with the even-odd rule
+        let bow_tie = Polygon::new(vec![
+            Point::new(0.0, 0.0),
+            Point::new(2.0, 2.0),
+            Point::new(2.0, 0.0),
+            Point::new(0.0, 2.0),
+        ]);
+        assert_area(&bow_tie.union(&Polygon::empty()), 2, 2.0);
+    }
+}
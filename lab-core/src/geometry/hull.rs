@@ -0,0 +1,153 @@
+//! Convex hull and minimum-area enclosing rectangle
+
+use super::Point;
+
+/// Convex hull of a point set (Andrew's monotone chain)
+///
+/// The hull has a positive signed area (counter-clockwise with y up) and no
+/// collinear vertices. Fewer than three distinct points are returned as is.
+pub fn convex_hull(points: &[Point]) -> Vec<Point> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    let turns_left = |hull: &[Point], point: &Point| {
+        cross(&hull[hull.len() - 2], &hull[hull.len() - 1], point) > 0.0
+    };
+    let mut hull: Vec<Point> = Vec::with_capacity(sorted.len() + 1);
+    // Lower chain, then the upper chain back to the start
+    for point in &sorted {
+        while hull.len() >= 2 && !turns_left(&hull, point) {
+            hull.pop();
+        }
+        hull.push(*point);
+    }
+    let lower = hull.len() + 1;
+    for point in sorted.iter().rev().skip(1) {
+        while hull.len() >= lower && !turns_left(&hull, point) {
+            hull.pop();
+        }
+        hull.push(*point);
+    }
+    // The start point closes the upper chain
+    hull.pop();
+    hull
+}
+
+/// Smallest-area rectangle enclosing the points, as four corners in order
+///
+/// One side of the rectangle lies on an edge of the convex hull (rotating
+/// calipers). Returns None for an empty point set.
+pub fn min_area_rect(points: &[Point]) -> Option<[Point; 4]> {
+    let hull = convex_hull(points);
+    let first = *hull.first()?;
+
+    // Axis-aligned if the hull has no edge to align with
+    let mut best = (f32::MAX, [1.0, 0.0]);
+    for i in 0..hull.len() {
+        let (p, q) = (hull[i], hull[(i + 1) % hull.len()]);
+        let length = p.distance_to(&q);
+        if length == 0.0 {
+            continue;
+        }
+        let axis = [(q.x - p.x) / length, (q.y - p.y) / length];
+        let ([min_u, max_u], [min_v, max_v]) = extents(&hull, axis);
+        let area = (max_u - min_u) * (max_v - min_v);
+        if area < best.0 {
+            best = (area, axis);
+        }
+    }
+
+    let axis = best.1;
+    let ([min_u, max_u], [min_v, max_v]) = extents(&hull, axis);
+    let corner = |u: f32, v: f32| {
+        Point::new(
+            first.x + u * axis[0] - v * axis[1],
+            first.y + u * axis[1] + v * axis[0],
+        )
+    };
+    Some([
+        corner(min_u, min_v),
+        corner(max_u, min_v),
+        corner(max_u, max_v),
+        corner(min_u, max_v),
+    ])
+}
+
+/// Ranges of the points along `axis` and its normal, relative to the first point
+fn extents(points: &[Point], axis: [f32; 2]) -> ([f32; 2], [f32; 2]) {
+    let origin = points[0];
+    let mut u_range = [f32::MAX, f32::MIN];
+    let mut v_range = [f32::MAX, f32::MIN];
+    for point in points {
+        let (dx, dy) = (point.x - origin.x, point.y - origin.y);
+        let u = dx * axis[0] + dy * axis[1];
+        let v = dy * axis[0] - dx * axis[1];
+        u_range = [u_range[0].min(u), u_range[1].max(u)];
+        v_range = [v_range[0].min(v), v_range[1].max(v)];
+    }
+    (u_range, v_range)
+}
+
+/// Cross product of o->a and o->b, positive for a left turn
+fn cross(o: &Point, a: &Point, b: &Point) -> f32 {
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Polygon;
+
+    #[test]
+    fn test_convex_hull() {
+        let points = vec![
+            Point::new(0.0, 0.0),
+            Point::new(0.5, 0.5),
+            Point::new(1.0, 0.0),
+            Point::new(1.0, 1.0),
+            Point::new(0.5, 1.0),
+            Point::new(0.0, 1.0),
+            Point::new(1.0, 0.0),
+        ];
+        let hull = convex_hull(&points);
+        assert_eq!(
+            hull,
+            vec![
+                Point::new(0.0, 0.0),
+                Point::new(1.0, 0.0),
+                Point::new(1.0, 1.0),
+                Point::new(0.0, 1.0),
+            ]
+        );
+        assert_eq!(convex_hull(&points[..2]).len(), 2);
+        assert!(convex_hull(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_min_area_rect() {
+        // A diamond is enclosed by its own rotated square
+        let diamond = vec![
+            Point::new(0.5, 0.0),
+            Point::new(1.0, 0.5),
+            Point::new(0.5, 1.0),
+            Point::new(0.0, 0.5),
+        ];
+        let rect = min_area_rect(&diamond).unwrap();
+        assert!((Polygon::new(rect.to_vec()).area() - 0.5).abs() < 1e-5);
+        for corner in diamond {
+            assert!(rect.iter().any(|p| p.distance_to(&corner) < 1e-5));
+        }
+
+        let line = min_area_rect(&[Point::new(0.0, 0.0), Point::new(1.0, 1.0)]).unwrap();
+        assert_eq!(Polygon::new(line.to_vec()).area(), 0.0);
+        assert_eq!(
+            min_area_rect(&[Point::new(0.3, 0.3)]),
+            Some([Point::new(0.3, 0.3); 4])
+        );
+        assert!(min_area_rect(&[]).is_none());
+    }
+}
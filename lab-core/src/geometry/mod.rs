@@ -0,0 +1,589 @@
+//! Points, polygons and the algorithms shared by the editor, exporters and tools
+
+mod boolean;
+mod hull;
+mod repair;
+mod simplify;
+
+pub use boolean::BooleanOp;
+pub use hull::{convex_hull, min_area_rect};
+pub use repair::{fix_self_intersections, has_self_intersections, segments_intersect};
+pub use simplify::{densify, simplify, simplify_indices, smooth};
+
+use serde::{Deserialize, Serialize};
+
+/// A 2D point with normalized coordinates (0.0 to 1.0)
+///
+/// Annotation coordinates refer to the upright image, after its EXIF
+/// orientation is applied; see [`crate::orientation`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Point {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    /// Convert from pixel coordinates to normalized coordinates
+    pub fn from_pixel(x: f32, y: f32, image_width: u32, image_height: u32) -> Self {
+        Self {
+            x: x / image_width as f32,
+            y: y / image_height as f32,
+        }
+    }
+
+    /// Convert to pixel coordinates
+    pub fn to_pixel(&self, image_width: u32, image_height: u32) -> (f32, f32) {
+        (self.x * image_width as f32, self.y * image_height as f32)
+    }
+
+    /// Calculate distance to another point
+    pub fn distance_to(&self, other: &Point) -> f32 {
+        let dx = self.x - other.x;
+        let dy = self.y - other.y;
+        (dx * dx + dy * dy).sqrt()
+    }
+
+    /// Calculate distance to the line segment from `a` to `b`
+    pub fn distance_to_segment(&self, a: &Point, b: &Point) -> f32 {
+        let dx = b.x - a.x;
+        let dy = b.y - a.y;
+        let length_sq = dx * dx + dy * dy;
+        if length_sq == 0.0 {
+            return self.distance_to(a);
+        }
+
+        let t = (((self.x - a.x) * dx + (self.y - a.y) * dy) / length_sq).clamp(0.0, 1.0);
+        self.distance_to(&Point::new(a.x + t * dx, a.y + t * dy))
+    }
+}
+
+/// A polygon defined by an outer ring of points and optional holes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Polygon {
+    pub points: Vec<Point>,
+
+    /// Inner rings cut out of the polygon
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub holes: Vec<Vec<Point>>,
+}
+
+impl Polygon {
+    pub fn new(points: Vec<Point>) -> Self {
+        Self {
+            points,
+            holes: Vec::new(),
+        }
+    }
+
+    /// Create a polygon with holes
+    pub fn with_holes(points: Vec<Point>, holes: Vec<Vec<Point>>) -> Self {
+        Self { points, holes }
+    }
+
+    /// Create an empty polygon
+    pub fn empty() -> Self {
+        Self::new(Vec::new())
+    }
+
+    /// Add a point to the polygon
+    pub fn add_point(&mut self, point: Point) {
+        self.points.push(point);
+    }
+
+    /// Check if the polygon is valid (at least 3 points in every ring)
+    pub fn is_valid(&self) -> bool {
+        self.points.len() >= 3 && self.holes.iter().all(|hole| hole.len() >= 3)
+    }
+
+    /// Calculate the area of the polygon minus its holes (shoelace formula)
+    pub fn area(&self) -> f32 {
+        let holes: f32 = self.holes.iter().map(|hole| ring_area(hole)).sum();
+        (ring_area(&self.points) - holes).max(0.0)
+    }
+
+    /// Check if a point is inside the polygon and outside its holes
+    pub fn contains(&self, point: &Point) -> bool {
+        point_in_polygon(point, &self.points)
+            && !self.holes.iter().any(|hole| point_in_polygon(point, hole))
+    }
+
+    /// All rings of the polygon, outer ring first
+    pub fn rings(&self) -> impl Iterator<Item = &Vec<Point>> {
+        std::iter::once(&self.points).chain(self.holes.iter())
+    }
+
+    /// Merge the holes into the outer ring through zero-width cuts
+    ///
+    /// Formats without hole support (COCO polygons, LabelMe) fill any
+    /// enclosed ring, so each hole is connected to the outer ring by a
+    /// bridge between their closest vertices. Holes are walked against
+    /// the outer ring's winding so the cut-out area is subtracted.
+    pub fn to_simple_ring(&self) -> Vec<Point> {
+        let mut ring = self.points.clone();
+        let outer_positive = signed_ring_area(&self.points) >= 0.0;
+        for hole in self.holes.iter().filter(|hole| !hole.is_empty()) {
+            if ring.is_empty() {
+                break;
+            }
+            let mut hole = hole.clone();
+            if (signed_ring_area(&hole) >= 0.0) == outer_positive {
+                hole.reverse();
+            }
+            let mut best = (0, 0, f32::MAX);
+            for (i, outer) in ring.iter().enumerate() {
+                for (j, inner) in hole.iter().enumerate() {
+                    let distance = outer.distance_to(inner);
+                    if distance < best.2 {
+                        best = (i, j, distance);
+                    }
+                }
+            }
+            let (i, j, _) = best;
+
+            let mut bridged = Vec::with_capacity(ring.len() + hole.len() + 2);
+            bridged.extend_from_slice(&ring[..=i]);
+            bridged.extend_from_slice(&hole[j..]);
+            bridged.extend_from_slice(&hole[..=j]);
+            bridged.extend_from_slice(&ring[i..]);
+            ring = bridged;
+        }
+        ring
+    }
+
+    /// Combine with another polygon; see [`BooleanOp`]
+    pub fn boolean(&self, other: &Polygon, op: BooleanOp) -> MultiPolygon {
+        boolean::overlay(std::slice::from_ref(self), std::slice::from_ref(other), op)
+    }
+
+    /// Region covered by either polygon
+    pub fn union(&self, other: &Polygon) -> MultiPolygon {
+        self.boolean(other, BooleanOp::Union)
+    }
+
+    /// Region covered by both polygons
+    pub fn intersection(&self, other: &Polygon) -> MultiPolygon {
+        self.boolean(other, BooleanOp::Intersection)
+    }
+
+    /// Region of this polygon outside `other`
+    pub fn difference(&self, other: &Polygon) -> MultiPolygon {
+        self.boolean(other, BooleanOp::Difference)
+    }
+
+    /// Region covered by exactly one of the polygons
+    pub fn xor(&self, other: &Polygon) -> MultiPolygon {
+        self.boolean(other, BooleanOp::Xor)
+    }
+
+    /// Axis-aligned bounding box as (min, max) corners
+    pub fn bounding_box(&self) -> Option<(Point, Point)> {
+        bounding_box(&self.points)
+    }
+
+    /// Total length of all rings, closing edges included
+    pub fn perimeter(&self) -> f32 {
+        self.rings().map(|ring| ring_perimeter(ring)).sum()
+    }
+
+    /// Area-weighted center, holes excluded
+    ///
+    /// Falls back to the mean of the outer vertices for degenerate polygons;
+    /// None if there are no vertices.
+    pub fn centroid(&self) -> Option<Point> {
+        if self.points.is_empty() {
+            return None;
+        }
+        let (area, x, y) = self.weighted_centroid();
+        if area.abs() > f32::EPSILON {
+            return Some(Point::new(x / area, y / area));
+        }
+        let n = self.points.len() as f32;
+        let (sum_x, sum_y) = self
+            .points
+            .iter()
+            .fold((0.0, 0.0), |(x, y), p| (x + p.x, y + p.y));
+        Some(Point::new(sum_x / n, sum_y / n))
+    }
+
+    /// Intersection over union with another polygon, 0 if both are empty
+    pub fn iou(&self, other: &Polygon) -> f32 {
+        iou(self.intersection(other).area(), self.union(other).area())
+    }
+
+    /// Douglas–Peucker simplification of every ring; see [`simplify()`]
+    ///
+    /// Holes that collapse below three vertices are dropped.
+    pub fn simplified(&self, tolerance: f32) -> Polygon {
+        self.map_rings(|ring| simplify(ring, tolerance, true))
+    }
+
+    /// Every ring resampled to edges of at most `max_length`; see [`densify()`]
+    pub fn densified(&self, max_length: f32) -> Polygon {
+        self.map_rings(|ring| densify(ring, max_length, true))
+    }
+
+    fn map_rings<F>(&self, f: F) -> Polygon
+    where
+        F: Fn(&[Point]) -> Vec<Point>,
+    {
+        Polygon::with_holes(
+            f(&self.points),
+            self.holes
+                .iter()
+                .map(|hole| f(hole))
+                .filter(|hole| hole.len() >= 3)
+                .collect(),
+        )
+    }
+
+    /// Area and area-weighted coordinate sums, holes subtracted
+    fn weighted_centroid(&self) -> (f32, f32, f32) {
+        let mut total = (0.0, 0.0, 0.0);
+        for (index, ring) in self.rings().enumerate() {
+            let (area, x, y) = ring_moments(ring);
+            // Outer ring adds and holes subtract, whatever their winding
+            let sign = if index == 0 { 1.0 } else { -1.0 } * area.signum();
+            total.0 += sign * area;
+            total.1 += sign * x;
+            total.2 += sign * y;
+        }
+        total
+    }
+}
+
+/// A set of polygons forming one region, e.g. an object split by an occluder
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct MultiPolygon {
+    pub polygons: Vec<Polygon>,
+}
+
+impl MultiPolygon {
+    pub fn new(polygons: Vec<Polygon>) -> Self {
+        Self { polygons }
+    }
+
+    /// Check that there is at least one part and every part is valid
+    pub fn is_valid(&self) -> bool {
+        !self.polygons.is_empty() && self.polygons.iter().all(Polygon::is_valid)
+    }
+
+    /// Total area of all parts
+    pub fn area(&self) -> f32 {
+        self.polygons.iter().map(Polygon::area).sum()
+    }
+
+    /// Check if a point is inside any part
+    pub fn contains(&self, point: &Point) -> bool {
+        self.polygons.iter().any(|polygon| polygon.contains(point))
+    }
+
+    /// Combine with another multi-polygon; see [`BooleanOp`]
+    pub fn boolean(&self, other: &MultiPolygon, op: BooleanOp) -> MultiPolygon {
+        boolean::overlay(&self.polygons, &other.polygons, op)
+    }
+
+    pub fn union(&self, other: &MultiPolygon) -> MultiPolygon {
+        self.boolean(other, BooleanOp::Union)
+    }
+
+    pub fn intersection(&self, other: &MultiPolygon) -> MultiPolygon {
+        self.boolean(other, BooleanOp::Intersection)
+    }
+
+    pub fn difference(&self, other: &MultiPolygon) -> MultiPolygon {
+        self.boolean(other, BooleanOp::Difference)
+    }
+
+    pub fn xor(&self, other: &MultiPolygon) -> MultiPolygon {
+        self.boolean(other, BooleanOp::Xor)
+    }
+
+    /// Whether there is no area left
+    pub fn is_empty(&self) -> bool {
+        self.polygons.is_empty()
+    }
+
+    /// Axis-aligned bounding box of all parts as (min, max) corners
+    pub fn bounding_box(&self) -> Option<(Point, Point)> {
+        let outer: Vec<Point> = self
+            .polygons
+            .iter()
+            .flat_map(|polygon| polygon.points.iter().copied())
+            .collect();
+        bounding_box(&outer)
+    }
+
+    /// Total length of the rings of all parts
+    pub fn perimeter(&self) -> f32 {
+        self.polygons.iter().map(Polygon::perimeter).sum()
+    }
+
+    /// Area-weighted center of all parts, see [`Polygon::centroid`]
+    pub fn centroid(&self) -> Option<Point> {
+        let (area, x, y) = self
+            .polygons
+            .iter()
+            .map(Polygon::weighted_centroid)
+            .fold((0.0, 0.0, 0.0), |a, b| (a.0 + b.0, a.1 + b.1, a.2 + b.2));
+        if area.abs() > f32::EPSILON {
+            return Some(Point::new(x / area, y / area));
+        }
+        self.polygons.iter().find_map(Polygon::centroid)
+    }
+
+    /// Intersection over union with another multi-polygon, 0 if both are empty
+    pub fn iou(&self, other: &MultiPolygon) -> f32 {
+        iou(self.intersection(other).area(), self.union(other).area())
+    }
+}
+
+/// Check if a point is inside a ring (ray casting, even-odd)
+pub fn point_in_polygon(point: &Point, polygon: &[Point]) -> bool {
+    if polygon.len() < 3 {
+        return false;
+    }
+
+    let mut inside = false;
+    let mut j = polygon.len() - 1;
+    for i in 0..polygon.len() {
+        let (pi, pj) = (&polygon[i], &polygon[j]);
+        if (pi.y > point.y) != (pj.y > point.y)
+            && point.x < (pj.x - pi.x) * (point.y - pi.y) / (pj.y - pi.y) + pi.x
+        {
+            inside = !inside;
+        }
+        j = i;
+    }
+
+    inside
+}
+
+/// Distance from a point to the line segment from `p1` to `p2`
+pub fn point_to_segment_distance(point: &Point, p1: &Point, p2: &Point) -> f32 {
+    point.distance_to_segment(p1, p2)
+}
+
+/// Axis-aligned bounding box of a point set as (min, max) corners
+pub fn bounding_box(points: &[Point]) -> Option<(Point, Point)> {
+    let first = points.first()?;
+    let mut min = *first;
+    let mut max = *first;
+    for point in &points[1..] {
+        min.x = min.x.min(point.x);
+        min.y = min.y.min(point.y);
+        max.x = max.x.max(point.x);
+        max.y = max.y.max(point.y);
+    }
+    Some((min, max))
+}
+
+/// Intersection over union from the two areas
+fn iou(intersection: f32, union: f32) -> f32 {
+    if union > 0.0 {
+        (intersection / union).clamp(0.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Length of a closed ring
+fn ring_perimeter(points: &[Point]) -> f32 {
+    (0..points.len())
+        .map(|i| points[i].distance_to(&points[(i + 1) % points.len()]))
+        .sum()
+}
+
+/// Signed area and first moments (area times centroid) of a single ring
+fn ring_moments(points: &[Point]) -> (f32, f32, f32) {
+    if points.len() < 3 {
+        return (0.0, 0.0, 0.0);
+    }
+
+    let (mut area, mut x, mut y) = (0.0, 0.0, 0.0);
+    for i in 0..points.len() {
+        let (p, q) = (points[i], points[(i + 1) % points.len()]);
+        let cross = p.x * q.y - q.x * p.y;
+        area += cross;
+        x += (p.x + q.x) * cross;
+        y += (p.y + q.y) * cross;
+    }
+    (area / 2.0, x / 6.0, y / 6.0)
+}
+
+/// Absolute shoelace area of a single ring
+fn ring_area(points: &[Point]) -> f32 {
+    signed_ring_area(points).abs()
+}
+
+/// Shoelace area of a single ring, its sign giving the winding direction
+fn signed_ring_area(points: &[Point]) -> f32 {
+    if points.len() < 3 {
+        return 0.0;
+    }
+
+    let mut sum = 0.0;
+    for i in 0..points.len() {
+        let j = (i + 1) % points.len();
+        sum += points[i].x * points[j].y;
+        sum -= points[j].x * points[i].y;
+    }
+
+    sum / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point_distance() {
+        let p1 = Point::new(0.0, 0.0);
+        let p2 = Point::new(3.0, 4.0);
+        assert_eq!(p1.distance_to(&p2), 5.0);
+    }
+
+    #[test]
+    fn test_point_in_polygon() {
+        let polygon = vec![
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 0.0),
+            Point::new(1.0, 1.0),
+            Point::new(0.0, 1.0),
+        ];
+
+        assert!(point_in_polygon(&Point::new(0.5, 0.5), &polygon));
+        assert!(!point_in_polygon(&Point::new(1.5, 0.5), &polygon));
+    }
+
+    #[test]
+    fn test_point_to_segment_distance() {
+        let p1 = Point::new(0.0, 0.0);
+        let p2 = Point::new(1.0, 0.0);
+        let point = Point::new(0.5, 0.5);
+
+        let distance = point_to_segment_distance(&point, &p1, &p2);
+        assert!((distance - 0.5).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let polygon = vec![
+            Point::new(0.2, 0.3),
+            Point::new(0.8, 0.3),
+            Point::new(0.8, 0.7),
+            Point::new(0.2, 0.7),
+        ];
+
+        let (min, max) = bounding_box(&polygon).unwrap();
+        assert_eq!(min.x, 0.2);
+        assert_eq!(min.y, 0.3);
+        assert_eq!(max.x, 0.8);
+        assert_eq!(max.y, 0.7);
+        assert!(bounding_box(&[]).is_none());
+    }
+
+    #[test]
+    fn test_polygon_area() {
+        // Unit square
+        let poly = Polygon::new(vec![
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 0.0),
+            Point::new(1.0, 1.0),
+            Point::new(0.0, 1.0),
+        ]);
+        assert!((poly.area() - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_polygon_contains_and_bounds() {
+        let poly = Polygon::new(vec![
+            Point::new(0.2, 0.2),
+            Point::new(0.6, 0.2),
+            Point::new(0.6, 0.8),
+        ]);
+        assert!(poly.contains(&Point::new(0.5, 0.3)));
+        assert!(!poly.contains(&Point::new(0.3, 0.7)));
+
+        let (min, max) = poly.bounding_box().unwrap();
+        assert_eq!((min.x, min.y, max.x, max.y), (0.2, 0.2, 0.6, 0.8));
+        assert!(Polygon::empty().bounding_box().is_none());
+    }
+
+    #[test]
+    fn test_polygon_with_holes() {
+        let square = |min: f32, max: f32| {
+            vec![
+                Point::new(min, min),
+                Point::new(max, min),
+                Point::new(max, max),
+                Point::new(min, max),
+            ]
+        };
+        let donut = Polygon::with_holes(square(0.0, 1.0), vec![square(0.25, 0.75)]);
+        assert!((donut.area() - 0.75).abs() < 1e-6);
+        assert!(donut.contains(&Point::new(0.1, 0.1)));
+        assert!(!donut.contains(&Point::new(0.5, 0.5)));
+
+        // The bridged ring encloses the same area
+        let ring = Polygon::new(donut.to_simple_ring());
+        assert_eq!(ring.points.len(), 4 + 4 + 2);
+        assert!((ring.area() - 0.75).abs() < 1e-6);
+
+        let parts = MultiPolygon::new(vec![donut, Polygon::new(square(2.0, 3.0))]);
+        assert!((parts.area() - 1.75).abs() < 1e-6);
+        assert!(parts.contains(&Point::new(2.5, 2.5)));
+        assert_eq!(parts.bounding_box().unwrap().1, Point::new(3.0, 3.0));
+    }
+
+    #[test]
+    fn test_polygon_metrics() {
+        let square = |min: f32, max: f32| {
+            vec![
+                Point::new(min, min),
+                Point::new(max, min),
+                Point::new(max, max),
+                Point::new(min, max),
+            ]
+        };
+        let unit = Polygon::new(square(0.0, 1.0));
+        assert_eq!(unit.perimeter(), 4.0);
+        assert_eq!(unit.centroid(), Some(Point::new(0.5, 0.5)));
+
+        // A hole in one corner pulls the centroid away from it, whatever its winding
+        let mut hole = square(0.0, 0.5);
+        let notched = Polygon::with_holes(square(0.0, 1.0), vec![hole.clone()]);
+        hole.reverse();
+        let reversed = Polygon::with_holes(square(0.0, 1.0), vec![hole]);
+        let centroid = notched.centroid().unwrap();
+        assert!((centroid.x - 7.0 / 12.0).abs() < 1e-5);
+        assert_eq!(reversed.centroid(), Some(centroid));
+        assert_eq!(notched.perimeter(), 6.0);
+
+        let degenerate = Polygon::new(vec![Point::new(0.0, 0.0), Point::new(1.0, 0.0)]);
+        assert_eq!(degenerate.centroid(), Some(Point::new(0.5, 0.0)));
+        assert!(Polygon::empty().centroid().is_none());
+
+        // Half-overlapping unit squares: 1 / 3
+        let shifted = Polygon::new(
+            square(0.0, 1.0)
+                .into_iter()
+                .map(|p| Point::new(p.x + 0.5, p.y))
+                .collect(),
+        );
+        assert!((unit.iou(&shifted) - 1.0 / 3.0).abs() < 1e-5);
+        assert_eq!(unit.iou(&unit), 1.0);
+        assert_eq!(Polygon::empty().iou(&Polygon::empty()), 0.0);
+
+        let parts = MultiPolygon::new(vec![unit.clone(), shifted.clone()]);
+        assert_eq!(parts.perimeter(), 8.0);
+        assert_eq!(parts.centroid(), Some(Point::new(0.75, 0.5)));
+
+        let dense = unit.densified(0.25);
+        assert_eq!(dense.points.len(), 16);
+        assert_eq!(dense.simplified(0.01), unit);
+    }
+}
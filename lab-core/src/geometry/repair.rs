@@ -0,0 +1,123 @@
+//! Detection and repair of self-intersecting rings
+
+use super::Point;
+
+/// Whether any two non-adjacent edges of a closed ring cross or touch
+pub fn has_self_intersections(points: &[Point]) -> bool {
+    find_crossing(points).is_some()
+}
+
+/// Untangle a closed ring by reversing the vertices between crossing edges
+///
+/// Each reversal removes one crossing (2-opt), so the ring keeps its
+/// vertices but no longer intersects itself. Returns whether the ring was
+/// changed.
+pub fn fix_self_intersections(points: &mut [Point]) -> bool {
+    let mut updated = false;
+    let max_iterations = points.len() * points.len();
+    for _ in 0..=max_iterations {
+        let Some((i, j)) = find_crossing(points) else {
+            break;
+        };
+        points[i + 1..=j].reverse();
+        updated = true;
+    }
+    updated
+}
+
+/// First pair of non-adjacent edges (i, j) with i < j that intersect
+fn find_crossing(points: &[Point]) -> Option<(usize, usize)> {
+    let count = points.len();
+    if count < 4 {
+        return None;
+    }
+
+    for i in 0..count {
+        let a1 = points[i];
+        let a2 = points[(i + 1) % count];
+
+        for j in (i + 2)..count {
+            if i == 0 && j == count - 1 {
+                continue;
+            }
+
+            let b1 = points[j];
+            let b2 = points[(j + 1) % count];
+
+            if a1 == b1 || a1 == b2 || a2 == b1 || a2 == b2 {
+                continue;
+            }
+
+            if segments_intersect(&a1, &a2, &b1, &b2) {
+                return Some((i, j));
+            }
+        }
+    }
+    None
+}
+
+fn orientation(a: &Point, b: &Point, c: &Point) -> f32 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+fn on_segment(a: &Point, b: &Point, c: &Point) -> bool {
+    let min_x = a.x.min(b.x);
+    let max_x = a.x.max(b.x);
+    let min_y = a.y.min(b.y);
+    let max_y = a.y.max(b.y);
+    let eps = 1e-6;
+
+    c.x >= min_x - eps && c.x <= max_x + eps && c.y >= min_y - eps && c.y <= max_y + eps
+}
+
+/// Whether the segments a1-a2 and b1-b2 cross or touch
+pub fn segments_intersect(a1: &Point, a2: &Point, b1: &Point, b2: &Point) -> bool {
+    let o1 = orientation(a1, a2, b1);
+    let o2 = orientation(a1, a2, b2);
+    let o3 = orientation(b1, b2, a1);
+    let o4 = orientation(b1, b2, a2);
+    let eps = 1e-6;
+
+    if o1.abs() < eps && on_segment(a1, a2, b1) {
+        return true;
+    }
+    if o2.abs() < eps && on_segment(a1, a2, b2) {
+        return true;
+    }
+    if o3.abs() < eps && on_segment(b1, b2, a1) {
+        return true;
+    }
+    if o4.abs() < eps && on_segment(b1, b2, a2) {
+        return true;
+    }
+
+    (o1 > eps && o2 < -eps || o1 < -eps && o2 > eps)
+        && (o3 > eps && o4 < -eps || o3 < -eps && o4 > eps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fix_self_intersections() {
+        let mut polygon = vec![
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 1.0),
+            Point::new(0.0, 1.0),
+            Point::new(1.0, 0.0),
+        ];
+
+        assert!(has_self_intersections(&polygon));
+        assert!(fix_self_intersections(&mut polygon));
+        assert!(!has_self_intersections(&polygon));
+        assert!(!fix_self_intersections(&mut polygon));
+
+        assert!(segments_intersect(
+            &Point::new(0.0, 0.0),
+            &Point::new(1.0, 0.0),
+            &Point::new(0.5, 0.0),
+            &Point::new(0.5, 1.0),
+        ));
+    }
+}
@@ -0,0 +1,211 @@
+//! Vertex reduction and resampling of rings and polylines
+
+use super::Point;
+
+/// Douglas–Peucker simplification
+///
+/// Keeps the vertices needed so that no dropped vertex is farther than
+/// `tolerance` from the simplified line. The end points of an open line are
+/// always kept; a closed ring is split at its first vertex and the vertex
+/// farthest from it. A closed ring may come back with fewer than three
+/// vertices when the tolerance exceeds its size.
+pub fn simplify(points: &[Point], tolerance: f32, closed: bool) -> Vec<Point> {
+    simplify_indices(points, tolerance, closed)
+        .into_iter()
+        .map(|index| points[index])
+        .collect()
+}
+
+/// Indices of the vertices kept by [`simplify`], in order
+pub fn simplify_indices(points: &[Point], tolerance: f32, closed: bool) -> Vec<usize> {
+    if points.len() < 3 || tolerance <= 0.0 {
+        return (0..points.len()).collect();
+    }
+    if !closed {
+        let mut keep = vec![false; points.len()];
+        mark_chain(points, tolerance, &mut keep);
+        return kept(&keep);
+    }
+
+    let far = (1..points.len())
+        .max_by(|&a, &b| {
+            let da = points[0].distance_to(&points[a]);
+            let db = points[0].distance_to(&points[b]);
+            da.total_cmp(&db)
+        })
+        .unwrap_or(0);
+    // Walk the ring once, returning to the first vertex
+    let mut ring = points.to_vec();
+    ring.push(points[0]);
+    let mut keep = vec![false; ring.len()];
+    mark_chain(&ring[..=far], tolerance, &mut keep[..=far]);
+    mark_chain(&ring[far..], tolerance, &mut keep[far..]);
+    keep.pop();
+    kept(&keep)
+}
+
+/// One round of Chaikin corner cutting
+///
+/// Every edge is replaced by the points at a quarter and three quarters of
+/// its length, which rounds off corners and doubles the vertex count. The
+/// end points of an open line stay in place.
+pub fn smooth(points: &[Point], closed: bool) -> Vec<Point> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let lerp =
+        |a: Point, b: Point, t: f32| Point::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t);
+    let edges = if closed {
+        points.len()
+    } else {
+        points.len() - 1
+    };
+    let mut result = Vec::with_capacity(edges * 2 + 2);
+    if !closed {
+        result.push(points[0]);
+    }
+    for i in 0..edges {
+        let (a, b) = (points[i], points[(i + 1) % points.len()]);
+        if closed || i > 0 {
+            result.push(lerp(a, b, 0.25));
+        }
+        if closed || i + 1 < edges {
+            result.push(lerp(a, b, 0.75));
+        }
+    }
+    if !closed {
+        result.push(points[points.len() - 1]);
+    }
+    result
+}
+
+/// Insert evenly spaced vertices so that no edge is longer than `max_length`
+///
+/// The closing edge is included for closed rings. Existing vertices are kept.
+pub fn densify(points: &[Point], max_length: f32, closed: bool) -> Vec<Point> {
+    if points.len() < 2 || max_length <= 0.0 {
+        return points.to_vec();
+    }
+
+    let edges = if closed {
+        points.len()
+    } else {
+        points.len() - 1
+    };
+    let mut result = Vec::with_capacity(points.len());
+    for i in 0..edges {
+        let (a, b) = (points[i], points[(i + 1) % points.len()]);
+        // Edges within rounding error of the limit are left whole
+        let steps = (a.distance_to(&b) / max_length - 1e-4).ceil().max(1.0) as usize;
+        for step in 0..steps {
+            let t = step as f32 / steps as f32;
+            result.push(Point::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t));
+        }
+    }
+    if !closed {
+        result.push(points[points.len() - 1]);
+    }
+    result
+}
+
+/// Mark the vertices of an open chain kept by Douglas–Peucker
+fn mark_chain(points: &[Point], tolerance: f32, keep: &mut [bool]) {
+    let last = points.len() - 1;
+    keep[0] = true;
+    keep[last] = true;
+
+    let mut stack = vec![(0, last)];
+    while let Some((start, end)) = stack.pop() {
+        let (a, b) = (points[start], points[end]);
+        let farthest = (start + 1..end)
+            .map(|i| (i, points[i].distance_to_segment(&a, &b)))
+            .max_by(|x, y| x.1.total_cmp(&y.1));
+        if let Some((index, distance)) = farthest {
+            if distance > tolerance {
+                keep[index] = true;
+                stack.push((start, index));
+                stack.push((index, end));
+            }
+        }
+    }
+}
+
+fn kept(keep: &[bool]) -> Vec<usize> {
+    (0..keep.len()).filter(|&index| keep[index]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simplify() {
+        // Small wiggles along a straight line disappear
+        let line = vec![
+            Point::new(0.0, 0.0),
+            Point::new(0.25, 0.01),
+            Point::new(0.5, -0.01),
+            Point::new(0.75, 0.3),
+            Point::new(1.0, 0.0),
+        ];
+        assert_eq!(
+            simplify(&line, 0.05, false),
+            vec![line[0], line[2], line[3], line[4]]
+        );
+        assert_eq!(simplify(&line, 1.0, false), vec![line[0], line[4]]);
+
+        // Midpoints of a square's edges are dropped, its corners kept
+        let square = densify(
+            &[
+                Point::new(0.0, 0.0),
+                Point::new(1.0, 0.0),
+                Point::new(1.0, 1.0),
+                Point::new(0.0, 1.0),
+            ],
+            0.5,
+            true,
+        );
+        assert_eq!(square.len(), 8);
+        assert_eq!(square[1], Point::new(0.5, 0.0));
+        let simplified = simplify(&square, 0.01, true);
+        assert_eq!(simplified, vec![square[0], square[2], square[4], square[6]]);
+    }
+
+    #[test]
+    fn test_smooth() {
+        let square = [
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 0.0),
+            Point::new(1.0, 1.0),
+            Point::new(0.0, 1.0),
+        ];
+        let smoothed = smooth(&square, true);
+        assert_eq!(smoothed.len(), 8);
+        assert_eq!(smoothed[0], Point::new(0.25, 0.0));
+        assert_eq!(smoothed[7], Point::new(0.0, 0.25));
+
+        let line = smooth(&square[..3], false);
+        assert_eq!(
+            line,
+            vec![
+                Point::new(0.0, 0.0),
+                Point::new(0.75, 0.0),
+                Point::new(1.0, 0.25),
+                Point::new(1.0, 1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_densify() {
+        let line = [Point::new(0.0, 0.0), Point::new(1.0, 0.0)];
+        let dense = densify(&line, 0.3, false);
+        assert_eq!(dense.len(), 5);
+        assert_eq!(dense[4], line[1]);
+        assert!(dense
+            .windows(2)
+            .all(|w| w[0].distance_to(&w[1]) <= 0.3 + 1e-6));
+        assert_eq!(densify(&line, 0.0, false), line.to_vec());
+    }
+}
@@ -0,0 +1,334 @@
+use crate::import::{
+    build_annotation, clamp01, find_category_id_by_name, rect_shape, ImportedDataset,
+    ImportedImage, Importer,
+};
+use crate::{Error, Keypoint, KeypointVisibility, Mask, Meta, Object, Point, Result, Shape};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// COCO format importer
+///
+/// The source is the annotation JSON; image files are looked up in
+/// `images_dir`. Categories are matched by id, then by name, and keypoints
+/// by name where the dataset declares names.
+pub struct CocoImporter {
+    pub images_dir: PathBuf,
+}
+
+impl Importer for CocoImporter {
+    fn import(&self, source: &Path, meta: &Meta, annotator: &str) -> Result<ImportedDataset> {
+        let content = fs::read_to_string(source)?;
+        let dataset: CocoDataset = serde_json::from_str(&content)?;
+
+        let mut category_map = HashMap::new();
+        let mut category_names = HashMap::new();
+        for cat in &dataset.categories {
+            category_names.insert(cat.id, cat.name.as_str());
+            if meta.find_category(cat.id).is_some() {
+                category_map.insert(cat.id, cat.id);
+            } else if let Some(id) = find_category_id_by_name(meta, &cat.name) {
+                category_map.insert(cat.id, id);
+            }
+        }
+
+        // Map COCO keypoint slots to the project's keypoint order, by name
+        // when the dataset declares names and by position otherwise
+        let mut keypoint_map: HashMap<i32, Vec<Option<usize>>> = HashMap::new();
+        for cat in &dataset.categories {
+            let Some(target) = category_map
+                .get(&cat.id)
+                .and_then(|id| meta.find_category(*id))
+            else {
+                continue;
+            };
+            let slots = if cat.keypoints.is_empty() {
+                (0..target.keypoints.len()).map(Some).collect()
+            } else {
+                cat.keypoints
+                    .iter()
+                    .map(|name| target.keypoints.iter().position(|kp| kp == name))
+                    .collect()
+            };
+            keypoint_map.insert(cat.id, slots);
+        }
+
+        let mut annotations_by_image: HashMap<i32, Vec<CocoAnnotation>> = HashMap::new();
+        for ann in dataset.annotations {
+            annotations_by_image
+                .entry(ann.image_id)
+                .or_default()
+                .push(ann);
+        }
+
+        let mut imported = ImportedDataset::default();
+        let report = &mut imported.report;
+        for image in dataset.images {
+            let file_name = Path::new(&image.file_name)
+                .file_name()
+                .and_then(|s| s.to_str())
+                .ok_or_else(|| Error::Import(format!("Invalid image name: {}", image.file_name)))?
+                .to_string();
+            let source_path = self.images_dir.join(&image.file_name);
+            if !source_path.exists() {
+                return Err(Error::Import(format!(
+                    "Missing image file: {:?}",
+                    source_path
+                )));
+            }
+
+            let mut objects = Vec::new();
+            for (index, ann) in annotations_by_image
+                .get(&image.id)
+                .into_iter()
+                .flatten()
+                .enumerate()
+            {
+                let Some(&category_id) = category_map.get(&ann.category_id) else {
+                    match category_names.get(&ann.category_id) {
+                        Some(name) => report.unknown_category(name),
+                        None => report.unknown_category(ann.category_id),
+                    }
+                    continue;
+                };
+
+                let location = format!("{} annotation {}", image.file_name, index + 1);
+                let shape = match &ann.segmentation {
+                    Some(segmentation) => {
+                        match segmentation_to_shape(segmentation, image.width, image.height) {
+                            Ok(shape) => shape,
+                            Err(reason) => {
+                                report.skip(location, reason);
+                                continue;
+                            }
+                        }
+                    }
+                    None => None,
+                };
+                let Some(shape) =
+                    shape.or_else(|| bbox_to_shape(&ann.bbox, image.width, image.height))
+                else {
+                    report.skip(location, "No segmentation or bounding box");
+                    continue;
+                };
+                let mut object = Object::with_shape(0, category_id, shape);
+
+                let slots = keypoint_map.get(&ann.category_id);
+                for (index, values) in ann.keypoints.chunks_exact(3).enumerate() {
+                    let Some(slot) = slots.and_then(|s| s.get(index).copied().flatten()) else {
+                        continue;
+                    };
+                    let visibility = KeypointVisibility::from_coco(values[2] as u8);
+                    if visibility.is_labeled() && image.width > 0 && image.height > 0 {
+                        let position = Point::new(
+                            clamp01(values[0] / image.width as f32),
+                            clamp01(values[1] / image.height as f32),
+                        );
+                        object.set_keypoint(slot, Keypoint::new(position, visibility));
+                    }
+                }
+                objects.push(object);
+            }
+
+            let annotation = build_annotation(objects, annotator, report);
+            imported.images.push(ImportedImage {
+                source_path,
+                file_name,
+                annotation,
+            });
+        }
+
+        Ok(imported)
+    }
+}
+
+fn bbox_to_shape(bbox: &[f32], width: u32, height: u32) -> Option<Shape> {
+    if bbox.len() < 4 || width == 0 || height == 0 {
+        return None;
+    }
+    let x = bbox[0] / width as f32;
+    let y = bbox[1] / height as f32;
+    let w = bbox[2] / width as f32;
+    let h = bbox[3] / height as f32;
+    rect_shape(x, y, x + w, y + h)
+}
+
+/// Read COCO segmentation; several polygon rings become a multi-part shape
+/// and RLE (compressed or not) becomes a mask
+///
+/// Empty segmentations give None, to fall back to the bounding box.
+fn segmentation_to_shape(
+    segmentation: &serde_json::Value,
+    width: u32,
+    height: u32,
+) -> std::result::Result<Option<Shape>, String> {
+    let rings: Vec<&Vec<serde_json::Value>> = match segmentation {
+        serde_json::Value::Object(rle) => {
+            let size = rle
+                .get("size")
+                .and_then(|size| size.as_array())
+                .and_then(|size| Some((size.first()?.as_u64()?, size.get(1)?.as_u64()?)));
+            let Some((rle_height, rle_width)) = size else {
+                return Err("Invalid RLE size".to_string());
+            };
+            let (rle_width, rle_height) = (rle_width as u32, rle_height as u32);
+            let mask = match rle.get("counts") {
+                Some(serde_json::Value::String(encoded)) => {
+                    Mask::from_compressed_counts(rle_width, rle_height, encoded)
+                }
+                Some(serde_json::Value::Array(counts)) => Mask::from_counts(
+                    rle_width,
+                    rle_height,
+                    counts
+                        .iter()
+                        .filter_map(|c| c.as_u64())
+                        .map(|c| c as u32)
+                        .collect(),
+                ),
+                _ => return Err("Invalid RLE counts".to_string()),
+            };
+            return match mask {
+                Ok(mask) if !mask.is_empty() => Ok(Some(Shape::Mask(mask))),
+                Ok(_) => Ok(None),
+                Err(e) => Err(format!("Invalid RLE segmentation: {}", e)),
+            };
+        }
+        serde_json::Value::Array(items) => {
+            if items.is_empty() {
+                return Ok(None);
+            }
+            if items[0].is_array() {
+                items.iter().filter_map(|item| item.as_array()).collect()
+            } else {
+                vec![items]
+            }
+        }
+        _ => return Ok(None),
+    };
+    if width == 0 || height == 0 {
+        return Ok(None);
+    }
+
+    let mut shape: Option<Shape> = None;
+    for coords in rings {
+        let mut points = Vec::new();
+        let mut iter = coords.iter().filter_map(|v| v.as_f64());
+        while let (Some(x), Some(y)) = (iter.next(), iter.next()) {
+            points.push(Point::new(
+                clamp01(x as f32 / width as f32),
+                clamp01(y as f32 / height as f32),
+            ));
+        }
+        if points.len() < 3 {
+            continue;
+        }
+        match &mut shape {
+            Some(shape) => {
+                shape.add_part(points);
+            }
+            None => shape = Some(Shape::polygon(points)),
+        }
+    }
+
+    Ok(shape)
+}
+
+#[derive(Debug, Deserialize)]
+struct CocoDataset {
+    images: Vec<CocoImage>,
+    annotations: Vec<CocoAnnotation>,
+    categories: Vec<CocoCategory>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CocoImage {
+    id: i32,
+    file_name: String,
+    width: u32,
+    height: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct CocoCategory {
+    id: i32,
+    name: String,
+    #[serde(default)]
+    keypoints: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CocoAnnotation {
+    image_id: i32,
+    category_id: i32,
+    #[serde(default)]
+    bbox: Vec<f32>,
+    #[serde(default)]
+    segmentation: Option<serde_json::Value>,
+    /// Flattened [x, y, v, ...] in pixels
+    #[serde(default)]
+    keypoints: Vec<f32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::test_meta;
+
+    #[test]
+    fn test_coco_import() {
+        let root = std::env::temp_dir().join("jlab_test_coco_import");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("a.jpg"), b"").unwrap();
+        let json = r#"{
+            "images": [{"id": 7, "file_name": "a.jpg", "width": 100, "height": 50}],
+            "categories": [
+                {"id": 1, "name": "cat", "keypoints": ["tail", "ear"]},
+                {"id": 5, "name": "dog"},
+                {"id": 6, "name": "bird"}
+            ],
+            "annotations": [
+                {"image_id": 7, "category_id": 1, "bbox": [10, 5, 20, 10],
+                 "keypoints": [15, 10, 2, 12, 8, 2]},
+                {"image_id": 7, "category_id": 5,
+                 "segmentation": [[0, 0, 50, 0, 50, 25], [60, 30, 80, 30, 80, 40]]},
+                {"image_id": 7, "category_id": 6, "bbox": [0, 0, 1, 1]},
+                {"image_id": 7, "category_id": 5, "segmentation": {"size": [2, 2], "counts": "?"}},
+                {"image_id": 7, "category_id": 1, "bbox": [10, 5, 0, 10]}
+            ]
+        }"#;
+        fs::write(root.join("annotations.json"), json).unwrap();
+
+        let importer = CocoImporter {
+            images_dir: root.clone(),
+        };
+        let dataset = importer
+            .import(&root.join("annotations.json"), &test_meta(), "alice")
+            .unwrap();
+        assert_eq!(dataset.images.len(), 1);
+        let annotation = dataset.images[0].annotation.as_ref().unwrap();
+        assert_eq!(annotation.objects.len(), 2);
+
+        // Keypoints are matched by name: only the tail is known to the project
+        let cat = &annotation.objects[0];
+        assert_eq!(
+            cat.keypoints
+                .iter()
+                .filter(|kp| kp.visibility.is_labeled())
+                .count(),
+            1
+        );
+        assert!(cat.keypoints[1].visibility.is_labeled());
+        let dog = &annotation.objects[1];
+        assert_eq!((dog.category, dog.shape.simple_rings().len()), (2, 2));
+
+        let report = &dataset.report;
+        assert_eq!(report.objects, 2);
+        assert_eq!(report.unknown_categories["bird"], 1);
+        let locations: Vec<_> = report.skipped.iter().map(|s| s.location.as_str()).collect();
+        assert_eq!(locations, ["a.jpg annotation 4", "a.jpg annotation 5"]);
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,215 @@
+use crate::import::{
+    build_annotation, clamp01, find_category_id_by_name, rect_shape, ImportReport, ImportedDataset,
+    ImportedImage, Importer,
+};
+use crate::{Error, Meta, Object, Point, Result, Shape};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// LabelMe format importer
+///
+/// Reads every `.json` file in the source folder, with its image next to
+/// it; shapes are matched to categories by label.
+pub struct LabelMeImporter;
+
+impl Importer for LabelMeImporter {
+    fn import(&self, source: &Path, meta: &Meta, annotator: &str) -> Result<ImportedDataset> {
+        let mut label_paths = Vec::new();
+        for entry in fs::read_dir(source)? {
+            let path = entry?.path();
+            if path.extension().and_then(|s| s.to_str()) == Some("json") {
+                label_paths.push(path);
+            }
+        }
+        label_paths.sort();
+
+        let mut dataset = ImportedDataset::default();
+        for path in label_paths {
+            let content = fs::read_to_string(&path)?;
+            let labelme: LabelMeFile = serde_json::from_str(&content)?;
+
+            let file_name = if let Some(image_path) = labelme.image_path.as_ref() {
+                Path::new(image_path)
+                    .file_name()
+                    .and_then(|s| s.to_str())
+                    .ok_or_else(|| Error::Import(format!("Invalid image name: {}", image_path)))?
+                    .to_string()
+            } else {
+                let stem = path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .ok_or_else(|| Error::Import(format!("Invalid label file name: {:?}", path)))?;
+                find_image_by_stem(source, stem)?
+            };
+
+            let source_path = source.join(&file_name);
+            if !source_path.exists() {
+                return Err(Error::Import(format!(
+                    "Missing image file: {:?}",
+                    source_path
+                )));
+            }
+
+            let location = path
+                .file_name()
+                .and_then(|s| s.to_str())
+                .unwrap_or_default();
+            let objects = parse_shapes(&labelme, meta, location, &mut dataset.report);
+            let annotation = build_annotation(objects, annotator, &mut dataset.report);
+            dataset.images.push(ImportedImage {
+                source_path,
+                file_name,
+                annotation,
+            });
+        }
+
+        Ok(dataset)
+    }
+}
+
+/// Objects of a LabelMe file; `location` names the file in the report
+fn parse_shapes(
+    labelme: &LabelMeFile,
+    meta: &Meta,
+    location: &str,
+    report: &mut ImportReport,
+) -> Vec<Object> {
+    let mut objects = Vec::new();
+    for (index, shape) in labelme.shapes.iter().enumerate() {
+        let Some(category_id) = find_category_id_by_name(meta, &shape.label) else {
+            report.unknown_category(&shape.label);
+            continue;
+        };
+
+        let Some(shape) = shape_to_shape(shape, labelme.image_width, labelme.image_height) else {
+            report.skip(
+                format!("{} shape {}", location, index + 1),
+                format!(
+                    "Cannot read {} shape",
+                    shape.shape_type.as_deref().unwrap_or("polygon")
+                ),
+            );
+            continue;
+        };
+        objects.push(Object::with_shape(0, category_id, shape));
+    }
+    objects
+}
+
+fn shape_to_shape(shape: &LabelMeShape, width: u32, height: u32) -> Option<Shape> {
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let shape_type = shape
+        .shape_type
+        .as_deref()
+        .unwrap_or("polygon")
+        .to_lowercase();
+
+    let points: Vec<Point> = shape
+        .points
+        .iter()
+        .filter_map(|p| {
+            if p.len() < 2 {
+                None
+            } else {
+                Some(Point::new(
+                    clamp01(p[0] as f32 / width as f32),
+                    clamp01(p[1] as f32 / height as f32),
+                ))
+            }
+        })
+        .collect();
+
+    match shape_type.as_str() {
+        "rectangle" if points.len() >= 2 => {
+            rect_shape(points[0].x, points[0].y, points[1].x, points[1].y)
+        }
+        "circle" if points.len() >= 2 => {
+            // Center and a point on the circle, radius measured in pixels
+            let dx = (points[1].x - points[0].x) * width as f32;
+            let dy = (points[1].y - points[0].y) * height as f32;
+            let radius = (dx * dx + dy * dy).sqrt();
+            Some(Shape::Ellipse {
+                center: points[0],
+                radius_x: radius / width as f32,
+                radius_y: radius / height as f32,
+            })
+        }
+        "point" if !points.is_empty() => Some(Shape::Point {
+            position: points[0],
+        }),
+        "line" | "linestrip" if points.len() >= 2 => Some(Shape::Polyline { points }),
+        _ if points.len() >= 3 => Some(Shape::polygon(points)),
+        _ => None,
+    }
+}
+
+fn find_image_by_stem(root: &Path, stem: &str) -> Result<String> {
+    let candidates = ["jpg", "jpeg", "png"];
+    for ext in candidates {
+        let path = root.join(format!("{}.{}", stem, ext));
+        if path.exists() {
+            return Ok(format!("{}.{}", stem, ext));
+        }
+    }
+    Err(Error::Import(format!("Cannot find image for {}", stem)))
+}
+
+#[derive(Debug, Deserialize)]
+struct LabelMeFile {
+    #[serde(rename = "imagePath")]
+    image_path: Option<String>,
+    #[serde(rename = "imageHeight")]
+    image_height: u32,
+    #[serde(rename = "imageWidth")]
+    image_width: u32,
+    #[serde(default)]
+    shapes: Vec<LabelMeShape>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LabelMeShape {
+    label: String,
+    points: Vec<Vec<f64>>,
+    #[serde(rename = "shape_type")]
+    shape_type: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::test_meta;
+    use crate::ShapeKind;
+
+    #[test]
+    fn test_parse_shapes() {
+        let labelme: LabelMeFile = serde_json::from_str(
+            r#"{
+                "imagePath": "a.jpg", "imageWidth": 200, "imageHeight": 100,
+                "shapes": [
+                    {"label": "cat", "points": [[20, 10], [60, 50]], "shape_type": "rectangle"},
+                    {"label": "dog", "points": [[100, 50], [110, 50]], "shape_type": "circle"},
+                    {"label": "cow", "points": [[0, 0], [1, 1], [2, 0]]},
+                    {"label": "dog", "points": [[0, 0], [1, 1]], "shape_type": "polygon"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let mut report = ImportReport::default();
+        let objects = parse_shapes(&labelme, &test_meta(), "a.json", &mut report);
+
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects[0].shape.kind(), ShapeKind::Box);
+        let Shape::Ellipse {
+            radius_x, radius_y, ..
+        } = objects[1].shape
+        else {
+            panic!("circle expected");
+        };
+        assert!((radius_x - 0.05).abs() < 1e-6 && (radius_y - 0.1).abs() < 1e-6);
+        assert_eq!(report.unknown_categories["cow"], 1);
+        assert_eq!(report.skipped[0].location, "a.json shape 4");
+    }
+}
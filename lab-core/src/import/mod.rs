@@ -0,0 +1,232 @@
+use crate::{Annotation, Meta, Object, ObjectSource, Point, Provenance, Result, Shape};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub mod coco;
+pub mod labelme;
+pub mod voc;
+pub mod yolo;
+
+/// Import format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    /// YOLO TXT format (`images/` and `labels/`)
+    Yolo,
+    /// YOLO segmentation TXT format, laid out like YOLO
+    YoloSeg,
+    /// YOLO oriented bounding box TXT format, laid out like YOLO
+    YoloObb,
+    /// Pascal VOC XML format (`JPEGImages/` and `Annotations/`)
+    Voc,
+    /// COCO JSON format
+    Coco,
+    /// LabelMe JSON files next to their images
+    LabelMe,
+}
+
+/// Trait for importing annotated datasets from different formats
+pub trait Importer {
+    /// Read the dataset at `source`
+    ///
+    /// Objects are matched to the categories of `meta` and stamped as
+    /// imported by `annotator`; anything that cannot be matched or read is
+    /// left out and listed in the report.
+    fn import(&self, source: &Path, meta: &Meta, annotator: &str) -> Result<ImportedDataset>;
+}
+
+/// Images and annotations read from a dataset
+#[derive(Debug, Clone, Default)]
+pub struct ImportedDataset {
+    pub images: Vec<ImportedImage>,
+    pub report: ImportReport,
+}
+
+/// An image of an imported dataset
+#[derive(Debug, Clone)]
+pub struct ImportedImage {
+    /// Image file in the dataset
+    pub source_path: PathBuf,
+    pub file_name: String,
+    /// None if the image has no objects
+    pub annotation: Option<Annotation>,
+}
+
+/// What an import kept and left out
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportReport {
+    /// Number of objects imported
+    pub objects: usize,
+
+    /// Category ids or names not in the project, with the number of objects using them
+    pub unknown_categories: BTreeMap<String, usize>,
+
+    /// Entries that could not be read
+    pub skipped: Vec<SkippedItem>,
+}
+
+impl ImportReport {
+    /// Whether everything in the dataset was imported
+    pub fn is_complete(&self) -> bool {
+        self.unknown_categories.is_empty() && self.skipped.is_empty()
+    }
+
+    pub(crate) fn unknown_category(&mut self, category: impl ToString) {
+        *self
+            .unknown_categories
+            .entry(category.to_string())
+            .or_default() += 1;
+    }
+
+    pub(crate) fn skip(&mut self, location: impl ToString, reason: impl ToString) {
+        self.skipped.push(SkippedItem {
+            location: location.to_string(),
+            reason: reason.to_string(),
+        });
+    }
+}
+
+/// An entry of a dataset left out of an import
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedItem {
+    /// File, with the line or object where it helps
+    pub location: String,
+    pub reason: String,
+}
+
+/// Image files directly inside a directory, sorted by name
+pub(crate) fn list_images_in_dir(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut images = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let ext = path
+            .extension()
+            .and_then(|s| s.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        if ext == "jpg" || ext == "jpeg" || ext == "png" {
+            images.push(path);
+        }
+    }
+    images.sort();
+    Ok(images)
+}
+
+/// File name and stem of an image path
+pub(crate) fn image_names(path: &Path) -> Result<(String, String)> {
+    let file_name = path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| crate::Error::Import(format!("Invalid image name: {:?}", path)))?
+        .to_string();
+    let stem = Path::new(&file_name)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(&file_name)
+        .to_string();
+    Ok((file_name, stem))
+}
+
+/// Box from normalized corners in any order, None if it has no area
+pub(crate) fn rect_shape(xmin: f32, ymin: f32, xmax: f32, ymax: f32) -> Option<Shape> {
+    let mut xmin = clamp01(xmin);
+    let mut ymin = clamp01(ymin);
+    let mut xmax = clamp01(xmax);
+    let mut ymax = clamp01(ymax);
+    if xmax < xmin {
+        std::mem::swap(&mut xmin, &mut xmax);
+    }
+    if ymax < ymin {
+        std::mem::swap(&mut ymin, &mut ymax);
+    }
+    if xmax <= xmin || ymax <= ymin {
+        return None;
+    }
+    Some(Shape::from_corners(
+        Point::new(xmin, ymin),
+        Point::new(xmax, ymax),
+    ))
+}
+
+/// Clamp a normalized coordinate into the image; NaN, which parses from
+/// "nan" in label files, becomes 0
+pub(crate) fn clamp01(value: f32) -> f32 {
+    if value.is_nan() {
+        0.0
+    } else {
+        value.clamp(0.0, 1.0)
+    }
+}
+
+/// Annotation of imported objects, numbered in order; None if there are none
+pub(crate) fn build_annotation(
+    objects: Vec<Object>,
+    annotator: &str,
+    report: &mut ImportReport,
+) -> Option<Annotation> {
+    if objects.is_empty() {
+        return None;
+    }
+    report.objects += objects.len();
+    let mut annotation = Annotation::new("import");
+    for (idx, mut obj) in objects.into_iter().enumerate() {
+        obj.id = idx as i32;
+        obj.provenance = Some(Provenance::created(annotator, ObjectSource::Imported));
+        annotation.add_object(obj);
+    }
+    Some(annotation)
+}
+
+pub(crate) fn find_category_id_by_name(meta: &Meta, name: &str) -> Option<i32> {
+    meta.categories
+        .iter()
+        .find(|cat| cat.name == name)
+        .map(|cat| cat.id)
+}
+
+/// Project metadata with categories `cat` (1, keypoints `head` and `tail`) and `dog` (2)
+#[cfg(test)]
+pub(crate) fn test_meta() -> Meta {
+    let meta = r##"
+id: 1
+name: test
+description: test
+shape: {title_style: 1, thickness: 2}
+roi: {color: "#800080"}
+categories:
+  - {id: 1, name: cat, description: Cat, hotkey: "1", color: "#FF0000", keypoints: [head, tail]}
+  - {id: 2, name: dog, description: Dog, hotkey: "2", color: "#00FF00"}
+property_types: []
+property_special_values: []
+"##;
+    serde_yaml::from_str(meta).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rect_shape() {
+        let shape = rect_shape(0.5, 0.6, 0.1, -0.2).unwrap();
+        let (min, max) = shape.bounding_box().unwrap();
+        assert_eq!((min, max), (Point::new(0.1, 0.0), Point::new(0.5, 0.6)));
+        assert!(rect_shape(0.2, 0.2, 0.2, 0.5).is_none());
+        assert_eq!(clamp01(f32::NAN), 0.0);
+    }
+
+    #[test]
+    fn test_report() {
+        let mut report = ImportReport::default();
+        assert!(report.is_complete());
+        report.unknown_category(7);
+        report.unknown_category(7);
+        report.skip("labels/a.txt:2", "Expected 5 values");
+        assert_eq!(report.unknown_categories["7"], 2);
+        assert!(!report.is_complete());
+    }
+}
@@ -0,0 +1,167 @@
+use crate::import::{
+    build_annotation, find_category_id_by_name, image_names, list_images_in_dir, rect_shape,
+    ImportReport, ImportedDataset, ImportedImage, Importer,
+};
+use crate::{Error, Meta, Object, Result};
+use std::fs;
+use std::path::Path;
+
+/// Pascal VOC format importer
+///
+/// Reads `JPEGImages/` and `Annotations/` under the dataset root; objects
+/// are matched to categories by name.
+pub struct VocImporter;
+
+impl Importer for VocImporter {
+    fn import(&self, source: &Path, meta: &Meta, annotator: &str) -> Result<ImportedDataset> {
+        let images_dir = source.join("JPEGImages");
+        let labels_dir = source.join("Annotations");
+        if !images_dir.exists() || !labels_dir.exists() {
+            return Err(Error::Import(
+                "VOC root must contain JPEGImages/ and Annotations/ directories".to_string(),
+            ));
+        }
+
+        let mut dataset = ImportedDataset::default();
+        for image_path in list_images_in_dir(&images_dir)? {
+            let (file_name, stem) = image_names(&image_path)?;
+            let label_path = labels_dir.join(format!("{}.xml", stem));
+
+            let mut objects = Vec::new();
+            if label_path.exists() {
+                let xml = fs::read_to_string(&label_path)?;
+                let location = format!("Annotations/{}.xml", stem);
+                objects = parse_annotation(&xml, meta, &location, &mut dataset.report);
+            }
+
+            let annotation = build_annotation(objects, annotator, &mut dataset.report);
+            dataset.images.push(ImportedImage {
+                source_path: image_path,
+                file_name,
+                annotation,
+            });
+        }
+        Ok(dataset)
+    }
+}
+
+/// Objects of a VOC annotation file; `location` names the file in the report
+fn parse_annotation(
+    xml: &str,
+    meta: &Meta,
+    location: &str,
+    report: &mut ImportReport,
+) -> Vec<Object> {
+    let Some((width, height)) = parse_voc_size(xml) else {
+        report.skip(location, "Missing image size");
+        return Vec::new();
+    };
+
+    let mut objects = Vec::new();
+    for (index, voc_obj) in parse_voc_objects(xml).into_iter().enumerate() {
+        let object_location = format!("{} object {}", location, index + 1);
+        let Some(voc_obj) = voc_obj else {
+            report.skip(object_location, "Missing name or bounding box");
+            continue;
+        };
+        let Some(category_id) = find_category_id_by_name(meta, &voc_obj.label) else {
+            report.unknown_category(&voc_obj.label);
+            continue;
+        };
+
+        let xmin = voc_obj.xmin / width;
+        let ymin = voc_obj.ymin / height;
+        let xmax = voc_obj.xmax / width;
+        let ymax = voc_obj.ymax / height;
+        let Some(shape) = rect_shape(xmin, ymin, xmax, ymax) else {
+            report.skip(object_location, "Empty box");
+            continue;
+        };
+        objects.push(Object::with_shape(0, category_id, shape));
+    }
+    objects
+}
+
+fn parse_voc_size(xml: &str) -> Option<(f32, f32)> {
+    let width = extract_tag_value(xml, "width")?.parse::<f32>().ok()?;
+    let height = extract_tag_value(xml, "height")?.parse::<f32>().ok()?;
+    if width <= 0.0 || height <= 0.0 {
+        None
+    } else {
+        Some((width, height))
+    }
+}
+
+/// Every `<object>` block in order, None for blocks missing a field
+fn parse_voc_objects(xml: &str) -> Vec<Option<VocObject>> {
+    let mut objects = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find("<object>") {
+        rest = &rest[start + "<object>".len()..];
+        let end = match rest.find("</object>") {
+            Some(end) => end,
+            None => break,
+        };
+        let block = &rest[..end];
+        rest = &rest[end + "</object>".len()..];
+        objects.push(parse_voc_object(block));
+    }
+    objects
+}
+
+fn parse_voc_object(block: &str) -> Option<VocObject> {
+    let coordinate = |tag| extract_tag_value(block, tag).and_then(|v| v.parse().ok());
+    Some(VocObject {
+        label: extract_tag_value(block, "name")?,
+        xmin: coordinate("xmin")?,
+        ymin: coordinate("ymin")?,
+        xmax: coordinate("xmax")?,
+        ymax: coordinate("ymax")?,
+    })
+}
+
+fn extract_tag_value(content: &str, tag: &str) -> Option<String> {
+    let start_tag = format!("<{}>", tag);
+    let end_tag = format!("</{}>", tag);
+    let start = content.find(&start_tag)? + start_tag.len();
+    let end = content[start..].find(&end_tag)? + start;
+    Some(content[start..end].trim().to_string())
+}
+
+#[derive(Debug)]
+struct VocObject {
+    label: String,
+    xmin: f32,
+    ymin: f32,
+    xmax: f32,
+    ymax: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::test_meta;
+
+    #[test]
+    fn test_parse_annotation() {
+        let xml = r#"<annotation>
+  <size><width>200</width><height>100</height></size>
+  <object><name>dog</name><bndbox><xmin>20</xmin><ymin>10</ymin><xmax>120</xmax><ymax>60</ymax></bndbox></object>
+  <object><name>bird</name><bndbox><xmin>0</xmin><ymin>0</ymin><xmax>10</xmax><ymax>10</ymax></bndbox></object>
+  <object><name>cat</name></object>
+</annotation>"#;
+        let mut report = ImportReport::default();
+        let objects = parse_annotation(xml, &test_meta(), "Annotations/a.xml", &mut report);
+
+        assert_eq!(objects.len(), 1);
+        assert_eq!(objects[0].category, 2);
+        let (min, max) = objects[0].shape.bounding_box().unwrap();
+        assert!((min.x - 0.1).abs() < 1e-6 && (max.y - 0.6).abs() < 1e-6);
+        assert_eq!(report.unknown_categories["bird"], 1);
+        assert_eq!(report.skipped[0].location, "Annotations/a.xml object 3");
+
+        let mut report = ImportReport::default();
+        assert!(parse_annotation("<annotation/>", &test_meta(), "a.xml", &mut report).is_empty());
+        assert_eq!(report.skipped.len(), 1);
+    }
+}
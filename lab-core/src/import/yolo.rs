@@ -0,0 +1,383 @@
+use crate::export::yolo::YoloClasses;
+use crate::import::{
+    build_annotation, clamp01, find_category_id_by_name, image_names, list_images_in_dir,
+    rect_shape, ImportReport, ImportedDataset, ImportedImage, Importer,
+};
+use crate::{Error, Meta, Object, Point, Result, Shape};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// YOLO format importer
+///
+/// Reads `images/` and `labels/` under the dataset root; each label line is
+/// `<class_id> <x_center> <y_center> <width> <height>`, normalized. Split
+/// datasets keep their images in subfolders such as `images/train/`, with
+/// labels in the matching `labels/train/`.
+///
+/// Class names from `data.yaml` or `classes.txt` in the root are matched to
+/// categories by name. Without them, class indices are read the way
+/// [`YoloClasses::with_defaults`] numbers the categories of the project.
+pub struct YoloImporter;
+
+impl Importer for YoloImporter {
+    fn import(&self, source: &Path, meta: &Meta, annotator: &str) -> Result<ImportedDataset> {
+        import_dataset(source, meta, annotator, Task::Detect)
+    }
+}
+
+/// YOLO segmentation format importer
+///
+/// Label lines are `<class_id> <x1> <y1> <x2> <y2> ...`, a normalized
+/// polygon of at least three points.
+pub struct YoloSegImporter;
+
+impl Importer for YoloSegImporter {
+    fn import(&self, source: &Path, meta: &Meta, annotator: &str) -> Result<ImportedDataset> {
+        import_dataset(source, meta, annotator, Task::Segment)
+    }
+}
+
+/// YOLO oriented bounding box format importer
+///
+/// Label lines are `<class_id> <x1> <y1> ... <x4> <y4>`, the normalized
+/// corners of a rotated box.
+pub struct YoloObbImporter;
+
+impl Importer for YoloObbImporter {
+    fn import(&self, source: &Path, meta: &Meta, annotator: &str) -> Result<ImportedDataset> {
+        import_dataset(source, meta, annotator, Task::Obb)
+    }
+}
+
+/// Kind of YOLO label lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Task {
+    Detect,
+    Segment,
+    Obb,
+}
+
+fn import_dataset(
+    source: &Path,
+    meta: &Meta,
+    annotator: &str,
+    task: Task,
+) -> Result<ImportedDataset> {
+    let images_dir = source.join("images");
+    let labels_dir = source.join("labels");
+    if !images_dir.exists() || !labels_dir.exists() {
+        return Err(Error::Import(
+            "YOLO root must contain images/ and labels/ directories".to_string(),
+        ));
+    }
+
+    let classes = class_map(source, meta)?;
+    let mut dataset = ImportedDataset::default();
+    for split in image_folders(&images_dir)? {
+        let labels_dir = labels_dir.join(&split);
+        for image_path in list_images_in_dir(&images_dir.join(&split))? {
+            let (file_name, stem) = image_names(&image_path)?;
+            let label_path = labels_dir.join(format!("{}.txt", stem));
+
+            let mut objects = Vec::new();
+            if label_path.exists() {
+                let content = fs::read_to_string(&label_path)?;
+                let location = if split.is_empty() {
+                    format!("labels/{}.txt", stem)
+                } else {
+                    format!("labels/{}/{}.txt", split, stem)
+                };
+                objects = parse_labels(&content, task, &classes, &location, &mut dataset.report);
+            }
+
+            let annotation = build_annotation(objects, annotator, &mut dataset.report);
+            dataset.images.push(ImportedImage {
+                source_path: image_path,
+                file_name,
+                annotation,
+            });
+        }
+    }
+    Ok(dataset)
+}
+
+/// Folders of `images_dir` holding images: the folder itself, then its
+/// subfolders (the splits) by name
+fn image_folders(images_dir: &Path) -> Result<Vec<String>> {
+    let mut splits = Vec::new();
+    for entry in fs::read_dir(images_dir)? {
+        let entry = entry?;
+        if entry.path().is_dir() {
+            if let Some(name) = entry.file_name().to_str() {
+                splits.push(name.to_string());
+            }
+        }
+    }
+    splits.sort();
+    splits.insert(0, String::new());
+    Ok(splits)
+}
+
+/// A class of a dataset: its name and the matching project category
+type ClassTarget = (String, Option<i32>);
+
+/// Classes of a dataset by index
+///
+/// Kept sparse: `data.yaml` may name classes by arbitrary indices.
+type ClassMap = BTreeMap<usize, ClassTarget>;
+
+/// Class of each index of the dataset at `source`
+fn class_map(source: &Path, meta: &Meta) -> Result<ClassMap> {
+    let names = match read_class_names(source)? {
+        Some(names) => names,
+        None => {
+            let classes = YoloClasses::with_defaults(meta);
+            return Ok(classes
+                .names()
+                .enumerate()
+                .map(|(index, name)| (index, (name.to_string(), classes.category(index))))
+                .collect());
+        }
+    };
+    Ok(names
+        .into_iter()
+        .map(|(index, name)| {
+            let category = find_category_id_by_name(meta, &name);
+            (index, (name, category))
+        })
+        .collect())
+}
+
+/// Class names of `data.yaml`, or else of `classes.txt`, if either exists
+fn read_class_names(source: &Path) -> Result<Option<BTreeMap<usize, String>>> {
+    let data_path = source.join("data.yaml");
+    if data_path.exists() {
+        let data: DataYaml = serde_yaml::from_str(&fs::read_to_string(&data_path)?)?;
+        let names = match data.names {
+            ClassNames::List(names) => names.into_iter().enumerate().collect(),
+            ClassNames::Map(names) => names,
+        };
+        return Ok(Some(names));
+    }
+
+    let classes_path = source.join("classes.txt");
+    if classes_path.exists() {
+        let content = fs::read_to_string(&classes_path)?;
+        let names = content
+            .lines()
+            .map(|line| line.trim().to_string())
+            .enumerate()
+            .collect();
+        return Ok(Some(names));
+    }
+    Ok(None)
+}
+
+/// The part of an Ultralytics `data.yaml` naming the classes
+#[derive(Debug, Deserialize)]
+struct DataYaml {
+    names: ClassNames,
+}
+
+/// Class names as a list, or as a map from index to name
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ClassNames {
+    List(Vec<String>),
+    Map(BTreeMap<usize, String>),
+}
+
+/// Objects of a YOLO label file; `location` names the file in the report
+fn parse_labels(
+    content: &str,
+    task: Task,
+    classes: &ClassMap,
+    location: &str,
+    report: &mut ImportReport,
+) -> Vec<Object> {
+    let mut objects = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line_location = format!("{}:{}", location, index + 1);
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let expected = match task {
+            Task::Detect => parts.len() == 5,
+            Task::Segment => parts.len() >= 7 && parts.len() % 2 == 1,
+            Task::Obb => parts.len() == 9,
+        };
+        if !expected {
+            let values = match task {
+                Task::Detect => "5 values",
+                Task::Segment => "a class id and at least 3 points",
+                Task::Obb => "9 values",
+            };
+            report.skip(
+                line_location,
+                format!("Expected {}, found {} values", values, parts.len()),
+            );
+            continue;
+        }
+        let Ok(class) = parts[0].parse::<usize>() else {
+            report.skip(line_location, format!("Invalid class id {}", parts[0]));
+            continue;
+        };
+        let category = match classes.get(&class) {
+            Some((_, Some(category))) => *category,
+            Some((name, None)) if !name.is_empty() => {
+                report.unknown_category(name);
+                continue;
+            }
+            _ => {
+                report.unknown_category(class);
+                continue;
+            }
+        };
+        let values: Vec<f32> = parts[1..].iter().filter_map(|v| v.parse().ok()).collect();
+        if values.len() != parts.len() - 1 {
+            report.skip(line_location, "Invalid coordinates");
+            continue;
+        }
+
+        let shape = match task {
+            Task::Detect => {
+                let (x_center, y_center) = (values[0], values[1]);
+                let (width, height) = (values[2], values[3]);
+                let xmin = x_center - width / 2.0;
+                let ymin = y_center - height / 2.0;
+                let xmax = x_center + width / 2.0;
+                let ymax = y_center + height / 2.0;
+                rect_shape(xmin, ymin, xmax, ymax)
+            }
+            Task::Segment => Some(Shape::polygon(
+                values
+                    .chunks_exact(2)
+                    .map(|p| Point::new(clamp01(p[0]), clamp01(p[1])))
+                    .collect(),
+            )),
+            // Corners are kept as given: clamping would bend the rectangle
+            Task::Obb => {
+                let corner = |i: usize| Point::new(values[2 * i], values[2 * i + 1]);
+                Some(Shape::RotatedBox {
+                    corners: [corner(0), corner(1), corner(2), corner(3)],
+                })
+            }
+        };
+        let Some(shape) = shape.filter(|shape| shape.is_valid() && shape.area() > 0.0) else {
+            report.skip(line_location, "Empty shape");
+            continue;
+        };
+        objects.push(Object::with_shape(0, category, shape));
+    }
+    objects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::test_meta;
+
+    /// Classes of a dataset without class names: cat is 0 and dog is 1
+    fn test_classes() -> ClassMap {
+        class_map(Path::new("/nonexistent"), &test_meta()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_labels() {
+        let content =
+            "0 0.5 0.5 0.2 0.4\n\n3 0.5 0.5 0.1 0.1\n0 0.5 0.5\n0 0.5 x 0.1 0.1\n0 0.5 0.5 0 0.1\n";
+        let mut report = ImportReport::default();
+        let objects = parse_labels(
+            content,
+            Task::Detect,
+            &test_classes(),
+            "labels/a.txt",
+            &mut report,
+        );
+
+        assert_eq!(objects.len(), 1);
+        assert_eq!(objects[0].category, 1);
+        let (min, max) = objects[0].shape.bounding_box().unwrap();
+        assert!((min.x - 0.4).abs() < 1e-6 && (max.y - 0.7).abs() < 1e-6);
+        assert_eq!(report.unknown_categories["3"], 1);
+        let locations: Vec<_> = report.skipped.iter().map(|s| s.location.as_str()).collect();
+        assert_eq!(
+            locations,
+            ["labels/a.txt:4", "labels/a.txt:5", "labels/a.txt:6"]
+        );
+    }
+
+    #[test]
+    fn test_parse_seg_and_obb_labels() {
+        let mut report = ImportReport::default();
+        let content = "0 0.1 0.1 0.5 0.1 0.5 0.5\n1 0.1 0.1 0.5 0.1\n1 0 0 0.5 0 1 0\n";
+        let objects = parse_labels(
+            content,
+            Task::Segment,
+            &test_classes(),
+            "a.txt",
+            &mut report,
+        );
+        assert_eq!(objects.len(), 1);
+        assert_eq!(objects[0].shape.vertices().len(), 3);
+        assert_eq!(report.skipped.len(), 2);
+
+        let mut report = ImportReport::default();
+        let content = "1 0.5 0 0.75 0.5 0.5 1 0.25 0.5\n1 0.1 0.1 0.5 0.1 0.5 0.5\n";
+        let objects = parse_labels(content, Task::Obb, &test_classes(), "a.txt", &mut report);
+        assert_eq!(objects.len(), 1);
+        assert_eq!(objects[0].category, 2);
+        assert_eq!(objects[0].shape.kind(), crate::ShapeKind::RotatedBox);
+        assert_eq!(report.skipped.len(), 1);
+    }
+
+    #[test]
+    fn test_class_map() {
+        let root = std::env::temp_dir().join("jlab_test_yolo_class_map");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let meta = test_meta();
+
+        fs::write(root.join("classes.txt"), "dog\nbird\n").unwrap();
+        let classes = class_map(&root, &meta).unwrap();
+        assert_eq!(
+            classes.into_values().collect::<Vec<_>>(),
+            [("dog".to_string(), Some(2)), ("bird".to_string(), None)]
+        );
+
+        // data.yaml takes precedence, with names as a list or by index
+        fs::write(
+            root.join("data.yaml"),
+            "nc: 3\nnames: {0: cat, 2: dog, 4000000000: bird}\n",
+        )
+        .unwrap();
+        let classes = class_map(&root, &meta).unwrap();
+        assert_eq!(classes.len(), 3);
+        assert_eq!(classes[&0], ("cat".to_string(), Some(1)));
+        assert_eq!(classes[&2], ("dog".to_string(), Some(2)));
+        assert!(!classes.contains_key(&1));
+        let data = YoloClasses::new(&meta, &[1])
+            .data_yaml("images/train", "images/val", None)
+            .unwrap();
+        fs::write(root.join("data.yaml"), data).unwrap();
+        let classes = class_map(&root, &meta).unwrap();
+        assert_eq!(classes.len(), 1);
+        assert_eq!(classes[&0], ("dog".to_string(), Some(2)));
+
+        let mut report = ImportReport::default();
+        let objects = parse_labels(
+            "1 0.5 0.5 0.2 0.2\n",
+            Task::Detect,
+            &classes,
+            "a",
+            &mut report,
+        );
+        assert!(objects.is_empty());
+        assert_eq!(report.unknown_categories["1"], 1);
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
@@ -1,5 +1,6 @@
-use crate::{Annotation, Meta, Result};
+use crate::{Annotation, Error, Meta, Result, StorageFormat};
 use std::fs;
+use std::io::Write;
 use std::path::Path;
 
 /// Load metadata from a YAML file
@@ -12,22 +13,60 @@ pub fn load_meta<P: AsRef<Path>>(path: P) -> Result<Meta> {
 /// Save metadata to a YAML file
 pub fn save_meta<P: AsRef<Path>>(path: P, meta: &Meta) -> Result<()> {
     let yaml = serde_yaml::to_string(meta)?;
-    fs::write(path, yaml)?;
-    Ok(())
+    write_atomic(path, yaml.as_bytes())
 }
 
-/// Load annotation from a YAML file
+/// Replace a file's content so that it is never seen half written
+///
+/// The content goes to a temporary file next to `path`, is flushed to disk
+/// and then renamed over `path`; after a crash or a failed write the old
+/// file is left untouched.
+pub fn write_atomic<P: AsRef<Path>>(path: P, content: &[u8]) -> Result<()> {
+    let path = path.as_ref();
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| Error::InvalidData(format!("Not a file path: {:?}", path)))?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = (|| {
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        fs::rename(&temp_path, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    Ok(result?)
+}
+
+/// Load an annotation file, migrating older schema versions
+///
+/// The encoding follows the file extension; unknown extensions are read as YAML.
 pub fn load_annotation<P: AsRef<Path>>(path: P) -> Result<Annotation> {
-    let content = fs::read_to_string(path)?;
-    let annotation: Annotation = serde_yaml::from_str(&content)?;
-    Ok(annotation)
+    Ok(load_annotation_with_status(path)?.0)
+}
+
+/// Load an annotation file, also returning whether it was migrated from an
+/// older schema version
+pub fn load_annotation_with_status<P: AsRef<Path>>(path: P) -> Result<(Annotation, bool)> {
+    let path = path.as_ref();
+    let content = fs::read(path)?;
+    storage_format(path).storage().decode(&content)
 }
 
-/// Save annotation to a YAML file
+/// Save an annotation file in the encoding given by its extension
 pub fn save_annotation<P: AsRef<Path>>(path: P, annotation: &Annotation) -> Result<()> {
-    let yaml = serde_yaml::to_string(annotation)?;
-    fs::write(path, yaml)?;
-    Ok(())
+    let path = path.as_ref();
+    let content = storage_format(path).storage().encode(annotation)?;
+    write_atomic(path, &content)
+}
+
+fn storage_format(path: &Path) -> StorageFormat {
+    StorageFormat::from_path(path).unwrap_or_default()
 }
 
 #[cfg(test)]
@@ -67,4 +106,23 @@ mod tests {
         // Cleanup
         let _ = fs::remove_file(test_file);
     }
+
+    #[test]
+    fn test_write_atomic() {
+        let dir = std::env::temp_dir().join("jlab_test_write_atomic");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("label.yaml");
+
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        // A failed write keeps the old content and leaves no temporary file
+        assert!(write_atomic(dir.join("missing/label.yaml"), b"third").is_err());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        let _ = fs::remove_dir_all(dir);
+    }
 }
@@ -4,15 +4,33 @@
 //! including metadata, annotation results, and geometry primitives.
 
 pub mod annotation;
+pub mod color;
+pub mod diagnostic;
 pub mod error;
 pub mod export;
 pub mod geometry;
+pub mod import;
 pub mod io;
+pub mod mask;
 pub mod meta;
+pub mod migrate;
+pub mod orientation;
+pub mod shape;
+pub mod storage;
 
-pub use annotation::{Annotation, Object, PropertyValueWithConfidence};
+pub use annotation::{
+    Annotation, ImageInfo, Keypoint, KeypointVisibility, Object, ObjectSource, PropertyData,
+    PropertyValueWithConfidence, Provenance, ReviewState,
+};
+pub use color::Color;
+pub use diagnostic::{Diagnostic, Diagnostics, Severity};
 pub use error::{Error, Result};
-pub use geometry::{Point, Polygon};
+pub use geometry::{MultiPolygon, Point, Polygon};
+pub use mask::Mask;
 pub use meta::{
-    Category, Meta, PropertySpecialValue, PropertyType, PropertyValue, RoiConfig, ShapeConfig,
+    Category, Meta, PropertyKind, PropertySpecialValue, PropertyType, PropertyValue, RoiConfig,
+    ShapeConfig,
 };
+pub use orientation::Orientation;
+pub use shape::{Shape, ShapeKind};
+pub use storage::StorageFormat;
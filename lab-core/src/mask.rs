@@ -0,0 +1,591 @@
+use crate::error::{Error, Result};
+use crate::geometry::{MultiPolygon, Point, Polygon};
+use crate::orientation::Orientation;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Binary segmentation mask stored as COCO-style run-length encoding
+///
+/// Pixels are counted in column-major order and runs alternate between
+/// background and foreground, starting with background, exactly like
+/// uncompressed COCO RLE. The mask has its own pixel size, normally the
+/// size of the image it was painted on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RleRecord", into = "RleRecord")]
+pub struct Mask {
+    width: u32,
+    height: u32,
+    counts: Vec<u32>,
+}
+
+/// Serialized form of a mask; `size` is [height, width] as in COCO
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RleRecord {
+    size: [u32; 2],
+    counts: Vec<u32>,
+}
+
+impl TryFrom<RleRecord> for Mask {
+    type Error = Error;
+
+    fn try_from(record: RleRecord) -> Result<Self> {
+        let [height, width] = record.size;
+        Self::from_counts(width, height, record.counts)
+    }
+}
+
+impl From<Mask> for RleRecord {
+    fn from(mask: Mask) -> Self {
+        Self {
+            size: [mask.height, mask.width],
+            counts: mask.counts,
+        }
+    }
+}
+
+/// Most pixels a mask may have, 16384x16384
+///
+/// Masks are decoded into one byte per pixel for editing, so sizes read
+/// from files are checked against this before anything is allocated.
+pub const MAX_PIXELS: u64 = 1 << 28;
+
+impl Mask {
+    /// Number of pixels of a mask size, failing for sizes above [`MAX_PIXELS`]
+    pub fn check_size(width: u32, height: u32) -> Result<usize> {
+        let total = width as u64 * height as u64;
+        if total > MAX_PIXELS {
+            return Err(Error::InvalidData(format!(
+                "Mask size {}x{} is too large",
+                width, height
+            )));
+        }
+        Ok(total as usize)
+    }
+
+    /// Create an empty mask
+    pub fn new(width: u32, height: u32) -> Result<Self> {
+        let total = Self::check_size(width, height)? as u32;
+        Ok(Self {
+            width,
+            height,
+            counts: if total > 0 { vec![total] } else { Vec::new() },
+        })
+    }
+
+    /// Create a mask from uncompressed COCO RLE counts
+    pub fn from_counts(width: u32, height: u32, counts: Vec<u32>) -> Result<Self> {
+        Self::check_size(width, height)?;
+        let total: u64 = counts.iter().map(|&c| c as u64).sum();
+        if total != width as u64 * height as u64 {
+            return Err(Error::InvalidData(format!(
+                "RLE counts cover {} pixels, expected {}x{}",
+                total, width, height
+            )));
+        }
+        Ok(Self {
+            width,
+            height,
+            counts,
+        })
+    }
+
+    /// Create a mask from compressed COCO RLE counts (the string form)
+    pub fn from_compressed_counts(width: u32, height: u32, encoded: &str) -> Result<Self> {
+        let bytes = encoded.as_bytes();
+        let mut counts: Vec<i64> = Vec::new();
+        let mut pos = 0;
+        while pos < bytes.len() {
+            let mut value: i64 = 0;
+            let mut shift = 0;
+            loop {
+                let Some(&byte) = bytes.get(pos) else {
+                    return Err(Error::InvalidData("Truncated compressed RLE".to_string()));
+                };
+                // A count is at most 64 bits; longer ones are corrupt
+                if shift >= 64 {
+                    return Err(Error::InvalidData(
+                        "Overlong count in compressed RLE".to_string(),
+                    ));
+                }
+                let chunk = byte as i64 - 48;
+                value |= (chunk & 0x1f) << shift;
+                pos += 1;
+                shift += 5;
+                if chunk & 0x20 == 0 {
+                    if chunk & 0x10 != 0 && shift < 64 {
+                        value |= -1 << shift;
+                    }
+                    break;
+                }
+            }
+            // Counts after the first two are stored relative to two runs back
+            if counts.len() > 2 {
+                value = value.checked_add(counts[counts.len() - 2]).ok_or_else(|| {
+                    Error::InvalidData("Count overflow in compressed RLE".to_string())
+                })?;
+            }
+            counts.push(value);
+        }
+
+        let counts = counts
+            .into_iter()
+            .map(u32::try_from)
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| Error::InvalidData("Negative run in compressed RLE".to_string()))?;
+        Self::from_counts(width, height, counts)
+    }
+
+    /// Create a mask from a column-major bitmap (`bits[x * height + y]`)
+    pub fn from_bitmap(width: u32, height: u32, bits: &[bool]) -> Self {
+        let mut counts = Vec::new();
+        let mut current = false;
+        let mut run = 0;
+        for &bit in bits {
+            if bit != current {
+                counts.push(run);
+                current = bit;
+                run = 0;
+            }
+            run += 1;
+        }
+        if !bits.is_empty() {
+            counts.push(run);
+        }
+        Self {
+            width,
+            height,
+            counts,
+        }
+    }
+
+    /// Rasterize normalized rings with the even-odd rule, sampling pixel centers
+    ///
+    /// Holes are simply further rings, so polygons with holes and
+    /// multi-part shapes can be passed as all of their rings.
+    pub fn from_rings(rings: &[Vec<Point>], width: u32, height: u32) -> Result<Self> {
+        let mut bits = vec![false; Self::check_size(width, height)?];
+        let mut crossings = Vec::new();
+        for y in 0..height {
+            let sample = y as f32 + 0.5;
+            crossings.clear();
+            for ring in rings.iter().filter(|ring| ring.len() >= 3) {
+                for i in 0..ring.len() {
+                    let (ax, ay) = ring[i].to_pixel(width, height);
+                    let (bx, by) = ring[(i + 1) % ring.len()].to_pixel(width, height);
+                    if (ay <= sample) != (by <= sample) {
+                        crossings.push(ax + (sample - ay) * (bx - ax) / (by - ay));
+                    }
+                }
+            }
+            crossings.sort_by(f32::total_cmp);
+
+            for pair in crossings.chunks_exact(2) {
+                let start = (pair[0] - 0.5).ceil().clamp(0.0, width as f32) as u32;
+                let end = (pair[1] - 0.5).ceil().clamp(0.0, width as f32) as u32;
+                for x in start..end {
+                    bits[(x * height + y) as usize] = true;
+                }
+            }
+        }
+        Ok(Self::from_bitmap(width, height, &bits))
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Uncompressed COCO RLE counts
+    pub fn counts(&self) -> &[u32] {
+        &self.counts
+    }
+
+    /// Decode the mask into a column-major bitmap (`bits[x * height + y]`)
+    pub fn to_bitmap(&self) -> Vec<bool> {
+        let mut bits = Vec::with_capacity(self.width as usize * self.height as usize);
+        for (index, &count) in self.counts.iter().enumerate() {
+            bits.extend(std::iter::repeat_n(index % 2 == 1, count as usize));
+        }
+        bits
+    }
+
+    /// Check whether no pixel is set
+    pub fn is_empty(&self) -> bool {
+        self.pixel_count() == 0
+    }
+
+    /// Number of foreground pixels
+    pub fn pixel_count(&self) -> u64 {
+        self.counts
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .map(|&c| c as u64)
+            .sum()
+    }
+
+    /// Foreground area as a fraction of the mask, like normalized polygon areas
+    pub fn area(&self) -> f32 {
+        let total = self.width as u64 * self.height as u64;
+        if total == 0 {
+            return 0.0;
+        }
+        self.pixel_count() as f32 / total as f32
+    }
+
+    /// Check whether the pixel at (x, y) is set
+    pub fn get(&self, x: u32, y: u32) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        let target = x * self.height + y;
+        let mut start = 0;
+        for (index, &count) in self.counts.iter().enumerate() {
+            if target < start + count {
+                return index % 2 == 1;
+            }
+            start += count;
+        }
+        false
+    }
+
+    /// Check whether a normalized point falls on a set pixel
+    pub fn contains(&self, point: &Point) -> bool {
+        if point.x < 0.0 || point.y < 0.0 {
+            return false;
+        }
+        let (x, y) = point.to_pixel(self.width, self.height);
+        self.get(x as u32, y as u32)
+    }
+
+    /// Vertical strips of set pixels as (x, y_start, y_end), end exclusive
+    pub fn foreground_runs(&self) -> Vec<(u32, u32, u32)> {
+        let mut runs = Vec::new();
+        if self.height == 0 {
+            return runs;
+        }
+        let mut start = 0;
+        for (index, &count) in self.counts.iter().enumerate() {
+            let end = start + count;
+            if index % 2 == 1 {
+                // Split runs that wrap into the next column
+                let mut pos = start;
+                while pos < end {
+                    let x = pos / self.height;
+                    let column_end = ((x + 1) * self.height).min(end);
+                    runs.push((x, pos % self.height, pos % self.height + column_end - pos));
+                    pos = column_end;
+                }
+            }
+            start = end;
+        }
+        runs
+    }
+
+    /// Normalized bounding box of the set pixels as (min, max) corners
+    pub fn bounding_box(&self) -> Option<(Point, Point)> {
+        let mut bounds: Option<(u32, u32, u32, u32)> = None;
+        for (x, y0, y1) in self.foreground_runs() {
+            let (min_x, min_y, max_x, max_y) = bounds.get_or_insert((x, y0, x, y1));
+            *min_x = (*min_x).min(x);
+            *min_y = (*min_y).min(y0);
+            *max_x = (*max_x).max(x);
+            *max_y = (*max_y).max(y1);
+        }
+        bounds.map(|(min_x, min_y, max_x, max_y)| {
+            (
+                Point::from_pixel(min_x as f32, min_y as f32, self.width, self.height),
+                Point::from_pixel((max_x + 1) as f32, max_y as f32, self.width, self.height),
+            )
+        })
+    }
+
+    /// Paint (or erase) a stroke of round brush stamps between two normalized points
+    ///
+    /// `radius` is given in mask pixels.
+    pub fn paint_stroke(&mut self, from: Point, to: Point, radius: f32, value: bool) {
+        let radius = radius.max(0.5);
+        let mut bits = self.to_bitmap();
+        let (x0, y0) = from.to_pixel(self.width, self.height);
+        let (x1, y1) = to.to_pixel(self.width, self.height);
+        let length = ((x1 - x0).powi(2) + (y1 - y0).powi(2)).sqrt();
+        let steps = (length / (radius * 0.5)).ceil().max(1.0) as u32;
+
+        for step in 0..=steps {
+            let t = step as f32 / steps as f32;
+            let (cx, cy) = (x0 + (x1 - x0) * t, y0 + (y1 - y0) * t);
+            let min_x = (cx - radius).floor().max(0.0) as u32;
+            let max_x = ((cx + radius).ceil().max(0.0) as u32).min(self.width);
+            let min_y = (cy - radius).floor().max(0.0) as u32;
+            let max_y = ((cy + radius).ceil().max(0.0) as u32).min(self.height);
+            for x in min_x..max_x {
+                for y in min_y..max_y {
+                    let dx = x as f32 + 0.5 - cx;
+                    let dy = y as f32 + 0.5 - cy;
+                    if dx * dx + dy * dy <= radius * radius {
+                        bits[(x * self.height + y) as usize] = value;
+                    }
+                }
+            }
+        }
+
+        *self = Self::from_bitmap(self.width, self.height, &bits);
+    }
+
+    /// Resample the mask to another size (nearest neighbour)
+    pub fn resized(&self, width: u32, height: u32) -> Result<Self> {
+        if width == self.width && height == self.height {
+            return Ok(self.clone());
+        }
+        let mut bits = vec![false; Self::check_size(width, height)?];
+        let source = self.to_bitmap();
+        for x in 0..width {
+            let sx = ((x as f32 + 0.5) * self.width as f32 / width as f32) as u32;
+            for y in 0..height {
+                let sy = ((y as f32 + 0.5) * self.height as f32 / height as f32) as u32;
+                if sx < self.width && sy < self.height {
+                    bits[(x * height + y) as usize] = source[(sx * self.height + sy) as usize];
+                }
+            }
+        }
+        Ok(Self::from_bitmap(width, height, &bits))
+    }
+
+    /// The mask as seen after an orientation transform, see [`Orientation`]
+    pub fn reoriented(&self, orientation: Orientation) -> Self {
+        if orientation.is_normal() {
+            return self.clone();
+        }
+        let (width, height) = orientation.apply_size(self.width, self.height);
+        let source = self.to_bitmap();
+        let inverse = orientation.inverse();
+        // Same number of pixels as this mask, just transposed for quarter turns
+        let mut bits = vec![false; source.len()];
+        for x in 0..width {
+            for y in 0..height {
+                let center = Point::from_pixel(x as f32 + 0.5, y as f32 + 0.5, width, height);
+                let (sx, sy) = inverse.apply(center).to_pixel(self.width, self.height);
+                let (sx, sy) = (sx as u32, sy as u32);
+                if sx < self.width && sy < self.height {
+                    bits[(x * height + y) as usize] = source[(sx * self.height + sy) as usize];
+                }
+            }
+        }
+        Self::from_bitmap(width, height, &bits)
+    }
+
+    /// Trace the pixel boundaries of the mask into polygons with holes
+    ///
+    /// Each 4-connected region becomes one part; the rings follow pixel
+    /// edges exactly, so converting back with [`Mask::from_rings`] gives
+    /// the same mask.
+    pub fn to_multi_polygon(&self) -> MultiPolygon {
+        let (width, height) = (self.width as i64, self.height as i64);
+        let bits = self.to_bitmap();
+        let set = |x: i64, y: i64| {
+            x >= 0 && y >= 0 && x < width && y < height && bits[(x * height + y) as usize]
+        };
+
+        // Directed pixel edges with the foreground on their right
+        let mut edges = Vec::new();
+        for x in 0..width {
+            for y in 0..height {
+                if !set(x, y) {
+                    continue;
+                }
+                if !set(x, y - 1) {
+                    edges.push(((x, y), (x + 1, y)));
+                }
+                if !set(x + 1, y) {
+                    edges.push(((x + 1, y), (x + 1, y + 1)));
+                }
+                if !set(x, y + 1) {
+                    edges.push(((x + 1, y + 1), (x, y + 1)));
+                }
+                if !set(x - 1, y) {
+                    edges.push(((x, y + 1), (x, y)));
+                }
+            }
+        }
+        let mut outgoing: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+        for (index, (start, _)) in edges.iter().enumerate() {
+            outgoing.entry(*start).or_default().push(index);
+        }
+        let direction = |index: usize| {
+            let ((x0, y0), (x1, y1)) = edges[index];
+            (x1 - x0, y1 - y0)
+        };
+
+        let mut used = vec![false; edges.len()];
+        let mut outers = Vec::new();
+        let mut holes = Vec::new();
+        for first in 0..edges.len() {
+            if used[first] {
+                continue;
+            }
+            let mut ring = Vec::new();
+            let mut current = first;
+            loop {
+                used[current] = true;
+                ring.push(edges[current].0);
+                // Turning right keeps diagonal pixels apart and rings simple
+                let (dx, dy) = direction(current);
+                let right = (-dy, dx);
+                let next = outgoing[&edges[current].1]
+                    .iter()
+                    .copied()
+                    .filter(|&edge| !used[edge])
+                    .min_by_key(|&edge| u8::from(direction(edge) != right));
+                match next {
+                    Some(edge) => current = edge,
+                    None => break,
+                }
+            }
+            let ring = remove_collinear(ring);
+
+            if signed_area(&ring) > 0 {
+                outers.push(ring);
+            } else {
+                holes.push(ring);
+            }
+        }
+
+        let to_points = |ring: &[(i64, i64)]| -> Vec<Point> {
+            ring.iter()
+                .map(|&(x, y)| Point::from_pixel(x as f32, y as f32, self.width, self.height))
+                .collect()
+        };
+        let pixel_polygon = |ring: &[(i64, i64)]| {
+            Polygon::new(
+                ring.iter()
+                    .map(|&(x, y)| Point::new(x as f32, y as f32))
+                    .collect(),
+            )
+        };
+
+        let outer_polygons: Vec<Polygon> = outers.iter().map(|ring| pixel_polygon(ring)).collect();
+        let mut polygons: Vec<Polygon> = outers
+            .iter()
+            .map(|ring| Polygon::new(to_points(ring)))
+            .collect();
+        for hole in holes {
+            // The centre of a background pixel just inside the hole's first edge
+            let ((x0, y0), (x1, y1)) = (hole[0], hole[1 % hole.len()]);
+            let (dx, dy) = ((x1 - x0).signum(), (y1 - y0).signum());
+            let probe = Point::new(
+                x0 as f32 + (dx + dy) as f32 * 0.5,
+                y0 as f32 + (dy - dx) as f32 * 0.5,
+            );
+            let owner = outer_polygons
+                .iter()
+                .enumerate()
+                .filter(|(_, polygon)| polygon.contains(&probe))
+                .min_by(|(_, a), (_, b)| a.area().total_cmp(&b.area()))
+                .map(|(index, _)| index);
+            if let Some(index) = owner {
+                polygons[index].holes.push(to_points(&hole));
+            }
+        }
+
+        MultiPolygon::new(polygons)
+    }
+}
+
+/// Drop ring vertices that lie on a straight line between their neighbours
+fn remove_collinear(ring: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+    let len = ring.len();
+    (0..len)
+        .filter(|&i| {
+            let (px, py) = ring[(i + len - 1) % len];
+            let (cx, cy) = ring[i];
+            let (nx, ny) = ring[(i + 1) % len];
+            ((cx - px).signum(), (cy - py).signum()) != ((nx - cx).signum(), (ny - cy).signum())
+        })
+        .map(|i| ring[i])
+        .collect()
+}
+
+/// Twice the signed shoelace area of an integer ring
+fn signed_area(ring: &[(i64, i64)]) -> i64 {
+    (0..ring.len())
+        .map(|i| {
+            let (x0, y0) = ring[i];
+            let (x1, y1) = ring[(i + 1) % ring.len()];
+            x0 * y1 - x1 * y0
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_rle_round_trip() {
+        let mut mask = Mask::new(4, 3).unwrap();
+        assert!(mask.is_empty());
+        mask.paint_stroke(Point::new(0.375, 0.5), Point::new(0.375, 0.5), 0.5, true);
+        assert_eq!(mask.pixel_count(), 1);
+        assert!(mask.get(1, 1));
+        assert_eq!(mask.counts(), &[4, 1, 7]);
+
+        let yaml = serde_yaml::to_string(&mask).unwrap();
+        assert!(yaml.contains("size:"));
+        assert_eq!(serde_yaml::from_str::<Mask>(&yaml).unwrap(), mask);
+        assert!(serde_yaml::from_str::<Mask>("size: [3, 4]\ncounts: [4, 1]").is_err());
+
+        // Compressed COCO string for the same runs
+        assert_eq!(Mask::from_compressed_counts(4, 3, "417").unwrap(), mask);
+    }
+
+    #[test]
+    fn test_mask_hostile_input() {
+        // Continuation bytes without end, and two i64::MAX counts whose sum overflows
+        let overlong = "o".repeat(20);
+        assert!(Mask::from_compressed_counts(4, 3, &overlong).is_err());
+        let max = format!("{}7", "o".repeat(12));
+        let overflow = format!("0{}0{}", max, max);
+        assert!(Mask::from_compressed_counts(4, 3, &overflow).is_err());
+
+        assert!(Mask::new(100_000, 100_000).is_err());
+        assert!(Mask::from_counts(u32::MAX, u32::MAX, vec![]).is_err());
+        let mask = Mask::new(4, 3).unwrap();
+        assert!(mask.resized(70_000, 70_000).is_err());
+        assert!(Mask::from_rings(&[], 70_000, 70_000).is_err());
+    }
+
+    #[test]
+    fn test_mask_polygon_conversion() {
+        let ring = |min: f32, max: f32| {
+            vec![
+                Point::new(min, min),
+                Point::new(max, min),
+                Point::new(max, max),
+                Point::new(min, max),
+            ]
+        };
+        // A 10x10 square with a 2x2 hole, plus a separate 2x2 square
+        let rings = [ring(0.0, 0.5), ring(0.2, 0.3), ring(0.7, 0.8)];
+        let mask = Mask::from_rings(&rings, 20, 20).unwrap();
+        assert_eq!(mask.pixel_count(), 100 - 4 + 4);
+
+        let polygons = mask.to_multi_polygon();
+        assert_eq!(polygons.polygons.len(), 2);
+        assert_eq!(polygons.polygons[0].points.len(), 4);
+        assert_eq!(polygons.polygons[0].holes.len(), 1);
+        assert!((polygons.area() - mask.area()).abs() < 1e-6);
+
+        let all_rings: Vec<Vec<Point>> = polygons
+            .polygons
+            .iter()
+            .flat_map(|polygon| polygon.rings().cloned())
+            .collect();
+        assert_eq!(Mask::from_rings(&all_rings, 20, 20).unwrap(), mask);
+
+        let (min, max) = mask.bounding_box().unwrap();
+        assert_eq!((min, max), (Point::new(0.0, 0.0), Point::new(0.8, 0.8)));
+    }
+}
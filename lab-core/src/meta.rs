@@ -1,4 +1,12 @@
+use crate::annotation::PropertyData;
+use crate::color::Color;
+use crate::diagnostic::Diagnostics;
+use crate::storage::StorageFormat;
+use crate::{Error, Result};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::hash::Hash;
 
 /// Main metadata structure for an annotation project
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +29,19 @@ pub struct Meta {
 
     /// Special property values (error, excluded, pending, etc.)
     pub property_special_values: Vec<PropertySpecialValue>,
+
+    /// Image-level properties (scene, weather, verified empty, etc.)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub image_properties: Vec<CategoryProperty>,
+
+    /// Encoding of the annotation files under `labels/`
+    #[serde(default, skip_serializing_if = "StorageFormat::is_default")]
+    pub storage: StorageFormat,
+
+    /// Number of previous versions of each annotation file kept under
+    /// `.jlab/backups/`; no backups if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backups: Option<usize>,
 }
 
 impl Meta {
@@ -38,6 +59,311 @@ impl Meta {
     pub fn find_special_value(&self, id: i32) -> Option<&PropertySpecialValue> {
         self.property_special_values.iter().find(|sv| sv.id == id)
     }
+
+    /// Find a property type by name, as referenced from properties
+    pub fn find_property_type_by_name(&self, name: &str) -> Option<&PropertyType> {
+        self.property_types.iter().find(|pt| pt.name == name)
+    }
+
+    /// Find an image-level property by ID
+    pub fn find_image_property(&self, id: i32) -> Option<&CategoryProperty> {
+        self.image_properties.iter().find(|p| p.id == id)
+    }
+
+    /// Name of a property value, looking up special values as well
+    pub fn property_value_name(&self, property: &CategoryProperty, value: i32) -> Option<&str> {
+        self.find_property_type_by_name(&property.property_type)
+            .and_then(|pt| pt.find_value(value))
+            .map(|v| v.name.as_str())
+            .or_else(|| self.find_special_value(value).map(|sv| sv.name.as_str()))
+    }
+
+    /// Check the metadata for problems that deserialization lets through
+    ///
+    /// Duplicate IDs, references to undeclared property types, bad skeleton
+    /// indices and invalid patterns or ranges are errors; duplicate names and
+    /// hotkeys and unreadable colors are warnings.
+    pub fn validate(&self) -> Diagnostics {
+        let mut report = Diagnostics::new();
+
+        check_color(&mut report, "roi.color", self.roi.color);
+
+        check_unique(
+            &mut report,
+            true,
+            "categories",
+            "id",
+            &self.categories,
+            |c| Some(c.id),
+        );
+        check_unique(
+            &mut report,
+            false,
+            "categories",
+            "name",
+            &self.categories,
+            |c| Some(c.name.as_str()),
+        );
+        check_unique(
+            &mut report,
+            false,
+            "categories",
+            "hotkey",
+            &self.categories,
+            |c| hotkey(&c.hotkey),
+        );
+        for (index, category) in self.categories.iter().enumerate() {
+            let location = format!("categories[{}]", index);
+            check_color(&mut report, &format!("{}.color", location), category.color);
+            self.check_properties(
+                &mut report,
+                &format!("{}.properties", location),
+                &category.properties,
+            );
+
+            check_unique(
+                &mut report,
+                false,
+                &format!("{}.keypoints", location),
+                "",
+                &category.keypoints,
+                |name| Some(name.as_str()),
+            );
+            for (edge_index, edge) in category.skeleton.iter().enumerate() {
+                if let Some(&bad) = edge.iter().find(|&&i| i >= category.keypoints.len()) {
+                    report.error(
+                        format!("{}.skeleton[{}]", location, edge_index),
+                        format!(
+                            "keypoint index {} is out of range ({} keypoints declared)",
+                            bad,
+                            category.keypoints.len()
+                        ),
+                    );
+                }
+            }
+        }
+
+        check_unique(
+            &mut report,
+            true,
+            "property_types",
+            "id",
+            &self.property_types,
+            |t| Some(t.id),
+        );
+        check_unique(
+            &mut report,
+            true,
+            "property_types",
+            "name",
+            &self.property_types,
+            |t| Some(t.name.as_str()),
+        );
+        for (index, prop_type) in self.property_types.iter().enumerate() {
+            self.check_property_type(
+                &mut report,
+                &format!("property_types[{}]", index),
+                prop_type,
+            );
+        }
+
+        let specials = &self.property_special_values;
+        check_unique(
+            &mut report,
+            true,
+            "property_special_values",
+            "id",
+            specials,
+            |v| Some(v.id),
+        );
+        check_unique(
+            &mut report,
+            false,
+            "property_special_values",
+            "hotkey",
+            specials,
+            |v| hotkey(&v.hotkey),
+        );
+        for (index, special) in specials.iter().enumerate() {
+            let location = format!("property_special_values[{}].color", index);
+            check_color(&mut report, &location, special.color);
+        }
+
+        self.check_properties(&mut report, "image_properties", &self.image_properties);
+        report
+    }
+
+    /// Check a list of property references of a category or the image
+    fn check_properties(
+        &self,
+        report: &mut Diagnostics,
+        location: &str,
+        properties: &[CategoryProperty],
+    ) {
+        check_unique(report, true, location, "id", properties, |p| Some(p.id));
+        for (index, prop) in properties.iter().enumerate() {
+            if self
+                .find_property_type_by_name(&prop.property_type)
+                .is_none()
+            {
+                report.error(
+                    format!("{}[{}].type", location, index),
+                    format!("unknown property type {:?}", prop.property_type),
+                );
+            }
+        }
+    }
+
+    fn check_property_type(
+        &self,
+        report: &mut Diagnostics,
+        location: &str,
+        prop_type: &PropertyType,
+    ) {
+        let kind = prop_type.kind.as_str();
+        if prop_type.kind.is_enum() {
+            if prop_type.values.is_empty() {
+                report.warning(
+                    format!("{}.values", location),
+                    "enum property has no values",
+                );
+            }
+        } else if !prop_type.values.is_empty() {
+            report.warning(
+                format!("{}.values", location),
+                format!("values are ignored for {} properties", kind),
+            );
+        }
+
+        if let Some(pattern) = &prop_type.pattern {
+            if prop_type.kind != PropertyKind::String {
+                report.warning(
+                    format!("{}.pattern", location),
+                    format!("pattern is ignored for {} properties", kind),
+                );
+            } else if let Err(e) = Regex::new(&format!("^(?:{})$", pattern)) {
+                report.error(
+                    format!("{}.pattern", location),
+                    format!("invalid pattern: {}", e),
+                );
+            }
+        }
+        let numeric = matches!(prop_type.kind, PropertyKind::Integer | PropertyKind::Float);
+        if !numeric && (prop_type.min.is_some() || prop_type.max.is_some()) {
+            report.warning(
+                location.to_string(),
+                format!("min/max are ignored for {} properties", kind),
+            );
+        }
+        if let (Some(min), Some(max)) = (prop_type.min, prop_type.max) {
+            if min > max {
+                report.error(
+                    format!("{}.min", location),
+                    format!("min {} is greater than max {}", min, max),
+                );
+            }
+        }
+
+        let values_location = format!("{}.values", location);
+        check_unique(
+            report,
+            true,
+            &values_location,
+            "id",
+            &prop_type.values,
+            |v| Some(v.id),
+        );
+        check_unique(
+            report,
+            false,
+            &values_location,
+            "hotkey",
+            &prop_type.values,
+            |v| hotkey(&v.hotkey),
+        );
+        for (index, value) in prop_type.values.iter().enumerate() {
+            let value_location = format!("{}[{}]", values_location, index);
+            check_color(report, &format!("{}.color", value_location), value.color);
+            if let Some(special) = self.find_special_value(value.id) {
+                report.error(
+                    format!("{}.id", value_location),
+                    format!(
+                        "id {} is already used by special value {:?}",
+                        value.id, special.name
+                    ),
+                );
+            }
+            if let Some(special) = hotkey(&value.hotkey).and_then(|key| {
+                self.property_special_values
+                    .iter()
+                    .find(|sv| hotkey(&sv.hotkey).as_ref() == Some(&key))
+            }) {
+                report.warning(
+                    format!("{}.hotkey", value_location),
+                    format!(
+                        "hotkey {:?} is also used by special value {:?}",
+                        value.hotkey, special.name
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// Hotkey as compared for conflicts; empty hotkeys never conflict
+fn hotkey(key: &str) -> Option<String> {
+    let key = key.trim();
+    (!key.is_empty()).then(|| key.to_lowercase())
+}
+
+/// Report items of `list` sharing a key with an earlier item
+///
+/// Duplicates are errors if `fatal`, warnings otherwise. `field` is appended to
+/// the item location; an empty field reports the item itself.
+fn check_unique<'a, T, K: Eq + Hash + std::fmt::Debug>(
+    report: &mut Diagnostics,
+    fatal: bool,
+    location: &str,
+    field: &str,
+    list: &'a [T],
+    key: impl Fn(&'a T) -> Option<K>,
+) {
+    let mut seen = HashMap::new();
+    for (index, item) in list.iter().enumerate() {
+        let Some(key) = key(item) else {
+            continue;
+        };
+        let item_location = if field.is_empty() {
+            format!("{}[{}]", location, index)
+        } else {
+            format!("{}[{}].{}", location, index, field)
+        };
+        if let Some(first) = seen.get(&key) {
+            let message = format!(
+                "duplicate {} {:?}, first used by {}[{}]",
+                if field.is_empty() { "entry" } else { field },
+                key,
+                location,
+                first
+            );
+            if fatal {
+                report.error(item_location, message);
+            } else {
+                report.warning(item_location, message);
+            }
+        } else {
+            seen.insert(key, index);
+        }
+    }
+}
+
+fn check_color(report: &mut Diagnostics, location: &str, color: Color) {
+    if color.is_unreadable() {
+        report.warning(
+            location.to_string(),
+            "unreadable color, shown in gray (expected #RRGGBB[AA], a CSS color name or rgb())",
+        );
+    }
 }
 
 /// Shape display configuration
@@ -69,8 +395,8 @@ fn default_vertex_radius() -> f32 {
 /// ROI (Region of Interest) configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RoiConfig {
-    /// Color for ROI display (hex format like "#800080")
-    pub color: String,
+    /// Color for ROI display
+    pub color: Color,
 }
 
 /// Object category definition
@@ -83,15 +409,30 @@ pub struct Category {
     /// Keyboard shortcut for quick selection
     pub hotkey: String,
 
-    /// Display color (hex format like "#FF0000")
-    pub color: String,
+    /// Display color
+    pub color: Color,
 
     /// Properties associated with this category
     #[serde(default)]
     pub properties: Vec<CategoryProperty>,
+
+    /// Keypoint names, in annotation order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keypoints: Vec<String>,
+
+    /// Skeleton edges as pairs of keypoint indices (0-based)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skeleton: Vec<[usize; 2]>,
+}
+
+impl Category {
+    /// Whether objects of this category carry keypoints
+    pub fn has_keypoints(&self) -> bool {
+        !self.keypoints.is_empty()
+    }
 }
 
-/// Property reference in a category
+/// Property reference in a category or in the image-level properties
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CategoryProperty {
     pub id: i32,
@@ -100,6 +441,10 @@ pub struct CategoryProperty {
     /// Property type name (references PropertyType)
     #[serde(rename = "type")]
     pub property_type: String,
+
+    /// Whether several values may be selected at once (multi-label)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub multiple: bool,
 }
 
 /// Property type definition
@@ -109,8 +454,25 @@ pub struct PropertyType {
     pub name: String,
     pub description: String,
 
-    /// Possible values for this property type
+    /// Kind of value this property holds
+    #[serde(default, skip_serializing_if = "PropertyKind::is_enum")]
+    pub kind: PropertyKind,
+
+    /// Possible values for this property type (enumerated kind)
+    #[serde(default)]
     pub values: Vec<PropertyValue>,
+
+    /// Regular expression that text values must match in full (string kind)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+
+    /// Inclusive lower bound (integer and float kinds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+
+    /// Inclusive upper bound (integer and float kinds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
 }
 
 impl PropertyType {
@@ -118,6 +480,74 @@ impl PropertyType {
     pub fn find_value(&self, id: i32) -> Option<&PropertyValue> {
         self.values.iter().find(|v| v.id == id)
     }
+
+    /// Check a typed value against the kind, pattern and range of this type
+    pub fn check_data(&self, data: &PropertyData) -> Result<()> {
+        let invalid = |reason: String| Err(Error::InvalidData(reason));
+        let number = match (self.kind, data) {
+            (PropertyKind::String, PropertyData::Text(text)) => {
+                if let Some(pattern) = &self.pattern {
+                    let regex = Regex::new(&format!("^(?:{})$", pattern)).map_err(|e| {
+                        Error::InvalidData(format!("invalid pattern {:?}: {}", pattern, e))
+                    })?;
+                    if !regex.is_match(text) {
+                        return invalid(format!("{:?} does not match {:?}", text, pattern));
+                    }
+                }
+                return Ok(());
+            }
+            (PropertyKind::Boolean, PropertyData::Bool(_)) => return Ok(()),
+            (PropertyKind::Integer, PropertyData::Int(value)) => *value as f64,
+            (PropertyKind::Float, PropertyData::Int(value)) => *value as f64,
+            (PropertyKind::Float, PropertyData::Float(value)) if value.is_finite() => *value,
+            (kind, data) => {
+                return invalid(format!("{} is not a valid {} value", data, kind.as_str()));
+            }
+        };
+
+        if self.min.is_some_and(|min| number < min) || self.max.is_some_and(|max| number > max) {
+            return invalid(format!(
+                "{} is outside {}..={}",
+                data,
+                self.min.map(|v| v.to_string()).unwrap_or_default(),
+                self.max.map(|v| v.to_string()).unwrap_or_default()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Kind of value held by a property type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PropertyKind {
+    /// One of the declared values, referenced by ID
+    #[default]
+    Enum,
+    /// Free text, optionally restricted by a pattern
+    String,
+    /// Whole number, optionally within a range
+    Integer,
+    /// Real number, optionally within a range
+    Float,
+    /// Yes or no
+    Boolean,
+}
+
+impl PropertyKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Enum => "enum",
+            Self::String => "string",
+            Self::Integer => "integer",
+            Self::Float => "float",
+            Self::Boolean => "boolean",
+        }
+    }
+
+    pub fn is_enum(&self) -> bool {
+        *self == Self::Enum
+    }
 }
 
 /// A possible value for a property type
@@ -131,7 +561,7 @@ pub struct PropertyValue {
     pub hotkey: String,
 
     /// Display color
-    pub color: String,
+    pub color: Color,
 
     /// Short sign for display on image (e.g., "M" for male, "F" for female)
     pub sign: String,
@@ -144,13 +574,14 @@ pub struct PropertySpecialValue {
     pub name: String,
     pub description: String,
     pub hotkey: String,
-    pub color: String,
+    pub color: Color,
     pub sign: String,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::diagnostic::Severity;
 
     #[test]
     fn test_meta_serialization() {
@@ -165,11 +596,14 @@ mod tests {
                 vertex_radius: 10.0,
             },
             roi: RoiConfig {
-                color: "#800080".to_string(),
+                color: "#800080".parse().unwrap(),
             },
             categories: vec![],
             property_types: vec![],
             property_special_values: vec![],
+            image_properties: vec![],
+            storage: Default::default(),
+            backups: None,
         };
 
         let yaml = serde_yaml::to_string(&meta).unwrap();
@@ -178,4 +612,135 @@ mod tests {
         assert_eq!(meta.id, deserialized.id);
         assert_eq!(meta.name, deserialized.name);
     }
+
+    #[test]
+    fn test_category_keypoints() {
+        let yaml = r##"
+id: 0
+name: person
+description: Person
+hotkey: "1"
+color: "#FF0000"
+keypoints: [nose, left_eye, right_eye]
+skeleton: [[0, 1], [0, 2]]
+"##;
+
+        let category: Category = serde_yaml::from_str(yaml).unwrap();
+        assert!(category.has_keypoints());
+        assert_eq!(category.keypoints[1], "left_eye");
+        assert_eq!(category.skeleton, vec![[0, 1], [0, 2]]);
+    }
+
+    #[test]
+    fn test_validate() {
+        let meta: Meta = serde_yaml::from_str(
+            r##"
+id: 1
+name: test
+description: test
+shape: {title_style: 1, thickness: 2}
+roi: {color: "#800080"}
+categories:
+  - id: 0
+    name: person
+    description: Person
+    hotkey: "1"
+    color: "#FF0000"
+    properties:
+      - {id: 0, name: gender, type: gender}
+      - {id: 0, name: age, type: age}
+    keypoints: [head, foot]
+    skeleton: [[0, 2]]
+  - {id: 0, name: cat, description: Cat, hotkey: "1", color: BRWN}
+property_types:
+  - id: 0
+    name: gender
+    description: Gender
+    values:
+      - {id: 0, name: male, description: Male, hotkey: "1", color: "#0000FF", sign: M}
+      - {id: -1, name: female, description: Female, hotkey: q, color: "#FF00FF", sign: F}
+  - {id: 1, name: plate, description: Plate, kind: string, pattern: "[A-Z"}
+  - {id: 2, name: speed, description: Speed, kind: float, min: 10, max: 1}
+property_special_values:
+  - {id: -1, name: pending, description: Pending, hotkey: q, color: WHTE, sign: P}
+image_properties:
+  - {id: 0, name: weather, type: weather}
+"##,
+        )
+        .unwrap();
+
+        let report = meta.validate();
+        let located = |severity: Severity| -> Vec<&str> {
+            report
+                .items
+                .iter()
+                .filter(|d| d.severity == severity)
+                .map(|d| d.location.as_str())
+                .collect()
+        };
+        assert_eq!(
+            located(Severity::Error),
+            vec![
+                "categories[1].id",
+                "categories[0].properties[1].id",
+                "categories[0].properties[1].type",
+                "categories[0].skeleton[0]",
+                "property_types[0].values[1].id",
+                "property_types[1].pattern",
+                "property_types[2].min",
+                "image_properties[0].type",
+            ]
+        );
+        assert_eq!(
+            located(Severity::Warning),
+            vec![
+                "categories[1].hotkey",
+                "categories[1].color",
+                "property_types[0].values[1].hotkey",
+                "property_special_values[0].color",
+            ]
+        );
+        assert!(report.has_errors());
+        assert!(report
+            .to_string()
+            .starts_with("error: categories[1].id: duplicate id 0, first used by categories[0]"));
+    }
+
+    #[test]
+    fn test_typed_property_check() {
+        let yaml = r#"
+- {id: 0, name: plate, description: Plate, kind: string, pattern: "[A-Z]{2}[0-9]+"}
+- {id: 1, name: age, description: Age, kind: integer, min: 0, max: 120}
+- {id: 2, name: occlusion, description: Occlusion, kind: float, min: 0.0, max: 1.0}
+- {id: 3, name: parked, description: Parked, kind: boolean}
+"#;
+        let types: Vec<PropertyType> = serde_yaml::from_str(yaml).unwrap();
+        assert!(types.iter().all(|t| t.values.is_empty()));
+        let [plate, age, occlusion, parked] = &types[..] else {
+            panic!("expected four property types");
+        };
+
+        assert!(plate
+            .check_data(&PropertyData::Text("AB123".into()))
+            .is_ok());
+        assert!(plate
+            .check_data(&PropertyData::Text("xAB123".into()))
+            .is_err());
+        assert!(age.check_data(&PropertyData::Int(42)).is_ok());
+        assert!(age.check_data(&PropertyData::Int(121)).is_err());
+        assert!(age.check_data(&PropertyData::Float(4.5)).is_err());
+        assert!(occlusion.check_data(&PropertyData::Float(0.5)).is_ok());
+        assert!(occlusion.check_data(&PropertyData::Int(1)).is_ok());
+        assert!(occlusion
+            .check_data(&PropertyData::Float(f64::NAN))
+            .is_err());
+        assert!(parked.check_data(&PropertyData::Bool(false)).is_ok());
+        assert!(parked
+            .check_data(&PropertyData::Text("yes".into()))
+            .is_err());
+
+        let yaml = serde_yaml::to_string(&types[1]).unwrap();
+        assert!(yaml.contains("kind: integer"));
+        assert!(!yaml.contains("pattern"));
+    }
 }
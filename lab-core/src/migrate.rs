@@ -0,0 +1,266 @@
+//! Annotation file schema versions and migration of older layouts
+//!
+//! Files are read as untyped YAML first, their `version` is checked and each
+//! migration step up to [`ANNOTATION_VERSION`] is applied before the data is
+//! turned into an [`Annotation`].
+//!
+//! - 1.x: a single `roi` polygon, scalar property values, optional metadata
+//! - 2.0: `rois` list, objects with a `polygon`
+//! - 2.1: objects with an explicit `shape`, keypoints, typed and image-level properties,
+//!   optional per-object provenance, optional image size and content hash
+
+use crate::{Annotation, Error, Result};
+use serde_yaml::{Mapping, Value};
+
+/// Schema version written by this library
+pub const ANNOTATION_VERSION: &str = "2.1";
+
+/// Version assumed for files without a `version` field
+const UNVERSIONED: (u32, u32) = (1, 0);
+
+/// Parse a "major.minor" version string
+pub fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.trim().splitn(2, '.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = match parts.next() {
+        Some(minor) => minor.parse().ok()?,
+        None => 0,
+    };
+    Some((major, minor))
+}
+
+/// Schema version of a raw annotation document
+pub fn document_version(doc: &Value) -> Result<(u32, u32)> {
+    let version = match doc.get("version") {
+        None | Some(Value::Null) => return Ok(UNVERSIONED),
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Number(n)) => n.to_string(),
+        Some(other) => {
+            return Err(Error::InvalidData(format!(
+                "Invalid annotation version: {:?}",
+                other
+            )))
+        }
+    };
+    parse_version(&version)
+        .ok_or_else(|| Error::InvalidData(format!("Invalid annotation version: {}", version)))
+}
+
+/// Whether a raw annotation document is older than [`ANNOTATION_VERSION`]
+pub fn needs_migration(doc: &Value) -> Result<bool> {
+    Ok(document_version(doc)? < current_version())
+}
+
+/// Bring a raw annotation document up to [`ANNOTATION_VERSION`]
+///
+/// Fails with [`Error::UnsupportedVersion`] for files written by a newer version.
+pub fn migrate_document(mut doc: Value) -> Result<Value> {
+    let version = document_version(&doc)?;
+    if version > current_version() {
+        return Err(Error::UnsupportedVersion {
+            found: format!("{}.{}", version.0, version.1),
+            supported: ANNOTATION_VERSION.to_string(),
+        });
+    }
+
+    let map = doc
+        .as_mapping_mut()
+        .ok_or_else(|| Error::InvalidData("Annotation is not a mapping".to_string()))?;
+    if version < (2, 0) {
+        migrate_v1(map);
+    }
+    if version < (2, 1) {
+        migrate_v2_0(map);
+    }
+    map.insert("version".into(), ANNOTATION_VERSION.into());
+    Ok(doc)
+}
+
+/// Parse annotation YAML of any supported version
+pub fn parse_annotation(content: &str) -> Result<Annotation> {
+    Ok(parse_annotation_with_status(content)?.0)
+}
+
+/// Parse annotation YAML of any supported version, also returning whether
+/// it was migrated from an older version
+pub fn parse_annotation_with_status(content: &str) -> Result<(Annotation, bool)> {
+    annotation_from_document(serde_yaml::from_str(content)?)
+}
+
+/// Turn a raw annotation document of any supported version into an
+/// [`Annotation`], also returning whether it was migrated
+pub fn annotation_from_document(doc: Value) -> Result<(Annotation, bool)> {
+    let migrated = needs_migration(&doc)?;
+    let annotation = serde_yaml::from_value(migrate_document(doc)?)?;
+    Ok((annotation, migrated))
+}
+
+fn current_version() -> (u32, u32) {
+    parse_version(ANNOTATION_VERSION).expect("valid annotation version")
+}
+
+/// 1.x -> 2.0: fill in metadata, list the single ROI, wrap scalar property values
+fn migrate_v1(map: &mut Mapping) {
+    let now = Value::String(chrono::Utc::now().to_rfc3339());
+    if !map.contains_key("user_agent") {
+        map.insert("user_agent".into(), "".into());
+    }
+    if !map.contains_key("created_at") {
+        map.insert("created_at".into(), now.clone());
+    }
+    if !map.contains_key("last_modified") {
+        let created = map.get("created_at").cloned().unwrap_or(now);
+        map.insert("last_modified".into(), created);
+    }
+    if !map.contains_key("objects") {
+        map.insert("objects".into(), Value::Sequence(Vec::new()));
+    }
+
+    migrate_single_roi(map);
+
+    for object in objects_mut(map) {
+        if !object.contains_key("confidence") {
+            object.insert("confidence".into(), 1.0.into());
+        }
+        if let Some(Value::Mapping(properties)) = object.get_mut("properties") {
+            for (_, values) in properties.iter_mut() {
+                let entries = match std::mem::take(values) {
+                    Value::Sequence(entries) => entries,
+                    single => vec![single],
+                };
+                *values = Value::Sequence(entries.into_iter().map(property_entry).collect());
+            }
+        }
+    }
+}
+
+/// 2.0 -> 2.1: turn legacy `polygon` lists into explicit shapes
+fn migrate_v2_0(map: &mut Mapping) {
+    // Some 2.0 writers still used the single-ROI form
+    migrate_single_roi(map);
+
+    for object in objects_mut(map) {
+        if object.contains_key("shape") {
+            object.remove("polygon");
+            continue;
+        }
+        let points = object
+            .remove("polygon")
+            .unwrap_or(Value::Sequence(Vec::new()));
+        let mut shape = Mapping::new();
+        shape.insert("type".into(), "polygon".into());
+        shape.insert("points".into(), points);
+        object.insert("shape".into(), Value::Mapping(shape));
+    }
+}
+
+/// Replace a single `roi` polygon by a one-element `rois` list
+fn migrate_single_roi(map: &mut Mapping) {
+    let Some(roi) = map.remove("roi") else {
+        return;
+    };
+    let has_points = roi.as_sequence().is_some_and(|points| !points.is_empty());
+    if has_points && !map.contains_key("rois") {
+        map.insert("rois".into(), Value::Sequence(vec![roi]));
+    }
+}
+
+fn objects_mut(map: &mut Mapping) -> impl Iterator<Item = &mut Mapping> {
+    map.get_mut("objects")
+        .and_then(Value::as_sequence_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_mapping_mut)
+}
+
+/// A `{value, confidence}` entry for a bare property value ID
+fn property_entry(value: Value) -> Value {
+    if value.is_mapping() {
+        return value;
+    }
+    let mut entry = Mapping::new();
+    entry.insert("value".into(), value);
+    entry.insert("confidence".into(), 1.0.into());
+    Value::Mapping(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ShapeKind;
+
+    #[test]
+    fn test_migrate_v1() {
+        let yaml = r#"
+roi:
+  - {x: 0.1, y: 0.1}
+  - {x: 0.2, y: 0.1}
+  - {x: 0.2, y: 0.2}
+objects:
+  - id: 0
+    category: 1
+    polygon:
+      - {x: 0.1, y: 0.1}
+      - {x: 0.5, y: 0.1}
+      - {x: 0.5, y: 0.5}
+    properties:
+      "0": 2
+      "1": [0, {value: 1, confidence: 0.5}]
+"#;
+
+        let annotation = parse_annotation(yaml).unwrap();
+        assert_eq!(annotation.version, ANNOTATION_VERSION);
+        assert_eq!(annotation.rois.len(), 1);
+        assert_eq!(annotation.rois[0].len(), 3);
+        let obj = &annotation.objects[0];
+        assert_eq!(obj.confidence, 1.0);
+        assert_eq!(obj.shape.kind(), ShapeKind::Polygon);
+        assert_eq!(obj.get_property(0).unwrap().value, 2);
+        assert_eq!(obj.property_values(1).len(), 2);
+        assert_eq!(obj.property_values(1)[1].confidence, 0.5);
+    }
+
+    #[test]
+    fn test_migrate_v2_0() {
+        let yaml = r#"
+version: "2.0"
+user_agent: "test"
+created_at: "2024-01-01T00:00:00Z"
+last_modified: "2024-01-01T00:00:00Z"
+objects:
+  - id: 0
+    category: 1
+    confidence: 0.9
+    polygon:
+      - {x: 0.1, y: 0.1}
+      - {x: 0.5, y: 0.1}
+      - {x: 0.5, y: 0.5}
+    properties: {}
+"#;
+
+        let doc: Value = serde_yaml::from_str(yaml).unwrap();
+        assert!(needs_migration(&doc).unwrap());
+        let doc = migrate_document(doc).unwrap();
+        assert!(!needs_migration(&doc).unwrap());
+        assert!(doc["objects"][0].get("polygon").is_none());
+
+        let annotation: Annotation = serde_yaml::from_value(doc).unwrap();
+        assert_eq!(annotation.objects[0].shape.vertices().len(), 3);
+        assert_eq!(
+            annotation.created_at.to_rfc3339(),
+            "2024-01-01T00:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_refuse_newer_version() {
+        let yaml = "version: '3.0'\nobjects: []\n";
+        let err = parse_annotation(yaml).unwrap_err();
+        assert!(matches!(err, Error::UnsupportedVersion { .. }));
+        assert!(err.to_string().contains("3.0"));
+
+        assert_eq!(parse_version("2.1"), Some((2, 1)));
+        assert_eq!(parse_version("2"), Some((2, 0)));
+        assert_eq!(parse_version("v2"), None);
+    }
+}
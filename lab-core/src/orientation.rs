@@ -0,0 +1,237 @@
+//! EXIF orientation and the coordinate frames of an image
+//!
+//! Normalized coordinates in annotations refer to the *upright* image: the
+//! pixels as stored in the file (the *raw* frame) with the EXIF orientation
+//! applied, which is how viewers and the editor display it. Exporters that
+//! copy images unchanged convert coordinates back to the raw frame.
+
+use crate::{Annotation, Point, Shape};
+use serde::{Deserialize, Serialize};
+
+/// Transform from the raw pixels of an image to its upright view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum Orientation {
+    /// Stored upright (EXIF 1)
+    #[default]
+    Normal,
+    /// Mirrored left to right (EXIF 2)
+    FlipHorizontal,
+    /// Rotated by 180 degrees (EXIF 3)
+    Rotate180,
+    /// Mirrored top to bottom (EXIF 4)
+    FlipVertical,
+    /// Mirrored along the main diagonal (EXIF 5)
+    Transpose,
+    /// Displayed rotated 90 degrees clockwise (EXIF 6)
+    Rotate90,
+    /// Mirrored along the anti-diagonal (EXIF 7)
+    Transverse,
+    /// Displayed rotated 90 degrees counter-clockwise (EXIF 8)
+    Rotate270,
+}
+
+impl Orientation {
+    /// Orientation for an EXIF orientation tag value
+    pub fn from_exif(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(Self::Normal),
+            2 => Some(Self::FlipHorizontal),
+            3 => Some(Self::Rotate180),
+            4 => Some(Self::FlipVertical),
+            5 => Some(Self::Transpose),
+            6 => Some(Self::Rotate90),
+            7 => Some(Self::Transverse),
+            8 => Some(Self::Rotate270),
+            _ => None,
+        }
+    }
+
+    /// EXIF orientation tag value
+    pub fn to_exif(self) -> u8 {
+        match self {
+            Self::Normal => 1,
+            Self::FlipHorizontal => 2,
+            Self::Rotate180 => 3,
+            Self::FlipVertical => 4,
+            Self::Transpose => 5,
+            Self::Rotate90 => 6,
+            Self::Transverse => 7,
+            Self::Rotate270 => 8,
+        }
+    }
+
+    pub fn is_normal(&self) -> bool {
+        *self == Self::Normal
+    }
+
+    /// Whether width and height trade places
+    pub fn swaps_axes(self) -> bool {
+        matches!(
+            self,
+            Self::Transpose | Self::Rotate90 | Self::Transverse | Self::Rotate270
+        )
+    }
+
+    /// The transform undoing this one
+    pub fn inverse(self) -> Self {
+        match self {
+            Self::Rotate90 => Self::Rotate270,
+            Self::Rotate270 => Self::Rotate90,
+            other => other,
+        }
+    }
+
+    /// Map a normalized point of the raw image to the upright image
+    pub fn apply(self, p: Point) -> Point {
+        let (x, y) = (p.x, p.y);
+        let (x, y) = match self {
+            Self::Normal => (x, y),
+            Self::FlipHorizontal => (1.0 - x, y),
+            Self::Rotate180 => (1.0 - x, 1.0 - y),
+            Self::FlipVertical => (x, 1.0 - y),
+            Self::Transpose => (y, x),
+            Self::Rotate90 => (1.0 - y, x),
+            Self::Transverse => (1.0 - y, 1.0 - x),
+            Self::Rotate270 => (y, 1.0 - x),
+        };
+        Point::new(x, y)
+    }
+
+    /// Size of the upright image for a raw image of `width` x `height`
+    pub fn apply_size(self, width: u32, height: u32) -> (u32, u32) {
+        if self.swaps_axes() {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    /// Map a shape from the raw image to the upright image
+    pub fn apply_shape(self, shape: &mut Shape) {
+        match shape {
+            Shape::Mask(mask) => *mask = mask.reoriented(self),
+            Shape::Ellipse {
+                center,
+                radius_x,
+                radius_y,
+            } => {
+                *center = self.apply(*center);
+                if self.swaps_axes() {
+                    std::mem::swap(radius_x, radius_y);
+                }
+            }
+            _ => shape.map_points(|p| *p = self.apply(*p)),
+        }
+    }
+
+    /// Map all geometry of an annotation from the raw image to the upright image
+    ///
+    /// The recorded image size, if any, is swapped along with the axes.
+    pub fn apply_annotation(self, annotation: &mut Annotation) {
+        if self.is_normal() {
+            return;
+        }
+        for roi in &mut annotation.rois {
+            roi.iter_mut().for_each(|p| *p = self.apply(*p));
+        }
+        for obj in &mut annotation.objects {
+            self.apply_shape(&mut obj.shape);
+            for keypoint in &mut obj.keypoints {
+                let p = self.apply(Point::new(keypoint.x, keypoint.y));
+                keypoint.x = p.x;
+                keypoint.y = p.y;
+            }
+        }
+        if let Some(info) = &mut annotation.image {
+            (info.width, info.height) = self.apply_size(info.width, info.height);
+        }
+    }
+}
+
+impl TryFrom<u8> for Orientation {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Self::from_exif(value).ok_or_else(|| format!("Invalid EXIF orientation: {}", value))
+    }
+}
+
+impl From<Orientation> for u8 {
+    fn from(orientation: Orientation) -> Self {
+        orientation.to_exif()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Mask, Object};
+
+    const ALL: [Orientation; 8] = [
+        Orientation::Normal,
+        Orientation::FlipHorizontal,
+        Orientation::Rotate180,
+        Orientation::FlipVertical,
+        Orientation::Transpose,
+        Orientation::Rotate90,
+        Orientation::Transverse,
+        Orientation::Rotate270,
+    ];
+
+    #[test]
+    fn test_apply_and_inverse() {
+        let p = Point::new(0.2, 0.1);
+        // The top-left corner of a raw image shown rotated clockwise ends up top right
+        assert_eq!(
+            Orientation::Rotate90.apply(Point::new(0.0, 0.0)),
+            Point::new(1.0, 0.0)
+        );
+        assert_eq!(Orientation::Rotate270.apply(p), Point::new(0.1, 0.8));
+        for orientation in ALL {
+            assert_eq!(
+                Orientation::from_exif(orientation.to_exif()),
+                Some(orientation)
+            );
+            let back = orientation.inverse().apply(orientation.apply(p));
+            assert!((back.x - p.x).abs() < 1e-6 && (back.y - p.y).abs() < 1e-6);
+        }
+        assert_eq!(Orientation::Rotate90.apply_size(640, 480), (480, 640));
+        assert_eq!(Orientation::from_exif(9), None);
+    }
+
+    #[test]
+    fn test_apply_annotation() {
+        let mut annotation = Annotation::new("test");
+        annotation.image = Some(crate::ImageInfo::new(40, 20, b""));
+        annotation.objects.push(Object::with_shape(
+            1,
+            1,
+            Shape::from_corners(Point::new(0.1, 0.2), Point::new(0.3, 0.6)),
+        ));
+        let mut mask = Mask::new(4, 2).unwrap();
+        mask.paint_stroke(Point::new(0.1, 0.25), Point::new(0.1, 0.25), 0.1, true);
+        annotation
+            .objects
+            .push(Object::with_shape(2, 1, Shape::Mask(mask)));
+
+        let mut rotated = annotation.clone();
+        Orientation::Rotate90.apply_annotation(&mut rotated);
+        let (min, max) = rotated.objects[0].shape.bounding_box().unwrap();
+        assert!((min.x - 0.4).abs() < 1e-6 && (min.y - 0.1).abs() < 1e-6);
+        assert!((max.x - 0.8).abs() < 1e-6 && (max.y - 0.3).abs() < 1e-6);
+        let Shape::Mask(mask) = &rotated.objects[1].shape else {
+            panic!("mask expected");
+        };
+        assert_eq!((mask.width(), mask.height()), (2, 4));
+        assert!(mask.get(1, 0));
+        assert_eq!(mask.pixel_count(), 1);
+        let info = rotated.image.as_ref().unwrap();
+        assert_eq!((info.width, info.height), (20, 40));
+
+        Orientation::Rotate270.apply_annotation(&mut rotated);
+        assert_eq!(rotated.objects[1], annotation.objects[1]);
+        let (min, max) = rotated.objects[0].shape.bounding_box().unwrap();
+        assert!((min.x - 0.1).abs() < 1e-6 && (max.y - 0.6).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,1003 @@
+use crate::geometry::{self, MultiPolygon, Point, Polygon};
+use crate::mask::Mask;
+use serde::{Deserialize, Serialize};
+
+/// Number of segments used to approximate an ellipse outline
+const ELLIPSE_SEGMENTS: usize = 48;
+
+/// Kind of an object shape, without its geometry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShapeKind {
+    Box,
+    RotatedBox,
+    Polygon,
+    Polyline,
+    Point,
+    Ellipse,
+    MultiPolygon,
+    Mask,
+}
+
+impl ShapeKind {
+    /// Get all shape kinds
+    pub fn all() -> [Self; 8] {
+        [
+            Self::Polygon,
+            Self::Box,
+            Self::RotatedBox,
+            Self::Ellipse,
+            Self::Polyline,
+            Self::Point,
+            Self::MultiPolygon,
+            Self::Mask,
+        ]
+    }
+
+    /// Shape kinds that can be drawn directly
+    ///
+    /// Multi-polygons are built by adding holes and parts to a polygon,
+    /// masks by painting with the brush.
+    pub fn drawable() -> [Self; 6] {
+        [
+            Self::Polygon,
+            Self::Box,
+            Self::RotatedBox,
+            Self::Ellipse,
+            Self::Polyline,
+            Self::Point,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Box => "box",
+            Self::RotatedBox => "rotated_box",
+            Self::Polygon => "polygon",
+            Self::Polyline => "polyline",
+            Self::Point => "point",
+            Self::Ellipse => "ellipse",
+            Self::MultiPolygon => "multi_polygon",
+            Self::Mask => "mask",
+        }
+    }
+
+    /// Number of clicks that define this shape, or None if open-ended
+    pub fn required_points(&self) -> Option<usize> {
+        match self {
+            Self::Box | Self::Ellipse => Some(2),
+            Self::RotatedBox => Some(3),
+            Self::Point => Some(1),
+            Self::Polygon | Self::Polyline | Self::MultiPolygon | Self::Mask => None,
+        }
+    }
+
+    /// Minimum number of clicks needed to create this shape
+    pub fn min_points(&self) -> usize {
+        match self {
+            Self::Polygon | Self::MultiPolygon => 3,
+            Self::Polyline => 2,
+            _ => self.required_points().unwrap_or(1),
+        }
+    }
+
+    /// Whether the shape encloses an area
+    pub fn is_closed(&self) -> bool {
+        !matches!(self, Self::Polyline | Self::Point)
+    }
+}
+
+/// Geometry of an annotated object, in normalized coordinates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Shape {
+    /// Axis-aligned box given by its top-left and bottom-right corners
+    Box { min: Point, max: Point },
+    /// Rotated box given by its four corners in drawing order
+    RotatedBox { corners: [Point; 4] },
+    /// Closed polygon
+    Polygon { points: Vec<Point> },
+    /// Open polyline
+    Polyline { points: Vec<Point> },
+    /// Single point
+    Point { position: Point },
+    /// Axis-aligned ellipse
+    Ellipse {
+        center: Point,
+        radius_x: f32,
+        radius_y: f32,
+    },
+    /// Polygons with holes, possibly split into several parts
+    MultiPolygon { polygons: Vec<Polygon> },
+    /// Painted pixel mask
+    Mask(Mask),
+}
+
+impl Shape {
+    /// Create a polygon shape
+    pub fn polygon(points: Vec<Point>) -> Self {
+        Self::Polygon { points }
+    }
+
+    /// Create an axis-aligned box from two opposite corners
+    pub fn from_corners(a: Point, b: Point) -> Self {
+        Self::Box {
+            min: Point::new(a.x.min(b.x), a.y.min(b.y)),
+            max: Point::new(a.x.max(b.x), a.y.max(b.y)),
+        }
+    }
+
+    /// Build a shape of the given kind from clicked points
+    ///
+    /// `aspect` is the image width divided by its height, needed to keep
+    /// rotated boxes rectangular in pixel space.
+    pub fn from_points(kind: ShapeKind, points: &[Point], aspect: f32) -> Option<Self> {
+        if points.len() < kind.min_points() {
+            return None;
+        }
+
+        let shape = match kind {
+            ShapeKind::Polygon => Self::Polygon {
+                points: points.to_vec(),
+            },
+            ShapeKind::MultiPolygon => Self::MultiPolygon {
+                polygons: vec![Polygon::new(points.to_vec())],
+            },
+            ShapeKind::Mask => return None,
+            ShapeKind::Polyline => Self::Polyline {
+                points: points.to_vec(),
+            },
+            ShapeKind::Point => Self::Point {
+                position: points[0],
+            },
+            ShapeKind::Box => Self::from_corners(points[0], points[1]),
+            ShapeKind::Ellipse => Self::Ellipse {
+                center: Point::new(
+                    (points[0].x + points[1].x) / 2.0,
+                    (points[0].y + points[1].y) / 2.0,
+                ),
+                radius_x: (points[1].x - points[0].x).abs() / 2.0,
+                radius_y: (points[1].y - points[0].y).abs() / 2.0,
+            },
+            ShapeKind::RotatedBox => {
+                // Work in pixel-proportional space so the box stays rectangular
+                let to_px = |p: &Point| (p.x * aspect, p.y);
+                let (x0, y0) = to_px(&points[0]);
+                let (x1, y1) = to_px(&points[1]);
+                let (x2, y2) = to_px(&points[2]);
+                let (ex, ey) = (x1 - x0, y1 - y0);
+                let length = (ex * ex + ey * ey).sqrt();
+                if length <= f32::EPSILON {
+                    return None;
+                }
+                let (nx, ny) = (-ey / length, ex / length);
+                let depth = (x2 - x0) * nx + (y2 - y0) * ny;
+                let from_px = |x: f32, y: f32| Point::new(x / aspect, y);
+                Self::RotatedBox {
+                    corners: [
+                        points[0],
+                        points[1],
+                        from_px(x1 + nx * depth, y1 + ny * depth),
+                        from_px(x0 + nx * depth, y0 + ny * depth),
+                    ],
+                }
+            }
+        };
+
+        Some(shape)
+    }
+
+    pub fn kind(&self) -> ShapeKind {
+        match self {
+            Self::Box { .. } => ShapeKind::Box,
+            Self::RotatedBox { .. } => ShapeKind::RotatedBox,
+            Self::Polygon { .. } => ShapeKind::Polygon,
+            Self::Polyline { .. } => ShapeKind::Polyline,
+            Self::Point { .. } => ShapeKind::Point,
+            Self::Ellipse { .. } => ShapeKind::Ellipse,
+            Self::MultiPolygon { .. } => ShapeKind::MultiPolygon,
+            Self::Mask(_) => ShapeKind::Mask,
+        }
+    }
+
+    /// Whether the shape encloses an area
+    pub fn is_closed(&self) -> bool {
+        self.kind().is_closed()
+    }
+
+    /// Editable handles of the shape
+    ///
+    /// Boxes expose their four corners, ellipses the ends of both axes
+    /// (right, bottom, left, top). Multi-polygons list every ring in
+    /// order: each part's outer ring followed by its holes. Masks have
+    /// no handles.
+    pub fn vertices(&self) -> Vec<Point> {
+        match self {
+            Self::Box { min, max } => vec![
+                *min,
+                Point::new(max.x, min.y),
+                *max,
+                Point::new(min.x, max.y),
+            ],
+            Self::RotatedBox { corners } => corners.to_vec(),
+            Self::Polygon { points } | Self::Polyline { points } => points.clone(),
+            Self::Point { position } => vec![*position],
+            Self::Ellipse {
+                center,
+                radius_x,
+                radius_y,
+            } => vec![
+                Point::new(center.x + radius_x, center.y),
+                Point::new(center.x, center.y + radius_y),
+                Point::new(center.x - radius_x, center.y),
+                Point::new(center.x, center.y - radius_y),
+            ],
+            Self::MultiPolygon { polygons } => polygons
+                .iter()
+                .flat_map(|polygon| polygon.rings().flatten().copied())
+                .collect(),
+            Self::Mask(_) => Vec::new(),
+        }
+    }
+
+    /// Points tracing the shape boundary (ellipses are approximated)
+    ///
+    /// For multi-polygons and masks this is the outer ring of the first
+    /// part; use [`Shape::rings`] to get every boundary.
+    pub fn outline(&self) -> Vec<Point> {
+        match self {
+            Self::Ellipse {
+                center,
+                radius_x,
+                radius_y,
+            } => (0..ELLIPSE_SEGMENTS)
+                .map(|i| {
+                    let angle = i as f32 / ELLIPSE_SEGMENTS as f32 * std::f32::consts::TAU;
+                    Point::new(
+                        center.x + radius_x * angle.cos(),
+                        center.y + radius_y * angle.sin(),
+                    )
+                })
+                .collect(),
+            Self::MultiPolygon { .. } | Self::Mask(_) => self.to_polygon().points,
+            _ => self.vertices(),
+        }
+    }
+
+    /// All boundaries of the shape, in the order of [`Shape::vertices`]
+    ///
+    /// Masks are traced on every call, so avoid this for them in hot paths.
+    pub fn rings(&self) -> Vec<Vec<Point>> {
+        match self {
+            Self::MultiPolygon { .. } | Self::Mask(_) => self
+                .to_multi_polygon()
+                .polygons
+                .iter()
+                .flat_map(|polygon| polygon.rings().cloned())
+                .collect(),
+            _ => vec![self.outline()],
+        }
+    }
+
+    /// One hole-free ring per part, for formats that cannot express holes
+    pub fn simple_rings(&self) -> Vec<Vec<Point>> {
+        match self {
+            Self::MultiPolygon { .. } | Self::Mask(_) => self
+                .to_multi_polygon()
+                .polygons
+                .iter()
+                .map(Polygon::to_simple_ring)
+                .collect(),
+            _ => vec![self.outline()],
+        }
+    }
+
+    /// Convert the shape boundary to a polygon
+    ///
+    /// Multi-polygons and masks yield their first part, holes included.
+    pub fn to_polygon(&self) -> Polygon {
+        match self {
+            Self::MultiPolygon { .. } | Self::Mask(_) => self
+                .to_multi_polygon()
+                .polygons
+                .into_iter()
+                .next()
+                .unwrap_or_else(Polygon::empty),
+            _ => Polygon::new(self.outline()),
+        }
+    }
+
+    /// A polygon, or a multi-polygon if there are several parts or holes
+    ///
+    /// Returns None if there are no parts, e.g. for an empty boolean result.
+    pub fn from_polygons(polygons: Vec<Polygon>) -> Option<Self> {
+        if polygons.is_empty() {
+            return None;
+        }
+        let mut shape = Self::MultiPolygon { polygons };
+        shape.normalize();
+        Some(shape)
+    }
+
+    /// A shape of the same family covering `region`, None if it is empty
+    ///
+    /// Masks are rasterized again at their own size; other shapes become a
+    /// polygon or multi-polygon.
+    pub fn with_region(&self, region: MultiPolygon) -> Option<Self> {
+        let shape = Self::from_polygons(region.polygons)?;
+        match self {
+            Self::Mask(mask) => shape
+                .to_mask(mask.width(), mask.height())
+                .filter(|mask| !mask.is_empty())
+                .map(Self::Mask),
+            _ => Some(shape),
+        }
+    }
+
+    /// Convert the shape to a multi-polygon with all of its parts
+    pub fn to_multi_polygon(&self) -> MultiPolygon {
+        match self {
+            Self::MultiPolygon { polygons } => MultiPolygon::new(polygons.clone()),
+            Self::Mask(mask) => mask.to_multi_polygon(),
+            _ => MultiPolygon::new(vec![self.to_polygon()]),
+        }
+    }
+
+    /// Rasterize a closed shape into a mask of the given pixel size
+    ///
+    /// Masks are resampled; open shapes have no area and give None, as do
+    /// sizes beyond [`crate::mask::MAX_PIXELS`].
+    pub fn to_mask(&self, width: u32, height: u32) -> Option<Mask> {
+        match self {
+            Self::Mask(mask) => mask.resized(width, height).ok(),
+            _ if self.is_closed() => Mask::from_rings(&self.rings(), width, height).ok(),
+            _ => None,
+        }
+    }
+
+    /// Convert a mask to a polygon, or a multi-polygon if it has holes or
+    /// several regions (None for other shapes and empty masks)
+    pub fn mask_to_polygon(&self) -> Option<Self> {
+        let Self::Mask(mask) = self else {
+            return None;
+        };
+        Self::from_polygons(mask.to_multi_polygon().polygons)
+    }
+
+    /// Pairs of vertex indices forming the editable edges of the shape
+    ///
+    /// Only shapes with free vertices have editable edges.
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        let ring_lengths = match self {
+            Self::Polygon { points } | Self::Polyline { points } => vec![points.len()],
+            Self::MultiPolygon { polygons } => polygons
+                .iter()
+                .flat_map(|polygon| polygon.rings().map(Vec::len))
+                .collect(),
+            _ => return Vec::new(),
+        };
+
+        let closed = self.is_closed();
+        let mut edges = Vec::new();
+        let mut start = 0;
+        for len in ring_lengths {
+            let count = if closed { len } else { len.saturating_sub(1) };
+            if len >= 2 {
+                edges.extend((0..count).map(|i| (start + i, start + (i + 1) % len)));
+            }
+            start += len;
+        }
+        edges
+    }
+
+    /// Check that the shape has enough geometry to be meaningful
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Self::Box { min, max } => max.x > min.x && max.y > min.y,
+            Self::RotatedBox { .. } => self.area() > 0.0,
+            Self::Polygon { points } => points.len() >= 3,
+            Self::Polyline { points } => points.len() >= 2,
+            Self::MultiPolygon { polygons } => MultiPolygon::new(polygons.clone()).is_valid(),
+            Self::Mask(mask) => !mask.is_empty(),
+            Self::Point { .. } => true,
+            Self::Ellipse {
+                radius_x, radius_y, ..
+            } => *radius_x > 0.0 && *radius_y > 0.0,
+        }
+    }
+
+    /// Axis-aligned bounding box as (min, max) corners
+    pub fn bounding_box(&self) -> Option<(Point, Point)> {
+        match self {
+            Self::Box { min, max } => Some((*min, *max)),
+            Self::Ellipse {
+                center,
+                radius_x,
+                radius_y,
+            } => Some((
+                Point::new(center.x - radius_x, center.y - radius_y),
+                Point::new(center.x + radius_x, center.y + radius_y),
+            )),
+            Self::Mask(mask) => mask.bounding_box(),
+            _ => Polygon::new(self.vertices()).bounding_box(),
+        }
+    }
+
+    /// Area in normalized units (zero for open shapes)
+    pub fn area(&self) -> f32 {
+        match self {
+            Self::Ellipse {
+                radius_x, radius_y, ..
+            } => std::f32::consts::PI * radius_x * radius_y,
+            Self::Polyline { .. } | Self::Point { .. } => 0.0,
+            Self::MultiPolygon { polygons } => polygons.iter().map(Polygon::area).sum(),
+            Self::Mask(mask) => mask.area(),
+            _ => self.to_polygon().area(),
+        }
+    }
+
+    /// Check if a point lies inside a closed shape
+    pub fn contains(&self, point: &Point) -> bool {
+        match self {
+            Self::Box { min, max } => {
+                point.x >= min.x && point.x <= max.x && point.y >= min.y && point.y <= max.y
+            }
+            Self::Ellipse {
+                center,
+                radius_x,
+                radius_y,
+            } => {
+                if *radius_x <= 0.0 || *radius_y <= 0.0 {
+                    return false;
+                }
+                let dx = (point.x - center.x) / radius_x;
+                let dy = (point.y - center.y) / radius_y;
+                dx * dx + dy * dy <= 1.0
+            }
+            Self::Polyline { .. } | Self::Point { .. } => false,
+            Self::MultiPolygon { polygons } => polygons.iter().any(|p| p.contains(point)),
+            Self::Mask(mask) => mask.contains(point),
+            _ => self.to_polygon().contains(point),
+        }
+    }
+
+    /// Distance from a point to the shape boundary
+    pub fn distance_to(&self, point: &Point) -> f32 {
+        let closed = self.is_closed();
+        self.rings()
+            .iter()
+            .map(|ring| match ring.len() {
+                0 => f32::MAX,
+                1 => point.distance_to(&ring[0]),
+                len => {
+                    let segments = if closed { len } else { len - 1 };
+                    (0..segments)
+                        .map(|i| point.distance_to_segment(&ring[i], &ring[(i + 1) % len]))
+                        .fold(f32::MAX, f32::min)
+                }
+            })
+            .fold(f32::MAX, f32::min)
+    }
+
+    /// Whether vertices can be inserted into or removed from the shape
+    pub fn has_free_vertices(&self) -> bool {
+        matches!(
+            self,
+            Self::Polygon { .. } | Self::Polyline { .. } | Self::MultiPolygon { .. }
+        )
+    }
+
+    /// Add a hole to the part of a polygon that contains `ring`
+    ///
+    /// A plain polygon becomes a multi-polygon. Returns false if the shape
+    /// is not a polygon or no part contains the hole.
+    pub fn add_hole(&mut self, ring: Vec<Point>) -> bool {
+        if ring.len() < 3 {
+            return false;
+        }
+        if let Self::Polygon { points } = self {
+            *self = Self::MultiPolygon {
+                polygons: vec![Polygon::new(std::mem::take(points))],
+            };
+        }
+        let Self::MultiPolygon { polygons } = self else {
+            return false;
+        };
+
+        let inside = |polygon: &Polygon| {
+            let outer = Polygon::new(polygon.points.clone());
+            ring.iter().all(|point| outer.contains(point))
+        };
+        let added = match polygons.iter_mut().find(|polygon| inside(polygon)) {
+            Some(polygon) => {
+                polygon.holes.push(ring);
+                true
+            }
+            None => false,
+        };
+        self.normalize();
+        added
+    }
+
+    /// Add a separate part to a polygon, making it a multi-polygon
+    pub fn add_part(&mut self, ring: Vec<Point>) -> bool {
+        if ring.len() < 3 {
+            return false;
+        }
+        match self {
+            Self::Polygon { points } => {
+                *self = Self::MultiPolygon {
+                    polygons: vec![Polygon::new(std::mem::take(points)), Polygon::new(ring)],
+                };
+                true
+            }
+            Self::MultiPolygon { polygons } => {
+                polygons.push(Polygon::new(ring));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Turn a multi-polygon with a single hole-free part back into a polygon
+    fn normalize(&mut self) {
+        if let Self::MultiPolygon { polygons } = self {
+            if polygons.len() == 1 && polygons[0].holes.is_empty() {
+                let points = std::mem::take(&mut polygons[0].points);
+                *self = Self::Polygon { points };
+            }
+        }
+    }
+
+    /// Find the ring holding the vertex at `index` of [`Shape::vertices`]
+    ///
+    /// Returns (part, ring, offset) where ring 0 is the outer ring and
+    /// ring k is hole k - 1.
+    fn locate_vertex(polygons: &[Polygon], index: usize) -> Option<(usize, usize, usize)> {
+        let mut start = 0;
+        for (part, polygon) in polygons.iter().enumerate() {
+            for (ring, points) in polygon.rings().enumerate() {
+                if index < start + points.len() {
+                    return Some((part, ring, index - start));
+                }
+                start += points.len();
+            }
+        }
+        None
+    }
+
+    fn ring_mut(polygon: &mut Polygon, ring: usize) -> &mut Vec<Point> {
+        match ring {
+            0 => &mut polygon.points,
+            _ => &mut polygon.holes[ring - 1],
+        }
+    }
+
+    /// Move the handle at `index` (see [`Shape::vertices`]) to `position`
+    pub fn move_vertex(&mut self, index: usize, position: Point) -> bool {
+        match self {
+            Self::Box { min, max } => {
+                if index >= 4 {
+                    return false;
+                }
+                let corners = [
+                    *min,
+                    Point::new(max.x, min.y),
+                    *max,
+                    Point::new(min.x, max.y),
+                ];
+                *self = Self::from_corners(position, corners[(index + 2) % 4]);
+            }
+            Self::RotatedBox { corners } => {
+                if index >= 4 {
+                    return false;
+                }
+                // Keep the opposite corner fixed and the edges parallel; this is
+                // an affine operation, so rectangles stay rectangles in pixels.
+                let origin = corners[(index + 2) % 4];
+                let a = corners[(index + 1) % 4];
+                let b = corners[(index + 3) % 4];
+                let (ax, ay) = (a.x - origin.x, a.y - origin.y);
+                let (bx, by) = (b.x - origin.x, b.y - origin.y);
+                let (px, py) = (position.x - origin.x, position.y - origin.y);
+                let det = ax * by - ay * bx;
+                if det.abs() <= f32::EPSILON {
+                    corners[index] = position;
+                    return true;
+                }
+                let alpha = (px * by - py * bx) / det;
+                let beta = (ax * py - ay * px) / det;
+                corners[(index + 1) % 4] = Point::new(origin.x + alpha * ax, origin.y + alpha * ay);
+                corners[(index + 3) % 4] = Point::new(origin.x + beta * bx, origin.y + beta * by);
+                corners[index] = position;
+            }
+            Self::Polygon { points } | Self::Polyline { points } => match points.get_mut(index) {
+                Some(point) => *point = position,
+                None => return false,
+            },
+            Self::MultiPolygon { polygons } => {
+                let Some((part, ring, offset)) = Self::locate_vertex(polygons, index) else {
+                    return false;
+                };
+                Self::ring_mut(&mut polygons[part], ring)[offset] = position;
+            }
+            Self::Mask(_) => return false,
+            Self::Point { position: current } => {
+                if index != 0 {
+                    return false;
+                }
+                *current = position;
+            }
+            Self::Ellipse {
+                center,
+                radius_x,
+                radius_y,
+            } => match index {
+                0 | 2 => *radius_x = (position.x - center.x).abs(),
+                1 | 3 => *radius_y = (position.y - center.y).abs(),
+                _ => return false,
+            },
+        }
+        true
+    }
+
+    /// Insert a vertex at `index` (polygons and polylines only)
+    ///
+    /// In multi-polygons the vertex joins the ring of the vertex before it.
+    pub fn insert_vertex(&mut self, index: usize, position: Point) -> bool {
+        match self {
+            Self::Polygon { points } | Self::Polyline { points } if index <= points.len() => {
+                points.insert(index, position);
+                true
+            }
+            Self::MultiPolygon { polygons } => {
+                let Some((part, ring, offset)) =
+                    index.checked_sub(1).map_or(Some((0, 0, 0)), |before| {
+                        Self::locate_vertex(polygons, before).map(|(p, r, o)| (p, r, o + 1))
+                    })
+                else {
+                    return false;
+                };
+                match polygons.get_mut(part) {
+                    Some(polygon) => {
+                        Self::ring_mut(polygon, ring).insert(offset, position);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    /// Remove the vertex at `index`, keeping the minimum vertex count
+    ///
+    /// Removing a vertex from a triangular hole or extra part of a
+    /// multi-polygon removes that whole ring.
+    pub fn remove_vertex(&mut self, index: usize) -> bool {
+        let min_points = self.kind().min_points();
+        match self {
+            Self::Polygon { points } | Self::Polyline { points }
+                if points.len() > min_points && index < points.len() =>
+            {
+                points.remove(index);
+                true
+            }
+            Self::MultiPolygon { polygons } => {
+                let Some((part, ring, offset)) = Self::locate_vertex(polygons, index) else {
+                    return false;
+                };
+                let points = Self::ring_mut(&mut polygons[part], ring);
+                if points.len() > min_points {
+                    points.remove(offset);
+                } else if ring > 0 {
+                    polygons[part].holes.remove(ring - 1);
+                } else if polygons.len() > 1 {
+                    polygons.remove(part);
+                } else {
+                    return false;
+                }
+                self.normalize();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Apply `f` to every stored point of the shape
+    pub fn map_points<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut Point),
+    {
+        match self {
+            Self::Box { min, max } => {
+                f(min);
+                f(max);
+                *self = Self::from_corners(*min, *max);
+            }
+            Self::RotatedBox { corners } => corners.iter_mut().for_each(f),
+            Self::Polygon { points } | Self::Polyline { points } => points.iter_mut().for_each(f),
+            Self::Point { position } => f(position),
+            Self::Ellipse { center, .. } => f(center),
+            Self::MultiPolygon { polygons } => polygons
+                .iter_mut()
+                .flat_map(|polygon| {
+                    std::iter::once(&mut polygon.points).chain(polygon.holes.iter_mut())
+                })
+                .flatten()
+                .for_each(f),
+            // Masks are pixel data without points to map
+            Self::Mask(_) => {}
+        }
+    }
+
+    /// Scale the shape about the center of its bounding box
+    pub fn scale(&mut self, factor: f32) {
+        let Some((min, max)) = self.bounding_box() else {
+            return;
+        };
+        let cx = (min.x + max.x) / 2.0;
+        let cy = (min.y + max.y) / 2.0;
+
+        if let Self::Ellipse {
+            radius_x, radius_y, ..
+        } = self
+        {
+            *radius_x *= factor;
+            *radius_y *= factor;
+            return;
+        }
+
+        self.map_points(|p| {
+            p.x = cx + (p.x - cx) * factor;
+            p.y = cy + (p.y - cy) * factor;
+        });
+    }
+
+    /// Drop vertices of polygon, polyline and multi-polygon rings that lie
+    /// within `tolerance` pixels of the simplified outline (Douglas–Peucker)
+    ///
+    /// Distances are measured on an image of `width` x `height` pixels. Rings
+    /// that would fall below their minimum size are left alone. Returns
+    /// whether any vertex was removed.
+    pub fn simplify(&mut self, tolerance: f32, width: u32, height: u32) -> bool {
+        let (sx, sy) = (width as f32, height as f32);
+        self.map_free_rings(|ring, closed| {
+            let pixels: Vec<Point> = ring
+                .iter()
+                .map(|p| Point::new(p.x * sx, p.y * sy))
+                .collect();
+            geometry::simplify_indices(&pixels, tolerance, closed)
+                .into_iter()
+                .map(|index| ring[index])
+                .collect()
+        })
+    }
+
+    /// Round off the corners of polygon, polyline and multi-polygon rings with
+    /// one round of Chaikin corner cutting, doubling their vertex count
+    pub fn smooth(&mut self) -> bool {
+        self.map_free_rings(geometry::smooth)
+    }
+
+    /// Insert vertices into polygon, polyline and multi-polygon rings so that
+    /// no edge is longer than `max_length` pixels of a `width` x `height` image
+    pub fn densify(&mut self, max_length: f32, width: u32, height: u32) -> bool {
+        let (sx, sy) = (width as f32, height as f32);
+        self.map_free_rings(|ring, closed| {
+            let pixels: Vec<Point> = ring
+                .iter()
+                .map(|p| Point::new(p.x * sx, p.y * sy))
+                .collect();
+            let dense = geometry::densify(&pixels, max_length, closed);
+            if dense.len() == ring.len() {
+                return ring.to_vec();
+            }
+            dense
+                .into_iter()
+                .map(|p| Point::new(p.x / sx, p.y / sy))
+                .collect()
+        })
+    }
+
+    /// Replace every ring of a shape with free vertices by `f(ring, closed)`,
+    /// unless the result is too small for the ring; returns whether any changed
+    fn map_free_rings<F>(&mut self, mut f: F) -> bool
+    where
+        F: FnMut(&[Point], bool) -> Vec<Point>,
+    {
+        let mut apply = |points: &mut Vec<Point>, closed: bool| {
+            let min_points = if closed { 3 } else { 2 };
+            let updated = f(points, closed);
+            if updated.len() < min_points || updated == *points {
+                return false;
+            }
+            *points = updated;
+            true
+        };
+        match self {
+            Self::Polygon { points } => apply(points, true),
+            Self::Polyline { points } => apply(points, false),
+            Self::MultiPolygon { polygons } => {
+                let mut changed = false;
+                for polygon in polygons {
+                    changed |= apply(&mut polygon.points, true);
+                    for hole in &mut polygon.holes {
+                        changed |= apply(hole, true);
+                    }
+                }
+                changed
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shape_serialization() {
+        let shape = Shape::from_corners(Point::new(0.5, 0.6), Point::new(0.1, 0.2));
+        let yaml = serde_yaml::to_string(&shape).unwrap();
+        assert!(yaml.contains("type: box"));
+
+        let parsed: Shape = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed, shape);
+        assert_eq!(parsed.bounding_box().unwrap().0, Point::new(0.1, 0.2));
+    }
+
+    #[test]
+    fn test_from_points() {
+        let points = [Point::new(0.2, 0.2), Point::new(0.6, 0.4)];
+        let ellipse = Shape::from_points(ShapeKind::Ellipse, &points, 1.0).unwrap();
+        assert!(ellipse.contains(&Point::new(0.4, 0.3)));
+        assert!(!ellipse.contains(&Point::new(0.21, 0.21)));
+        assert!(Shape::from_points(ShapeKind::Polygon, &points, 1.0).is_none());
+
+        // Rotated box on a 2:1 image stays rectangular in pixel space
+        let points = [
+            Point::new(0.1, 0.1),
+            Point::new(0.3, 0.3),
+            Point::new(0.3, 0.1),
+        ];
+        let Shape::RotatedBox { corners } =
+            Shape::from_points(ShapeKind::RotatedBox, &points, 2.0).unwrap()
+        else {
+            panic!("expected a rotated box");
+        };
+        let [a, b, c, _] = corners;
+        let dot = (b.x - a.x) * (c.x - b.x) * 4.0 + (b.y - a.y) * (c.y - b.y);
+        assert!(dot.abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_move_box_corner() {
+        let mut shape = Shape::from_corners(Point::new(0.2, 0.2), Point::new(0.4, 0.4));
+        assert!(shape.move_vertex(2, Point::new(0.6, 0.5)));
+        assert_eq!(
+            shape.bounding_box().unwrap(),
+            (Point::new(0.2, 0.2), Point::new(0.6, 0.5))
+        );
+    }
+
+    #[test]
+    fn test_open_shapes() {
+        let polyline = Shape::Polyline {
+            points: vec![Point::new(0.0, 0.0), Point::new(1.0, 0.0)],
+        };
+        assert_eq!(polyline.area(), 0.0);
+        assert!(!polyline.contains(&Point::new(0.5, 0.0)));
+        assert!((polyline.distance_to(&Point::new(0.5, 0.2)) - 0.2).abs() < 1e-6);
+
+        let mut polyline = polyline;
+        assert!(!polyline.remove_vertex(0));
+        assert!(polyline.insert_vertex(1, Point::new(0.5, 0.5)));
+        assert!(polyline.remove_vertex(1));
+    }
+
+    #[test]
+    fn test_holes_and_parts() {
+        let square = |min: f32, max: f32| {
+            vec![
+                Point::new(min, min),
+                Point::new(max, min),
+                Point::new(max, max),
+                Point::new(min, max),
+            ]
+        };
+        let mut shape = Shape::polygon(square(0.0, 0.4));
+        assert!(!shape.add_hole(square(0.5, 0.6)));
+        assert!(shape.add_hole(square(0.1, 0.2)));
+        assert!(shape.add_part(square(0.6, 0.8)));
+        assert_eq!(shape.kind(), ShapeKind::MultiPolygon);
+        assert_eq!(shape.vertices().len(), 12);
+        assert_eq!(shape.edges().len(), 12);
+        assert_eq!(shape.simple_rings().len(), 2);
+        assert!((shape.area() - (0.16 - 0.01 + 0.04)).abs() < 1e-6);
+        assert!(!shape.contains(&Point::new(0.15, 0.15)));
+        assert!(shape.contains(&Point::new(0.7, 0.7)));
+
+        // Vertices are indexed across rings: outer, hole, second part
+        assert!(shape.move_vertex(4, Point::new(0.05, 0.05)));
+        assert!(shape.insert_vertex(8, Point::new(0.15, 0.25)));
+        assert_eq!(shape.rings()[1].len(), 5);
+
+        let yaml = serde_yaml::to_string(&shape).unwrap();
+        assert!(yaml.contains("type: multi_polygon"));
+        assert_eq!(serde_yaml::from_str::<Shape>(&yaml).unwrap(), shape);
+
+        // Dropping the extra part and the hole turns it back into a polygon
+        for _ in 0..2 {
+            assert!(shape.remove_vertex(9));
+        }
+        assert!(shape.remove_vertex(4));
+        assert!(shape.remove_vertex(4));
+        assert_eq!(shape.kind(), ShapeKind::MultiPolygon);
+        assert!(shape.remove_vertex(4));
+        assert_eq!(shape, Shape::polygon(square(0.0, 0.4)));
+    }
+
+    #[test]
+    fn test_mask_shape() {
+        let square = Shape::from_corners(Point::new(0.25, 0.25), Point::new(0.75, 0.75));
+        let shape = Shape::Mask(square.to_mask(8, 8).unwrap());
+        assert!((shape.area() - 0.25).abs() < 1e-6);
+        assert!(shape.contains(&Point::new(0.5, 0.5)));
+        assert_eq!(shape.bounding_box(), square.bounding_box());
+        assert!(shape.vertices().is_empty());
+
+        let yaml = serde_yaml::to_string(&shape).unwrap();
+        assert!(yaml.contains("type: mask"));
+        assert_eq!(serde_yaml::from_str::<Shape>(&yaml).unwrap(), shape);
+
+        let polygon = shape.mask_to_polygon().unwrap();
+        assert_eq!(polygon.kind(), ShapeKind::Polygon);
+        assert_eq!(polygon.bounding_box(), square.bounding_box());
+    }
+
+    #[test]
+    fn test_simplify_smooth_densify() {
+        // Square outline with a 1px bump on its top edge, on a 100 x 50 image
+        let mut shape = Shape::polygon(vec![
+            Point::new(0.1, 0.2),
+            Point::new(0.3, 0.2),
+            Point::new(0.5, 0.22),
+            Point::new(0.7, 0.2),
+            Point::new(0.9, 0.2),
+            Point::new(0.9, 0.8),
+            Point::new(0.1, 0.8),
+        ]);
+        let original = shape.clone();
+        // Collinear vertices go first, the bump only with a larger tolerance
+        assert!(shape.simplify(0.5, 100, 50));
+        assert_eq!(shape.vertices().len(), 5);
+        assert!(!shape.simplify(0.5, 100, 50));
+        assert!(shape.simplify(2.0, 100, 50));
+        assert_eq!(shape.vertices().len(), 4);
+        assert_eq!(shape.vertices()[0], Point::new(0.1, 0.2));
+        // Nothing is left to drop without going below a triangle
+        assert!(!shape.clone().simplify(1000.0, 100, 50));
+
+        // 80 x 30 px box, split into edges of at most 10 px
+        assert!(shape.densify(10.0, 100, 50));
+        assert_eq!(shape.vertices().len(), 8 + 3 + 8 + 3);
+        assert!(!shape.densify(10.0, 100, 50));
+
+        let mut line = Shape::Polyline {
+            points: original.vertices()[..3].to_vec(),
+        };
+        assert!(line.smooth());
+        assert_eq!(line.vertices().len(), 4);
+        assert_eq!(line.vertices()[0], Point::new(0.1, 0.2));
+
+        let mut ellipse = Shape::Ellipse {
+            center: Point::new(0.5, 0.5),
+            radius_x: 0.1,
+            radius_y: 0.1,
+        };
+        assert!(!ellipse.smooth());
+        assert!(!ellipse.simplify(1.0, 100, 50));
+    }
+}
@@ -0,0 +1,217 @@
+//! Encodings of annotation files
+//!
+//! Projects store annotations as YAML unless `storage` in `meta.yaml` selects
+//! JSON or a compact binary encoding (MessagePack). Every encoding holds the
+//! same document, so files of older schema versions are migrated whichever
+//! encoding they are stored in, see [`crate::migrate`].
+
+use crate::migrate::{annotation_from_document, ANNOTATION_VERSION};
+use crate::{Annotation, Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::path::Path;
+
+/// Encoding of the annotation files of a project
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageFormat {
+    #[default]
+    Yaml,
+    Json,
+    /// MessagePack
+    Binary,
+}
+
+impl StorageFormat {
+    pub fn all() -> [Self; 3] {
+        [Self::Yaml, Self::Json, Self::Binary]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Yaml => "yaml",
+            Self::Json => "json",
+            Self::Binary => "binary",
+        }
+    }
+
+    /// File extension of annotation files in this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Yaml => "yaml",
+            Self::Json => "json",
+            Self::Binary => "msgpack",
+        }
+    }
+
+    /// Format of an annotation file, judged by its extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "yaml" | "yml" => Some(Self::Yaml),
+            "json" => Some(Self::Json),
+            "msgpack" => Some(Self::Binary),
+            _ => None,
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Encoder and decoder for this format
+    pub fn storage(&self) -> &'static dyn AnnotationStorage {
+        match self {
+            Self::Yaml => &YamlStorage,
+            Self::Json => &JsonStorage,
+            Self::Binary => &BinaryStorage,
+        }
+    }
+}
+
+/// Trait for reading and writing annotation files in one encoding
+pub trait AnnotationStorage {
+    /// Encode an annotation as file content
+    fn encode(&self, annotation: &Annotation) -> Result<Vec<u8>>;
+
+    /// Decode file content of any supported schema version, also returning
+    /// whether it was migrated from an older version
+    fn decode(&self, content: &[u8]) -> Result<(Annotation, bool)>;
+}
+
+/// Human-readable YAML, the default
+pub struct YamlStorage;
+
+impl AnnotationStorage for YamlStorage {
+    fn encode(&self, annotation: &Annotation) -> Result<Vec<u8>> {
+        Ok(serde_yaml::to_string(annotation)?.into_bytes())
+    }
+
+    fn decode(&self, content: &[u8]) -> Result<(Annotation, bool)> {
+        annotation_from_document(serde_yaml::from_slice(content)?)
+    }
+}
+
+/// Pretty-printed JSON for tooling
+pub struct JsonStorage;
+
+impl AnnotationStorage for JsonStorage {
+    fn encode(&self, annotation: &Annotation) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec_pretty(annotation)?)
+    }
+
+    fn decode(&self, content: &[u8]) -> Result<(Annotation, bool)> {
+        annotation_from_document(serde_json::from_slice(content)?)
+    }
+}
+
+/// Compact MessagePack, fast to parse for dense annotations
+///
+/// Structs are written as maps with field names, so the document can still
+/// be read untyped when it needs migrating.
+pub struct BinaryStorage;
+
+impl AnnotationStorage for BinaryStorage {
+    fn encode(&self, annotation: &Annotation) -> Result<Vec<u8>> {
+        rmp_serde::to_vec_named(annotation).map_err(invalid)
+    }
+
+    fn decode(&self, content: &[u8]) -> Result<(Annotation, bool)> {
+        // Files of the current version decode directly; anything else is
+        // read as a raw document and goes through migration
+        if let Ok(annotation) = rmp_serde::from_slice::<Annotation>(content) {
+            if annotation.version == ANNOTATION_VERSION {
+                return Ok((annotation, false));
+            }
+        }
+        let doc: Value = rmp_serde::from_slice(content).map_err(invalid)?;
+        annotation_from_document(doc)
+    }
+}
+
+fn invalid(err: impl std::fmt::Display) -> Error {
+    Error::InvalidData(format!("Invalid MessagePack annotation: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::annotation::Object;
+    use crate::geometry::Point;
+
+    fn sample() -> Annotation {
+        let mut annotation = Annotation::new("test");
+        annotation.rois.push(vec![
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 0.0),
+            Point::new(0.5, 1.0),
+        ]);
+        let mut obj = Object::new(
+            -3,
+            300,
+            (0..300)
+                .map(|i| Point::new(i as f32 / 300.0, 0.123_456_7))
+                .collect(),
+        );
+        obj.confidence = 0.25;
+        obj.set_property(0, 70_000, 1.0);
+        annotation.add_object(obj);
+        annotation
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let annotation = sample();
+        for format in StorageFormat::all() {
+            let storage = format.storage();
+            let content = storage.encode(&annotation).unwrap();
+            let (decoded, migrated) = storage.decode(&content).unwrap();
+            assert_eq!(decoded, annotation, "{}", format.as_str());
+            assert!(!migrated);
+        }
+
+        let yaml = YamlStorage.encode(&annotation).unwrap();
+        let binary = BinaryStorage.encode(&annotation).unwrap();
+        assert!(binary.len() * 2 < yaml.len());
+    }
+
+    #[test]
+    fn test_messagepack_decoding() {
+        let content = BinaryStorage.encode(&sample()).unwrap();
+        assert!(BinaryStorage.decode(&content[..content.len() / 2]).is_err());
+        assert!(BinaryStorage
+            .decode(&[0xdd, 0xff, 0xff, 0xff, 0xff])
+            .is_err());
+
+        // Older documents are migrated
+        let doc: Value =
+            serde_yaml::from_str("roi: [{x: 0.1, y: 0.1}, {x: 0.2, y: 0.1}, {x: 0.2, y: 0.2}]")
+                .unwrap();
+        let content = rmp_serde::to_vec_named(&doc).unwrap();
+        let (annotation, migrated) = BinaryStorage.decode(&content).unwrap();
+        assert!(migrated);
+        assert_eq!(annotation.rois.len(), 1);
+    }
+
+    #[test]
+    fn test_json_migration() {
+        let json =
+            br#"{"roi": [{"x": 0.1, "y": 0.1}, {"x": 0.2, "y": 0.1}, {"x": 0.2, "y": 0.2}]}"#;
+        let (annotation, migrated) = JsonStorage.decode(json).unwrap();
+        assert!(migrated);
+        assert_eq!(annotation.version, ANNOTATION_VERSION);
+        assert_eq!(annotation.rois.len(), 1);
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        for format in StorageFormat::all() {
+            let path = Path::new("labels/0001").with_extension(format.extension());
+            assert_eq!(StorageFormat::from_path(&path), Some(format));
+        }
+        assert_eq!(
+            StorageFormat::from_path(Path::new("a.yml")),
+            Some(StorageFormat::Yaml)
+        );
+        assert_eq!(StorageFormat::from_path(Path::new("a.txt")), None);
+    }
+}
@@ -1,20 +1,31 @@
 use crate::canvas::Canvas;
 use crate::state::AppState;
 use crate::tools::DrawingTools;
+use anyhow::Context as _;
 use egui::{Context, Vec2};
 use std::path::PathBuf;
 
 mod about_dialog;
 mod canvas_view;
+mod export_dialog;
 mod import_export;
+mod maintenance_dialog;
 mod menu;
 mod options_dialog;
 mod panels;
+mod recovery_dialog;
+mod shape_tool_dialog;
 mod status_bar;
 mod toolbar;
 
 use about_dialog::AboutDialogState;
+use export_dialog::ExportDialogState;
+use maintenance_dialog::{
+    MaintenanceAction, MaintenanceDialogState, MaintenanceReport, MaintenanceTask,
+};
 use options_dialog::{DialogButtonAction, OptionsDialogState};
+use recovery_dialog::{RecoveryAction, RecoveryDialogState};
+use shape_tool_dialog::{ShapeTool, ShapeToolAction, ShapeToolDialogState};
 
 const ZOOM_LEVELS: [f32; 9] = [25.0, 50.0, 75.0, 100.0, 125.0, 150.0, 200.0, 300.0, 400.0];
 
@@ -24,6 +35,10 @@ pub struct LabApp {
     shortcut_editor: Option<crate::shortcuts::ShortcutEditorState>,
     options_dialog: OptionsDialogState,
     about_dialog: AboutDialogState,
+    shape_tool_dialog: ShapeToolDialogState,
+    recovery_dialog: RecoveryDialogState,
+    export_dialog: ExportDialogState,
+    maintenance_dialog: MaintenanceDialogState,
     // UI settings
     ui_scale: f32,
     pixels_per_point: f32,
@@ -74,22 +89,29 @@ impl LabApp {
             shortcut_editor: None,
             options_dialog: OptionsDialogState::new(crate::i18n::Language::ZhCN, false),
             about_dialog: AboutDialogState::new(),
+            shape_tool_dialog: ShapeToolDialogState::new(),
+            recovery_dialog: RecoveryDialogState::new(),
+            export_dialog: ExportDialogState::new(),
+            maintenance_dialog: MaintenanceDialogState::new(),
             ui_scale,
             pixels_per_point,
         }
     }
 
-    fn with_polygon_mut<F>(annotation: &mut lab_core::Annotation, shape_id: i32, mutator: F) -> bool
+    fn with_shape_mut<F>(annotation: &mut lab_core::Annotation, shape_id: i32, mutator: F) -> bool
     where
-        F: FnOnce(&mut Vec<lab_core::Point>),
+        F: FnOnce(&mut lab_core::Shape),
     {
         if let Some(roi_index) = crate::state::roi_index_from_id(shape_id) {
             if let Some(roi_points) = annotation.rois.get_mut(roi_index) {
-                mutator(roi_points);
+                // ROIs are stored as plain polygons
+                let mut shape = lab_core::Shape::polygon(std::mem::take(roi_points));
+                mutator(&mut shape);
+                *roi_points = shape.outline();
                 return true;
             }
         } else if let Some(obj) = annotation.objects.iter_mut().find(|o| o.id == shape_id) {
-            mutator(&mut obj.polygon);
+            mutator(&mut obj.shape);
             return true;
         }
 
@@ -102,6 +124,78 @@ impl LabApp {
         self.canvas.reset_view();
         Ok(())
     }
+
+    /// Open a project, first asking about unsaved edits left by a crashed session
+    pub fn open_project(&mut self, path: PathBuf) {
+        // Reopening the same project: the open image's journal is this session's
+        let own_image = self
+            .state
+            .project
+            .as_ref()
+            .filter(|project| project.root == path)
+            .and(self.state.current_image.as_ref())
+            .and_then(|image| image.path.file_name())
+            .and_then(|name| name.to_str())
+            .map(str::to_string);
+        let pending = lab_utils::Project::open(&path)
+            .and_then(|project| lab_utils::recovery::pending_edits(&project))
+            .map(|edits| {
+                edits
+                    .into_iter()
+                    .filter(|edit| Some(&edit.image_name) != own_image.as_ref())
+                    .collect::<Vec<_>>()
+            });
+        match pending {
+            Ok(edits) if !edits.is_empty() => {
+                log::warn!(
+                    "Found unsaved edits of {} images in {:?}",
+                    edits.len(),
+                    path
+                );
+                self.recovery_dialog.open(path, edits);
+            }
+            // Problems opening the project are reported when it is loaded
+            _ => self.finish_open_project(path),
+        }
+    }
+
+    /// Load a project, reporting load failures and metadata warnings in a dialog
+    fn finish_open_project(&mut self, path: PathBuf) {
+        match self.load_project_from_path(path.clone()) {
+            Ok(()) => {
+                log::info!("Successfully loaded project from {:?}", path);
+                let warnings = self
+                    .state
+                    .project
+                    .as_ref()
+                    .map(|project| project.meta_warnings())
+                    .unwrap_or_default();
+                if !warnings.is_empty() {
+                    let message = warnings
+                        .iter()
+                        .map(|warning| warning.to_string())
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    log::warn!("Problems in {:?}/meta.yaml:\n{}", path, message);
+                    let _ = rfd::MessageDialog::new()
+                        .set_title(self.state.i18n.t("error.meta_warnings"))
+                        .set_description(&message)
+                        .set_buttons(rfd::MessageButtons::Ok)
+                        .set_level(rfd::MessageLevel::Warning)
+                        .show();
+                }
+            }
+            Err(e) => {
+                log::error!("Failed to load project from {:?}: {}", path, e);
+                let _ = rfd::MessageDialog::new()
+                    .set_title(self.state.i18n.t("error.load_project"))
+                    .set_description(e.to_string())
+                    .set_buttons(rfd::MessageButtons::Ok)
+                    .set_level(rfd::MessageLevel::Error)
+                    .show();
+            }
+        }
+    }
 }
 
 impl eframe::App for LabApp {
@@ -128,6 +222,14 @@ impl eframe::App for LabApp {
         self.show_shortcut_settings(ctx);
         self.show_options_dialog(ctx);
         self.show_about_dialog(ctx);
+        self.show_shape_tool_dialog(ctx);
+        self.show_recovery_dialog(ctx);
+        self.show_export_dialog(ctx);
+        self.show_maintenance_dialog(ctx);
+
+        if let Some(wait) = self.state.flush_journal() {
+            ctx.request_repaint_after(wait);
+        }
     }
 }
 
@@ -842,6 +944,12 @@ impl LabApp {
             }
 
             // Edit operations
+            ShortcutAction::Undo => {
+                self.state.undo();
+            }
+            ShortcutAction::Redo => {
+                self.state.redo();
+            }
             ShortcutAction::Copy => {
                 if editing_mode {
                     self.state.copy_selected();
@@ -872,6 +980,15 @@ impl LabApp {
             ShortcutAction::FixSelfIntersection => {
                 self.fix_selected_self_intersections();
             }
+            ShortcutAction::SimplifyShape => {
+                self.open_shape_tool(ShapeTool::Simplify);
+            }
+            ShortcutAction::SmoothShape => {
+                self.smooth_selected_shape();
+            }
+            ShortcutAction::DensifyShape => {
+                self.open_shape_tool(ShapeTool::Densify);
+            }
 
             // Mode switching
             ShortcutAction::SwitchToNormalMode => {
@@ -1070,27 +1187,27 @@ impl LabApp {
                 // Priority 1: Move selected vertex if any
                 if let Some((obj_id, vertex_idx)) = self.state.editing_state.selected_vertex {
                     let mut updated = false;
-                    Self::with_polygon_mut(annotation, obj_id, |polygon| {
-                        if vertex_idx < polygon.len() {
-                            polygon[vertex_idx].x =
-                                (polygon[vertex_idx].x + dx_norm).clamp(0.0, 1.0);
-                            polygon[vertex_idx].y =
-                                (polygon[vertex_idx].y + dy_norm).clamp(0.0, 1.0);
-                            updated = true;
+                    Self::with_shape_mut(annotation, obj_id, |shape| {
+                        if let Some(vertex) = shape.vertices().get(vertex_idx) {
+                            let moved = lab_core::Point::new(
+                                (vertex.x + dx_norm).clamp(0.0, 1.0),
+                                (vertex.y + dy_norm).clamp(0.0, 1.0),
+                            );
+                            updated = shape.move_vertex(vertex_idx, moved);
                         }
                     });
                     if updated {
-                        self.state.has_unsaved_changes = true;
+                        self.state.record_repeated_edit("Move vertex");
                     }
                 } else if let Some(obj_id) = self.state.selected_object_id {
                     // Priority 2: Move selected object
-                    Self::with_polygon_mut(annotation, obj_id, |polygon| {
-                        for vertex in polygon {
+                    Self::with_shape_mut(annotation, obj_id, |shape| {
+                        shape.map_points(|vertex| {
                             vertex.x = (vertex.x + dx_norm).clamp(0.0, 1.0);
                             vertex.y = (vertex.y + dy_norm).clamp(0.0, 1.0);
-                        }
+                        });
                     });
-                    self.state.has_unsaved_changes = true;
+                    self.state.record_repeated_edit("Move");
                 }
             }
         }
@@ -1145,8 +1262,10 @@ impl LabApp {
     fn finish_drawing(&mut self) -> bool {
         self.flush_pending_draw_clicks();
 
-        if self.state.temp_points.len() < 3 {
-            log::warn!("Need at least 3 points to create a shape");
+        let shape_kind = self.state.active_draw_shape();
+        let min_points = shape_kind.min_points();
+        if self.state.temp_points.len() < min_points {
+            log::warn!("Need at least {} points to create a shape", min_points);
             return false;
         }
 
@@ -1154,9 +1273,9 @@ impl LabApp {
             if let Some(annotation) = &mut self.state.current_annotation {
                 annotation.rois.push(self.state.temp_points.clone());
                 annotation.touch();
-                self.state.has_unsaved_changes = true;
                 let roi_id = crate::state::roi_id_from_index(annotation.rois.len() - 1);
                 self.state.selected_object_id = Some(roi_id);
+                self.state.record_edit("Create ROI");
             }
 
             self.state.clear_drawing_state();
@@ -1164,7 +1283,25 @@ impl LabApp {
             return true;
         }
 
-        let polygon = self.state.temp_points.clone();
+        if matches!(
+            self.state.draw_target,
+            crate::state::DrawTarget::Hole | crate::state::DrawTarget::Part
+        ) {
+            return self.add_ring_to_selected();
+        }
+
+        let aspect = self
+            .state
+            .current_image
+            .as_ref()
+            .filter(|image| image.height > 0)
+            .map(|image| image.width as f32 / image.height as f32)
+            .unwrap_or(1.0);
+        let Some(shape) = lab_core::Shape::from_points(shape_kind, &self.state.temp_points, aspect)
+        else {
+            log::warn!("Invalid {} drawn", shape_kind.as_str());
+            return false;
+        };
 
         // Get next object ID
         let new_id = if let Some(annotation) = &self.state.current_annotation {
@@ -1182,21 +1319,15 @@ impl LabApp {
             .unwrap_or(0);
 
         // Create new object
-        let new_object = lab_core::Object {
-            id: new_id,
-            category: default_category,
-            confidence: 1.0,
-            polygon,
-            properties: std::collections::HashMap::new(),
-        };
+        let new_object = lab_core::Object::with_shape(new_id, default_category, shape);
 
         // Add to annotation
         if let Some(annotation) = &mut self.state.current_annotation {
             annotation.objects.push(new_object);
-            self.state.has_unsaved_changes = true;
 
             // Select the new object
             self.state.selected_object_id = Some(new_id);
+            self.state.record_edit("Create object");
         }
 
         self.state.clear_drawing_state();
@@ -1205,6 +1336,99 @@ impl LabApp {
         true
     }
 
+    /// Rasterize the selected shape into a mask at the image resolution
+    fn convert_selected_to_mask(&mut self) {
+        if self.state.editing_state.mode != crate::state::EditMode::Editing {
+            return;
+        }
+        let Some((width, height)) = self
+            .state
+            .current_image
+            .as_ref()
+            .map(|image| (image.width, image.height))
+        else {
+            return;
+        };
+
+        if let Some(obj) = self
+            .state
+            .selected_object_id
+            .zip(self.state.current_annotation.as_mut())
+            .and_then(|(obj_id, annotation)| annotation.find_object_mut(obj_id))
+        {
+            if matches!(obj.shape, lab_core::Shape::Mask(_)) {
+                return;
+            }
+            match obj.shape.to_mask(width, height) {
+                Some(mask) if !mask.is_empty() => {
+                    obj.shape = lab_core::Shape::Mask(mask);
+                    self.state.editing_state.selected_vertex = None;
+                    self.state.record_edit("Convert to mask");
+                }
+                _ => log::warn!("Only shapes that enclose pixels can become masks"),
+            }
+        }
+    }
+
+    /// Trace the selected mask into a polygon (with holes and parts as needed)
+    fn convert_selected_mask_to_polygon(&mut self) {
+        if self.state.editing_state.mode != crate::state::EditMode::Editing {
+            return;
+        }
+
+        if let Some(obj) = self
+            .state
+            .selected_object_id
+            .zip(self.state.current_annotation.as_mut())
+            .and_then(|(obj_id, annotation)| annotation.find_object_mut(obj_id))
+        {
+            if let Some(shape) = obj.shape.mask_to_polygon() {
+                obj.shape = shape;
+                self.state.record_edit("Convert mask to polygon");
+            }
+        }
+    }
+
+    /// Add the drawn ring to the selected polygon as a hole or a new part
+    fn add_ring_to_selected(&mut self) -> bool {
+        let is_hole = self.state.draw_target == crate::state::DrawTarget::Hole;
+        let ring = self.state.temp_points.clone();
+        let Some(obj) = self
+            .state
+            .selected_object_id
+            .zip(self.state.current_annotation.as_mut())
+            .and_then(|(obj_id, annotation)| annotation.find_object_mut(obj_id))
+        else {
+            log::warn!("Select a polygon before drawing a hole or part");
+            return false;
+        };
+
+        let obj_id = obj.id;
+        let added = if is_hole {
+            obj.shape.add_hole(ring)
+        } else {
+            obj.shape.add_part(ring)
+        };
+        if !added {
+            if is_hole {
+                log::warn!("Hole must lie inside a polygon of object #{}", obj_id);
+            } else {
+                log::warn!("Parts can only be added to polygons");
+            }
+            return false;
+        }
+
+        self.state.clear_drawing_state();
+        if is_hole {
+            self.state.record_edit("Add hole");
+            log::info!("Added hole to object #{}", obj_id);
+        } else {
+            self.state.record_edit("Add part");
+            log::info!("Added part to object #{}", obj_id);
+        }
+        true
+    }
+
     fn open_project_dialog(&mut self) {
         // Use rfd to open a folder picker dialog
         if let Some(path) = rfd::FileDialog::new()
@@ -1214,15 +1438,7 @@ impl LabApp {
             log::info!("Selected project directory: {:?}", path);
 
             // Load the project
-            match self.state.load_project(path.clone()) {
-                Ok(()) => {
-                    log::info!("Successfully loaded project from {:?}", path);
-                    self.canvas.reset_view();
-                }
-                Err(e) => {
-                    log::error!("Failed to load project from {:?}: {}", path, e);
-                }
-            }
+            self.open_project(path);
         } else {
             log::info!("Project selection cancelled");
         }
@@ -1238,20 +1454,16 @@ impl LabApp {
             self.state.selected_object_id,
         ) {
             let mut updated = false;
-            Self::with_polygon_mut(annotation, obj_id, |polygon| {
-                if let Some((min, max)) = crate::geometry::bounding_box(polygon) {
-                    *polygon = vec![
-                        lab_core::Point::new(min.x, min.y),
-                        lab_core::Point::new(max.x, min.y),
-                        lab_core::Point::new(max.x, max.y),
-                        lab_core::Point::new(min.x, max.y),
-                    ];
-                    updated = true;
+            Self::with_shape_mut(annotation, obj_id, |shape| {
+                if let Some((min, max)) = lab_core::geometry::bounding_box(&shape.vertices()) {
+                    let rectangle = lab_core::Shape::from_corners(min, max);
+                    updated = *shape != rectangle;
+                    *shape = rectangle;
                 }
             });
             if updated {
-                self.state.has_unsaved_changes = true;
                 self.state.editing_state.selected_vertex = None;
+                self.state.record_edit("Convert to rectangle");
             }
         }
     }
@@ -1266,18 +1478,326 @@ impl LabApp {
             self.state.selected_object_id,
         ) {
             let mut updated = false;
-            Self::with_polygon_mut(annotation, obj_id, |polygon| {
-                if crate::geometry::fix_self_intersections(polygon) {
-                    updated = true;
+            Self::with_shape_mut(annotation, obj_id, |shape| match shape {
+                lab_core::Shape::Polygon { points } => {
+                    updated = lab_core::geometry::fix_self_intersections(points);
+                }
+                lab_core::Shape::MultiPolygon { polygons } => {
+                    for polygon in polygons {
+                        updated |= lab_core::geometry::fix_self_intersections(&mut polygon.points);
+                        for hole in &mut polygon.holes {
+                            updated |= lab_core::geometry::fix_self_intersections(hole);
+                        }
+                    }
                 }
+                _ => {}
             });
             if updated {
-                self.state.has_unsaved_changes = true;
                 self.state.editing_state.selected_vertex = None;
+                self.state.record_edit("Fix self-intersections");
             }
         }
     }
 
+    /// Size of the current image in pixels
+    fn current_image_size(&self) -> Option<(u32, u32)> {
+        self.state
+            .current_image
+            .as_ref()
+            .map(|image| (image.width, image.height))
+    }
+
+    /// Backed up versions of the current image's annotation, newest first
+    fn current_backups(&self) -> Vec<lab_utils::Backup> {
+        let (Some(project), Some(image)) = (&self.state.project, &self.state.current_image) else {
+            return Vec::new();
+        };
+        image
+            .path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| project.list_backups(name).ok())
+            .unwrap_or_default()
+    }
+
+    fn restore_backup(&mut self, path: &std::path::Path) {
+        match lab_core::io::load_annotation(path) {
+            Ok(annotation) => {
+                if self.state.editing_state.mode == crate::state::EditMode::Drawing {
+                    self.finish_drawing();
+                }
+                self.state.restore_version(annotation);
+                log::info!("Restored annotation from {:?}", path);
+            }
+            Err(e) => {
+                log::error!("Failed to restore annotation from {:?}: {}", path, e);
+                let _ = rfd::MessageDialog::new()
+                    .set_title(self.state.i18n.t("error.restore_backup"))
+                    .set_description(e.to_string())
+                    .set_buttons(rfd::MessageButtons::Ok)
+                    .set_level(rfd::MessageLevel::Error)
+                    .show();
+            }
+        }
+    }
+
+    /// The selected object, if its outline has free vertices
+    fn selected_free_shape(&self) -> Option<(i32, &lab_core::Shape)> {
+        let obj_id = self.state.selected_object_id?;
+        let obj = self
+            .state
+            .current_annotation
+            .as_ref()?
+            .find_object(obj_id)?;
+        obj.shape
+            .has_free_vertices()
+            .then_some((obj_id, &obj.shape))
+    }
+
+    /// Open the simplify or densify dialog for the selected object
+    fn open_shape_tool(&mut self, tool: ShapeTool) {
+        if self.state.editing_state.mode != crate::state::EditMode::Editing {
+            return;
+        }
+        if let Some((obj_id, _)) = self.selected_free_shape() {
+            self.shape_tool_dialog.open(tool, obj_id);
+        }
+    }
+
+    /// Pending result of the open shape tool dialog, for the canvas preview
+    fn shape_tool_preview(&self) -> Option<lab_core::Shape> {
+        let (_, target_id) = self.shape_tool_dialog.target?;
+        let (obj_id, shape) = self.selected_free_shape()?;
+        let (width, height) = self.current_image_size()?;
+        (obj_id == target_id)
+            .then(|| self.shape_tool_dialog.preview(shape, width, height))
+            .flatten()
+    }
+
+    fn show_recovery_dialog(&mut self, ctx: &egui::Context) {
+        let Some(action) = self.recovery_dialog.show(ctx, &self.state.i18n) else {
+            return;
+        };
+        let Some(root) = self.recovery_dialog.project_root.take() else {
+            return;
+        };
+        let edits = std::mem::take(&mut self.recovery_dialog.edits);
+        if action == RecoveryAction::Cancel {
+            return;
+        }
+
+        let result = lab_utils::Project::open(&root).map(|project| {
+            edits
+                .iter()
+                .filter_map(|(edit, restore)| {
+                    let result = if *restore {
+                        lab_utils::recovery::restore_edit(&project, edit)
+                    } else {
+                        lab_utils::recovery::clear_edit(&project, &edit.image_name)
+                    };
+                    result.err().map(|e| format!("{}: {}", edit.image_name, e))
+                })
+                .collect::<Vec<_>>()
+        });
+        match result {
+            Ok(failures) if failures.is_empty() => {}
+            Ok(failures) => {
+                let message = failures.join("\n");
+                log::error!("Failed to recover edits:\n{}", message);
+                let _ = rfd::MessageDialog::new()
+                    .set_title(self.state.i18n.t("error.recover_edits"))
+                    .set_description(&message)
+                    .set_buttons(rfd::MessageButtons::Ok)
+                    .set_level(rfd::MessageLevel::Error)
+                    .show();
+            }
+            // Reported by loading below
+            Err(e) => log::error!("Failed to recover edits in {:?}: {}", root, e),
+        }
+        self.finish_open_project(root);
+    }
+
+    /// Preview a rewrite of the project's annotation files
+    fn open_maintenance_dialog(&mut self, task: MaintenanceTask) {
+        match self.run_maintenance_task(task, true) {
+            Ok(preview) => self.maintenance_dialog.open(task, preview),
+            Err(e) => self.show_io_error(self.state.i18n.t("error.maintenance_failed"), e.to_string()),
+        }
+    }
+
+    fn run_maintenance_task(
+        &mut self,
+        task: MaintenanceTask,
+        dry_run: bool,
+    ) -> anyhow::Result<MaintenanceReport> {
+        let project = self
+            .state
+            .project
+            .as_mut()
+            .context(self.state.i18n.t("error.no_project"))?;
+        let report = match task {
+            MaintenanceTask::ConvertStorage(format) => {
+                lab_utils::storage::convert_storage(project, format, dry_run)?.into()
+            }
+            MaintenanceTask::Migrate => {
+                lab_utils::migration::migrate_project(project, dry_run)?.into()
+            }
+        };
+        Ok(report)
+    }
+
+    fn show_maintenance_dialog(&mut self, ctx: &egui::Context) {
+        let Some(action) = self.maintenance_dialog.show(ctx, &self.state.i18n) else {
+            return;
+        };
+        let Some(task) = self.maintenance_dialog.task else {
+            return;
+        };
+        match action {
+            MaintenanceAction::Preview => self.open_maintenance_dialog(task),
+            MaintenanceAction::Apply => {
+                // Pending edits are rewritten along with the other files
+                if self.state.has_unsaved_changes {
+                    if let Err(e) = self.state.save_annotation() {
+                        log::error!("Failed to save annotation: {}", e);
+                    }
+                }
+                match self.run_maintenance_task(task, false) {
+                    Ok(report) => {
+                        self.maintenance_dialog.report = report;
+                        self.maintenance_dialog.applied = true;
+                    }
+                    Err(e) => self
+                        .show_io_error(self.state.i18n.t("error.maintenance_failed"), e.to_string()),
+                }
+            }
+            MaintenanceAction::Close => self.maintenance_dialog.task = None,
+        }
+    }
+
+    fn show_shape_tool_dialog(&mut self, ctx: &egui::Context) {
+        let Some((tool, target_id)) = self.shape_tool_dialog.target else {
+            return;
+        };
+        // Close when the object is deselected or editing ends
+        let before = match self.selected_free_shape() {
+            Some((obj_id, shape))
+                if obj_id == target_id
+                    && self.state.editing_state.mode == crate::state::EditMode::Editing =>
+            {
+                shape.vertices().len()
+            }
+            _ => {
+                self.shape_tool_dialog.close();
+                return;
+            }
+        };
+        let preview = self.shape_tool_preview();
+        let after = preview
+            .as_ref()
+            .map_or(before, |shape| shape.vertices().len());
+
+        match self
+            .shape_tool_dialog
+            .show(ctx, &self.state.i18n, before, after)
+        {
+            Some(ShapeToolAction::Apply) => {
+                if let (Some(shape), Some(obj)) = (
+                    preview,
+                    self.state
+                        .current_annotation
+                        .as_mut()
+                        .and_then(|annotation| annotation.find_object_mut(target_id)),
+                ) {
+                    obj.shape = shape;
+                    self.state.editing_state.selected_vertex = None;
+                    self.state.record_edit(tool.edit_label());
+                }
+                self.shape_tool_dialog.close();
+            }
+            Some(ShapeToolAction::Cancel) => self.shape_tool_dialog.close(),
+            None => {}
+        }
+    }
+
+    /// Round off the corners of the selected outline
+    fn smooth_selected_shape(&mut self) {
+        if self.state.editing_state.mode != crate::state::EditMode::Editing {
+            return;
+        }
+
+        if let Some(obj) = self
+            .state
+            .selected_object_id
+            .zip(self.state.current_annotation.as_mut())
+            .and_then(|(obj_id, annotation)| annotation.find_object_mut(obj_id))
+        {
+            if obj.shape.smooth() {
+                self.state.editing_state.selected_vertex = None;
+                self.state.record_edit("Smooth");
+            }
+        }
+    }
+
+    /// Merge the selected object with touching objects of its category
+    fn merge_selected_objects(&mut self) {
+        if self.state.editing_state.mode != crate::state::EditMode::Editing {
+            return;
+        }
+
+        if let (Some(annotation), Some(obj_id)) = (
+            &mut self.state.current_annotation,
+            self.state.selected_object_id,
+        ) {
+            let merged = annotation.merge_objects(obj_id);
+            if merged.is_empty() {
+                log::info!("No touching objects of the same category to merge");
+                return;
+            }
+            self.state.editing_state.selected_vertex = None;
+            self.state.record_edit("Merge objects");
+        }
+    }
+
+    /// Cut the selected object out of the objects it overlaps
+    fn subtract_selected_object(&mut self) {
+        if self.state.editing_state.mode != crate::state::EditMode::Editing {
+            return;
+        }
+
+        if let (Some(annotation), Some(obj_id)) = (
+            &mut self.state.current_annotation,
+            self.state.selected_object_id,
+        ) {
+            if annotation.subtract_object(obj_id).is_empty() {
+                log::info!("The selected object does not overlap other objects");
+                return;
+            }
+            self.state.record_edit("Subtract");
+        }
+    }
+
+    /// Clip the selected object to the ROIs, removing it if it lies outside
+    fn clip_selected_to_roi(&mut self) {
+        if self.state.editing_state.mode != crate::state::EditMode::Editing {
+            return;
+        }
+
+        if let (Some(annotation), Some(obj_id)) = (
+            &mut self.state.current_annotation,
+            self.state.selected_object_id,
+        ) {
+            if annotation.clip_to_rois(Some(obj_id)).is_empty() {
+                return;
+            }
+            if annotation.find_object(obj_id).is_none() {
+                self.state.selected_object_id = None;
+            }
+            self.state.editing_state.selected_vertex = None;
+            self.state.record_edit("Clip to ROI");
+        }
+    }
+
     /// Scale selected objects by a factor
     fn scale_selected_object(&mut self, scale_factor: f32) {
         if self.state.editing_state.mode != crate::state::EditMode::Editing {
@@ -1288,20 +1808,14 @@ impl LabApp {
             &mut self.state.current_annotation,
             self.state.selected_object_id,
         ) {
-            Self::with_polygon_mut(annotation, obj_id, |polygon| {
-                if let Some((min, max)) = crate::geometry::bounding_box(polygon) {
-                    let center_x = (min.x + max.x) / 2.0;
-                    let center_y = (min.y + max.y) / 2.0;
-
-                    for vertex in polygon {
-                        let dx = vertex.x - center_x;
-                        let dy = vertex.y - center_y;
-                        vertex.x = (center_x + dx * scale_factor).clamp(0.0, 1.0);
-                        vertex.y = (center_y + dy * scale_factor).clamp(0.0, 1.0);
-                    }
-                }
+            Self::with_shape_mut(annotation, obj_id, |shape| {
+                shape.scale(scale_factor);
+                shape.map_points(|vertex| {
+                    vertex.x = vertex.x.clamp(0.0, 1.0);
+                    vertex.y = vertex.y.clamp(0.0, 1.0);
+                });
             });
-            self.state.has_unsaved_changes = true;
+            self.state.record_repeated_edit("Scale");
         }
     }
 }
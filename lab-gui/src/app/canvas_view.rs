@@ -1,4 +1,5 @@
 use super::LabApp;
+use crate::tools::DrawingTools;
 use egui::{CentralPanel, Context};
 
 impl LabApp {
@@ -15,6 +16,12 @@ impl LabApp {
                 self.state.i18n.t("canvas.open_project_hint")
             );
 
+            let next_keypoint = self.state.next_keypoint().map(|(_, _, name)| name);
+            self.canvas.brush_radius = (self.state.editing_state.mode
+                == crate::state::EditMode::Drawing
+                && self.state.draw_target == crate::state::DrawTarget::Mask)
+                .then_some(self.state.brush_radius);
+            self.canvas.preview_shape = self.shape_tool_preview();
             let canvas_response = self.canvas.show(
                 ui,
                 self.state.current_image.as_ref(),
@@ -23,6 +30,8 @@ impl LabApp {
                 self.state.selected_object_id,
                 self.state.editing_state.selected_vertex,
                 &self.state.temp_points,
+                self.state.active_draw_shape(),
+                next_keypoint.as_deref(),
                 self.state.editing_state.mode,
                 &no_image_text,
             );
@@ -75,7 +84,11 @@ impl LabApp {
 
             if canvas_response.right_clicked {
                 // In Drawing mode, delete last point
-                if self.state.editing_state.mode == crate::state::EditMode::Drawing {
+                if self.state.editing_state.mode == crate::state::EditMode::Drawing
+                    && self.state.draw_target == crate::state::DrawTarget::Keypoints
+                {
+                    DrawingTools::remove_last_keypoint(&mut self.state);
+                } else if self.state.editing_state.mode == crate::state::EditMode::Drawing {
                     if self.state.pending_draw_clicks.pop().is_none() {
                         self.state.temp_points.pop();
                     }
@@ -98,7 +111,13 @@ impl LabApp {
 
             // Handle canvas interactions
             if let Some(clicked_pos) = canvas_response.clicked_pos {
-                if !canvas_response.finish_drawing
+                if self.state.draw_target == crate::state::DrawTarget::Keypoints {
+                    // Keypoints are placed immediately; Shift marks them occluded
+                    if self.state.editing_state.mode == crate::state::EditMode::Drawing {
+                        let occluded = ctx.input(|i| i.modifiers.shift);
+                        DrawingTools::place_keypoint(&mut self.state, clicked_pos, occluded);
+                    }
+                } else if !canvas_response.finish_drawing
                     && self.state.editing_state.mode == crate::state::EditMode::Drawing
                 {
                     self.state
@@ -112,17 +131,32 @@ impl LabApp {
 
             self.apply_pending_draw_clicks(now, double_click_delay);
 
+            // Shapes with a fixed number of points finish on the last click
+            if let Some(required) = self.state.active_draw_shape().required_points() {
+                if self.state.editing_state.mode == crate::state::EditMode::Drawing
+                    && self.state.temp_points.len() >= required
+                {
+                    self.finish_drawing();
+                }
+            }
+
+            // Handle mask brush strokes
+            if let Some((from, to)) = canvas_response.brush_stroke {
+                DrawingTools::paint_mask(&mut self.state, from, to);
+            }
+            if canvas_response.brush_finished {
+                DrawingTools::finish_mask_stroke(&mut self.state);
+            }
+
             // Handle vertex added
             if let Some((obj_id, edge_idx, new_pos)) = canvas_response.vertex_added {
                 if let Some(annotation) = &mut self.state.current_annotation {
                     let mut updated = false;
-                    Self::with_polygon_mut(annotation, obj_id, |polygon| {
-                        if edge_idx < polygon.len() {
-                            polygon.insert(edge_idx + 1, new_pos);
-                            updated = true;
-                        }
+                    Self::with_shape_mut(annotation, obj_id, |shape| {
+                        updated = shape.insert_vertex(edge_idx + 1, new_pos);
                     });
                     if updated {
+                        // Recorded together with the drag that follows
                         self.state.has_unsaved_changes = true;
                         log::info!("Added vertex to shape #{} at edge {}", obj_id, edge_idx);
                     }
@@ -133,11 +167,8 @@ impl LabApp {
             if let Some((obj_id, vertex_idx, new_pos)) = canvas_response.vertex_dragged {
                 if let Some(annotation) = &mut self.state.current_annotation {
                     let mut updated = false;
-                    Self::with_polygon_mut(annotation, obj_id, |polygon| {
-                        if vertex_idx < polygon.len() {
-                            polygon[vertex_idx] = new_pos;
-                            updated = true;
-                        }
+                    Self::with_shape_mut(annotation, obj_id, |shape| {
+                        updated = shape.move_vertex(vertex_idx, new_pos);
                     });
                     if updated {
                         self.state.has_unsaved_changes = true;
@@ -149,11 +180,11 @@ impl LabApp {
             if let Some((obj_id, offset)) = canvas_response.object_dragged {
                 if let Some(annotation) = &mut self.state.current_annotation {
                     let mut updated = false;
-                    Self::with_polygon_mut(annotation, obj_id, |polygon| {
-                        for vertex in polygon {
+                    Self::with_shape_mut(annotation, obj_id, |shape| {
+                        shape.map_points(|vertex| {
                             vertex.x = (vertex.x + offset.x).clamp(0.0, 1.0);
                             vertex.y = (vertex.y + offset.y).clamp(0.0, 1.0);
-                        }
+                        });
                         updated = true;
                     });
                     if updated {
@@ -162,24 +193,51 @@ impl LabApp {
                 }
             }
 
+            // Handle keypoint dragging
+            if let Some((obj_id, keypoint_idx, new_pos)) = canvas_response.keypoint_dragged {
+                if let Some(keypoint) = self
+                    .state
+                    .current_annotation
+                    .as_mut()
+                    .and_then(|annotation| annotation.find_object_mut(obj_id))
+                    .and_then(|obj| obj.keypoints.get_mut(keypoint_idx))
+                {
+                    keypoint.x = new_pos.x;
+                    keypoint.y = new_pos.y;
+                    self.state.has_unsaved_changes = true;
+                }
+            }
+
+            if let Some((obj_id, keypoint_idx)) = canvas_response.keypoint_toggled {
+                DrawingTools::toggle_keypoint_visibility(&mut self.state, obj_id, keypoint_idx);
+            }
+
+            // A finished drag is a single undo step
+            if canvas_response.drag_finished {
+                self.state.record_edit("Drag");
+            }
+
             // Handle vertex deleted
             if let Some((obj_id, vertex_idx)) = canvas_response.vertex_deleted {
                 if let Some(annotation) = &mut self.state.current_annotation {
                     let mut updated = false;
-                    Self::with_polygon_mut(annotation, obj_id, |polygon| {
-                        let min_vertices = 3;
-                        if polygon.len() > min_vertices && vertex_idx < polygon.len() {
-                            polygon.remove(vertex_idx);
+                    Self::with_shape_mut(annotation, obj_id, |shape| {
+                        if shape.remove_vertex(vertex_idx) {
                             updated = true;
-                        } else {
+                        } else if shape.has_free_vertices() {
                             log::warn!(
                                 "Cannot delete vertex: minimum {} vertices required",
-                                min_vertices
+                                shape.kind().min_points()
+                            );
+                        } else {
+                            log::warn!(
+                                "Cannot delete vertex: {} has a fixed number of vertices",
+                                shape.kind().as_str()
                             );
                         }
                     });
                     if updated {
-                        self.state.has_unsaved_changes = true;
+                        self.state.record_edit("Delete vertex");
                         log::info!("Deleted vertex {} from shape #{}", vertex_idx, obj_id);
                     }
                 }
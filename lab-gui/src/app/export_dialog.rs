@@ -0,0 +1,379 @@
+// Export options dialog for JLab
+use super::import_export::DatasetFormat;
+use egui::Context;
+use lab_core::export::segmentation::{DrawOrder, SegmentationOptions};
+use lab_core::Meta;
+use lab_utils::split::{GroupBy, SplitConfig, SplitStrategy};
+
+/// Button pressed in the dialog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportDialogAction {
+    /// Pick the output folder and export
+    Export,
+    /// Do not export
+    Cancel,
+}
+
+/// Choices made before exporting a dataset
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// Categories left out of the export
+    pub excluded_categories: Vec<i32>,
+    /// Train/val/test split, None to export all images together
+    pub split: Option<SplitConfig>,
+    /// Rendering of segmentation masks
+    pub segmentation: SegmentationOptions,
+    /// Whether to write class masks and instance masks
+    pub class_masks: bool,
+    pub instance_masks: bool,
+    /// Whether segmentation masks use the Pascal VOC folder names
+    pub voc_layout: bool,
+}
+
+/// Split strategy as offered in the dialog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitChoice {
+    Random,
+    Ordered,
+    Stratified,
+    Prefix,
+    Property,
+}
+
+impl SplitChoice {
+    const ALL: [SplitChoice; 5] = [
+        SplitChoice::Random,
+        SplitChoice::Ordered,
+        SplitChoice::Stratified,
+        SplitChoice::Prefix,
+        SplitChoice::Property,
+    ];
+
+    fn label(self, i18n: &crate::i18n::I18n) -> String {
+        match self {
+            SplitChoice::Random => i18n.t("export.split_random"),
+            SplitChoice::Ordered => i18n.t("export.split_ordered"),
+            SplitChoice::Stratified => i18n.t("export.split_stratified"),
+            SplitChoice::Prefix => i18n.t("export.split_prefix"),
+            SplitChoice::Property => i18n.t("export.split_property"),
+        }
+    }
+}
+
+/// Export options dialog state
+///
+/// Split settings are kept between exports; categories are read again from
+/// the project each time the dialog opens.
+pub struct ExportDialogState {
+    /// Format waiting to be exported, None while the dialog is closed
+    pub format: Option<DatasetFormat>,
+    /// Category ID and name, with whether it is exported (YOLO and
+    /// segmentation only)
+    pub categories: Vec<(i32, String, bool)>,
+    /// Image-level properties of the project, to group by
+    pub image_properties: Vec<String>,
+
+    pub split_enabled: bool,
+    pub split_choice: SplitChoice,
+    /// Percentages of train, val and test
+    pub split_ratios: [f64; 3],
+    pub split_seed: u64,
+    /// Separator ending the file name prefix to group by
+    pub group_separator: String,
+    /// Image-level property to group by
+    pub group_property: String,
+
+    pub class_masks: bool,
+    pub instance_masks: bool,
+    pub draw_order: DrawOrder,
+    pub background: u8,
+    /// Whether pixels outside the ROIs get the ignore value
+    pub ignore_outside_rois: bool,
+    pub ignore_value: u8,
+    pub voc_layout: bool,
+}
+
+impl ExportDialogState {
+    pub fn new() -> Self {
+        Self {
+            format: None,
+            categories: Vec::new(),
+            image_properties: Vec::new(),
+            split_enabled: false,
+            split_choice: SplitChoice::Random,
+            split_ratios: [80.0, 10.0, 10.0],
+            split_seed: 0,
+            group_separator: "_".to_string(),
+            group_property: String::new(),
+            class_masks: true,
+            instance_masks: true,
+            draw_order: DrawOrder::Annotation,
+            background: 0,
+            ignore_outside_rois: true,
+            ignore_value: 255,
+            voc_layout: true,
+        }
+    }
+
+    /// Ask for the options of an export
+    ///
+    /// Categories with negative IDs, such as `pending`, are placeholders
+    /// rather than classes and start unchecked.
+    pub fn open(&mut self, format: DatasetFormat, meta: &Meta) {
+        self.format = Some(format);
+        self.categories = if format.is_yolo() || matches!(format, DatasetFormat::Segmentation) {
+            meta.categories
+                .iter()
+                .map(|category| (category.id, category.name.clone(), category.id >= 0))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        self.image_properties = meta
+            .image_properties
+            .iter()
+            .map(|property| property.name.clone())
+            .collect();
+        if !self.image_properties.contains(&self.group_property) {
+            self.group_property = self.image_properties.first().cloned().unwrap_or_default();
+        }
+        if self.image_properties.is_empty() && self.split_choice == SplitChoice::Property {
+            self.split_choice = SplitChoice::Random;
+        }
+    }
+
+    /// Options chosen in the dialog
+    pub fn options(&self) -> ExportOptions {
+        let strategy = match self.split_choice {
+            SplitChoice::Random => SplitStrategy::Random,
+            SplitChoice::Ordered => SplitStrategy::Ordered,
+            SplitChoice::Stratified => SplitStrategy::Stratified,
+            SplitChoice::Prefix => SplitStrategy::Grouped(GroupBy::Prefix(
+                self.group_separator.chars().next().unwrap_or('_'),
+            )),
+            SplitChoice::Property => {
+                SplitStrategy::Grouped(GroupBy::Property(self.group_property.clone()))
+            }
+        };
+        let excluded_categories: Vec<i32> = self
+            .categories
+            .iter()
+            .filter(|(_, _, included)| !included)
+            .map(|(id, _, _)| *id)
+            .collect();
+        ExportOptions {
+            excluded_categories: excluded_categories.clone(),
+            split: self.split_enabled.then_some(SplitConfig {
+                strategy,
+                ratios: self.split_ratios,
+                seed: self.split_seed,
+            }),
+            segmentation: SegmentationOptions {
+                draw_order: self.draw_order,
+                excluded_categories,
+                background: self.background,
+                ignore: self.ignore_outside_rois.then_some(self.ignore_value),
+            },
+            class_masks: self.class_masks,
+            instance_masks: self.instance_masks,
+            voc_layout: self.voc_layout,
+        }
+    }
+
+    /// Render the dialog: classes and mask settings, then the split settings
+    pub fn show(&mut self, ctx: &Context, i18n: &crate::i18n::I18n) -> Option<ExportDialogAction> {
+        self.format?;
+
+        let mut open = true;
+        let mut action = None;
+
+        egui::Window::new(i18n.t("export.options_title"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let segmentation = matches!(self.format, Some(DatasetFormat::Segmentation));
+                if !self.categories.is_empty() {
+                    let label = if segmentation {
+                        i18n.t("export.segmentation_classes")
+                    } else {
+                        i18n.t("export.classes")
+                    };
+                    ui.label(label);
+                    ui.add_space(8.0);
+
+                    // Segmentation classes skip the background and ignore values
+                    let reserved = SegmentationOptions {
+                        background: self.background,
+                        ignore: self.ignore_outside_rois.then_some(self.ignore_value),
+                        ..Default::default()
+                    };
+                    let is_reserved = |index: u32| {
+                        segmentation && u8::try_from(index).is_ok_and(|v| reserved.is_reserved(v))
+                    };
+                    let mut index = 0;
+                    egui::ScrollArea::vertical()
+                        .max_height(300.0)
+                        .show(ui, |ui| {
+                            for (id, name, included) in &mut self.categories {
+                                let text = if *included {
+                                    while is_reserved(index) {
+                                        index += 1;
+                                    }
+                                    let text = format!("{}: {} ({})", index, name, id);
+                                    index += 1;
+                                    text
+                                } else {
+                                    format!("-: {} ({})", name, id)
+                                };
+                                ui.checkbox(included, text);
+                            }
+                        });
+                    ui.separator();
+                }
+                if segmentation {
+                    self.show_segmentation_settings(ui, i18n);
+                    ui.separator();
+                }
+
+                ui.checkbox(&mut self.split_enabled, i18n.t("export.split"));
+                ui.add_enabled_ui(self.split_enabled, |ui| self.show_split_settings(ui, i18n));
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button(i18n.t("export.export")).clicked() {
+                        action = Some(ExportDialogAction::Export);
+                    }
+                    if ui.button(i18n.t("export.cancel")).clicked() {
+                        action = Some(ExportDialogAction::Cancel);
+                    }
+                });
+            });
+
+        if !open {
+            action = Some(ExportDialogAction::Cancel);
+        }
+        action
+    }
+
+    fn show_segmentation_settings(&mut self, ui: &mut egui::Ui, i18n: &crate::i18n::I18n) {
+        egui::Grid::new("export_segmentation_grid")
+            .num_columns(2)
+            .spacing([16.0, 4.0])
+            .show(ui, |ui| {
+                ui.label(i18n.t("export.masks"));
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.class_masks, i18n.t("export.class_masks"));
+                    ui.checkbox(&mut self.instance_masks, i18n.t("export.instance_masks"));
+                });
+                ui.end_row();
+
+                ui.label(i18n.t("export.layout"));
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.voc_layout, true, i18n.t("export.layout_voc"));
+                    ui.radio_value(&mut self.voc_layout, false, i18n.t("export.layout_plain"));
+                });
+                ui.end_row();
+
+                ui.label(i18n.t("export.draw_order"));
+                let orders = [
+                    (
+                        DrawOrder::Annotation,
+                        i18n.t("export.draw_order_annotation"),
+                    ),
+                    (
+                        DrawOrder::LargestFirst,
+                        i18n.t("export.draw_order_largest_first"),
+                    ),
+                    (DrawOrder::Category, i18n.t("export.draw_order_category")),
+                ];
+                let selected = orders
+                    .iter()
+                    .find(|(order, _)| *order == self.draw_order)
+                    .map(|(_, label)| label.clone())
+                    .unwrap_or_default();
+                egui::ComboBox::from_id_salt("export_draw_order")
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        for (order, label) in orders {
+                            ui.selectable_value(&mut self.draw_order, order, label);
+                        }
+                    });
+                ui.end_row();
+
+                ui.label(i18n.t("export.background"));
+                ui.add(egui::DragValue::new(&mut self.background));
+                ui.end_row();
+
+                ui.checkbox(&mut self.ignore_outside_rois, i18n.t("export.ignore"));
+                ui.add_enabled(
+                    self.ignore_outside_rois,
+                    egui::DragValue::new(&mut self.ignore_value),
+                );
+                ui.end_row();
+            });
+    }
+
+    fn show_split_settings(&mut self, ui: &mut egui::Ui, i18n: &crate::i18n::I18n) {
+        egui::Grid::new("export_split_grid")
+            .num_columns(2)
+            .spacing([16.0, 4.0])
+            .show(ui, |ui| {
+                ui.label(i18n.t("export.split_strategy"));
+                egui::ComboBox::from_id_salt("export_split_strategy")
+                    .selected_text(self.split_choice.label(i18n))
+                    .show_ui(ui, |ui| {
+                        for choice in SplitChoice::ALL {
+                            if choice == SplitChoice::Property && self.image_properties.is_empty() {
+                                continue;
+                            }
+                            ui.selectable_value(&mut self.split_choice, choice, choice.label(i18n));
+                        }
+                    });
+                ui.end_row();
+
+                match self.split_choice {
+                    SplitChoice::Prefix => {
+                        ui.label(i18n.t("export.split_separator"));
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.group_separator)
+                                .char_limit(1)
+                                .desired_width(40.0),
+                        );
+                        ui.end_row();
+                    }
+                    SplitChoice::Property => {
+                        ui.label(i18n.t("export.split_group_property"));
+                        egui::ComboBox::from_id_salt("export_split_property")
+                            .selected_text(self.group_property.clone())
+                            .show_ui(ui, |ui| {
+                                for name in &self.image_properties {
+                                    ui.selectable_value(
+                                        &mut self.group_property,
+                                        name.clone(),
+                                        name,
+                                    );
+                                }
+                            });
+                        ui.end_row();
+                    }
+                    _ => {}
+                }
+
+                let names = [
+                    "export.split_train",
+                    "export.split_val",
+                    "export.split_test",
+                ];
+                for (ratio, name) in self.split_ratios.iter_mut().zip(names) {
+                    ui.label(i18n.t(name));
+                    ui.add(egui::DragValue::new(ratio).range(0.0..=100.0).suffix("%"));
+                    ui.end_row();
+                }
+
+                ui.label(i18n.t("export.split_seed"));
+                ui.add(egui::DragValue::new(&mut self.split_seed));
+                ui.end_row();
+            });
+    }
+}
@@ -1,19 +1,49 @@
+use super::export_dialog::{ExportDialogAction, ExportOptions};
 use super::LabApp;
 use anyhow::Context;
-use image::GenericImageView;
-use lab_core::{Annotation, Object, Point};
-use lab_utils::conversion::{export_annotation, export_coco_batch, ExportFormat};
-use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use lab_core::export::property_attributes;
+use lab_core::export::yolo::YoloClasses;
+use lab_core::import::{
+    coco::CocoImporter,
+    labelme::LabelMeImporter,
+    voc::VocImporter,
+    yolo::{YoloImporter, YoloObbImporter, YoloSegImporter},
+    ImportedDataset, ImportedImage, Importer,
+};
+use lab_core::{Annotation, Orientation, Point, Shape};
+use lab_utils::conversion::{
+    export_annotation, export_classification_manifest, export_coco_batch, export_segmentation,
+    export_segmentation_labelmap, export_voc_image_sets, export_yolo_annotation,
+    export_yolo_classes, ExportFormat,
+};
+use lab_utils::split::{assign_splits, Split};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// Most skipped items listed after an import
+const MAX_REPORTED_ITEMS: usize = 20;
+
 #[derive(Debug, Clone, Copy)]
 pub(super) enum DatasetFormat {
     Yolo,
+    YoloSeg,
+    YoloObb,
     Voc,
     Coco,
     LabelMe,
+    Segmentation,
+}
+
+impl DatasetFormat {
+    /// Whether the format numbers categories as YOLO classes
+    pub(super) fn is_yolo(self) -> bool {
+        matches!(
+            self,
+            DatasetFormat::Yolo | DatasetFormat::YoloSeg | DatasetFormat::YoloObb
+        )
+    }
 }
 
 impl LabApp {
@@ -24,8 +54,29 @@ impl LabApp {
         }
     }
 
+    /// Export the project, asking for the classes and split first
     pub(super) fn export_dataset(&mut self, format: DatasetFormat) {
-        let result = self.try_export_dataset(format);
+        match self.state.project.as_ref() {
+            Some(project) => self.export_dialog.open(format, &project.meta),
+            None => self.finish_export_dataset(format, &ExportOptions::default()),
+        }
+    }
+
+    pub(super) fn show_export_dialog(&mut self, ctx: &egui::Context) {
+        let Some(action) = self.export_dialog.show(ctx, &self.state.i18n) else {
+            return;
+        };
+        let Some(format) = self.export_dialog.format.take() else {
+            return;
+        };
+        if action == ExportDialogAction::Export {
+            let options = self.export_dialog.options();
+            self.finish_export_dataset(format, &options);
+        }
+    }
+
+    fn finish_export_dataset(&mut self, format: DatasetFormat, options: &ExportOptions) {
+        let result = self.try_export_dataset(format, options);
         if let Err(err) = result {
             self.show_io_error(self.state.i18n.t("error.export_failed"), err.to_string());
         }
@@ -33,7 +84,7 @@ impl LabApp {
 }
 
 impl LabApp {
-    fn show_io_error(&self, title: String, message: String) {
+    pub(super) fn show_io_error(&self, title: String, message: String) {
         log::error!("{}: {}", title, message);
         let _ = rfd::MessageDialog::new()
             .set_title(&title)
@@ -62,15 +113,20 @@ impl LabApp {
             .collect::<HashSet<String>>();
         let duplicate_template = self.state.i18n.t("error.import_duplicate_image");
 
-        let imported = match format {
-            DatasetFormat::Yolo => {
+        let (importer, source): (Box<dyn Importer>, PathBuf) = match format {
+            DatasetFormat::Yolo | DatasetFormat::YoloSeg | DatasetFormat::YoloObb => {
                 let Some(root) = rfd::FileDialog::new()
                     .set_title("Select YOLO dataset root")
                     .pick_folder()
                 else {
                     return Ok(());
                 };
-                self.import_from_yolo(&root, &meta)?
+                let importer: Box<dyn Importer> = match format {
+                    DatasetFormat::YoloSeg => Box::new(YoloSegImporter),
+                    DatasetFormat::YoloObb => Box::new(YoloObbImporter),
+                    _ => Box::new(YoloImporter),
+                };
+                (importer, root)
             }
             DatasetFormat::Voc => {
                 let Some(root) = rfd::FileDialog::new()
@@ -79,7 +135,7 @@ impl LabApp {
                 else {
                     return Ok(());
                 };
-                self.import_from_voc(&root, &meta)?
+                (Box::new(VocImporter), root)
             }
             DatasetFormat::Coco => {
                 let Some(json_path) = rfd::FileDialog::new()
@@ -95,7 +151,7 @@ impl LabApp {
                 else {
                     return Ok(());
                 };
-                self.import_from_coco(&json_path, &images_dir, &meta)?
+                (Box::new(CocoImporter { images_dir }), json_path)
             }
             DatasetFormat::LabelMe => {
                 let Some(root) = rfd::FileDialog::new()
@@ -104,16 +160,76 @@ impl LabApp {
                 else {
                     return Ok(());
                 };
-                self.import_from_labelme(&root, &meta)?
+                (Box::new(LabelMeImporter), root)
             }
+            // Masks are only exported
+            DatasetFormat::Segmentation => return Ok(()),
         };
 
-        merge_imported_images(imported, project, &existing_names, &duplicate_template)?;
+        let dataset = importer.import(&source, &meta, &self.state.annotator)?;
+        merge_imported_images(
+            &dataset.images,
+            project,
+            &existing_names,
+            &duplicate_template,
+        )?;
         self.refresh_project_images()?;
+        if !dataset.report.is_complete() {
+            self.show_import_report(&dataset);
+        }
         Ok(())
     }
 
-    fn try_export_dataset(&mut self, format: DatasetFormat) -> anyhow::Result<()> {
+    /// Tell what was left out of an import
+    fn show_import_report(&self, dataset: &ImportedDataset) {
+        let report = &dataset.report;
+        let mut lines = vec![self
+            .state
+            .i18n
+            .t("import.report_summary")
+            .replace("{images}", &dataset.images.len().to_string())
+            .replace("{objects}", &report.objects.to_string())];
+        if !report.unknown_categories.is_empty() {
+            let categories = report
+                .unknown_categories
+                .iter()
+                .map(|(category, count)| format!("{} ({})", category, count))
+                .collect::<Vec<_>>()
+                .join(", ");
+            lines.push(format!(
+                "{} {}",
+                self.state.i18n.t("import.report_unknown"),
+                categories
+            ));
+        }
+        if !report.skipped.is_empty() {
+            lines.push(self.state.i18n.t("import.report_skipped"));
+            for item in report.skipped.iter().take(MAX_REPORTED_ITEMS) {
+                lines.push(format!("  {}: {}", item.location, item.reason));
+            }
+            if report.skipped.len() > MAX_REPORTED_ITEMS {
+                lines.push(format!(
+                    "  … +{}",
+                    report.skipped.len() - MAX_REPORTED_ITEMS
+                ));
+            }
+        }
+
+        let message = lines.join("\n");
+        log::warn!("Import incomplete:\n{}", message);
+        let _ = rfd::MessageDialog::new()
+            .set_title(self.state.i18n.t("import.report_title"))
+            .set_description(&message)
+            .set_buttons(rfd::MessageButtons::Ok)
+            .set_level(rfd::MessageLevel::Warning)
+            .show();
+    }
+
+    fn try_export_dataset(
+        &mut self,
+        format: DatasetFormat,
+        options: &ExportOptions,
+    ) -> anyhow::Result<()> {
         let project = self
             .state
             .project
@@ -128,28 +244,77 @@ impl LabApp {
             return Ok(());
         };
 
-        let export_items = self.collect_export_items(project)?;
+        let mut export_items = self.collect_export_items(project)?;
 
+        // Images with EXIF rotation are either written upright or copied
+        // unchanged with coordinates in their raw frame
+        let mut bake_orientation = false;
+        if export_items
+            .iter()
+            .any(|item| !item.orientation.is_normal())
+        {
+            match rfd::MessageDialog::new()
+                .set_title(self.state.i18n.t("export.exif_title"))
+                .set_description(self.state.i18n.t("export.exif_message"))
+                .set_buttons(rfd::MessageButtons::YesNoCancel)
+                .set_level(rfd::MessageLevel::Info)
+                .show()
+            {
+                rfd::MessageDialogResult::Yes => bake_orientation = true,
+                rfd::MessageDialogResult::No => {
+                    export_items.iter_mut().for_each(ExportItem::use_raw_frame)
+                }
+                _ => return Ok(()),
+            }
+        }
+
+        if let Some(config) = &options.split {
+            let images: Vec<(&str, &Annotation)> = export_items
+                .iter()
+                .map(|item| (item.file_name.as_str(), &item.annotation))
+                .collect();
+            let splits = assign_splits(&images, &meta, config);
+            for (item, split) in export_items.iter_mut().zip(splits) {
+                item.split = Some(split);
+            }
+        }
+        let splits: Vec<Split> = export_items
+            .iter()
+            .filter_map(|item| item.split)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        // Split datasets follow the layout native to each format
         match format {
-            DatasetFormat::Yolo => {
-                let images_dir = output_root.join("images");
-                let labels_dir = output_root.join("labels");
-                fs::create_dir_all(&images_dir)?;
-                fs::create_dir_all(&labels_dir)?;
+            DatasetFormat::Yolo | DatasetFormat::YoloSeg | DatasetFormat::YoloObb => {
+                let export_format = match format {
+                    DatasetFormat::YoloSeg => ExportFormat::YoloSeg,
+                    DatasetFormat::YoloObb => ExportFormat::YoloObb,
+                    _ => ExportFormat::Yolo,
+                };
+                let classes = YoloClasses::new(&meta, &options.excluded_categories);
+                fs::create_dir_all(output_root.join("images"))?;
+                fs::create_dir_all(output_root.join("labels"))?;
 
                 for item in &export_items {
+                    let images_dir = item.split_dir(&output_root.join("images"));
+                    let labels_dir = item.split_dir(&output_root.join("labels"));
+                    fs::create_dir_all(&images_dir)?;
+                    fs::create_dir_all(&labels_dir)?;
                     let label_path = labels_dir.join(format!("{}.txt", item.stem));
-                    export_annotation(
+                    export_yolo_annotation(
                         &label_path,
                         &item.annotation,
                         &meta,
-                        item.image_path.to_string_lossy().as_ref(),
                         item.width,
                         item.height,
-                        ExportFormat::Yolo,
+                        export_format,
+                        &classes,
                     )?;
-                    fs::copy(&item.image_path, images_dir.join(&item.file_name))?;
+                    item.copy_image(&images_dir.join(&item.file_name), bake_orientation)?;
                 }
+                export_yolo_classes(&output_root, &classes, &splits)?;
             }
             DatasetFormat::Voc => {
                 let images_dir = output_root.join("JPEGImages");
@@ -168,57 +333,173 @@ impl LabApp {
                         item.height,
                         ExportFormat::Voc,
                     )?;
-                    fs::copy(&item.image_path, images_dir.join(&item.file_name))?;
+                    item.copy_image(&images_dir.join(&item.file_name), bake_orientation)?;
+                }
+                if !splits.is_empty() {
+                    let image_sets: Vec<(String, Split)> = export_items
+                        .iter()
+                        .filter_map(|item| Some((item.stem.clone(), item.split?)))
+                        .collect();
+                    export_voc_image_sets(&output_root, "Main", &image_sets)?;
                 }
             }
             DatasetFormat::Coco => {
-                let images_dir = output_root.join("images");
-                fs::create_dir_all(&images_dir)?;
+                fs::create_dir_all(output_root.join("images"))?;
+                let mut batches: BTreeMap<Option<Split>, Vec<_>> = BTreeMap::new();
+                if splits.is_empty() {
+                    batches.insert(None, Vec::new());
+                }
                 for item in &export_items {
-                    fs::copy(&item.image_path, images_dir.join(&item.file_name))?;
+                    let images_dir = item.split_dir(&output_root.join("images"));
+                    fs::create_dir_all(&images_dir)?;
+                    item.copy_image(&images_dir.join(&item.file_name), bake_orientation)?;
+                    batches
+                        .entry(item.split)
+                        .or_default()
+                        .push(item.batch_entry());
                 }
 
-                let coco_path = output_root.join("annotations.json");
-                let coco_items: Vec<(String, Annotation, u32, u32)> = export_items
-                    .iter()
-                    .map(|item| {
-                        (
-                            item.file_name.clone(),
-                            item.annotation.clone(),
-                            item.width,
-                            item.height,
-                        )
-                    })
-                    .collect();
-                export_coco_batch(&coco_path, &coco_items, &meta)?;
+                // One annotation file per split, as in COCO's own releases
+                for (split, coco_items) in &batches {
+                    let coco_path = match split {
+                        Some(split) => {
+                            let annotations_dir = output_root.join("annotations");
+                            fs::create_dir_all(&annotations_dir)?;
+                            annotations_dir.join(format!("instances_{}.json", split.name()))
+                        }
+                        None => output_root.join("annotations.json"),
+                    };
+                    export_coco_batch(&coco_path, coco_items, &meta)?;
+                }
             }
             DatasetFormat::LabelMe => {
                 fs::create_dir_all(&output_root)?;
                 for item in &export_items {
-                    fs::copy(&item.image_path, output_root.join(&item.file_name))?;
-                    let label_path = output_root.join(format!("{}.json", item.stem));
+                    let item_dir = item.split_dir(&output_root);
+                    fs::create_dir_all(&item_dir)?;
+                    item.copy_image(&item_dir.join(&item.file_name), bake_orientation)?;
+                    let label_path = item_dir.join(format!("{}.json", item.stem));
                     self.export_labelme_annotation(&label_path, &item, &meta)?;
                 }
             }
+            DatasetFormat::Segmentation => {
+                let (images_name, class_name, instance_name) = if options.voc_layout {
+                    ("JPEGImages", "SegmentationClass", "SegmentationObject")
+                } else {
+                    ("images", "masks", "instances")
+                };
+                for item in &export_items {
+                    // VOC keeps all images in one folder and lists the splits instead
+                    let dir = |name: &str| -> anyhow::Result<PathBuf> {
+                        let dir = if options.voc_layout {
+                            output_root.join(name)
+                        } else {
+                            item.split_dir(&output_root.join(name))
+                        };
+                        fs::create_dir_all(&dir)?;
+                        Ok(dir)
+                    };
+                    let mask_name = format!("{}.png", item.stem);
+                    let mut class_path = None;
+                    if options.class_masks {
+                        class_path = Some(dir(class_name)?.join(&mask_name));
+                    }
+                    let mut instance_path = None;
+                    if options.instance_masks {
+                        instance_path = Some(dir(instance_name)?.join(&mask_name));
+                    }
+                    export_segmentation(
+                        class_path.as_deref(),
+                        instance_path.as_deref(),
+                        &item.annotation,
+                        &meta,
+                        item.width,
+                        item.height,
+                        &options.segmentation,
+                    )?;
+                    item.copy_image(&dir(images_name)?.join(&item.file_name), bake_orientation)?;
+                }
+                export_segmentation_labelmap(
+                    output_root.join("labelmap.txt"),
+                    &meta,
+                    &options.segmentation,
+                )?;
+                if options.voc_layout && !splits.is_empty() {
+                    let image_sets: Vec<(String, Split)> = export_items
+                        .iter()
+                        .filter_map(|item| Some((item.stem.clone(), item.split?)))
+                        .collect();
+                    export_voc_image_sets(&output_root, "Segmentation", &image_sets)?;
+                }
+            }
+        }
+
+        // Image-level properties are exported alongside every format
+        if !meta.image_properties.is_empty() {
+            let manifest_items: Vec<(String, Annotation, u32, u32)> =
+                export_items.iter().map(ExportItem::batch_entry).collect();
+            export_classification_manifest(
+                output_root.join("classification.json"),
+                &manifest_items,
+                &meta,
+            )?;
         }
 
         Ok(())
     }
 }
 
-struct ImportedImage {
-    source_path: PathBuf,
-    file_name: String,
-    annotation: Option<Annotation>,
-}
-
 struct ExportItem {
     image_path: PathBuf,
     file_name: String,
     stem: String,
+    /// Upright size of the image
     width: u32,
     height: u32,
+    orientation: Orientation,
+    /// Annotation in the upright frame
     annotation: Annotation,
+    /// Split the image is exported to, None if the dataset is not split
+    split: Option<Split>,
+}
+
+impl ExportItem {
+    /// Folder of the item's split under `dir`, or `dir` itself if not split
+    fn split_dir(&self, dir: &Path) -> PathBuf {
+        match self.split {
+            Some(split) => dir.join(split.name()),
+            None => dir.to_path_buf(),
+        }
+    }
+
+    /// Switch coordinates and size to the raw pixels of the image
+    fn use_raw_frame(&mut self) {
+        let inverse = self.orientation.inverse();
+        inverse.apply_annotation(&mut self.annotation);
+        (self.width, self.height) = inverse.apply_size(self.width, self.height);
+        self.orientation = Orientation::Normal;
+    }
+
+    /// Copy the image into the dataset, turned upright if `bake_orientation`
+    fn copy_image(&self, target: &Path, bake_orientation: bool) -> anyhow::Result<()> {
+        if bake_orientation && !self.orientation.is_normal() {
+            crate::image_io::save_upright(&self.image_path, target)
+                .with_context(|| format!("Failed to write image {:?}", target))?;
+        } else {
+            fs::copy(&self.image_path, target)?;
+        }
+        Ok(())
+    }
+
+    /// Entry for batch exporters: (file name, annotation, width, height)
+    fn batch_entry(&self) -> (String, Annotation, u32, u32) {
+        (
+            self.file_name.clone(),
+            self.annotation.clone(),
+            self.width,
+            self.height,
+        )
+    }
 }
 
 impl LabApp {
@@ -256,21 +537,27 @@ impl LabApp {
                 .unwrap_or(&file_name)
                 .to_string();
 
-            let (width, height) = image::open(&image_path)
-                .with_context(|| format!("Failed to read image {:?}", image_path))?
-                .dimensions();
-
-            let annotation = project
+            let mut annotation = project
                 .load_annotation(&file_name)?
                 .unwrap_or_else(|| Annotation::new("export"));
 
+            // Only read images whose size was not recorded on save
+            let (width, height, orientation) = match &annotation.image {
+                Some(info) => (info.width, info.height, info.orientation),
+                None => crate::image_io::read_header(&image_path)
+                    .with_context(|| format!("Failed to read image {:?}", image_path))?,
+            };
+            annotation.reorient_to(orientation);
+
             items.push(ExportItem {
                 image_path,
                 file_name,
                 stem,
                 width,
                 height,
+                orientation,
                 annotation,
+                split: None,
             });
         }
         Ok(items)
@@ -278,13 +565,13 @@ impl LabApp {
 }
 
 fn merge_imported_images(
-    imported: Vec<ImportedImage>,
+    imported: &[ImportedImage],
     project: &lab_utils::Project,
     existing_names: &HashSet<String>,
     duplicate_template: &str,
 ) -> anyhow::Result<()> {
     let mut incoming_names = HashSet::new();
-    for item in &imported {
+    for item in imported {
         if existing_names.contains(&item.file_name) {
             return Err(anyhow::anyhow!(
                 duplicate_template.replace("{name}", &item.file_name)
@@ -304,327 +591,16 @@ fn merge_imported_images(
         let dest_image = project.images_dir().join(&item.file_name);
         fs::copy(&item.source_path, &dest_image)?;
 
-        if let Some(annotation) = item.annotation {
-            let label_path = project.annotation_path(&item.file_name);
-            lab_core::io::save_annotation(&label_path, &annotation)?;
+        // Saved through the project, so a leftover label of the same name is
+        // backed up and its copies in other storage formats are removed
+        if let Some(annotation) = &item.annotation {
+            project.save_annotation(&item.file_name, annotation)?;
         }
     }
 
     Ok(())
 }
 
-impl LabApp {
-    fn import_from_yolo(
-        &self,
-        root: &Path,
-        meta: &lab_core::Meta,
-    ) -> anyhow::Result<Vec<ImportedImage>> {
-        let images_dir = root.join("images");
-        let labels_dir = root.join("labels");
-        if !images_dir.exists() || !labels_dir.exists() {
-            return Err(anyhow::anyhow!(
-                "YOLO root must contain images/ and labels/ directories"
-            ));
-        }
-
-        let image_paths = list_images_in_dir(&images_dir)?;
-        let mut imported = Vec::new();
-
-        for image_path in image_paths {
-            let file_name = image_path
-                .file_name()
-                .and_then(|s| s.to_str())
-                .context("Invalid image name")?
-                .to_string();
-            let stem = Path::new(&file_name)
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or(&file_name);
-            let label_path = labels_dir.join(format!("{}.txt", stem));
-
-            let mut objects = Vec::new();
-            if label_path.exists() {
-                let content = fs::read_to_string(&label_path)?;
-                for line in content.lines() {
-                    if line.trim().is_empty() {
-                        continue;
-                    }
-                    let parts: Vec<&str> = line.split_whitespace().collect();
-                    if parts.len() != 5 {
-                        continue;
-                    }
-                    let class_id: i32 = match parts[0].parse() {
-                        Ok(val) => val,
-                        Err(_) => continue,
-                    };
-                    if meta.find_category(class_id).is_none() {
-                        log::warn!("Unknown category id {} in {:?}", class_id, label_path);
-                        continue;
-                    }
-                    let x_center: f32 = match parts[1].parse() {
-                        Ok(val) => val,
-                        Err(_) => continue,
-                    };
-                    let y_center: f32 = match parts[2].parse() {
-                        Ok(val) => val,
-                        Err(_) => continue,
-                    };
-                    let width: f32 = match parts[3].parse() {
-                        Ok(val) => val,
-                        Err(_) => continue,
-                    };
-                    let height: f32 = match parts[4].parse() {
-                        Ok(val) => val,
-                        Err(_) => continue,
-                    };
-
-                    let xmin = x_center - width / 2.0;
-                    let ymin = y_center - height / 2.0;
-                    let xmax = x_center + width / 2.0;
-                    let ymax = y_center + height / 2.0;
-                    let polygon = rect_polygon(xmin, ymin, xmax, ymax);
-                    if polygon.len() < 3 {
-                        continue;
-                    }
-                    objects.push(Object::new(0, class_id, polygon));
-                }
-            }
-
-            let annotation = build_annotation(objects, "import");
-            imported.push(ImportedImage {
-                source_path: image_path,
-                file_name,
-                annotation,
-            });
-        }
-
-        Ok(imported)
-    }
-
-    fn import_from_voc(
-        &self,
-        root: &Path,
-        meta: &lab_core::Meta,
-    ) -> anyhow::Result<Vec<ImportedImage>> {
-        let images_dir = root.join("JPEGImages");
-        let labels_dir = root.join("Annotations");
-        if !images_dir.exists() || !labels_dir.exists() {
-            return Err(anyhow::anyhow!(
-                "VOC root must contain JPEGImages/ and Annotations/ directories"
-            ));
-        }
-
-        let image_paths = list_images_in_dir(&images_dir)?;
-        let mut imported = Vec::new();
-
-        for image_path in image_paths {
-            let file_name = image_path
-                .file_name()
-                .and_then(|s| s.to_str())
-                .context("Invalid image name")?
-                .to_string();
-            let stem = Path::new(&file_name)
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or(&file_name);
-            let label_path = labels_dir.join(format!("{}.xml", stem));
-
-            let mut objects = Vec::new();
-            if label_path.exists() {
-                let xml = fs::read_to_string(&label_path)?;
-                if let Some((width, height)) = parse_voc_size(&xml) {
-                    let voc_objects = parse_voc_objects(&xml);
-                    for voc_obj in voc_objects {
-                        let category_id = match find_category_id_by_name(meta, &voc_obj.label) {
-                            Some(id) => id,
-                            None => {
-                                log::warn!(
-                                    "Unknown category name {} in {:?}",
-                                    voc_obj.label,
-                                    label_path
-                                );
-                                continue;
-                            }
-                        };
-
-                        let xmin = voc_obj.xmin / width;
-                        let ymin = voc_obj.ymin / height;
-                        let xmax = voc_obj.xmax / width;
-                        let ymax = voc_obj.ymax / height;
-                        let polygon = rect_polygon(xmin, ymin, xmax, ymax);
-                        if polygon.len() < 3 {
-                            continue;
-                        }
-                        objects.push(Object::new(0, category_id, polygon));
-                    }
-                }
-            }
-
-            let annotation = build_annotation(objects, "import");
-            imported.push(ImportedImage {
-                source_path: image_path,
-                file_name,
-                annotation,
-            });
-        }
-
-        Ok(imported)
-    }
-
-    fn import_from_coco(
-        &self,
-        json_path: &Path,
-        images_dir: &Path,
-        meta: &lab_core::Meta,
-    ) -> anyhow::Result<Vec<ImportedImage>> {
-        let content = fs::read_to_string(json_path)?;
-        let dataset: CocoDataset = serde_json::from_str(&content)?;
-
-        let mut category_map = HashMap::new();
-        for cat in &dataset.categories {
-            if meta.find_category(cat.id).is_some() {
-                category_map.insert(cat.id, cat.id);
-            } else if let Some(id) = find_category_id_by_name(meta, &cat.name) {
-                category_map.insert(cat.id, id);
-            }
-        }
-
-        let mut annotations_by_image: HashMap<i32, Vec<CocoAnnotation>> = HashMap::new();
-        for ann in dataset.annotations {
-            annotations_by_image
-                .entry(ann.image_id)
-                .or_default()
-                .push(ann);
-        }
-
-        let mut imported = Vec::new();
-        for image in dataset.images {
-            let file_name = Path::new(&image.file_name)
-                .file_name()
-                .and_then(|s| s.to_str())
-                .context("Invalid image name")?
-                .to_string();
-            let source_path = images_dir.join(&image.file_name);
-            if !source_path.exists() {
-                return Err(anyhow::anyhow!(format!(
-                    "Missing image file: {:?}",
-                    source_path
-                )));
-            }
-
-            let mut objects = Vec::new();
-            if let Some(anns) = annotations_by_image.get(&image.id) {
-                for ann in anns {
-                    let category_id = match category_map.get(&ann.category_id) {
-                        Some(id) => *id,
-                        None => {
-                            log::warn!(
-                                "Unknown category id {} for image {}",
-                                ann.category_id,
-                                image.file_name
-                            );
-                            continue;
-                        }
-                    };
-
-                    let polygon = if let Some(segmentation) = ann.segmentation.as_ref() {
-                        if let Some(points) =
-                            coco_segmentation_to_polygon(segmentation, image.width, image.height)
-                        {
-                            points
-                        } else {
-                            bbox_to_polygon(&ann.bbox, image.width, image.height)
-                        }
-                    } else {
-                        bbox_to_polygon(&ann.bbox, image.width, image.height)
-                    };
-
-                    if polygon.len() < 3 {
-                        continue;
-                    }
-                    objects.push(Object::new(0, category_id, polygon));
-                }
-            }
-
-            let annotation = build_annotation(objects, "import");
-            imported.push(ImportedImage {
-                source_path,
-                file_name,
-                annotation,
-            });
-        }
-
-        Ok(imported)
-    }
-
-    fn import_from_labelme(
-        &self,
-        root: &Path,
-        meta: &lab_core::Meta,
-    ) -> anyhow::Result<Vec<ImportedImage>> {
-        let mut imported = Vec::new();
-        for entry in fs::read_dir(root)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) != Some("json") {
-                continue;
-            }
-
-            let content = fs::read_to_string(&path)?;
-            let labelme: LabelMeFile = serde_json::from_str(&content)?;
-
-            let file_name = if let Some(image_path) = labelme.image_path.as_ref() {
-                Path::new(image_path)
-                    .file_name()
-                    .and_then(|s| s.to_str())
-                    .context("Invalid image name")?
-                    .to_string()
-            } else {
-                let stem = path
-                    .file_stem()
-                    .and_then(|s| s.to_str())
-                    .context("Invalid label file name")?;
-                find_image_by_stem(root, stem)?
-            };
-
-            let source_path = root.join(&file_name);
-            if !source_path.exists() {
-                return Err(anyhow::anyhow!(format!(
-                    "Missing image file: {:?}",
-                    source_path
-                )));
-            }
-
-            let mut objects = Vec::new();
-            for shape in labelme.shapes {
-                let category_id = match find_category_id_by_name(meta, &shape.label) {
-                    Some(id) => id,
-                    None => {
-                        log::warn!("Unknown category name {} in {:?}", shape.label, path);
-                        continue;
-                    }
-                };
-
-                let polygon =
-                    labelme_shape_to_polygon(&shape, labelme.image_width, labelme.image_height);
-                if polygon.len() < 3 {
-                    continue;
-                }
-                objects.push(Object::new(0, category_id, polygon));
-            }
-
-            let annotation = build_annotation(objects, "import");
-            imported.push(ImportedImage {
-                source_path,
-                file_name,
-                annotation,
-            });
-        }
-
-        Ok(imported)
-    }
-}
-
 impl LabApp {
     fn export_labelme_annotation(
         &self,
@@ -634,31 +610,69 @@ impl LabApp {
     ) -> anyhow::Result<()> {
         let mut shapes = Vec::new();
         for obj in &item.annotation.objects {
-            if obj.polygon.len() < 3 {
+            if !obj.shape.is_valid() {
                 continue;
             }
-            let label = meta
-                .find_category(obj.category)
+            let category = meta.find_category(obj.category);
+            let label = category
                 .map(|c| c.name.clone())
                 .unwrap_or_else(|| "unknown".to_string());
-            let points = obj
-                .polygon
-                .iter()
-                .map(|point| {
-                    vec![
-                        (point.x * item.width as f32) as f64,
-                        (point.y * item.height as f32) as f64,
-                    ]
-                })
-                .collect();
+            let attributes = category
+                .map(|c| property_attributes(&obj.properties, &c.properties, meta))
+                .unwrap_or_default();
+            let to_pixels = |point: &Point| {
+                vec![
+                    (point.x * item.width as f32) as f64,
+                    (point.y * item.height as f32) as f64,
+                ]
+            };
+            // Multi-polygons and masks become one polygon per part, grouped by object id
+            let (parts, group_id) = match &obj.shape {
+                Shape::Box { min, max } => (
+                    vec![("rectangle", vec![to_pixels(min), to_pixels(max)])],
+                    None,
+                ),
+                Shape::Polyline { points } => (
+                    vec![("linestrip", points.iter().map(to_pixels).collect())],
+                    None,
+                ),
+                Shape::Point { position } => (vec![("point", vec![to_pixels(position)])], None),
+                Shape::Ellipse {
+                    center,
+                    radius_x,
+                    radius_y,
+                } if (radius_x * item.width as f32 - radius_y * item.height as f32).abs() < 1.0 => {
+                    // LabelMe circles are given by the center and a point on the circle
+                    let edge = Point::new(center.x + radius_x, center.y);
+                    (
+                        vec![("circle", vec![to_pixels(center), to_pixels(&edge)])],
+                        None,
+                    )
+                }
+                Shape::MultiPolygon { .. } | Shape::Mask(_) => (
+                    obj.shape
+                        .simple_rings()
+                        .iter()
+                        .map(|ring| ("polygon", ring.iter().map(to_pixels).collect()))
+                        .collect(),
+                    Some(obj.id),
+                ),
+                shape => (
+                    vec![("polygon", shape.outline().iter().map(to_pixels).collect())],
+                    None,
+                ),
+            };
 
-            shapes.push(LabelMeShapeOut {
-                label,
-                points,
-                group_id: None,
-                shape_type: "polygon".to_string(),
-                flags: HashMap::new(),
-            });
+            for (shape_type, points) in parts {
+                shapes.push(LabelMeShapeOut {
+                    label: label.clone(),
+                    points,
+                    group_id,
+                    shape_type: shape_type.to_string(),
+                    flags: HashMap::new(),
+                    attributes: attributes.clone(),
+                });
+            }
         }
 
         let labelme = LabelMeOut {
@@ -669,6 +683,11 @@ impl LabApp {
             image_data: None,
             image_height: item.height,
             image_width: item.width,
+            attributes: property_attributes(
+                &item.annotation.properties,
+                &meta.image_properties,
+                meta,
+            ),
         };
 
         let content = serde_json::to_string_pretty(&labelme)?;
@@ -677,296 +696,6 @@ impl LabApp {
     }
 }
 
-fn list_images_in_dir(dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
-    let mut images = Vec::new();
-    for entry in fs::read_dir(dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        if !path.is_file() {
-            continue;
-        }
-        let ext = path
-            .extension()
-            .and_then(|s| s.to_str())
-            .unwrap_or("")
-            .to_lowercase();
-        if ext == "jpg" || ext == "jpeg" || ext == "png" {
-            images.push(path);
-        }
-    }
-    images.sort();
-    Ok(images)
-}
-
-fn rect_polygon(xmin: f32, ymin: f32, xmax: f32, ymax: f32) -> Vec<Point> {
-    let mut xmin = clamp01(xmin);
-    let mut ymin = clamp01(ymin);
-    let mut xmax = clamp01(xmax);
-    let mut ymax = clamp01(ymax);
-    if xmax < xmin {
-        std::mem::swap(&mut xmin, &mut xmax);
-    }
-    if ymax < ymin {
-        std::mem::swap(&mut ymin, &mut ymax);
-    }
-    if xmax <= xmin || ymax <= ymin {
-        return Vec::new();
-    }
-    vec![
-        Point::new(xmin, ymin),
-        Point::new(xmax, ymin),
-        Point::new(xmax, ymax),
-        Point::new(xmin, ymax),
-    ]
-}
-
-fn clamp01(value: f32) -> f32 {
-    value.max(0.0).min(1.0)
-}
-
-fn build_annotation(objects: Vec<Object>, user_agent: &str) -> Option<Annotation> {
-    if objects.is_empty() {
-        return None;
-    }
-    let mut annotation = Annotation::new(user_agent);
-    for (idx, mut obj) in objects.into_iter().enumerate() {
-        obj.id = idx as i32;
-        annotation.add_object(obj);
-    }
-    Some(annotation)
-}
-
-fn find_category_id_by_name(meta: &lab_core::Meta, name: &str) -> Option<i32> {
-    meta.categories
-        .iter()
-        .find(|cat| cat.name == name)
-        .map(|cat| cat.id)
-}
-
-fn parse_voc_size(xml: &str) -> Option<(f32, f32)> {
-    let width = extract_tag_value(xml, "width")?.parse::<f32>().ok()?;
-    let height = extract_tag_value(xml, "height")?.parse::<f32>().ok()?;
-    if width <= 0.0 || height <= 0.0 {
-        None
-    } else {
-        Some((width, height))
-    }
-}
-
-fn parse_voc_objects(xml: &str) -> Vec<VocObject> {
-    let mut objects = Vec::new();
-    let mut rest = xml;
-    while let Some(start) = rest.find("<object>") {
-        rest = &rest[start + "<object>".len()..];
-        let end = match rest.find("</object>") {
-            Some(end) => end,
-            None => break,
-        };
-        let block = &rest[..end];
-        rest = &rest[end + "</object>".len()..];
-
-        let label = match extract_tag_value(block, "name") {
-            Some(val) => val,
-            None => continue,
-        };
-        let xmin = match extract_tag_value(block, "xmin").and_then(|v| v.parse().ok()) {
-            Some(val) => val,
-            None => continue,
-        };
-        let ymin = match extract_tag_value(block, "ymin").and_then(|v| v.parse().ok()) {
-            Some(val) => val,
-            None => continue,
-        };
-        let xmax = match extract_tag_value(block, "xmax").and_then(|v| v.parse().ok()) {
-            Some(val) => val,
-            None => continue,
-        };
-        let ymax = match extract_tag_value(block, "ymax").and_then(|v| v.parse().ok()) {
-            Some(val) => val,
-            None => continue,
-        };
-
-        objects.push(VocObject {
-            label,
-            xmin,
-            ymin,
-            xmax,
-            ymax,
-        });
-    }
-    objects
-}
-
-fn extract_tag_value(content: &str, tag: &str) -> Option<String> {
-    let start_tag = format!("<{}>", tag);
-    let end_tag = format!("</{}>", tag);
-    let start = content.find(&start_tag)? + start_tag.len();
-    let end = content[start..].find(&end_tag)? + start;
-    Some(content[start..end].trim().to_string())
-}
-
-fn bbox_to_polygon(bbox: &[f32], width: u32, height: u32) -> Vec<Point> {
-    if bbox.len() < 4 || width == 0 || height == 0 {
-        return Vec::new();
-    }
-    let x = bbox[0] / width as f32;
-    let y = bbox[1] / height as f32;
-    let w = bbox[2] / width as f32;
-    let h = bbox[3] / height as f32;
-    rect_polygon(x, y, x + w, y + h)
-}
-
-fn coco_segmentation_to_polygon(
-    segmentation: &serde_json::Value,
-    width: u32,
-    height: u32,
-) -> Option<Vec<Point>> {
-    let coords = match segmentation {
-        serde_json::Value::Array(items) => {
-            if items.is_empty() {
-                return None;
-            }
-            if items[0].is_array() {
-                items[0].as_array()?.clone()
-            } else {
-                items.clone()
-            }
-        }
-        _ => return None,
-    };
-
-    let mut points = Vec::new();
-    let mut iter = coords.iter().filter_map(|v| v.as_f64());
-    while let (Some(x), Some(y)) = (iter.next(), iter.next()) {
-        if width == 0 || height == 0 {
-            break;
-        }
-        points.push(Point::new(
-            clamp01(x as f32 / width as f32),
-            clamp01(y as f32 / height as f32),
-        ));
-    }
-
-    if points.len() >= 3 {
-        Some(points)
-    } else {
-        None
-    }
-}
-
-fn labelme_shape_to_polygon(shape: &LabelMeShape, width: u32, height: u32) -> Vec<Point> {
-    if width == 0 || height == 0 {
-        return Vec::new();
-    }
-    let shape_type = shape
-        .shape_type
-        .as_deref()
-        .unwrap_or("polygon")
-        .to_lowercase();
-
-    let points = match shape_type.as_str() {
-        "rectangle" if shape.points.len() >= 2 => {
-            let p1 = &shape.points[0];
-            let p2 = &shape.points[1];
-            if p1.len() < 2 || p2.len() < 2 {
-                return Vec::new();
-            }
-            let x1 = p1[0] as f32 / width as f32;
-            let y1 = p1[1] as f32 / height as f32;
-            let x2 = p2[0] as f32 / width as f32;
-            let y2 = p2[1] as f32 / height as f32;
-            rect_polygon(x1, y1, x2, y2)
-        }
-        _ => shape
-            .points
-            .iter()
-            .filter_map(|p| {
-                if p.len() < 2 {
-                    None
-                } else {
-                    Some(Point::new(
-                        clamp01(p[0] as f32 / width as f32),
-                        clamp01(p[1] as f32 / height as f32),
-                    ))
-                }
-            })
-            .collect(),
-    };
-
-    points
-}
-
-fn find_image_by_stem(root: &Path, stem: &str) -> anyhow::Result<String> {
-    let candidates = ["jpg", "jpeg", "png"];
-    for ext in candidates {
-        let path = root.join(format!("{}.{}", stem, ext));
-        if path.exists() {
-            return Ok(format!("{}.{}", stem, ext));
-        }
-    }
-    Err(anyhow::anyhow!("Cannot find image for {}", stem))
-}
-
-#[derive(Debug)]
-struct VocObject {
-    label: String,
-    xmin: f32,
-    ymin: f32,
-    xmax: f32,
-    ymax: f32,
-}
-
-#[derive(Debug, Deserialize)]
-struct CocoDataset {
-    images: Vec<CocoImage>,
-    annotations: Vec<CocoAnnotation>,
-    categories: Vec<CocoCategory>,
-}
-
-#[derive(Debug, Deserialize)]
-struct CocoImage {
-    id: i32,
-    file_name: String,
-    width: u32,
-    height: u32,
-}
-
-#[derive(Debug, Deserialize)]
-struct CocoCategory {
-    id: i32,
-    name: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct CocoAnnotation {
-    image_id: i32,
-    category_id: i32,
-    #[serde(default)]
-    bbox: Vec<f32>,
-    #[serde(default)]
-    segmentation: Option<serde_json::Value>,
-}
-
-#[derive(Debug, Deserialize)]
-struct LabelMeFile {
-    #[serde(rename = "imagePath")]
-    image_path: Option<String>,
-    #[serde(rename = "imageHeight")]
-    image_height: u32,
-    #[serde(rename = "imageWidth")]
-    image_width: u32,
-    #[serde(default)]
-    shapes: Vec<LabelMeShape>,
-}
-
-#[derive(Debug, Deserialize)]
-struct LabelMeShape {
-    label: String,
-    points: Vec<Vec<f64>>,
-    #[serde(rename = "shape_type")]
-    shape_type: Option<String>,
-}
-
 #[derive(Serialize)]
 struct LabelMeOut {
     version: String,
@@ -980,6 +709,9 @@ struct LabelMeOut {
     image_height: u32,
     #[serde(rename = "imageWidth")]
     image_width: u32,
+    /// Image-level properties, kept by LabelMe as extra data
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    attributes: BTreeMap<String, serde_json::Value>,
 }
 
 #[derive(Serialize)]
@@ -991,4 +723,7 @@ struct LabelMeShapeOut {
     #[serde(rename = "shape_type")]
     shape_type: String,
     flags: HashMap<String, serde_json::Value>,
+    /// Object properties, kept by LabelMe as extra shape data
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    attributes: BTreeMap<String, serde_json::Value>,
 }
@@ -0,0 +1,180 @@
+// Project maintenance dialog for JLab
+use egui::Context;
+use lab_core::StorageFormat;
+use lab_utils::migration::MigrationReport;
+use lab_utils::storage::StorageConversionReport;
+use std::path::PathBuf;
+
+/// Most files listed per section of a report
+const MAX_LISTED_FILES: usize = 200;
+
+/// Rewrite of all annotation files of the project
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaintenanceTask {
+    /// Switch the annotation files to another storage format
+    ConvertStorage(StorageFormat),
+    /// Rewrite annotation files of older schema versions in the current one
+    Migrate,
+}
+
+/// Button pressed or choice changed in the dialog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaintenanceAction {
+    /// The task changed, preview it again
+    Preview,
+    /// Rewrite the files
+    Apply,
+    /// Close the dialog
+    Close,
+}
+
+/// Files changed by a task, or to be changed by it while previewing
+#[derive(Debug, Clone, Default)]
+pub struct MaintenanceReport {
+    pub changed: Vec<PathBuf>,
+    pub up_to_date: usize,
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+impl From<MigrationReport> for MaintenanceReport {
+    fn from(report: MigrationReport) -> Self {
+        Self {
+            changed: report.migrated,
+            up_to_date: report.up_to_date,
+            failed: report.failed,
+        }
+    }
+}
+
+impl From<StorageConversionReport> for MaintenanceReport {
+    fn from(report: StorageConversionReport) -> Self {
+        Self {
+            changed: report.converted,
+            up_to_date: report.up_to_date,
+            failed: report.failed,
+        }
+    }
+}
+
+/// Maintenance dialog state
+///
+/// The dialog first shows a dry run of the task; applying it replaces the
+/// preview by the report of what was done.
+pub struct MaintenanceDialogState {
+    /// Task shown, None while the dialog is closed
+    pub task: Option<MaintenanceTask>,
+    pub report: MaintenanceReport,
+    /// Whether the report is of files written rather than a preview
+    pub applied: bool,
+}
+
+impl MaintenanceDialogState {
+    pub fn new() -> Self {
+        Self {
+            task: None,
+            report: MaintenanceReport::default(),
+            applied: false,
+        }
+    }
+
+    /// Show the preview of a task
+    pub fn open(&mut self, task: MaintenanceTask, preview: MaintenanceReport) {
+        self.task = Some(task);
+        self.report = preview;
+        self.applied = false;
+    }
+
+    /// Render the dialog: the task settings, then the report
+    pub fn show(&mut self, ctx: &Context, i18n: &crate::i18n::I18n) -> Option<MaintenanceAction> {
+        let task = self.task?;
+
+        let mut open = true;
+        let mut action = None;
+
+        let title = match task {
+            MaintenanceTask::ConvertStorage(_) => i18n.t("maintenance.convert_title"),
+            MaintenanceTask::Migrate => i18n.t("maintenance.migrate_title"),
+        };
+        egui::Window::new(title)
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if let Some(MaintenanceTask::ConvertStorage(format)) = &mut self.task {
+                    ui.horizontal(|ui| {
+                        ui.label(i18n.t("maintenance.storage_format"));
+                        let before = *format;
+                        ui.add_enabled_ui(!self.applied, |ui| {
+                            egui::ComboBox::from_id_salt("maintenance_storage_format")
+                                .selected_text(format.as_str())
+                                .show_ui(ui, |ui| {
+                                    for choice in StorageFormat::all() {
+                                        ui.selectable_value(format, choice, choice.as_str());
+                                    }
+                                });
+                        });
+                        if *format != before {
+                            action = Some(MaintenanceAction::Preview);
+                        }
+                    });
+                    ui.add_space(8.0);
+                }
+
+                let changed_label = match task {
+                    MaintenanceTask::ConvertStorage(_) if self.applied => {
+                        i18n.t("maintenance.converted")
+                    }
+                    MaintenanceTask::ConvertStorage(_) => i18n.t("maintenance.to_convert"),
+                    MaintenanceTask::Migrate if self.applied => i18n.t("maintenance.migrated"),
+                    MaintenanceTask::Migrate => i18n.t("maintenance.to_migrate"),
+                };
+                let report = &self.report;
+                ui.label(format!("{} {}", changed_label, report.changed.len()));
+                ui.label(format!(
+                    "{} {}",
+                    i18n.t("maintenance.up_to_date"),
+                    report.up_to_date
+                ));
+                ui.label(format!(
+                    "{} {}",
+                    i18n.t("maintenance.failed"),
+                    report.failed.len()
+                ));
+
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        for path in report.changed.iter().take(MAX_LISTED_FILES) {
+                            ui.label(path.display().to_string());
+                        }
+                        if report.changed.len() > MAX_LISTED_FILES {
+                            ui.label(format!("… +{}", report.changed.len() - MAX_LISTED_FILES));
+                        }
+                        for (path, reason) in report.failed.iter().take(MAX_LISTED_FILES) {
+                            ui.colored_label(
+                                ui.visuals().error_fg_color,
+                                format!("{}: {}", path.display(), reason),
+                            );
+                        }
+                        if report.failed.len() > MAX_LISTED_FILES {
+                            ui.label(format!("… +{}", report.failed.len() - MAX_LISTED_FILES));
+                        }
+                    });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if !self.applied && ui.button(i18n.t("maintenance.apply")).clicked() {
+                        action = Some(MaintenanceAction::Apply);
+                    }
+                    if ui.button(i18n.t("maintenance.close")).clicked() {
+                        action = Some(MaintenanceAction::Close);
+                    }
+                });
+            });
+
+        if !open {
+            action = Some(MaintenanceAction::Close);
+        }
+        action
+    }
+}
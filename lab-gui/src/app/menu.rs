@@ -1,4 +1,6 @@
 use super::import_export::DatasetFormat;
+use super::maintenance_dialog::MaintenanceTask;
+use super::shape_tool_dialog::ShapeTool;
 use super::LabApp;
 use egui::{Context, TopBottomPanel, Vec2};
 
@@ -43,9 +45,7 @@ impl LabApp {
                                     format!("{} {}", recent_hint_prefix, path),
                                 );
                                 if recent_response.clicked() {
-                                    if let Err(e) = self.state.load_project(project_path) {
-                                        log::error!("Failed to load project: {}", e);
-                                    }
+                                    self.open_project(project_path);
                                     ui.close_menu();
                                 }
                             }
@@ -73,6 +73,30 @@ impl LabApp {
                             self.import_dataset(DatasetFormat::Yolo);
                         }
 
+                        let yolo_seg_label = self.state.i18n.t("menu.file_import_yolo_seg");
+                        let yolo_seg_hint = self.state.i18n.t("hint.file_import_yolo_seg");
+                        let yolo_seg_response = ui.add_enabled(
+                            can_import_export,
+                            egui::Button::new(yolo_seg_label.clone()),
+                        );
+                        Self::update_status_hint(status_hint, &yolo_seg_response, yolo_seg_hint);
+                        if yolo_seg_response.clicked() {
+                            ui.close_menu();
+                            self.import_dataset(DatasetFormat::YoloSeg);
+                        }
+
+                        let yolo_obb_label = self.state.i18n.t("menu.file_import_yolo_obb");
+                        let yolo_obb_hint = self.state.i18n.t("hint.file_import_yolo_obb");
+                        let yolo_obb_response = ui.add_enabled(
+                            can_import_export,
+                            egui::Button::new(yolo_obb_label.clone()),
+                        );
+                        Self::update_status_hint(status_hint, &yolo_obb_response, yolo_obb_hint);
+                        if yolo_obb_response.clicked() {
+                            ui.close_menu();
+                            self.import_dataset(DatasetFormat::YoloObb);
+                        }
+
                         let voc_label = self.state.i18n.t("menu.file_import_voc");
                         let voc_hint = self.state.i18n.t("hint.file_import_voc");
                         let voc_response =
@@ -124,6 +148,30 @@ impl LabApp {
                             self.export_dataset(DatasetFormat::Yolo);
                         }
 
+                        let yolo_seg_label = self.state.i18n.t("menu.file_export_yolo_seg");
+                        let yolo_seg_hint = self.state.i18n.t("hint.file_export_yolo_seg");
+                        let yolo_seg_response = ui.add_enabled(
+                            can_import_export,
+                            egui::Button::new(yolo_seg_label.clone()),
+                        );
+                        Self::update_status_hint(status_hint, &yolo_seg_response, yolo_seg_hint);
+                        if yolo_seg_response.clicked() {
+                            ui.close_menu();
+                            self.export_dataset(DatasetFormat::YoloSeg);
+                        }
+
+                        let yolo_obb_label = self.state.i18n.t("menu.file_export_yolo_obb");
+                        let yolo_obb_hint = self.state.i18n.t("hint.file_export_yolo_obb");
+                        let yolo_obb_response = ui.add_enabled(
+                            can_import_export,
+                            egui::Button::new(yolo_obb_label.clone()),
+                        );
+                        Self::update_status_hint(status_hint, &yolo_obb_response, yolo_obb_hint);
+                        if yolo_obb_response.clicked() {
+                            ui.close_menu();
+                            self.export_dataset(DatasetFormat::YoloObb);
+                        }
+
                         let voc_label = self.state.i18n.t("menu.file_export_voc");
                         let voc_hint = self.state.i18n.t("hint.file_export_voc");
                         let voc_response =
@@ -155,6 +203,16 @@ impl LabApp {
                             ui.close_menu();
                             self.export_dataset(DatasetFormat::LabelMe);
                         }
+
+                        let seg_label = self.state.i18n.t("menu.file_export_segmentation");
+                        let seg_hint = self.state.i18n.t("hint.file_export_segmentation");
+                        let seg_response = ui
+                            .add_enabled(can_import_export, egui::Button::new(seg_label.clone()));
+                        Self::update_status_hint(status_hint, &seg_response, seg_hint);
+                        if seg_response.clicked() {
+                            ui.close_menu();
+                            self.export_dataset(DatasetFormat::Segmentation);
+                        }
                     });
                     Self::update_status_hint(
                         status_hint,
@@ -183,6 +241,52 @@ impl LabApp {
                         ui.close_menu();
                     }
 
+                    // Previous versions of the current annotation
+                    let backups = self.current_backups();
+                    let restore_label = self.state.i18n.t("menu.file_restore");
+                    let restore_hint = self.state.i18n.t("hint.file_restore");
+                    let restore_menu_response = ui
+                        .add_enabled_ui(!backups.is_empty(), |ui| {
+                            ui.menu_button(restore_label.clone(), |ui| {
+                                for backup in backups {
+                                    let time = backup.saved_at.format("%Y-%m-%d %H:%M:%S UTC");
+                                    if ui.button(time.to_string()).clicked() {
+                                        ui.close_menu();
+                                        self.restore_backup(&backup.path);
+                                    }
+                                }
+                            })
+                        })
+                        .response;
+                    Self::update_status_hint(status_hint, &restore_menu_response, restore_hint);
+
+                    let has_project = self.state.project.is_some();
+                    let convert_label = self.state.i18n.t("menu.file_convert_storage");
+                    let convert_hint = self.state.i18n.t("hint.file_convert_storage");
+                    let convert_response =
+                        ui.add_enabled(has_project, egui::Button::new(convert_label.clone()));
+                    Self::update_status_hint(status_hint, &convert_response, convert_hint);
+                    if convert_response.clicked() {
+                        ui.close_menu();
+                        let format = self
+                            .state
+                            .project
+                            .as_ref()
+                            .map(|project| project.meta.storage)
+                            .unwrap_or_default();
+                        self.open_maintenance_dialog(MaintenanceTask::ConvertStorage(format));
+                    }
+
+                    let migrate_label = self.state.i18n.t("menu.file_migrate");
+                    let migrate_hint = self.state.i18n.t("hint.file_migrate");
+                    let migrate_response =
+                        ui.add_enabled(has_project, egui::Button::new(migrate_label.clone()));
+                    Self::update_status_hint(status_hint, &migrate_response, migrate_hint);
+                    if migrate_response.clicked() {
+                        ui.close_menu();
+                        self.open_maintenance_dialog(MaintenanceTask::Migrate);
+                    }
+
                     let close_label = self.state.i18n.t("menu.file_close");
                     let close_hint = self.hint_with_shortcut(
                         self.state.i18n.t("hint.file_close"),
@@ -295,6 +399,44 @@ impl LabApp {
 
                     ui.separator();
 
+                    let undo_label = self.state.i18n.t("menu.edit_undo");
+                    let undo_hint = self.hint_with_shortcut(
+                        self.state.i18n.t("hint.edit_undo"),
+                        crate::shortcuts::ShortcutAction::Undo,
+                    );
+                    let undo_response = ui.add_enabled(
+                        self.state.can_undo(),
+                        egui::Button::new(self.menu_text(
+                            undo_label.clone(),
+                            crate::shortcuts::ShortcutAction::Undo,
+                        )),
+                    );
+                    Self::update_status_hint(status_hint, &undo_response, undo_hint);
+                    if undo_response.clicked() {
+                        self.state.undo();
+                        ui.close_menu();
+                    }
+
+                    let redo_label = self.state.i18n.t("menu.edit_redo");
+                    let redo_hint = self.hint_with_shortcut(
+                        self.state.i18n.t("hint.edit_redo"),
+                        crate::shortcuts::ShortcutAction::Redo,
+                    );
+                    let redo_response = ui.add_enabled(
+                        self.state.can_redo(),
+                        egui::Button::new(self.menu_text(
+                            redo_label.clone(),
+                            crate::shortcuts::ShortcutAction::Redo,
+                        )),
+                    );
+                    Self::update_status_hint(status_hint, &redo_response, redo_hint);
+                    if redo_response.clicked() {
+                        self.state.redo();
+                        ui.close_menu();
+                    }
+
+                    ui.separator();
+
                     let copy_label = self.state.i18n.t("menu.edit_copy");
                     let copy_hint = self.hint_with_shortcut(
                         self.state.i18n.t("hint.edit_copy"),
@@ -432,6 +574,120 @@ impl LabApp {
                             self.fix_selected_self_intersections();
                             ui.close_menu();
                         }
+
+                        let simplify_label = self.state.i18n.t("menu.edit_shape_simplify");
+                        let simplify_hint = self.hint_with_shortcut(
+                            self.state.i18n.t("hint.edit_shape_simplify"),
+                            crate::shortcuts::ShortcutAction::SimplifyShape,
+                        );
+                        let simplify_response = ui.add_enabled(
+                            can_modify_shape,
+                            egui::Button::new(self.menu_text(
+                                simplify_label.clone(),
+                                crate::shortcuts::ShortcutAction::SimplifyShape,
+                            )),
+                        );
+                        Self::update_status_hint(status_hint, &simplify_response, simplify_hint);
+                        if simplify_response.clicked() {
+                            self.open_shape_tool(ShapeTool::Simplify);
+                            ui.close_menu();
+                        }
+
+                        let smooth_label = self.state.i18n.t("menu.edit_shape_smooth");
+                        let smooth_hint = self.hint_with_shortcut(
+                            self.state.i18n.t("hint.edit_shape_smooth"),
+                            crate::shortcuts::ShortcutAction::SmoothShape,
+                        );
+                        let smooth_response = ui.add_enabled(
+                            can_modify_shape,
+                            egui::Button::new(self.menu_text(
+                                smooth_label.clone(),
+                                crate::shortcuts::ShortcutAction::SmoothShape,
+                            )),
+                        );
+                        Self::update_status_hint(status_hint, &smooth_response, smooth_hint);
+                        if smooth_response.clicked() {
+                            self.smooth_selected_shape();
+                            ui.close_menu();
+                        }
+
+                        let densify_label = self.state.i18n.t("menu.edit_shape_densify");
+                        let densify_hint = self.hint_with_shortcut(
+                            self.state.i18n.t("hint.edit_shape_densify"),
+                            crate::shortcuts::ShortcutAction::DensifyShape,
+                        );
+                        let densify_response = ui.add_enabled(
+                            can_modify_shape,
+                            egui::Button::new(self.menu_text(
+                                densify_label.clone(),
+                                crate::shortcuts::ShortcutAction::DensifyShape,
+                            )),
+                        );
+                        Self::update_status_hint(status_hint, &densify_response, densify_hint);
+                        if densify_response.clicked() {
+                            self.open_shape_tool(ShapeTool::Densify);
+                            ui.close_menu();
+                        }
+
+                        let merge_label = self.state.i18n.t("menu.edit_shape_merge");
+                        let merge_hint = self.state.i18n.t("hint.edit_shape_merge");
+                        let merge_response = ui
+                            .add_enabled(can_modify_shape, egui::Button::new(merge_label.clone()));
+                        Self::update_status_hint(status_hint, &merge_response, merge_hint);
+                        if merge_response.clicked() {
+                            self.merge_selected_objects();
+                            ui.close_menu();
+                        }
+
+                        let subtract_label = self.state.i18n.t("menu.edit_shape_subtract");
+                        let subtract_hint = self.state.i18n.t("hint.edit_shape_subtract");
+                        let subtract_response = ui.add_enabled(
+                            can_modify_shape,
+                            egui::Button::new(subtract_label.clone()),
+                        );
+                        Self::update_status_hint(status_hint, &subtract_response, subtract_hint);
+                        if subtract_response.clicked() {
+                            self.subtract_selected_object();
+                            ui.close_menu();
+                        }
+
+                        let clip_label = self.state.i18n.t("menu.edit_shape_clip");
+                        let clip_hint = self.state.i18n.t("hint.edit_shape_clip");
+                        let clip_response =
+                            ui.add_enabled(can_modify_shape, egui::Button::new(clip_label.clone()));
+                        Self::update_status_hint(status_hint, &clip_response, clip_hint);
+                        if clip_response.clicked() {
+                            self.clip_selected_to_roi();
+                            ui.close_menu();
+                        }
+
+                        let to_mask_label = self.state.i18n.t("menu.edit_shape_to_mask");
+                        let to_mask_hint = self.state.i18n.t("hint.edit_shape_to_mask");
+                        let to_mask_response = ui.add_enabled(
+                            can_modify_shape,
+                            egui::Button::new(to_mask_label.clone()),
+                        );
+                        Self::update_status_hint(status_hint, &to_mask_response, to_mask_hint);
+                        if to_mask_response.clicked() {
+                            self.convert_selected_to_mask();
+                            ui.close_menu();
+                        }
+
+                        let to_polygon_label = self.state.i18n.t("menu.edit_shape_to_polygon");
+                        let to_polygon_hint = self.state.i18n.t("hint.edit_shape_to_polygon");
+                        let to_polygon_response = ui.add_enabled(
+                            can_modify_shape,
+                            egui::Button::new(to_polygon_label.clone()),
+                        );
+                        Self::update_status_hint(
+                            status_hint,
+                            &to_polygon_response,
+                            to_polygon_hint,
+                        );
+                        if to_polygon_response.clicked() {
+                            self.convert_selected_mask_to_polygon();
+                            ui.close_menu();
+                        }
                     });
                     Self::update_status_hint(status_hint, &shape_response.response, shape_hint);
 
@@ -487,8 +743,92 @@ impl LabApp {
                             self.state.draw_target = crate::state::DrawTarget::Roi;
                             ui.close_menu();
                         }
+
+                        let target_keypoints_label =
+                            self.state.i18n.t("menu.edit_draw_target_keypoints");
+                        let target_keypoints_hint = self.state.i18n.t("hint.draw_target_keypoints");
+                        let target_keypoints_response = ui.selectable_label(
+                            self.state.draw_target == crate::state::DrawTarget::Keypoints,
+                            target_keypoints_label.clone(),
+                        );
+                        Self::update_status_hint(
+                            status_hint,
+                            &target_keypoints_response,
+                            target_keypoints_hint,
+                        );
+                        if target_keypoints_response.clicked() {
+                            self.state.draw_target = crate::state::DrawTarget::Keypoints;
+                            self.state.clear_drawing_state();
+                            ui.close_menu();
+                        }
+
+                        let target_hole_label = self.state.i18n.t("menu.edit_draw_target_hole");
+                        let target_hole_hint = self.state.i18n.t("hint.draw_target_hole");
+                        let target_hole_response = ui.selectable_label(
+                            self.state.draw_target == crate::state::DrawTarget::Hole,
+                            target_hole_label.clone(),
+                        );
+                        Self::update_status_hint(
+                            status_hint,
+                            &target_hole_response,
+                            target_hole_hint,
+                        );
+                        if target_hole_response.clicked() {
+                            self.state.draw_target = crate::state::DrawTarget::Hole;
+                            ui.close_menu();
+                        }
+
+                        let target_part_label = self.state.i18n.t("menu.edit_draw_target_part");
+                        let target_part_hint = self.state.i18n.t("hint.draw_target_part");
+                        let target_part_response = ui.selectable_label(
+                            self.state.draw_target == crate::state::DrawTarget::Part,
+                            target_part_label.clone(),
+                        );
+                        Self::update_status_hint(
+                            status_hint,
+                            &target_part_response,
+                            target_part_hint,
+                        );
+                        if target_part_response.clicked() {
+                            self.state.draw_target = crate::state::DrawTarget::Part;
+                            ui.close_menu();
+                        }
+
+                        let target_mask_label = self.state.i18n.t("menu.edit_draw_target_mask");
+                        let target_mask_hint = self.state.i18n.t("hint.draw_target_mask");
+                        let target_mask_response = ui.selectable_label(
+                            self.state.draw_target == crate::state::DrawTarget::Mask,
+                            target_mask_label.clone(),
+                        );
+                        Self::update_status_hint(
+                            status_hint,
+                            &target_mask_response,
+                            target_mask_hint,
+                        );
+                        if target_mask_response.clicked() {
+                            self.state.draw_target = crate::state::DrawTarget::Mask;
+                            self.state.clear_drawing_state();
+                            ui.close_menu();
+                        }
                     });
                     Self::update_status_hint(status_hint, &target_response.response, target_hint);
+
+                    let shape_label = self.state.i18n.t("menu.edit_draw_shape");
+                    let shape_hint = self.state.i18n.t("hint.menu_edit_draw_shape");
+                    let shape_response = ui.menu_button(shape_label.clone(), |ui| {
+                        for kind in lab_core::ShapeKind::drawable() {
+                            let kind_label = self.state.i18n.t(&format!("shape.{}", kind.as_str()));
+                            let kind_response =
+                                ui.selectable_label(self.state.draw_shape == kind, kind_label);
+                            if kind_response.clicked() {
+                                self.state.draw_shape = kind;
+                                self.state.draw_target = crate::state::DrawTarget::Object;
+                                self.state.clear_drawing_state();
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    Self::update_status_hint(status_hint, &shape_response.response, shape_hint);
                 });
                 Self::update_status_hint(status_hint, &edit_menu_response.response, edit_menu_hint);
 
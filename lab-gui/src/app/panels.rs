@@ -1,5 +1,8 @@
 use super::LabApp;
 use egui::{Context, RichText, SidePanel, TextStyle};
+use lab_core::meta::CategoryProperty;
+use lab_core::{PropertyData, PropertyKind, PropertyValueWithConfidence, ReviewState};
+use std::collections::HashMap;
 
 impl LabApp {
     pub(super) fn show_left_panel(&mut self, ctx: &Context) {
@@ -118,161 +121,547 @@ impl LabApp {
                             .strong(),
                     );
 
-                // Collect data first to avoid borrow checker issues
-                let selected_id = self.state.selected_object_id;
-                let selected_object_id = selected_id.filter(|id| *id >= 0);
-                let roi_selected = selected_id.and_then(crate::state::roi_index_from_id);
-                let meta_clone = self.state.get_meta().cloned();
-                let can_edit_properties =
-                    self.state.editing_state.mode == crate::state::EditMode::Editing;
+                    // Collect data first to avoid borrow checker issues
+                    let selected_id = self.state.selected_object_id;
+                    let selected_object_id = selected_id.filter(|id| *id >= 0);
+                    let roi_selected = selected_id.and_then(crate::state::roi_index_from_id);
+                    let meta_clone = self.state.get_meta().cloned();
+                    let can_edit_properties =
+                        self.state.editing_state.mode == crate::state::EditMode::Editing;
+                    let mut edit_label: Option<&'static str> = None;
+                    let mut typing_label: Option<&'static str> = None;
 
-                if !can_edit_properties {
-                    ui.colored_label(
-                        egui::Color32::GRAY,
-                        self.state.i18n.t("sidebar.properties_read_only"),
-                    );
-                    ui.separator();
-                }
-
-                if let (Some(annotation), Some(selected_id), Some(meta)) = (
-                    &mut self.state.current_annotation,
-                    selected_object_id,
-                    meta_clone.as_ref(),
-                ) {
-                    // Single object editing mode
-                    if let Some(obj) = annotation.objects.iter_mut().find(|o| o.id == selected_id) {
-                        ui.label(format!("Object #{}", obj.id));
+                    if !can_edit_properties {
+                        ui.colored_label(
+                            egui::Color32::GRAY,
+                            self.state.i18n.t("sidebar.properties_read_only"),
+                        );
                         ui.separator();
-                        ui.add_enabled_ui(can_edit_properties, |ui| {
-                            // Category selector
-                            ui.label(format!("{}:", self.state.i18n.t("sidebar.category")));
-                            egui::ScrollArea::vertical()
-                                .max_height(160.0)
-                                .show(ui, |ui| {
-                                    for category in &meta.categories {
-                                        let hotkey_suffix = category
-                                            .hotkey
-                                            .parse::<u32>()
-                                            .ok()
-                                            .filter(|d| (1..=9).contains(d))
-                                            .map(|d| format!(" ({})", d))
-                                            .unwrap_or_default();
-                                        let item_text =
-                                            format!("{}{}", category.name, hotkey_suffix);
-                                        if ui
-                                            .selectable_label(
-                                                obj.category == category.id,
-                                                item_text,
-                                            )
-                                            .clicked()
-                                        {
-                                            obj.category = category.id;
-                                            // Clear properties when category changes
-                                            obj.properties.clear();
-                                            self.state.has_unsaved_changes = true;
-                                        }
-                                    }
-                                });
+                    }
 
+                    if let (Some(annotation), Some(selected_id), Some(meta)) = (
+                        &mut self.state.current_annotation,
+                        selected_object_id,
+                        meta_clone.as_ref(),
+                    ) {
+                        // Single object editing mode
+                        if let Some(obj) =
+                            annotation.objects.iter_mut().find(|o| o.id == selected_id)
+                        {
+                            ui.label(format!("Object #{}", obj.id));
                             ui.separator();
-
-                            // Get category to find its properties
-                            if let Some(category) = meta.find_category(obj.category) {
-                                if category.properties.is_empty() {
-                                    ui.label(self.state.i18n.t("sidebar.no_properties"));
-                                } else {
-                                    egui::ScrollArea::vertical().show(ui, |ui| {
-                                        for prop_def in &category.properties {
-                                            // Find property type definition
-                                            if let Some(prop_type) = meta
-                                                .property_types
-                                                .iter()
-                                                .find(|pt| pt.name == prop_def.property_type)
+                            ui.add_enabled_ui(can_edit_properties, |ui| {
+                                // Category selector
+                                ui.label(format!("{}:", self.state.i18n.t("sidebar.category")));
+                                egui::ScrollArea::vertical()
+                                    .max_height(160.0)
+                                    .show(ui, |ui| {
+                                        for category in &meta.categories {
+                                            let hotkey_suffix = category
+                                                .hotkey
+                                                .parse::<u32>()
+                                                .ok()
+                                                .filter(|d| (1..=9).contains(d))
+                                                .map(|d| format!(" ({})", d))
+                                                .unwrap_or_default();
+                                            let item_text =
+                                                format!("{}{}", category.name, hotkey_suffix);
+                                            if ui
+                                                .selectable_label(
+                                                    obj.category == category.id,
+                                                    item_text,
+                                                )
+                                                .clicked()
                                             {
-                                                ui.label(format!("{}:", prop_type.name));
-
-                                                // Get current value
-                                                let current_value = obj
-                                                    .properties
-                                                    .get(&prop_def.id.to_string())
-                                                    .and_then(|values| values.first())
-                                                    .map(|v| v.value);
-
-                                                // Render property values as list
-                                                let selected_value = current_value.unwrap_or(-1);
-
-                                                if ui
-                                                    .selectable_label(
-                                                        selected_value == -1,
-                                                        self.state.i18n.t("sidebar.not_set"),
-                                                    )
-                                                    .clicked()
-                                                {
-                                                    obj.properties
-                                                        .remove(&prop_def.id.to_string());
-                                                    self.state.has_unsaved_changes = true;
+                                                // Keypoints only carry over between
+                                                // categories with the same definition
+                                                let same_keypoints =
+                                                    meta.find_category(obj.category).is_some_and(
+                                                        |old| old.keypoints == category.keypoints,
+                                                    );
+                                                if !same_keypoints {
+                                                    obj.keypoints.clear();
                                                 }
+                                                obj.category = category.id;
+                                                // Clear properties when category changes
+                                                obj.properties.clear();
+                                                edit_label = Some("Change category");
+                                            }
+                                        }
+                                    });
+
+                                ui.separator();
 
-                                                for value in &prop_type.values {
-                                                    if ui
-                                                        .selectable_label(
-                                                            selected_value == value.id,
-                                                            &value.name,
-                                                        )
-                                                        .clicked()
+                                // Keypoint visibility editor
+                                if let Some(category) = meta
+                                    .find_category(obj.category)
+                                    .filter(|c| c.has_keypoints())
+                                {
+                                    ui.label(format!(
+                                        "{}:",
+                                        self.state.i18n.t("sidebar.keypoints")
+                                    ));
+                                    egui::Grid::new("keypoint_grid").num_columns(2).show(
+                                        ui,
+                                        |ui| {
+                                            for (index, name) in
+                                                category.keypoints.iter().enumerate()
+                                            {
+                                                let current = obj
+                                                    .keypoints
+                                                    .get(index)
+                                                    .map(|kp| kp.visibility)
+                                                    .unwrap_or_default();
+                                                ui.label(name);
+                                                egui::ComboBox::from_id_salt((
+                                                    "keypoint_visibility",
+                                                    index,
+                                                ))
+                                                .selected_text(
+                                                    self.state.i18n.t(&format!(
+                                                        "keypoint.{}",
+                                                        current.as_str()
+                                                    )),
+                                                )
+                                                .show_ui(ui, |ui| {
+                                                    for visibility in
+                                                        lab_core::KeypointVisibility::all()
                                                     {
-                                                        // Update property value
-                                                        obj.properties.insert(
-                                                            prop_def.id.to_string(),
-                                                            vec![lab_core::PropertyValueWithConfidence {
-                                                                value: value.id,
-                                                                confidence: 1.0,
-                                                            }],
+                                                        // Only placed keypoints can change visibility
+                                                        let enabled = visibility
+                                                        == lab_core::KeypointVisibility::NotLabeled
+                                                        || current.is_labeled();
+                                                        let label = self.state.i18n.t(&format!(
+                                                            "keypoint.{}",
+                                                            visibility.as_str()
+                                                        ));
+                                                        let response = ui.add_enabled(
+                                                            enabled,
+                                                            egui::SelectableLabel::new(
+                                                                current == visibility,
+                                                                label,
+                                                            ),
                                                         );
-                                                        self.state.has_unsaved_changes = true;
+                                                        if response.clicked()
+                                                            && current != visibility
+                                                        {
+                                                            if let Some(kp) =
+                                                                obj.keypoints.get_mut(index)
+                                                            {
+                                                                kp.visibility = visibility;
+                                                                edit_label =
+                                                                    Some("Change keypoint");
+                                                            }
+                                                        }
                                                     }
-                                                }
+                                                });
+                                                ui.end_row();
+                                            }
+                                        },
+                                    );
+                                    ui.separator();
+                                }
 
-                                                if !meta.property_special_values.is_empty() {
-                                                    ui.separator();
-                                                    ui.label(self.state.i18n.t(
-                                                        "sidebar.special_values",
-                                                    ));
-                                                    for special in &meta.property_special_values {
-                                                        if ui
-                                                            .selectable_label(
-                                                                selected_value == special.id,
-                                                                &special.name,
-                                                            )
-                                                            .clicked()
-                                                        {
-                                                            // Update property value
-                                                            obj.properties.insert(
-                                                                prop_def.id.to_string(),
-                                                                vec![lab_core::PropertyValueWithConfidence {
-                                                                    value: special.id,
-                                                                    confidence: 1.0,
-                                                                }],
-                                                            );
-                                                            self.state.has_unsaved_changes = true;
+                                // Get category to find its properties
+                                if let Some(category) = meta.find_category(obj.category) {
+                                    if category.properties.is_empty() {
+                                        ui.label(self.state.i18n.t("sidebar.no_properties"));
+                                    } else {
+                                        egui::ScrollArea::vertical().show(ui, |ui| {
+                                            for prop_def in &category.properties {
+                                                // Find property type definition
+                                                if let Some(prop_type) = meta
+                                                    .property_types
+                                                    .iter()
+                                                    .find(|pt| pt.name == prop_def.property_type)
+                                                {
+                                                    ui.label(format!("{}:", prop_type.name));
+                                                    match property_value_selector(
+                                                        ui,
+                                                        &self.state.i18n,
+                                                        meta,
+                                                        prop_type,
+                                                        &mut obj.properties,
+                                                        prop_def,
+                                                    ) {
+                                                        PropertyEdit::Selected => {
+                                                            edit_label = Some("Change property")
+                                                        }
+                                                        PropertyEdit::Typed => {
+                                                            typing_label = Some("Edit property")
                                                         }
+                                                        PropertyEdit::Unchanged => {}
                                                     }
                                                 }
                                             }
-                                        }
-                                    });
+                                        });
+                                    }
                                 }
+                            });
+
+                            ui.separator();
+                            if provenance_section(
+                                ui,
+                                &self.state.i18n,
+                                obj,
+                                &self.state.annotator,
+                                can_edit_properties,
+                            ) {
+                                edit_label = Some("Change review");
                             }
+                        } else {
+                            ui.label(self.state.i18n.t("sidebar.no_object_selected"));
+                        }
+                    } else if roi_selected.is_some() {
+                        ui.label(self.state.i18n.t("sidebar.roi_no_properties"));
+                    } else if let (Some(annotation), Some(meta)) = (
+                        self.state
+                            .current_annotation
+                            .as_mut()
+                            .filter(|_| selected_id.is_none()),
+                        meta_clone
+                            .as_ref()
+                            .filter(|m| !m.image_properties.is_empty()),
+                    ) {
+                        // Nothing selected: edit image-level properties
+                        ui.label(self.state.i18n.t("sidebar.image_properties"));
+                        ui.separator();
+                        ui.add_enabled_ui(can_edit_properties, |ui| {
+                            egui::ScrollArea::vertical().show(ui, |ui| {
+                                for prop_def in &meta.image_properties {
+                                    let Some(prop_type) =
+                                        meta.find_property_type_by_name(&prop_def.property_type)
+                                    else {
+                                        continue;
+                                    };
+                                    ui.label(format!("{}:", prop_def.name));
+                                    match property_value_selector(
+                                        ui,
+                                        &self.state.i18n,
+                                        meta,
+                                        prop_type,
+                                        &mut annotation.properties,
+                                        prop_def,
+                                    ) {
+                                        PropertyEdit::Selected => {
+                                            edit_label = Some("Change image property")
+                                        }
+                                        PropertyEdit::Typed => {
+                                            typing_label = Some("Edit image property")
+                                        }
+                                        PropertyEdit::Unchanged => {}
+                                    }
+                                    ui.separator();
+                                }
+                            });
                         });
                     } else {
-                        ui.label(self.state.i18n.t("sidebar.no_object_selected"));
+                        ui.label(self.state.i18n.t("sidebar.select_to_edit"));
                     }
-                } else if roi_selected.is_some() {
-                    ui.label(self.state.i18n.t("sidebar.roi_no_properties"));
-                } else {
-                    ui.label(self.state.i18n.t("sidebar.select_to_edit"));
+
+                    if let Some(label) = edit_label {
+                        self.state.record_edit(label);
+                    } else if let Some(label) = typing_label {
+                        self.state.record_repeated_edit(label);
+                    }
+                });
+        }
+    }
+}
+
+/// Read-only creator/editor details of an object and its review state selector
+///
+/// Returns true if the review state was changed.
+fn provenance_section(
+    ui: &mut egui::Ui,
+    i18n: &crate::i18n::I18n,
+    obj: &mut lab_core::Object,
+    annotator: &str,
+    can_edit: bool,
+) -> bool {
+    let provenance = obj.provenance.clone().unwrap_or_default();
+    let stamp = |user: &Option<String>, at: Option<String>| match (user, at) {
+        (Some(user), Some(at)) => format!("{} ({})", user, at),
+        (Some(user), None) => user.clone(),
+        (None, Some(at)) => at,
+        (None, None) => "-".to_string(),
+    };
+    const TIME_FORMAT: &str = "%Y-%m-%d %H:%M UTC";
+
+    egui::Grid::new("provenance_grid")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label(format!("{}:", i18n.t("sidebar.source")));
+            ui.label(i18n.t(&format!("source.{}", provenance.source.as_str())));
+            ui.end_row();
+            ui.label(format!("{}:", i18n.t("sidebar.created_by")));
+            ui.label(stamp(
+                &provenance.created_by,
+                provenance
+                    .created_at
+                    .map(|at| at.format(TIME_FORMAT).to_string()),
+            ));
+            ui.end_row();
+            ui.label(format!("{}:", i18n.t("sidebar.modified_by")));
+            ui.label(stamp(
+                &provenance.modified_by,
+                provenance
+                    .modified_at
+                    .map(|at| at.format(TIME_FORMAT).to_string()),
+            ));
+            ui.end_row();
+            if provenance.review != ReviewState::Unreviewed {
+                ui.label(format!("{}:", i18n.t("sidebar.reviewed_by")));
+                ui.label(stamp(
+                    &provenance.reviewed_by,
+                    provenance
+                        .reviewed_at
+                        .map(|at| at.format(TIME_FORMAT).to_string()),
+                ));
+                ui.end_row();
+            }
+        });
+
+    let mut changed = false;
+    ui.add_enabled_ui(can_edit, |ui| {
+        ui.horizontal(|ui| {
+            ui.label(format!("{}:", i18n.t("sidebar.review")));
+            for review in ReviewState::all() {
+                let label = i18n.t(&format!("review.{}", review.as_str()));
+                if ui
+                    .selectable_label(provenance.review == review, label)
+                    .clicked()
+                    && provenance.review != review
+                {
+                    obj.provenance
+                        .get_or_insert_with(Default::default)
+                        .reviewed(review, annotator);
+                    changed = true;
                 }
-            });
+            }
+        });
+    });
+    changed
+}
+
+/// How a property editor changed the stored value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PropertyEdit {
+    Unchanged,
+    /// A value was picked, recorded as its own undo step
+    Selected,
+    /// Text or a number is being edited, merged into one undo step
+    Typed,
+}
+
+/// Editor for one property: a value list, text field, number field or
+/// yes/no choice depending on the property kind, followed by the special values
+fn property_value_selector(
+    ui: &mut egui::Ui,
+    i18n: &crate::i18n::I18n,
+    meta: &lab_core::Meta,
+    prop_type: &lab_core::PropertyType,
+    properties: &mut HashMap<String, Vec<PropertyValueWithConfidence>>,
+    prop_def: &CategoryProperty,
+) -> PropertyEdit {
+    let property_id = prop_def.id;
+    let key = property_id.to_string();
+    let current = properties.get(&key).and_then(|values| values.first());
+    let is_set = current.is_some();
+    let selected_values: Vec<i32> = properties
+        .get(&key)
+        .into_iter()
+        .flatten()
+        .filter(|v| v.data.is_none())
+        .map(|v| v.value)
+        .collect();
+    let current_data = current.and_then(|v| v.data.clone());
+    let mut new_value = None;
+    let mut toggled_value = None;
+    let mut edit = PropertyEdit::Selected;
+
+    if ui
+        .selectable_label(!is_set, i18n.t("sidebar.not_set"))
+        .clicked()
+    {
+        properties.remove(&key);
+        return PropertyEdit::Selected;
+    }
+
+    let (min, max) = (
+        prop_type.min.unwrap_or(f64::MIN),
+        prop_type.max.unwrap_or(f64::MAX),
+    );
+    match prop_type.kind {
+        PropertyKind::Enum => {
+            for value in &prop_type.values {
+                if ui
+                    .selectable_label(selected_values.contains(&value.id), &value.name)
+                    .clicked()
+                {
+                    // Multi-label values toggle unless a special value is set
+                    let special_set = selected_values
+                        .iter()
+                        .any(|id| meta.find_special_value(*id).is_some());
+                    if prop_def.multiple && !special_set {
+                        toggled_value = Some(value.id);
+                    } else {
+                        new_value = Some(PropertyValueWithConfidence::new(value.id, 1.0));
+                    }
+                }
+            }
         }
+        PropertyKind::String => {
+            let mut text = match &current_data {
+                Some(PropertyData::Text(text)) => text.clone(),
+                _ => String::new(),
+            };
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut text)
+                    .id_salt(("property_text", property_id))
+                    .hint_text(prop_type.pattern.clone().unwrap_or_default()),
+            );
+            if response.changed() {
+                if text.is_empty() {
+                    properties.remove(&key);
+                    return PropertyEdit::Typed;
+                }
+                new_value = Some(PropertyValueWithConfidence::with_data(
+                    PropertyData::Text(text),
+                    1.0,
+                ));
+                edit = PropertyEdit::Typed;
+            }
+        }
+        PropertyKind::Integer => {
+            let mut number = match current_data {
+                Some(PropertyData::Int(value)) => value,
+                _ => 0.0_f64.max(min).min(max) as i64,
+            };
+            if ui
+                .add(egui::DragValue::new(&mut number).range(min as i64..=max as i64))
+                .changed()
+            {
+                new_value = Some(PropertyValueWithConfidence::with_data(
+                    PropertyData::Int(number),
+                    1.0,
+                ));
+                edit = PropertyEdit::Typed;
+            }
+        }
+        PropertyKind::Float => {
+            let mut number = current_data
+                .as_ref()
+                .and_then(PropertyData::as_f64)
+                .unwrap_or_else(|| 0.0_f64.max(min).min(max));
+            let speed = match (prop_type.min, prop_type.max) {
+                (Some(min), Some(max)) => (max - min) / 200.0,
+                _ => 0.1,
+            };
+            if ui
+                .add(
+                    egui::DragValue::new(&mut number)
+                        .range(min..=max)
+                        .speed(speed),
+                )
+                .changed()
+            {
+                new_value = Some(PropertyValueWithConfidence::with_data(
+                    PropertyData::Float(number),
+                    1.0,
+                ));
+                edit = PropertyEdit::Typed;
+            }
+        }
+        PropertyKind::Boolean => {
+            for (flag, label) in [(true, "sidebar.yes"), (false, "sidebar.no")] {
+                if ui
+                    .selectable_label(
+                        current_data == Some(PropertyData::Bool(flag)),
+                        i18n.t(label),
+                    )
+                    .clicked()
+                {
+                    new_value = Some(PropertyValueWithConfidence::with_data(
+                        PropertyData::Bool(flag),
+                        1.0,
+                    ));
+                }
+            }
+        }
+    }
+
+    if current_data
+        .as_ref()
+        .is_some_and(|data| prop_type.check_data(data).is_err())
+    {
+        ui.colored_label(egui::Color32::RED, i18n.t("sidebar.invalid_value"));
+    }
+
+    if !meta.property_special_values.is_empty() {
+        ui.separator();
+        ui.label(i18n.t("sidebar.special_values"));
+        for special in &meta.property_special_values {
+            if ui
+                .selectable_label(selected_values.contains(&special.id), &special.name)
+                .clicked()
+            {
+                new_value = Some(PropertyValueWithConfidence::new(special.id, 1.0));
+                edit = PropertyEdit::Selected;
+            }
+        }
+    }
+
+    if let Some(value) = toggled_value {
+        let values = properties.entry(key.clone()).or_default();
+        match values
+            .iter()
+            .position(|v| v.data.is_none() && v.value == value)
+        {
+            Some(index) => {
+                values.remove(index);
+            }
+            None => values.push(PropertyValueWithConfidence::new(value, 1.0)),
+        }
+        if values.is_empty() {
+            properties.remove(&key);
+        }
+        return PropertyEdit::Selected;
+    }
+    if let Some(value) = new_value {
+        properties.insert(key, vec![value]);
+        return edit;
+    }
+
+    // Confidence of each selected value
+    let Some(values) = properties.get_mut(&key) else {
+        return PropertyEdit::Unchanged;
+    };
+    let mut confidence_changed = false;
+    ui.label(i18n.t("sidebar.confidence"));
+    for value in values.iter_mut() {
+        let name = match &value.data {
+            Some(data) => data.to_string(),
+            None => meta
+                .property_value_name(prop_def, value.value)
+                .unwrap_or("?")
+                .to_string(),
+        };
+        ui.horizontal(|ui| {
+            ui.label(name);
+            confidence_changed |= ui
+                .add(
+                    egui::DragValue::new(&mut value.confidence)
+                        .range(0.0..=1.0)
+                        .speed(0.01)
+                        .fixed_decimals(2),
+                )
+                .changed();
+        });
+    }
+    if confidence_changed {
+        PropertyEdit::Typed
+    } else {
+        PropertyEdit::Unchanged
     }
 }
@@ -0,0 +1,91 @@
+// Crash recovery dialog for JLab
+use egui::Context;
+use lab_utils::recovery::RecoveredEdit;
+use std::path::PathBuf;
+
+/// Button pressed in the dialog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryAction {
+    /// Apply the choices and open the project
+    Open,
+    /// Leave the journal alone and do not open the project
+    Cancel,
+}
+
+/// Recovery dialog state
+pub struct RecoveryDialogState {
+    /// Project waiting to be opened, None while the dialog is closed
+    pub project_root: Option<PathBuf>,
+    /// Unsaved edits found in the journal, with whether to restore them
+    pub edits: Vec<(RecoveredEdit, bool)>,
+}
+
+impl RecoveryDialogState {
+    pub fn new() -> Self {
+        Self {
+            project_root: None,
+            edits: Vec::new(),
+        }
+    }
+
+    /// Ask about the journaled edits of a project; all are restored unless changed
+    pub fn open(&mut self, project_root: PathBuf, edits: Vec<RecoveredEdit>) {
+        self.project_root = Some(project_root);
+        self.edits = edits.into_iter().map(|edit| (edit, true)).collect();
+    }
+
+    /// Render the dialog with one restore/discard choice per image
+    pub fn show(&mut self, ctx: &Context, i18n: &crate::i18n::I18n) -> Option<RecoveryAction> {
+        self.project_root.as_ref()?;
+
+        let mut open = true;
+        let mut action = None;
+
+        egui::Window::new(i18n.t("recovery.title"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(i18n.t("recovery.message"));
+                ui.add_space(8.0);
+
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("recovery_grid")
+                            .num_columns(3)
+                            .spacing([16.0, 4.0])
+                            .show(ui, |ui| {
+                                for (edit, restore) in &mut self.edits {
+                                    ui.label(&edit.image_name);
+                                    ui.label(
+                                        edit.journaled_at
+                                            .format("%Y-%m-%d %H:%M:%S UTC")
+                                            .to_string(),
+                                    );
+                                    ui.horizontal(|ui| {
+                                        ui.radio_value(restore, true, i18n.t("recovery.restore"));
+                                        ui.radio_value(restore, false, i18n.t("recovery.discard"));
+                                    });
+                                    ui.end_row();
+                                }
+                            });
+                    });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button(i18n.t("recovery.open")).clicked() {
+                        action = Some(RecoveryAction::Open);
+                    }
+                    if ui.button(i18n.t("recovery.cancel")).clicked() {
+                        action = Some(RecoveryAction::Cancel);
+                    }
+                });
+            });
+
+        if !open {
+            action = Some(RecoveryAction::Cancel);
+        }
+        action
+    }
+}
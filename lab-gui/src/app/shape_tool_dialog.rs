@@ -0,0 +1,134 @@
+// Simplify / densify dialog for JLab
+use egui::Context;
+use lab_core::Shape;
+
+/// Outline operation adjusted in the dialog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShapeTool {
+    Simplify,
+    Densify,
+}
+
+impl ShapeTool {
+    pub fn title_key(&self) -> &'static str {
+        match self {
+            Self::Simplify => "shape_tool.simplify_title",
+            Self::Densify => "shape_tool.densify_title",
+        }
+    }
+
+    /// Label recorded in the undo history
+    pub fn edit_label(&self) -> &'static str {
+        match self {
+            Self::Simplify => "Simplify",
+            Self::Densify => "Densify",
+        }
+    }
+}
+
+/// Button pressed in the dialog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShapeToolAction {
+    Apply,
+    Cancel,
+}
+
+/// Shape tool dialog state
+pub struct ShapeToolDialogState {
+    /// Tool and object being edited, None while the dialog is closed
+    pub target: Option<(ShapeTool, i32)>,
+    /// Simplification tolerance in image pixels
+    pub tolerance: f32,
+    /// Maximum edge length in image pixels when densifying
+    pub spacing: f32,
+}
+
+impl ShapeToolDialogState {
+    pub fn new() -> Self {
+        Self {
+            target: None,
+            tolerance: 1.0,
+            spacing: 10.0,
+        }
+    }
+
+    pub fn open(&mut self, tool: ShapeTool, obj_id: i32) {
+        self.target = Some((tool, obj_id));
+    }
+
+    pub fn close(&mut self) {
+        self.target = None;
+    }
+
+    /// The shape after applying the current tool, None if nothing changes
+    pub fn preview(&self, shape: &Shape, width: u32, height: u32) -> Option<Shape> {
+        let (tool, _) = self.target?;
+        let mut result = shape.clone();
+        let changed = match tool {
+            ShapeTool::Simplify => result.simplify(self.tolerance, width, height),
+            ShapeTool::Densify => result.densify(self.spacing, width, height),
+        };
+        changed.then_some(result)
+    }
+
+    /// Render the dialog with the vertex counts before and after
+    pub fn show(
+        &mut self,
+        ctx: &Context,
+        i18n: &crate::i18n::I18n,
+        vertices_before: usize,
+        vertices_after: usize,
+    ) -> Option<ShapeToolAction> {
+        let (tool, _) = self.target?;
+
+        let mut open = true;
+        let mut action = None;
+
+        egui::Window::new(i18n.t(tool.title_key()))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                match tool {
+                    ShapeTool::Simplify => {
+                        ui.add(
+                            egui::Slider::new(&mut self.tolerance, 0.1..=20.0)
+                                .logarithmic(true)
+                                .text(i18n.t("shape_tool.tolerance")),
+                        );
+                    }
+                    ShapeTool::Densify => {
+                        ui.add(
+                            egui::Slider::new(&mut self.spacing, 1.0..=100.0)
+                                .logarithmic(true)
+                                .text(i18n.t("shape_tool.spacing")),
+                        );
+                    }
+                }
+                ui.label(format!(
+                    "{}: {} → {}",
+                    i18n.t("shape_tool.vertices"),
+                    vertices_before,
+                    vertices_after
+                ));
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button(i18n.t("shape_tool.apply")).clicked() {
+                        action = Some(ShapeToolAction::Apply);
+                    }
+                    if ui
+                        .button(format!("{} (Esc)", i18n.t("shape_tool.cancel")))
+                        .clicked()
+                    {
+                        action = Some(ShapeToolAction::Cancel);
+                    }
+                });
+            });
+
+        if !open {
+            action = Some(ShapeToolAction::Cancel);
+        }
+        action
+    }
+}
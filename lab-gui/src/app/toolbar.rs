@@ -81,6 +81,115 @@ impl LabApp {
                     self.state.draw_target = crate::state::DrawTarget::Roi;
                 }
 
+                let target_keypoints_label = self.state.i18n.t("toolbar.draw_target_keypoints");
+                let target_keypoints_hint = self.state.i18n.t("hint.draw_target_keypoints");
+                let target_keypoints_response = ui.selectable_label(
+                    self.state.draw_target == crate::state::DrawTarget::Keypoints,
+                    target_keypoints_label.clone(),
+                );
+                Self::update_status_hint(
+                    status_hint,
+                    &target_keypoints_response,
+                    target_keypoints_hint,
+                );
+                if target_keypoints_response.clicked() {
+                    self.state.draw_target = crate::state::DrawTarget::Keypoints;
+                    self.state.clear_drawing_state();
+                }
+
+                let target_hole_label = self.state.i18n.t("toolbar.draw_target_hole");
+                let target_hole_hint = self.state.i18n.t("hint.draw_target_hole");
+                let target_hole_response = ui.selectable_label(
+                    self.state.draw_target == crate::state::DrawTarget::Hole,
+                    target_hole_label.clone(),
+                );
+                Self::update_status_hint(status_hint, &target_hole_response, target_hole_hint);
+                if target_hole_response.clicked() {
+                    self.state.draw_target = crate::state::DrawTarget::Hole;
+                }
+
+                let target_part_label = self.state.i18n.t("toolbar.draw_target_part");
+                let target_part_hint = self.state.i18n.t("hint.draw_target_part");
+                let target_part_response = ui.selectable_label(
+                    self.state.draw_target == crate::state::DrawTarget::Part,
+                    target_part_label.clone(),
+                );
+                Self::update_status_hint(status_hint, &target_part_response, target_part_hint);
+                if target_part_response.clicked() {
+                    self.state.draw_target = crate::state::DrawTarget::Part;
+                }
+
+                let target_mask_label = self.state.i18n.t("toolbar.draw_target_mask");
+                let target_mask_hint = self.state.i18n.t("hint.draw_target_mask");
+                let target_mask_response = ui.selectable_label(
+                    self.state.draw_target == crate::state::DrawTarget::Mask,
+                    target_mask_label.clone(),
+                );
+                Self::update_status_hint(status_hint, &target_mask_response, target_mask_hint);
+                if target_mask_response.clicked() {
+                    self.state.draw_target = crate::state::DrawTarget::Mask;
+                    self.state.clear_drawing_state();
+                }
+
+                ui.label(self.state.i18n.t("toolbar.draw_shape"));
+                let draw_shape_hint = self.state.i18n.t("hint.draw_shape");
+                let active_shape = self.state.active_draw_shape();
+                let shape_labels: Vec<_> = lab_core::ShapeKind::drawable()
+                    .into_iter()
+                    .map(|kind| (kind, self.state.i18n.t(&format!("shape.{}", kind.as_str()))))
+                    .collect();
+                let active_label = shape_labels
+                    .iter()
+                    .find(|(kind, _)| *kind == active_shape)
+                    .map(|(_, label)| label.clone())
+                    .unwrap_or_default();
+                let draw_target_is_object =
+                    self.state.draw_target == crate::state::DrawTarget::Object;
+                let draw_shape_response = ui
+                    .add_enabled_ui(draw_target_is_object, |ui| {
+                        egui::ComboBox::from_id_salt("toolbar_draw_shape")
+                            .selected_text(active_label)
+                            .show_ui(ui, |ui| {
+                                for (kind, label) in shape_labels {
+                                    ui.selectable_value(&mut self.state.draw_shape, kind, label);
+                                }
+                            })
+                            .response
+                    })
+                    .inner;
+                Self::update_status_hint(status_hint, &draw_shape_response, draw_shape_hint);
+                if self.state.active_draw_shape() != active_shape {
+                    self.state.clear_drawing_state();
+                }
+
+                if self.state.draw_target == crate::state::DrawTarget::Mask {
+                    let brush_label = self.state.i18n.t("toolbar.brush");
+                    let brush_hint = self.state.i18n.t("hint.brush");
+                    let brush_response =
+                        ui.selectable_label(!self.state.brush_erase, brush_label.clone());
+                    Self::update_status_hint(status_hint, &brush_response, brush_hint);
+                    if brush_response.clicked() {
+                        self.state.brush_erase = false;
+                    }
+
+                    let eraser_label = self.state.i18n.t("toolbar.eraser");
+                    let eraser_hint = self.state.i18n.t("hint.eraser");
+                    let eraser_response =
+                        ui.selectable_label(self.state.brush_erase, eraser_label.clone());
+                    Self::update_status_hint(status_hint, &eraser_response, eraser_hint);
+                    if eraser_response.clicked() {
+                        self.state.brush_erase = true;
+                    }
+
+                    let radius_hint = self.state.i18n.t("hint.brush_radius");
+                    let radius_response = ui.add(
+                        egui::Slider::new(&mut self.state.brush_radius, 1.0..=100.0)
+                            .logarithmic(true)
+                            .suffix(" px"),
+                    );
+                    Self::update_status_hint(status_hint, &radius_response, radius_hint);
+                }
+
                 ui.separator();
 
                 let zoom_label = self.state.i18n.t("toolbar.zoom");
@@ -1,5 +1,5 @@
 use egui::{Color32, ColorImage, Pos2, Rect, Sense, Stroke, TextureHandle, Ui, Vec2};
-use lab_core::{Annotation, Meta, Point};
+use lab_core::{Annotation, KeypointVisibility, Meta, Point, Shape, ShapeKind};
 use std::collections::HashMap;
 
 /// Pending click for double-click detection
@@ -25,13 +25,28 @@ pub struct Canvas {
     /// Currently dragging object (object_id, drag_start_pos)
     dragging_object: Option<(i32, Point)>,
 
+    /// Currently dragging keypoint (object_id, keypoint_index)
+    dragging_keypoint: Option<(i32, usize)>,
+
     /// Pending click for double-click detection in editing mode
     pending_click: Option<PendingClick>,
+
+    /// Brush radius in image pixels while the mask brush is active
+    pub brush_radius: Option<f32>,
+
+    /// Last brush position of the stroke in progress
+    brush_last: Option<Point>,
+
+    /// Pending result of a shape tool, drawn over the annotations
+    pub preview_shape: Option<Shape>,
 }
 
 /// Double-click detection timeout in seconds
 const DOUBLE_CLICK_TIMEOUT: f64 = 0.2;
 
+/// Hit distance for open shapes (polylines, points) in normalized coordinates
+const OPEN_SHAPE_HIT_THRESHOLD: f32 = 0.01;
+
 impl Canvas {
     pub fn new() -> Self {
         Self {
@@ -40,7 +55,11 @@ impl Canvas {
             texture_cache: HashMap::new(),
             dragging_vertex: None,
             dragging_object: None,
+            dragging_keypoint: None,
             pending_click: None,
+            brush_radius: None,
+            brush_last: None,
+            preview_shape: None,
         }
     }
 
@@ -80,6 +99,8 @@ impl Canvas {
         selected_object_id: Option<i32>,
         selected_vertex: Option<(i32, usize)>,
         temp_points: &[Point],
+        draw_shape: ShapeKind,
+        next_keypoint: Option<&str>,
         edit_mode: crate::state::EditMode,
         no_image_text: &str,
     ) -> CanvasResponse {
@@ -187,9 +208,24 @@ impl Canvas {
                 );
             }
 
+            if let Some(preview) = &self.preview_shape {
+                self.draw_preview_shape(&painter, preview, image_rect, image_size);
+            }
+
             // Draw temporary points (for drawing new shapes)
             if !temp_points.is_empty() {
-                self.draw_temp_points(&painter, temp_points, image_rect, image_size);
+                let hover_pos = canvas_response
+                    .hover_pos()
+                    .filter(|pos| image_rect.contains(*pos))
+                    .map(|pos| self.screen_to_normalized(pos, image_rect, image_size));
+                self.draw_temp_points(
+                    &painter,
+                    temp_points,
+                    draw_shape,
+                    hover_pos,
+                    image_rect,
+                    image_size,
+                );
             }
 
             // Draw crosshair in Drawing mode
@@ -197,8 +233,59 @@ impl Canvas {
                 if let Some(pointer_pos) = canvas_response.hover_pos() {
                     if image_rect.contains(pointer_pos) {
                         self.draw_crosshair(&painter, pointer_pos, image_rect);
+
+                        // Name of the keypoint the next click will place
+                        if let Some(name) = next_keypoint {
+                            painter.text(
+                                pointer_pos + Vec2::new(10.0, -10.0),
+                                egui::Align2::LEFT_BOTTOM,
+                                name,
+                                egui::FontId::proportional(13.0),
+                                Color32::YELLOW,
+                            );
+                        }
+                    }
+                }
+            }
+
+            // Paint with the mask brush; Ctrl+drag still pans
+            if let Some(radius) = self.brush_radius {
+                if let Some(pointer_pos) = canvas_response.hover_pos() {
+                    let scale = image_rect.width() / image_size.x;
+                    painter.circle_stroke(
+                        pointer_pos,
+                        radius * scale,
+                        Stroke::new(1.5, Color32::WHITE),
+                    );
+                }
+
+                if !ui.input(|i| i.modifiers.ctrl) {
+                    if let Some(pointer_pos) = canvas_response.interact_pointer_pos() {
+                        if canvas_response.dragged_by(egui::PointerButton::Primary)
+                            || canvas_response.clicked()
+                        {
+                            let pos =
+                                self.screen_to_normalized(pointer_pos, image_rect, image_size);
+                            // Start the stroke where the button went down
+                            let from = self.brush_last.unwrap_or_else(|| {
+                                ui.input(|i| i.pointer.press_origin())
+                                    .map(|origin| {
+                                        self.screen_to_normalized(origin, image_rect, image_size)
+                                    })
+                                    .unwrap_or(pos)
+                            });
+                            response.brush_stroke = Some((from, pos));
+                            self.brush_last = Some(pos);
+                        }
                     }
                 }
+
+                if self.brush_last.is_some()
+                    && (canvas_response.drag_stopped() || canvas_response.clicked())
+                {
+                    response.brush_finished = true;
+                    self.brush_last = None;
+                }
             }
 
             // Handle vertex dragging
@@ -207,11 +294,20 @@ impl Canvas {
                     if canvas_response.drag_started()
                         && self.dragging_vertex.is_none()
                         && self.dragging_object.is_none()
+                        && self.dragging_keypoint.is_none()
                     {
                         match edit_mode {
                             crate::state::EditMode::Editing => {
-                                // Check edge first (higher priority than vertex)
-                                if let Some((obj_id, edge_idx)) = self.find_edge_at_pos(
+                                // Keypoints sit on top of the outline
+                                if let Some(keypoint) = self.find_keypoint_at_pos(
+                                    pointer_pos,
+                                    annotation,
+                                    selected_object_id,
+                                    image_rect,
+                                    image_size,
+                                ) {
+                                    self.dragging_keypoint = Some(keypoint);
+                                } else if let Some((obj_id, edge_idx)) = self.find_edge_at_pos(
                                     pointer_pos,
                                     annotation,
                                     selected_object_id,
@@ -272,6 +368,15 @@ impl Canvas {
                         }
                     }
 
+                    // Continue dragging keypoint
+                    if edit_mode == crate::state::EditMode::Editing && canvas_response.dragged() {
+                        if let Some((obj_id, keypoint_idx)) = self.dragging_keypoint {
+                            let new_pos =
+                                self.screen_to_normalized(pointer_pos, image_rect, image_size);
+                            response.keypoint_dragged = Some((obj_id, keypoint_idx, new_pos));
+                        }
+                    }
+
                     // Continue dragging object
                     if edit_mode == crate::state::EditMode::Editing
                         && canvas_response.dragged()
@@ -292,8 +397,12 @@ impl Canvas {
 
                     // Stop dragging
                     if canvas_response.drag_stopped() {
+                        response.drag_finished = self.dragging_vertex.is_some()
+                            || self.dragging_object.is_some()
+                            || self.dragging_keypoint.is_some();
                         self.dragging_vertex = None;
                         self.dragging_object = None;
+                        self.dragging_keypoint = None;
                     }
                 }
             }
@@ -301,8 +410,9 @@ impl Canvas {
             // Get current time for double-click detection
             let current_time = ui.input(|i| i.time);
 
-            // Handle double click first (before single click to avoid triggering both)
-            let is_double_click = canvas_response.double_clicked();
+            // Handle double click first (before single click to avoid triggering both);
+            // the brush treats every click as a stroke
+            let is_double_click = canvas_response.double_clicked() && self.brush_radius.is_none();
 
             if is_double_click {
                 // Clear pending click since this is a double-click
@@ -335,11 +445,14 @@ impl Canvas {
                 }
             }
 
-            // Handle mouse clicks for annotation (only if not dragging and not double-click)
+            // Handle mouse clicks for annotation (only if not dragging, not double-click
+            // and not painting with the brush)
             if canvas_response.clicked()
                 && !is_double_click
+                && self.brush_radius.is_none()
                 && self.dragging_vertex.is_none()
                 && self.dragging_object.is_none()
+                && self.dragging_keypoint.is_none()
                 && image_rect.contains(canvas_response.interact_pointer_pos().unwrap_or_default())
             {
                 if let Some(pos) = canvas_response.interact_pointer_pos() {
@@ -413,8 +526,18 @@ impl Canvas {
                     let mut handled = false;
                     if edit_mode == crate::state::EditMode::Editing {
                         if let Some(annotation) = annotation {
-                            // Check if right-clicking on vertex to delete
-                            if let Some((obj_id, vertex_idx)) = self.find_vertex_at_pos(
+                            // Check if right-clicking on a keypoint to toggle its visibility,
+                            // then on a vertex to delete it
+                            if let Some(keypoint) = self.find_keypoint_at_pos(
+                                pos,
+                                annotation,
+                                selected_object_id,
+                                image_rect,
+                                image_size,
+                            ) {
+                                response.keypoint_toggled = Some(keypoint);
+                                handled = true;
+                            } else if let Some((obj_id, vertex_idx)) = self.find_vertex_at_pos(
                                 pos,
                                 annotation,
                                 selected_object_id,
@@ -468,7 +591,7 @@ impl Canvas {
     ) {
         if !annotation.rois.is_empty() {
             let roi_color = meta
-                .and_then(|m| parse_color(&m.roi.color))
+                .map(|m| to_color32(m.roi.color))
                 .unwrap_or(Color32::from_rgb(128, 0, 128));
 
             for (idx, roi_points) in annotation.rois.iter().enumerate() {
@@ -497,8 +620,10 @@ impl Canvas {
                     None
                 };
 
-                self.draw_polygon(
+                self.draw_shape(
                     painter,
+                    std::slice::from_ref(roi_points),
+                    true,
                     roi_points,
                     image_rect,
                     image_size,
@@ -542,49 +667,130 @@ impl Canvas {
             // Get category color
             let color = meta
                 .and_then(|m| m.find_category(obj.category))
-                .and_then(|c| parse_color(&c.color))
+                .map(|c| to_color32(c.color))
                 .unwrap_or(Color32::RED);
 
-            self.draw_polygon(
-                painter,
-                &obj.polygon,
-                image_rect,
-                image_size,
-                color,
-                is_selected,
-                draw_vertices,
-                hovered_vertex_idx,
-                selected_vertex_idx,
-                obj.id,
-            );
+            let vertices = obj.shape.vertices();
+            if let Shape::Mask(mask) = &obj.shape {
+                self.draw_mask(painter, mask, image_rect, image_size, color, is_selected);
+            } else {
+                self.draw_shape(
+                    painter,
+                    &obj.shape.rings(),
+                    obj.shape.is_closed(),
+                    &vertices,
+                    image_rect,
+                    image_size,
+                    color,
+                    is_selected,
+                    draw_vertices,
+                    hovered_vertex_idx,
+                    selected_vertex_idx,
+                    obj.id,
+                );
+            }
 
-            // Draw label
-            if !obj.polygon.is_empty() {
+            // Draw label at the first vertex, or the corner of a mask
+            let label_anchor = vertices
+                .first()
+                .copied()
+                .or_else(|| obj.shape.bounding_box().map(|(min, _)| min));
+            if let Some(first_point) = label_anchor.as_ref() {
                 let category_name = meta
                     .and_then(|m| m.find_category(obj.category))
                     .map(|c| c.name.as_str())
                     .unwrap_or("Unknown");
 
-                let first_point = &obj.polygon[0];
                 let screen_pos = self.normalized_to_screen(first_point, image_rect, image_size);
 
                 let label_text = format!("#{} {}", obj.id, category_name);
-                painter.text(
+                let label_rect = painter.text(
                     screen_pos + Vec2::new(5.0, -5.0),
                     egui::Align2::LEFT_BOTTOM,
                     label_text,
                     egui::FontId::proportional(12.0),
                     Color32::WHITE,
                 );
+
+                // Property value signs follow the label in their own colors
+                if let Some((meta, category)) =
+                    meta.and_then(|m| m.find_category(obj.category).map(|c| (m, c)))
+                {
+                    let mut x = label_rect.right() + 4.0;
+                    for (sign, sign_color) in property_signs(meta, category, obj) {
+                        let rect = painter.text(
+                            Pos2::new(x, label_rect.bottom()),
+                            egui::Align2::LEFT_BOTTOM,
+                            sign,
+                            egui::FontId::proportional(12.0),
+                            to_color32(sign_color),
+                        );
+                        x = rect.right() + 3.0;
+                    }
+                }
+            }
+
+            if let Some(category) = meta.and_then(|m| m.find_category(obj.category)) {
+                self.draw_keypoints(painter, obj, category, is_selected, image_rect, image_size);
             }
         }
     }
 
-    /// Draw a polygon
-    fn draw_polygon(
+    /// Draw the keypoints of an object and its skeleton
+    fn draw_keypoints(
         &self,
         painter: &egui::Painter,
-        points: &[Point],
+        obj: &lab_core::Object,
+        category: &lab_core::Category,
+        is_selected: bool,
+        image_rect: Rect,
+        image_size: Vec2,
+    ) {
+        let color = to_color32(category.color);
+        let screen_pos = |index: usize| {
+            obj.keypoints
+                .get(index)
+                .filter(|kp| kp.visibility.is_labeled())
+                .map(|kp| self.normalized_to_screen(&kp.position(), image_rect, image_size))
+        };
+
+        for [a, b] in &category.skeleton {
+            if let (Some(start), Some(end)) = (screen_pos(*a), screen_pos(*b)) {
+                painter.line_segment([start, end], Stroke::new(1.5, color));
+            }
+        }
+
+        for (index, keypoint) in obj.keypoints.iter().enumerate() {
+            let Some(pos) = screen_pos(index) else {
+                continue;
+            };
+            // Occluded keypoints are drawn hollow
+            if keypoint.visibility == KeypointVisibility::Visible {
+                painter.circle_filled(pos, 4.0, color);
+            }
+            painter.circle_stroke(pos, 4.0, Stroke::new(1.5, Color32::WHITE));
+
+            if is_selected {
+                if let Some(name) = category.keypoints.get(index) {
+                    painter.text(
+                        pos + Vec2::new(6.0, 0.0),
+                        egui::Align2::LEFT_CENTER,
+                        name,
+                        egui::FontId::proportional(11.0),
+                        Color32::WHITE,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Draw a shape outline, including holes and extra parts, and its vertex handles
+    fn draw_shape(
+        &self,
+        painter: &egui::Painter,
+        rings: &[Vec<Point>],
+        closed: bool,
+        vertices: &[Point],
         image_rect: Rect,
         image_size: Vec2,
         color: Color32,
@@ -594,29 +800,41 @@ impl Canvas {
         selected_vertex_idx: Option<usize>,
         _obj_id: i32,
     ) {
-        if points.is_empty() {
-            return;
-        }
-
-        // Convert normalized coordinates to screen coordinates
-        let screen_points: Vec<Pos2> = points
-            .iter()
-            .map(|p| self.normalized_to_screen(p, image_rect, image_size))
-            .collect();
-
-        // Draw polygon edges
+        // Draw shape edges
         let stroke_width = if is_selected { 3.0 } else { 2.0 };
         let stroke = Stroke::new(stroke_width, color);
 
-        for i in 0..screen_points.len() {
-            let start = screen_points[i];
-            let end = screen_points[(i + 1) % screen_points.len()];
-            painter.line_segment([start, end], stroke);
+        for ring in rings {
+            // Convert normalized coordinates to screen coordinates
+            let screen_points: Vec<Pos2> = ring
+                .iter()
+                .map(|p| self.normalized_to_screen(p, image_rect, image_size))
+                .collect();
+
+            if screen_points.len() == 1 {
+                // Point shapes are drawn as a marker
+                painter.circle_stroke(screen_points[0], 5.0, stroke);
+                painter.circle_filled(screen_points[0], 2.0, color);
+            }
+
+            let edge_count = if closed {
+                screen_points.len()
+            } else {
+                screen_points.len().saturating_sub(1)
+            };
+            for i in 0..edge_count {
+                let start = screen_points[i];
+                let end = screen_points[(i + 1) % screen_points.len()];
+                painter.line_segment([start, end], stroke);
+            }
         }
 
         // Draw vertices
         if draw_vertices {
-            for (i, point) in screen_points.iter().enumerate() {
+            let screen_vertices = vertices
+                .iter()
+                .map(|p| self.normalized_to_screen(p, image_rect, image_size));
+            for (i, point) in screen_vertices.enumerate() {
                 let is_hovered = hovered_vertex_idx == Some(i);
                 let is_selected_vertex = selected_vertex_idx == Some(i);
 
@@ -636,18 +854,48 @@ impl Canvas {
                 };
 
                 if is_filled {
-                    painter.circle_filled(*point, radius, vertex_color);
+                    painter.circle_filled(point, radius, vertex_color);
                 }
-                painter.circle_stroke(*point, radius, Stroke::new(2.0, Color32::WHITE));
+                painter.circle_stroke(point, radius, Stroke::new(2.0, Color32::WHITE));
             }
         }
     }
 
+    /// Draw a mask as translucent pixel strips
+    fn draw_mask(
+        &self,
+        painter: &egui::Painter,
+        mask: &lab_core::Mask,
+        image_rect: Rect,
+        image_size: Vec2,
+        color: Color32,
+        is_selected: bool,
+    ) {
+        let (width, height) = (mask.width(), mask.height());
+        if width == 0 || height == 0 {
+            return;
+        }
+        let alpha = if is_selected { 140 } else { 90 };
+        let fill = Color32::from_rgba_unmultiplied(color.r(), color.g(), color.b(), alpha);
+
+        for (x, y0, y1) in mask.foreground_runs() {
+            let min = Point::from_pixel(x as f32, y0 as f32, width, height);
+            let max = Point::from_pixel((x + 1) as f32, y1 as f32, width, height);
+            let strip = Rect::from_min_max(
+                self.normalized_to_screen(&min, image_rect, image_size),
+                self.normalized_to_screen(&max, image_rect, image_size),
+            );
+            painter.rect_filled(strip, 0.0, fill);
+        }
+    }
+
     /// Draw temporary points (while drawing)
     fn draw_temp_points(
         &self,
         painter: &egui::Painter,
         points: &[Point],
+        kind: ShapeKind,
+        hover_pos: Option<Point>,
         image_rect: Rect,
         image_size: Vec2,
     ) {
@@ -656,6 +904,29 @@ impl Canvas {
         }
 
         let color = Color32::YELLOW;
+
+        // Preview fixed-size shapes with the cursor as the next click
+        if kind.required_points().is_some() {
+            if let Some(hover_pos) = hover_pos {
+                let mut preview_points = points.to_vec();
+                preview_points.push(hover_pos);
+                let aspect = image_size.x / image_size.y.max(1.0);
+                if let Some(preview) = Shape::from_points(kind, &preview_points, aspect) {
+                    let outline: Vec<Pos2> = preview
+                        .outline()
+                        .iter()
+                        .map(|p| self.normalized_to_screen(p, image_rect, image_size))
+                        .collect();
+                    for i in 0..outline.len() {
+                        painter.line_segment(
+                            [outline[i], outline[(i + 1) % outline.len()]],
+                            Stroke::new(1.0, color.linear_multiply(0.5)),
+                        );
+                    }
+                }
+            }
+        }
+
         let screen_points: Vec<Pos2> = points
             .iter()
             .map(|p| self.normalized_to_screen(p, image_rect, image_size))
@@ -676,7 +947,7 @@ impl Canvas {
         }
 
         // Draw line from last point to first (for preview)
-        if screen_points.len() > 2 {
+        if kind == ShapeKind::Polygon && screen_points.len() > 2 {
             painter.line_segment(
                 [*screen_points.last().unwrap(), screen_points[0]],
                 Stroke::new(1.0, color.linear_multiply(0.5)),
@@ -684,6 +955,35 @@ impl Canvas {
         }
     }
 
+    /// Draw the pending result of a shape tool as a dashed outline with its vertices
+    fn draw_preview_shape(
+        &self,
+        painter: &egui::Painter,
+        shape: &Shape,
+        image_rect: Rect,
+        image_size: Vec2,
+    ) {
+        let color = Color32::YELLOW;
+        for ring in shape.rings() {
+            let screen_points: Vec<Pos2> = ring
+                .iter()
+                .map(|p| self.normalized_to_screen(p, image_rect, image_size))
+                .collect();
+            let edge_count = if shape.is_closed() {
+                screen_points.len()
+            } else {
+                screen_points.len().saturating_sub(1)
+            };
+            for i in 0..edge_count {
+                let end = screen_points[(i + 1) % screen_points.len()];
+                self.draw_dashed_line(painter, screen_points[i], end, color);
+            }
+            for point in &screen_points {
+                painter.circle_filled(*point, 2.5, color);
+            }
+        }
+    }
+
     /// Convert normalized coordinates (0.0-1.0) to screen coordinates
     fn normalized_to_screen(&self, point: &Point, image_rect: Rect, _image_size: Vec2) -> Pos2 {
         let x = image_rect.left() + point.x * image_rect.width();
@@ -738,7 +1038,7 @@ impl Canvas {
                     }
                 }
             } else if let Some(obj) = annotation.objects.iter().find(|o| o.id == selected_id) {
-                for (i, point) in obj.polygon.iter().enumerate() {
+                for (i, point) in obj.shape.vertices().iter().enumerate() {
                     let screen_pos = self.normalized_to_screen(point, image_rect, image_size);
                     let distance = pos.distance(screen_pos);
                     if distance < threshold {
@@ -751,6 +1051,33 @@ impl Canvas {
         None
     }
 
+    /// Find a labeled keypoint of the selected object near mouse position
+    /// Returns (object_id, keypoint_index) if found
+    fn find_keypoint_at_pos(
+        &self,
+        pos: Pos2,
+        annotation: &Annotation,
+        selected_object_id: Option<i32>,
+        image_rect: Rect,
+        image_size: Vec2,
+    ) -> Option<(i32, usize)> {
+        let threshold = 10.0; // pixels
+
+        let selected_id = selected_object_id?;
+        let obj = annotation.objects.iter().find(|o| o.id == selected_id)?;
+        obj.keypoints
+            .iter()
+            .position(|kp| {
+                kp.visibility.is_labeled()
+                    && pos.distance(self.normalized_to_screen(
+                        &kp.position(),
+                        image_rect,
+                        image_size,
+                    )) < threshold
+            })
+            .map(|index| (obj.id, index))
+    }
+
     /// Find vertex near mouse position in any object
     /// Returns (object_id, vertex_index) if found
     /// Uses vertex_radius from meta configuration
@@ -766,7 +1093,7 @@ impl Canvas {
 
         // Check all objects (in reverse order for top-to-bottom priority)
         for obj in annotation.objects.iter().rev() {
-            for (i, point) in obj.polygon.iter().enumerate() {
+            for (i, point) in obj.shape.vertices().iter().enumerate() {
                 let screen_pos = self.normalized_to_screen(point, image_rect, image_size);
                 let distance = pos.distance(screen_pos);
                 if distance < threshold {
@@ -816,16 +1143,18 @@ impl Canvas {
                         }
 
                         let distance =
-                            crate::geometry::point_to_segment_distance(&normalized_pos, p1, p2);
+                            lab_core::geometry::point_to_segment_distance(&normalized_pos, p1, p2);
                         if distance < threshold {
                             return Some((selected_id, i));
                         }
                     }
                 }
             } else if let Some(obj) = annotation.objects.iter().find(|o| o.id == selected_id) {
-                for i in 0..obj.polygon.len() {
-                    let p1 = &obj.polygon[i];
-                    let p2 = &obj.polygon[(i + 1) % obj.polygon.len()];
+                // Only shapes with free vertices have edges that accept new ones
+                let points = obj.shape.vertices();
+                for (i, j) in obj.shape.edges() {
+                    let p1 = &points[i];
+                    let p2 = &points[j];
 
                     let t = Self::segment_parameter(p1, p2, &normalized_pos);
                     if !(0.1..=0.9).contains(&t) {
@@ -833,7 +1162,7 @@ impl Canvas {
                     }
 
                     let distance =
-                        crate::geometry::point_to_segment_distance(&normalized_pos, p1, p2);
+                        lab_core::geometry::point_to_segment_distance(&normalized_pos, p1, p2);
                     if distance < threshold {
                         return Some((obj.id, i));
                     }
@@ -857,13 +1186,13 @@ impl Canvas {
 
         // Check objects in reverse order (top to bottom)
         for obj in annotation.objects.iter().rev() {
-            if crate::geometry::point_in_polygon(&normalized_pos, &obj.polygon) {
+            if Self::shape_hit(&obj.shape, &normalized_pos) {
                 return Some(obj.id);
             }
         }
 
         for (idx, roi_points) in annotation.rois.iter().enumerate().rev() {
-            if crate::geometry::point_in_polygon(&normalized_pos, roi_points) {
+            if lab_core::geometry::point_in_polygon(&normalized_pos, roi_points) {
                 return Some(crate::state::roi_id_from_index(idx));
             }
         }
@@ -871,6 +1200,17 @@ impl Canvas {
         None
     }
 
+    /// Check if a normalized point hits a shape
+    ///
+    /// Closed shapes are hit inside their outline, open shapes near it.
+    fn shape_hit(shape: &Shape, point: &Point) -> bool {
+        if shape.is_closed() {
+            shape.contains(point)
+        } else {
+            shape.distance_to(point) < OPEN_SHAPE_HIT_THRESHOLD
+        }
+    }
+
     /// Find the smallest-area object at mouse position
     fn find_smallest_object_at_pos(
         &self,
@@ -884,8 +1224,8 @@ impl Canvas {
         let eps = 1e-6;
 
         for obj in annotation.objects.iter().rev() {
-            if crate::geometry::point_in_polygon(&normalized_pos, &obj.polygon) {
-                let area = Self::polygon_area(&obj.polygon);
+            if Self::shape_hit(&obj.shape, &normalized_pos) {
+                let area = obj.shape.area();
                 match best {
                     None => best = Some((obj.id, area)),
                     Some((_, best_area)) => {
@@ -898,7 +1238,7 @@ impl Canvas {
         }
 
         for (idx, roi_points) in annotation.rois.iter().enumerate().rev() {
-            if crate::geometry::point_in_polygon(&normalized_pos, roi_points) {
+            if lab_core::geometry::point_in_polygon(&normalized_pos, roi_points) {
                 let area = Self::polygon_area(roi_points);
                 match best {
                     None => best = Some((crate::state::roi_id_from_index(idx), area)),
@@ -926,7 +1266,10 @@ impl Canvas {
         ctrl_held: bool,
     ) -> egui::CursorIcon {
         // If dragging, show grabbing cursor
-        if self.dragging_vertex.is_some() || self.dragging_object.is_some() {
+        if self.dragging_vertex.is_some()
+            || self.dragging_object.is_some()
+            || self.dragging_keypoint.is_some()
+        {
             return egui::CursorIcon::Grabbing;
         }
 
@@ -950,10 +1293,19 @@ impl Canvas {
                     return egui::CursorIcon::Crosshair;
                 }
 
-                // Check for vertex
+                // Check for vertex or keypoint
                 if self
                     .find_vertex_at_pos(pos, annotation, selected_object_id, image_rect, image_size)
                     .is_some()
+                    || self
+                        .find_keypoint_at_pos(
+                            pos,
+                            annotation,
+                            selected_object_id,
+                            image_rect,
+                            image_size,
+                        )
+                        .is_some()
                 {
                     return egui::CursorIcon::PointingHand;
                 }
@@ -1054,6 +1406,9 @@ pub struct CanvasResponse {
     /// Object dragged (object_id, offset)
     pub object_dragged: Option<(i32, Point)>,
 
+    /// A vertex or object drag ended this frame
+    pub drag_finished: bool,
+
     /// Vertex added (object_id, edge_index, new_position)
     pub vertex_added: Option<(i32, usize, Point)>,
 
@@ -1075,6 +1430,18 @@ pub struct CanvasResponse {
     /// Object double-clicked (enter edit mode)
     pub object_double_clicked: Option<i32>,
 
+    /// Keypoint being dragged (object_id, keypoint_index, new_position)
+    pub keypoint_dragged: Option<(i32, usize, Point)>,
+
+    /// Keypoint right-clicked to toggle visibility (object_id, keypoint_index)
+    pub keypoint_toggled: Option<(i32, usize)>,
+
+    /// Brush stroke segment (from, to) in normalized coordinates
+    pub brush_stroke: Option<(Point, Point)>,
+
+    /// A brush stroke ended this frame
+    pub brush_finished: bool,
+
     /// Double-clicked in Drawing mode to finish drawing
     pub finish_drawing: bool,
 
@@ -1082,15 +1449,33 @@ pub struct CanvasResponse {
     pub finish_drawing_pos: Option<Point>,
 }
 
-/// Parse color string (hex format like "#FF0000")
-fn parse_color(color_str: &str) -> Option<Color32> {
-    if !color_str.starts_with('#') || color_str.len() != 7 {
-        return None;
-    }
-
-    let r = u8::from_str_radix(&color_str[1..3], 16).ok()?;
-    let g = u8::from_str_radix(&color_str[3..5], 16).ok()?;
-    let b = u8::from_str_radix(&color_str[5..7], 16).ok()?;
+fn to_color32(color: lab_core::Color) -> Color32 {
+    Color32::from_rgba_unmultiplied(color.r, color.g, color.b, color.a)
+}
 
-    Some(Color32::from_rgb(r, g, b))
+/// Signs and colors of the enumerated and special property values of an object,
+/// in the order the category declares its properties
+fn property_signs<'a>(
+    meta: &'a Meta,
+    category: &lab_core::Category,
+    obj: &lab_core::Object,
+) -> Vec<(&'a str, lab_core::Color)> {
+    let mut signs = Vec::new();
+    for prop in &category.properties {
+        let prop_type = meta.find_property_type_by_name(&prop.property_type);
+        for value in obj.property_values(prop.id) {
+            if value.data.is_some() {
+                continue;
+            }
+            let sign = prop_type
+                .and_then(|pt| pt.find_value(value.value))
+                .map(|v| (v.sign.as_str(), v.color))
+                .or_else(|| {
+                    meta.find_special_value(value.value)
+                        .map(|sv| (sv.sign.as_str(), sv.color))
+                });
+            signs.extend(sign.filter(|(sign, _)| !sign.is_empty()));
+        }
+    }
+    signs
 }
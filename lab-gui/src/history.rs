@@ -0,0 +1,255 @@
+use lab_core::Annotation;
+
+/// Maximum number of undo steps kept per image
+const MAX_UNDO_STEPS: usize = 100;
+
+/// A recorded edit: annotation state before and after the change
+struct EditCommand {
+    label: &'static str,
+    before: Annotation,
+    after: Annotation,
+    /// Revision id of the state produced by this edit
+    revision: u64,
+}
+
+/// Undo/redo stack for a single image
+pub struct EditHistory {
+    undo_stack: Vec<EditCommand>,
+    redo_stack: Vec<EditCommand>,
+    /// Annotation state after the last recorded edit
+    baseline: Annotation,
+    /// Last revision id handed out
+    last_revision: u64,
+    /// Revision id of the state below the oldest undo step
+    floor_revision: u64,
+    /// Revision id of the state on disk
+    saved_revision: u64,
+}
+
+impl EditHistory {
+    /// Create a history whose initial (saved) state is `annotation`
+    pub fn new(annotation: &Annotation) -> Self {
+        Self {
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            baseline: annotation.clone(),
+            last_revision: 0,
+            floor_revision: 0,
+            saved_revision: 0,
+        }
+    }
+
+    /// Annotation state the next edit will be compared against
+    pub fn baseline(&self) -> &Annotation {
+        &self.baseline
+    }
+
+    /// Record the change from the baseline to `current` as one undo step
+    ///
+    /// Returns false if nothing changed.
+    pub fn record(&mut self, label: &'static str, current: &Annotation) -> bool {
+        if *current == self.baseline {
+            return false;
+        }
+
+        self.last_revision += 1;
+        self.undo_stack.push(EditCommand {
+            label,
+            before: std::mem::replace(&mut self.baseline, current.clone()),
+            after: current.clone(),
+            revision: self.last_revision,
+        });
+        self.redo_stack.clear();
+
+        if self.undo_stack.len() > MAX_UNDO_STEPS {
+            let dropped = self.undo_stack.remove(0);
+            self.floor_revision = dropped.revision;
+        }
+        true
+    }
+
+    /// Record `current`, merging into the previous step if it has the same label
+    ///
+    /// Used for repeated small edits such as keyboard nudges.
+    pub fn record_merged(&mut self, label: &'static str, current: &Annotation) -> bool {
+        let mergeable = self.redo_stack.is_empty()
+            && self
+                .undo_stack
+                .last()
+                .is_some_and(|cmd| cmd.label == label && cmd.revision != self.saved_revision);
+
+        if !mergeable {
+            return self.record(label, current);
+        }
+        if *current == self.baseline {
+            return false;
+        }
+
+        self.last_revision += 1;
+        let revision = self.last_revision;
+        if let Some(cmd) = self.undo_stack.last_mut() {
+            cmd.after = current.clone();
+            cmd.revision = revision;
+        }
+        self.baseline = current.clone();
+        true
+    }
+
+    /// Step back one edit, returning the annotation to restore
+    pub fn undo(&mut self) -> Option<Annotation> {
+        let cmd = self.undo_stack.pop()?;
+        let restored = cmd.before.clone();
+        self.baseline = restored.clone();
+        self.redo_stack.push(cmd);
+        Some(restored)
+    }
+
+    /// Re-apply the last undone edit, returning the annotation to restore
+    pub fn redo(&mut self) -> Option<Annotation> {
+        let cmd = self.redo_stack.pop()?;
+        let restored = cmd.after.clone();
+        self.baseline = restored.clone();
+        self.undo_stack.push(cmd);
+        Some(restored)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Label of the edit that would be undone next
+    pub fn undo_label(&self) -> Option<&'static str> {
+        self.undo_stack.last().map(|cmd| cmd.label)
+    }
+
+    /// Label of the edit that would be redone next
+    pub fn redo_label(&self) -> Option<&'static str> {
+        self.redo_stack.last().map(|cmd| cmd.label)
+    }
+
+    /// Mark the current state as the one on disk
+    pub fn mark_saved(&mut self) {
+        self.saved_revision = self.current_revision();
+    }
+
+    /// Whether the current state matches the one on disk
+    pub fn is_saved(&self) -> bool {
+        self.current_revision() == self.saved_revision
+    }
+
+    fn current_revision(&self) -> u64 {
+        self.undo_stack
+            .last()
+            .map(|cmd| cmd.revision)
+            .unwrap_or(self.floor_revision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lab_core::{Object, Point};
+
+    fn annotation_with_objects(count: i32) -> Annotation {
+        let mut annotation = Annotation::new("test");
+        for id in 0..count {
+            annotation.add_object(Object::new(
+                id,
+                0,
+                vec![
+                    Point::new(0.1, 0.1),
+                    Point::new(0.2, 0.1),
+                    Point::new(0.2, 0.2),
+                ],
+            ));
+        }
+        annotation
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let initial = annotation_with_objects(0);
+        let mut history = EditHistory::new(&initial);
+
+        let mut current = initial.clone();
+        current.add_object(Object::new(0, 0, vec![Point::new(0.5, 0.5)]));
+        assert!(history.record("Create", &current));
+        assert!(!history.record("Create", &current));
+
+        let undone = history.undo().unwrap();
+        assert!(undone.objects.is_empty());
+        assert!(history.can_redo());
+
+        let redone = history.redo().unwrap();
+        assert_eq!(redone.objects.len(), 1);
+        assert!(history.undo().is_some());
+        assert!(history.undo().is_none());
+    }
+
+    #[test]
+    fn test_saved_state_tracking() {
+        let initial = annotation_with_objects(1);
+        let mut history = EditHistory::new(&initial);
+        assert!(history.is_saved());
+
+        let mut current = initial.clone();
+        current.objects[0].category = 1;
+        history.record("Change category", &current);
+        assert!(!history.is_saved());
+
+        history.mark_saved();
+        assert!(history.is_saved());
+
+        history.undo();
+        assert!(!history.is_saved());
+        history.redo();
+        assert!(history.is_saved());
+
+        // A new edit after undo drops the saved state from the stack
+        history.undo();
+        current.objects[0].category = 2;
+        history.record("Change category", &current);
+        assert!(!history.is_saved());
+        history.undo();
+        assert!(!history.is_saved());
+    }
+
+    #[test]
+    fn test_record_merged() {
+        let initial = annotation_with_objects(1);
+        let mut history = EditHistory::new(&initial);
+
+        let mut current = initial.clone();
+        for _ in 0..3 {
+            current.objects[0].shape.map_points(|p| p.x += 0.01);
+            history.record_merged("Move", &current);
+        }
+
+        let undone = history.undo().unwrap();
+        assert_eq!(undone, initial);
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn test_max_undo_steps() {
+        let initial = annotation_with_objects(1);
+        let mut history = EditHistory::new(&initial);
+
+        let mut current = initial.clone();
+        for i in 0..(MAX_UNDO_STEPS + 10) {
+            current.objects[0].category = i as i32 + 1;
+            history.record("Change category", &current);
+        }
+
+        let mut steps = 0;
+        while history.undo().is_some() {
+            steps += 1;
+        }
+        assert_eq!(steps, MAX_UNDO_STEPS);
+        assert!(!history.is_saved());
+    }
+}
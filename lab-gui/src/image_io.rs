@@ -0,0 +1,48 @@
+use image::{DynamicImage, ImageDecoder, ImageReader, ImageResult};
+use lab_core::Orientation;
+use std::io::Cursor;
+use std::path::Path;
+
+/// Decode an image file and turn it upright according to its EXIF orientation
+pub fn decode_upright(content: &[u8]) -> ImageResult<(DynamicImage, Orientation)> {
+    let mut decoder = ImageReader::new(Cursor::new(content))
+        .with_guessed_format()?
+        .into_decoder()?;
+    let orientation = read_orientation(&mut decoder);
+    let mut img = DynamicImage::from_decoder(decoder)?;
+    img.apply_orientation(to_image_orientation(orientation));
+    Ok((img, orientation))
+}
+
+/// Upright size and EXIF orientation of an image file, without decoding the pixels
+pub fn read_header(path: &Path) -> ImageResult<(u32, u32, Orientation)> {
+    let mut decoder = ImageReader::open(path)?
+        .with_guessed_format()?
+        .into_decoder()?;
+    let orientation = read_orientation(&mut decoder);
+    let (width, height) = decoder.dimensions();
+    let (width, height) = orientation.apply_size(width, height);
+    Ok((width, height, orientation))
+}
+
+/// Write an image file turned upright, keeping the format of its extension
+pub fn save_upright(source: &Path, target: &Path) -> anyhow::Result<()> {
+    let content = std::fs::read(source)?;
+    let (img, _) = decode_upright(&content)?;
+    img.save(target)?;
+    Ok(())
+}
+
+/// Missing or unreadable orientation tags count as upright
+fn read_orientation(decoder: &mut impl ImageDecoder) -> Orientation {
+    decoder
+        .orientation()
+        .ok()
+        .and_then(|orientation| Orientation::from_exif(orientation.to_exif()))
+        .unwrap_or_default()
+}
+
+fn to_image_orientation(orientation: Orientation) -> image::metadata::Orientation {
+    image::metadata::Orientation::from_exif(orientation.to_exif())
+        .unwrap_or(image::metadata::Orientation::NoTransforms)
+}
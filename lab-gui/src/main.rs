@@ -1,7 +1,8 @@
 mod app;
 mod canvas;
-mod geometry;
+mod history;
 mod i18n;
+mod image_io;
 mod shortcuts;
 mod state;
 mod tools;
@@ -51,9 +52,7 @@ fn main() -> Result<(), eframe::Error> {
             // Auto-open project if specified
             if let Some(project_dir) = args.project_dir {
                 log::info!("Auto-opening project: {:?}", project_dir);
-                if let Err(e) = app.load_project_from_path(project_dir) {
-                    log::error!("Failed to auto-open project: {}", e);
-                }
+                app.open_project(project_dir);
             }
 
             Ok(Box::new(app))
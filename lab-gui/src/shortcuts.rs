@@ -91,6 +91,8 @@ pub enum ShortcutAction {
     Quit,
 
     // Edit operations
+    Undo,
+    Redo,
     Copy,
     Paste,
     Delete,
@@ -98,6 +100,9 @@ pub enum ShortcutAction {
     FinishDrawing,
     ConvertToRectangle,
     FixSelfIntersection,
+    SimplifyShape,
+    SmoothShape,
+    DensifyShape,
 
     // Mode switching
     SwitchToNormalMode,
@@ -154,6 +159,8 @@ impl ShortcutAction {
             Self::Save,
             Self::CloseProject,
             Self::Quit,
+            Self::Undo,
+            Self::Redo,
             Self::Copy,
             Self::Paste,
             Self::Delete,
@@ -161,6 +168,9 @@ impl ShortcutAction {
             Self::FinishDrawing,
             Self::ConvertToRectangle,
             Self::FixSelfIntersection,
+            Self::SimplifyShape,
+            Self::SmoothShape,
+            Self::DensifyShape,
             Self::SwitchToNormalMode,
             Self::SwitchToDrawingMode,
             Self::SwitchToEditingMode,
@@ -202,6 +212,8 @@ impl ShortcutAction {
             Self::Save => Some(S),
             Self::CloseProject => None,
             Self::Quit => Some(Q),
+            Self::Undo => Some(Z),
+            Self::Redo => Some(Z),
             Self::Copy => Some(C),
             Self::Paste => Some(V),
             Self::Delete => Some(Delete),
@@ -209,6 +221,9 @@ impl ShortcutAction {
             Self::FinishDrawing => Some(Space),
             Self::ConvertToRectangle => Some(R),
             Self::FixSelfIntersection => Some(B),
+            Self::SimplifyShape => Some(G),
+            Self::SmoothShape => Some(H),
+            Self::DensifyShape => Some(J),
             Self::SwitchToNormalMode => Some(Num1),
             Self::SwitchToDrawingMode => Some(Num2),
             Self::SwitchToEditingMode => Some(Num3),
@@ -250,11 +265,16 @@ impl ShortcutAction {
                 shift: false,
                 alt: false,
             },
-            Self::Copy | Self::Paste => KeyModifiers {
+            Self::Copy | Self::Paste | Self::Undo => KeyModifiers {
                 ctrl: true,
                 shift: false,
                 alt: false,
             },
+            Self::Redo => KeyModifiers {
+                ctrl: true,
+                shift: true,
+                alt: false,
+            },
             Self::SwitchToNormalMode | Self::SwitchToDrawingMode | Self::SwitchToEditingMode => {
                 KeyModifiers {
                     ctrl: false,
@@ -297,7 +317,7 @@ impl ShortcutAction {
             Self::OpenProject | Self::Save | Self::CloseProject | Self::Quit => {
                 ShortcutScope::Global
             }
-            Self::Delete => ShortcutScope::Global,
+            Self::Delete | Self::Undo | Self::Redo => ShortcutScope::Global,
             Self::Copy | Self::Paste => ShortcutScope::Editing,
             Self::SwitchToNormalMode | Self::SwitchToDrawingMode | Self::SwitchToEditingMode => {
                 ShortcutScope::Global
@@ -309,7 +329,11 @@ impl ShortcutAction {
             Self::CycleNextObject | Self::CyclePreviousObject => ShortcutScope::Normal,
             Self::Deselect => ShortcutScope::Normal,
             Self::FinishDrawing => ShortcutScope::Drawing,
-            Self::ConvertToRectangle | Self::FixSelfIntersection => ShortcutScope::Editing,
+            Self::ConvertToRectangle
+            | Self::FixSelfIntersection
+            | Self::SimplifyShape
+            | Self::SmoothShape
+            | Self::DensifyShape => ShortcutScope::Editing,
             Self::MoveLeft | Self::MoveRight | Self::MoveUp | Self::MoveDown => {
                 ShortcutScope::Editing
             }
@@ -336,13 +360,18 @@ impl ShortcutAction {
             Self::OpenProject | Self::Save | Self::CloseProject | Self::Quit => {
                 ShortcutCategory::File
             }
-            Self::Copy
+            Self::Undo
+            | Self::Redo
+            | Self::Copy
             | Self::Paste
             | Self::Delete
             | Self::Deselect
             | Self::FinishDrawing
             | Self::ConvertToRectangle
-            | Self::FixSelfIntersection => ShortcutCategory::Edit,
+            | Self::FixSelfIntersection
+            | Self::SimplifyShape
+            | Self::SmoothShape
+            | Self::DensifyShape => ShortcutCategory::Edit,
             Self::SwitchToNormalMode | Self::SwitchToDrawingMode | Self::SwitchToEditingMode => {
                 ShortcutCategory::Mode
             }
@@ -382,6 +411,8 @@ impl ShortcutAction {
             Self::Save => "shortcut_actions.save",
             Self::CloseProject => "shortcut_actions.close_project",
             Self::Quit => "shortcut_actions.quit",
+            Self::Undo => "shortcut_actions.undo",
+            Self::Redo => "shortcut_actions.redo",
             Self::Copy => "shortcut_actions.copy",
             Self::Paste => "shortcut_actions.paste",
             Self::Delete => "shortcut_actions.delete",
@@ -389,6 +420,9 @@ impl ShortcutAction {
             Self::FinishDrawing => "shortcut_actions.finish_drawing",
             Self::ConvertToRectangle => "shortcut_actions.convert_to_rectangle",
             Self::FixSelfIntersection => "shortcut_actions.fix_self_intersection",
+            Self::SimplifyShape => "shortcut_actions.simplify_shape",
+            Self::SmoothShape => "shortcut_actions.smooth_shape",
+            Self::DensifyShape => "shortcut_actions.densify_shape",
             Self::SwitchToNormalMode => "shortcut_actions.switch_to_normal",
             Self::SwitchToDrawingMode => "shortcut_actions.switch_to_drawing",
             Self::SwitchToEditingMode => "shortcut_actions.switch_to_editing",
@@ -429,6 +463,8 @@ impl ShortcutAction {
             Self::Save => "Save",
             Self::CloseProject => "CloseProject",
             Self::Quit => "Quit",
+            Self::Undo => "Undo",
+            Self::Redo => "Redo",
             Self::Copy => "Copy",
             Self::Paste => "Paste",
             Self::Delete => "Delete",
@@ -436,6 +472,9 @@ impl ShortcutAction {
             Self::FinishDrawing => "FinishDrawing",
             Self::ConvertToRectangle => "ConvertToRectangle",
             Self::FixSelfIntersection => "FixSelfIntersection",
+            Self::SimplifyShape => "SimplifyShape",
+            Self::SmoothShape => "SmoothShape",
+            Self::DensifyShape => "DensifyShape",
             Self::SwitchToNormalMode => "SwitchToNormalMode",
             Self::SwitchToDrawingMode => "SwitchToDrawingMode",
             Self::SwitchToEditingMode => "SwitchToEditingMode",
@@ -675,6 +714,8 @@ impl ShortcutManager {
                 "Save" => ShortcutAction::Save,
                 "CloseProject" => ShortcutAction::CloseProject,
                 "Quit" => ShortcutAction::Quit,
+                "Undo" => ShortcutAction::Undo,
+                "Redo" => ShortcutAction::Redo,
                 "Copy" => ShortcutAction::Copy,
                 "Paste" => ShortcutAction::Paste,
                 "Delete" => ShortcutAction::Delete,
@@ -682,6 +723,9 @@ impl ShortcutManager {
                 "FinishDrawing" => ShortcutAction::FinishDrawing,
                 "ConvertToRectangle" => ShortcutAction::ConvertToRectangle,
                 "FixSelfIntersection" => ShortcutAction::FixSelfIntersection,
+                "SimplifyShape" => ShortcutAction::SimplifyShape,
+                "SmoothShape" => ShortcutAction::SmoothShape,
+                "DensifyShape" => ShortcutAction::DensifyShape,
                 "SwitchToNormalMode" => ShortcutAction::SwitchToNormalMode,
                 "SwitchToDrawingMode" => ShortcutAction::SwitchToDrawingMode,
                 "SwitchToEditingMode" => ShortcutAction::SwitchToEditingMode,
@@ -927,6 +971,8 @@ impl ShortcutManager {
             ShortcutAction::Save,
             ShortcutAction::CloseProject,
             ShortcutAction::Quit,
+            ShortcutAction::Undo,
+            ShortcutAction::Redo,
             ShortcutAction::Copy,
             ShortcutAction::Paste,
             ShortcutAction::Delete,
@@ -934,6 +980,9 @@ impl ShortcutManager {
             ShortcutAction::FinishDrawing,
             ShortcutAction::ConvertToRectangle,
             ShortcutAction::FixSelfIntersection,
+            ShortcutAction::SimplifyShape,
+            ShortcutAction::SmoothShape,
+            ShortcutAction::DensifyShape,
             ShortcutAction::SwitchToNormalMode,
             ShortcutAction::SwitchToDrawingMode,
             ShortcutAction::SwitchToEditingMode,
@@ -1001,6 +1050,8 @@ impl ShortcutManager {
                 "Save" => ShortcutAction::Save,
                 "CloseProject" => ShortcutAction::CloseProject,
                 "Quit" => ShortcutAction::Quit,
+                "Undo" => ShortcutAction::Undo,
+                "Redo" => ShortcutAction::Redo,
                 "Copy" => ShortcutAction::Copy,
                 "Paste" => ShortcutAction::Paste,
                 "Delete" => ShortcutAction::Delete,
@@ -1008,6 +1059,9 @@ impl ShortcutManager {
                 "FinishDrawing" => ShortcutAction::FinishDrawing,
                 "ConvertToRectangle" => ShortcutAction::ConvertToRectangle,
                 "FixSelfIntersection" => ShortcutAction::FixSelfIntersection,
+                "SimplifyShape" => ShortcutAction::SimplifyShape,
+                "SmoothShape" => ShortcutAction::SmoothShape,
+                "DensifyShape" => ShortcutAction::DensifyShape,
                 "SwitchToNormalMode" => ShortcutAction::SwitchToNormalMode,
                 "SwitchToDrawingMode" => ShortcutAction::SwitchToDrawingMode,
                 "SwitchToEditingMode" => ShortcutAction::SwitchToEditingMode,
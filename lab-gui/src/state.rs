@@ -1,9 +1,15 @@
+use crate::history::EditHistory;
 use crate::shortcuts::ShortcutManager;
-use lab_core::{Annotation, Meta, Object, Point};
+use lab_core::{Annotation, ImageInfo, Meta, Object, Point, ShapeKind};
 use lab_utils::Project;
 use serde::{Deserialize, Serialize};
 use serde_json;
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Shortest time between two writes of the recovery journal
+const JOURNAL_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Theme color preference
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -94,9 +100,27 @@ pub struct AppState {
     /// Has unsaved changes
     pub has_unsaved_changes: bool,
 
+    /// The recovery journal lags behind the current annotation
+    journal_pending: bool,
+
+    /// When the recovery journal was last written
+    journal_written: Instant,
+
+    /// Undo/redo history per image path
+    pub histories: HashMap<PathBuf, EditHistory>,
+
     /// Target type for new shapes
     pub draw_target: DrawTarget,
 
+    /// Shape kind for new objects
+    pub draw_shape: ShapeKind,
+
+    /// Mask brush radius in image pixels
+    pub brush_radius: f32,
+
+    /// Whether the mask brush erases instead of painting
+    pub brush_erase: bool,
+
     /// Default category for new objects
     pub default_category_id: Option<i32>,
 
@@ -157,6 +181,9 @@ pub struct AppState {
 
     /// Show scrollbar setting
     pub show_scrollbar: bool,
+
+    /// Name recorded as creator/editor in object provenance
+    pub annotator: String,
 }
 
 impl AppState {
@@ -192,7 +219,13 @@ impl AppState {
             current_annotation: None,
             current_image: None,
             has_unsaved_changes: false,
+            journal_pending: false,
+            journal_written: Instant::now(),
+            histories: HashMap::new(),
             draw_target: DrawTarget::Object,
+            draw_shape: ShapeKind::Polygon,
+            brush_radius: 10.0,
+            brush_erase: false,
             default_category_id: None,
             selected_object_id: None,
             temp_points: Vec::new(),
@@ -217,6 +250,7 @@ impl AppState {
             font_size,
             ui_scale,
             show_scrollbar,
+            annotator: annotator_name(),
         };
         // Load recent projects from file
         let _ = state.load_recent_projects();
@@ -226,6 +260,7 @@ impl AppState {
     /// Load a project
     pub fn load_project(&mut self, path: PathBuf) -> anyhow::Result<()> {
         let mut project = Project::open(&path)?;
+        self.drop_unsaved_edits();
         let images = project.list_images()?;
 
         // Apply global auto-save setting to project
@@ -270,6 +305,8 @@ impl AppState {
             }
         }
 
+        self.drop_unsaved_edits();
+
         // Clear all state
         self.project = None;
         self.images.clear();
@@ -277,6 +314,7 @@ impl AppState {
         self.current_annotation = None;
         self.current_image = None;
         self.has_unsaved_changes = false;
+        self.histories.clear();
         self.default_category_id = None;
         self.selected_object_id = None;
         self.clear_drawing_state();
@@ -294,13 +332,17 @@ impl AppState {
             return Ok(());
         }
 
-        let image_path = &self.images[self.current_image_index];
+        let image_path = self.images[self.current_image_index].clone();
+        self.drop_unsaved_edits();
 
-        // Load image
-        let img = image::open(image_path)?;
+        // Load image, shown upright as its EXIF orientation asks
+        let content = std::fs::read(&image_path)?;
+        let (img, orientation) = crate::image_io::decode_upright(&content)?;
         let rgba = img.to_rgba8();
         let size = [rgba.width() as usize, rgba.height() as usize];
         let pixels = rgba.into_raw();
+        let image_info =
+            ImageInfo::new(size[0] as u32, size[1] as u32, &content).with_orientation(orientation);
 
         self.current_image = Some(ImageData {
             path: image_path.clone(),
@@ -312,6 +354,9 @@ impl AppState {
         // Load annotation if exists
         if let Some(project) = &self.project {
             if let Some(filename) = image_path.file_name().and_then(|s| s.to_str()) {
+                // Never keep the previous image's annotation around: it would
+                // be saved over a file this version cannot read
+                self.current_annotation = None;
                 self.current_annotation = project.load_annotation(filename)?;
 
                 if self.current_annotation.is_none() {
@@ -321,6 +366,40 @@ impl AppState {
             }
         }
 
+        // The annotation is saved for the image as loaded now
+        if let Some(annotation) = &mut self.current_annotation {
+            // Older files were annotated on the raw pixels
+            if annotation.reorient_to(orientation) {
+                log::info!("Turned annotation of {:?} upright", image_path);
+            }
+            if let Some(recorded) = &annotation.image {
+                if recorded.width != image_info.width
+                    || recorded.height != image_info.height
+                    || !recorded.matches_content(&content)
+                {
+                    log::warn!(
+                        "Image {:?} differs from the one it was annotated on",
+                        image_path
+                    );
+                }
+            }
+            annotation.image = Some(image_info);
+        }
+
+        // Keep the history only if it still describes what was loaded from disk
+        if let Some(annotation) = &self.current_annotation {
+            let stale = self
+                .histories
+                .get(&image_path)
+                .is_some_and(|history| history.baseline() != annotation);
+            if stale {
+                self.histories.remove(&image_path);
+            }
+            self.histories
+                .entry(image_path)
+                .or_insert_with(|| EditHistory::new(annotation));
+        }
+
         self.has_unsaved_changes = false;
         self.selected_object_id = None;
         self.clear_drawing_state();
@@ -357,6 +436,29 @@ impl AppState {
         Ok(())
     }
 
+    /// Shape kind used for the shape being drawn (ROIs, holes and parts are always polygons)
+    pub fn active_draw_shape(&self) -> ShapeKind {
+        match self.draw_target {
+            DrawTarget::Object => self.draw_shape,
+            DrawTarget::Roi | DrawTarget::Keypoints | DrawTarget::Hole | DrawTarget::Part => {
+                ShapeKind::Polygon
+            }
+            DrawTarget::Mask => ShapeKind::Mask,
+        }
+    }
+
+    /// Next keypoint to place on the selected object, as (object_id, index, name)
+    pub fn next_keypoint(&self) -> Option<(i32, usize, String)> {
+        if self.draw_target != DrawTarget::Keypoints {
+            return None;
+        }
+        let obj_id = self.selected_object_id?;
+        let obj = self.current_annotation.as_ref()?.find_object(obj_id)?;
+        let category = self.get_meta()?.find_category(obj.category)?;
+        let index = obj.next_unlabeled_keypoint(category.keypoints.len())?;
+        Some((obj_id, index, category.keypoints[index].clone()))
+    }
+
     pub fn clear_drawing_state(&mut self) {
         self.temp_points.clear();
         self.pending_draw_clicks.clear();
@@ -393,11 +495,169 @@ impl AppState {
             if let Some(filename) = image.path.file_name().and_then(|s| s.to_str()) {
                 project.save_annotation(filename, annotation)?;
                 self.has_unsaved_changes = false;
+                if let Some(history) = self.histories.get_mut(&image.path) {
+                    history.mark_saved();
+                }
+                self.update_journal();
             }
         }
         Ok(())
     }
 
+    /// Write the recovery journal if it lags behind and was not written just now
+    ///
+    /// Returns how long to wait before the journal can be written again.
+    pub fn flush_journal(&mut self) -> Option<Duration> {
+        if !self.journal_pending {
+            return None;
+        }
+        let elapsed = self.journal_written.elapsed();
+        if elapsed < JOURNAL_INTERVAL {
+            return Some(JOURNAL_INTERVAL - elapsed);
+        }
+        self.update_journal();
+        None
+    }
+
+    /// Journal the unsaved edits of the current image, or clear them once there are none
+    fn update_journal(&mut self) {
+        self.journal_pending = false;
+        self.journal_written = Instant::now();
+        let (Some(project), Some(image)) = (&self.project, &self.current_image) else {
+            return;
+        };
+        let Some(filename) = image.path.file_name().and_then(|s| s.to_str()) else {
+            return;
+        };
+        let result = match &self.current_annotation {
+            Some(annotation) if self.has_unsaved_changes => {
+                lab_utils::recovery::journal_edit(project, filename, annotation)
+            }
+            _ => lab_utils::recovery::clear_edit(project, filename),
+        };
+        if let Err(e) = result {
+            log::warn!("Failed to update recovery journal: {}", e);
+        }
+    }
+
+    /// Forget the unsaved edits of the current image, which is about to be replaced
+    fn drop_unsaved_edits(&mut self) {
+        if self.has_unsaved_changes || self.journal_pending {
+            self.has_unsaved_changes = false;
+            self.update_journal();
+        }
+    }
+
+    /// Replace the current annotation with a previous version of it, as one undo step
+    pub fn restore_version(&mut self, mut restored: Annotation) {
+        let Some(annotation) = &mut self.current_annotation else {
+            return;
+        };
+        // The image is the one loaded now, whatever the version recorded
+        if let Some(info) = annotation.image.clone() {
+            restored.reorient_to(info.orientation);
+            restored.image = Some(info);
+        }
+        *annotation = restored;
+        self.selected_object_id = None;
+        self.clear_drawing_state();
+        self.record_edit("Restore version");
+    }
+
+    /// Record the current annotation as one undo step for the current image
+    pub fn record_edit(&mut self, label: &'static str) {
+        self.push_history(label, false);
+    }
+
+    /// Record a repeated edit, merging it with the previous step of the same kind
+    pub fn record_repeated_edit(&mut self, label: &'static str) {
+        self.push_history(label, true);
+    }
+
+    fn push_history(&mut self, label: &'static str, merge: bool) {
+        let (Some(annotation), Some(image)) = (&mut self.current_annotation, &self.current_image)
+        else {
+            return;
+        };
+
+        let history = self
+            .histories
+            .entry(image.path.clone())
+            .or_insert_with(|| EditHistory::new(annotation));
+        annotation.update_provenance(history.baseline(), &self.annotator);
+        let recorded = if merge {
+            history.record_merged(label, annotation)
+        } else {
+            history.record(label, annotation)
+        };
+        if recorded {
+            log::debug!("Recorded edit: {}", label);
+        }
+        self.has_unsaved_changes = !history.is_saved();
+        self.journal_pending = true;
+    }
+
+    fn current_history(&mut self) -> Option<&mut EditHistory> {
+        let image = self.current_image.as_ref()?;
+        self.histories.get_mut(&image.path)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.current_image
+            .as_ref()
+            .and_then(|image| self.histories.get(&image.path))
+            .is_some_and(|history| history.can_undo())
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.current_image
+            .as_ref()
+            .and_then(|image| self.histories.get(&image.path))
+            .is_some_and(|history| history.can_redo())
+    }
+
+    /// Undo the last edit on the current image
+    pub fn undo(&mut self) {
+        let Some(history) = self.current_history() else {
+            return;
+        };
+        let label = history.undo_label();
+        if let Some(annotation) = history.undo() {
+            let saved = history.is_saved();
+            self.restore_annotation(annotation, saved);
+            log::info!("Undo: {}", label.unwrap_or_default());
+        }
+    }
+
+    /// Redo the last undone edit on the current image
+    pub fn redo(&mut self) {
+        let Some(history) = self.current_history() else {
+            return;
+        };
+        let label = history.redo_label();
+        if let Some(annotation) = history.redo() {
+            let saved = history.is_saved();
+            self.restore_annotation(annotation, saved);
+            log::info!("Redo: {}", label.unwrap_or_default());
+        }
+    }
+
+    fn restore_annotation(&mut self, annotation: Annotation, saved: bool) {
+        let selection_exists = self.selected_object_id.is_some_and(|id| {
+            roi_index_from_id(id).map_or_else(
+                || annotation.objects.iter().any(|o| o.id == id),
+                |roi_index| roi_index < annotation.rois.len(),
+            )
+        });
+        if !selection_exists {
+            self.selected_object_id = None;
+        }
+        self.editing_state.selected_vertex = None;
+        self.current_annotation = Some(annotation);
+        self.has_unsaved_changes = !saved;
+        self.journal_pending = true;
+    }
+
     /// Copy selected target or all objects/ROIs to clipboard
     pub fn copy_selected(&mut self) {
         self.clipboard_objects.clear();
@@ -449,12 +709,13 @@ impl AppState {
             for obj in &self.clipboard_objects {
                 let mut new_obj = obj.clone();
                 new_obj.id = next_id;
+                // A pasted copy is a new object of the current annotator
+                new_obj.provenance = None;
                 next_id += 1;
-                new_obj.polygon = new_obj
-                    .polygon
-                    .iter()
-                    .map(|p| Point::new((p.x + offset).min(1.0), (p.y + offset).min(1.0)))
-                    .collect();
+                new_obj.shape.map_points(|p| {
+                    p.x = (p.x + offset).min(1.0);
+                    p.y = (p.y + offset).min(1.0);
+                });
                 annotation.objects.push(new_obj);
                 last_object_id = Some(next_id - 1);
             }
@@ -479,7 +740,7 @@ impl AppState {
         }
 
         if last_object_id.is_some() || last_roi_id.is_some() {
-            self.has_unsaved_changes = true;
+            self.record_edit("Paste");
         }
     }
 
@@ -493,14 +754,14 @@ impl AppState {
                     annotation.rois.remove(roi_index);
                     self.selected_object_id = None;
                     self.editing_state.selected_vertex = None;
-                    self.has_unsaved_changes = true;
+                    self.record_edit("Delete ROI");
                     log::info!("Deleted ROI #{}", roi_index);
                 }
             } else if let Some(pos) = annotation.objects.iter().position(|o| o.id == selected_id) {
                 annotation.objects.remove(pos);
                 self.selected_object_id = None;
                 self.editing_state.selected_vertex = None;
-                self.has_unsaved_changes = true;
+                self.record_edit("Delete object");
                 log::info!("Deleted object #{}", selected_id);
             }
         }
@@ -744,6 +1005,14 @@ pub struct ImageData {
 pub enum DrawTarget {
     Object,
     Roi,
+    /// Keypoints of the selected object, placed in category order
+    Keypoints,
+    /// Hole cut out of the selected polygon
+    Hole,
+    /// Extra part added to the selected polygon
+    Part,
+    /// Pixel mask painted with the brush
+    Mask,
 }
 
 pub fn roi_id_from_index(index: usize) -> i32 {
@@ -757,3 +1026,12 @@ pub fn roi_index_from_id(id: i32) -> Option<usize> {
         None
     }
 }
+
+/// Name of the person annotating: `JLAB_USER`, else the login name
+pub fn annotator_name() -> String {
+    ["JLAB_USER", "USER", "USERNAME"]
+        .iter()
+        .filter_map(|key| std::env::var(key).ok())
+        .find(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
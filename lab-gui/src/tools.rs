@@ -1,5 +1,5 @@
 use crate::state::AppState;
-use lab_core::Point;
+use lab_core::{Keypoint, KeypointVisibility, Mask, Object, Point, Shape};
 
 /// Handle drawing tool interactions
 pub struct DrawingTools;
@@ -12,18 +12,95 @@ impl DrawingTools {
 
     /// Change category of selected object
     pub fn change_selected_category(state: &mut AppState, category_id: i32) {
+        // Keypoints only carry over between categories with the same definition
+        let keypoint_names = |id: i32| {
+            state
+                .get_meta()
+                .and_then(|m| m.find_category(id))
+                .map(|c| c.keypoints.clone())
+                .unwrap_or_default()
+        };
+        let keep_keypoints = state
+            .selected_object_id
+            .and_then(|id| state.current_annotation.as_ref()?.find_object(id))
+            .is_some_and(|obj| keypoint_names(obj.category) == keypoint_names(category_id));
+
         if let (Some(annotation), Some(obj_id)) =
             (&mut state.current_annotation, state.selected_object_id)
         {
             if let Some(obj) = annotation.find_object_mut(obj_id) {
                 obj.category = category_id;
+                if !keep_keypoints {
+                    obj.keypoints.clear();
+                }
                 annotation.touch();
-                state.has_unsaved_changes = true;
+                state.record_edit("Change category");
                 log::info!("Changed object #{} category to {}", obj_id, category_id);
             }
         }
     }
 
+    /// Place the next keypoint of the selected object
+    pub fn place_keypoint(state: &mut AppState, clicked_pos: Point, occluded: bool) {
+        let Some((obj_id, index, name)) = state.next_keypoint() else {
+            log::warn!("No keypoint left to place on the selected object");
+            return;
+        };
+        let visibility = if occluded {
+            KeypointVisibility::Occluded
+        } else {
+            KeypointVisibility::Visible
+        };
+
+        if let Some(obj) = state
+            .current_annotation
+            .as_mut()
+            .and_then(|annotation| annotation.find_object_mut(obj_id))
+        {
+            obj.set_keypoint(index, Keypoint::new(clicked_pos, visibility));
+            state.record_edit("Place keypoint");
+            log::info!("Placed keypoint {} on object #{}", name, obj_id);
+        }
+    }
+
+    /// Remove the most recently placed keypoint of the selected object
+    pub fn remove_last_keypoint(state: &mut AppState) {
+        let Some(obj_id) = state.selected_object_id else {
+            return;
+        };
+        if let Some(obj) = state
+            .current_annotation
+            .as_mut()
+            .and_then(|annotation| annotation.find_object_mut(obj_id))
+        {
+            if let Some(keypoint) = obj
+                .keypoints
+                .iter_mut()
+                .rev()
+                .find(|kp| kp.visibility.is_labeled())
+            {
+                *keypoint = Keypoint::default();
+                state.record_edit("Remove keypoint");
+            }
+        }
+    }
+
+    /// Toggle a keypoint between visible and occluded
+    pub fn toggle_keypoint_visibility(state: &mut AppState, obj_id: i32, index: usize) {
+        if let Some(keypoint) = state
+            .current_annotation
+            .as_mut()
+            .and_then(|annotation| annotation.find_object_mut(obj_id))
+            .and_then(|obj| obj.keypoints.get_mut(index))
+        {
+            keypoint.visibility = match keypoint.visibility {
+                KeypointVisibility::Visible => KeypointVisibility::Occluded,
+                _ => KeypointVisibility::Visible,
+            };
+            state.record_edit("Toggle keypoint visibility");
+        }
+    }
+
     /// Cancel current drawing
     pub fn cancel_drawing(state: &mut AppState) {
         if !state.temp_points.is_empty() || !state.pending_draw_clicks.is_empty() {
@@ -31,4 +108,81 @@ impl DrawingTools {
             log::info!("Cancelled drawing");
         }
     }
+
+    /// Paint or erase a brush stroke on the selected mask
+    ///
+    /// Painting with no mask selected starts a new mask object at the image
+    /// resolution; the stroke is recorded by [`DrawingTools::finish_mask_stroke`].
+    pub fn paint_mask(state: &mut AppState, from: Point, to: Point) {
+        let Some((image_width, image_height)) = state
+            .current_image
+            .as_ref()
+            .map(|image| (image.width, image.height))
+        else {
+            return;
+        };
+        let default_category = state
+            .get_meta()
+            .and_then(|m| m.categories.first())
+            .map(|c| c.id)
+            .unwrap_or(0);
+        let (radius, erase) = (state.brush_radius, state.brush_erase);
+        let Some(annotation) = state.current_annotation.as_mut() else {
+            return;
+        };
+
+        let mask_selected = state
+            .selected_object_id
+            .and_then(|id| annotation.find_object(id))
+            .is_some_and(|obj| matches!(obj.shape, Shape::Mask(_)));
+        if !mask_selected {
+            if erase {
+                return;
+            }
+            let new_id = annotation.objects.iter().map(|o| o.id).max().unwrap_or(-1) + 1;
+            let mask = match Mask::new(image_width, image_height) {
+                Ok(mask) => mask,
+                Err(e) => {
+                    log::warn!("Cannot create a mask for this image: {}", e);
+                    return;
+                }
+            };
+            annotation.objects.push(Object::with_shape(
+                new_id,
+                default_category,
+                Shape::Mask(mask),
+            ));
+            state.selected_object_id = Some(new_id);
+            log::info!("Created new mask object #{}", new_id);
+        }
+
+        if let Some(Shape::Mask(mask)) = state
+            .selected_object_id
+            .and_then(|id| annotation.find_object_mut(id))
+            .map(|obj| &mut obj.shape)
+        {
+            // Imported masks may not match the image resolution
+            let scale = mask.width() as f32 / image_width.max(1) as f32;
+            mask.paint_stroke(from, to, radius * scale, !erase);
+            state.has_unsaved_changes = true;
+        }
+    }
+
+    /// Record a finished brush stroke, dropping the mask if it was erased entirely
+    pub fn finish_mask_stroke(state: &mut AppState) {
+        let erase = state.brush_erase;
+        if let (Some(annotation), Some(obj_id)) =
+            (&mut state.current_annotation, state.selected_object_id)
+        {
+            let erased = annotation
+                .find_object(obj_id)
+                .is_some_and(|obj| matches!(&obj.shape, Shape::Mask(mask) if mask.is_empty()));
+            if erased {
+                annotation.remove_object(obj_id);
+                state.selected_object_id = None;
+                log::info!("Removed empty mask object #{}", obj_id);
+            }
+        }
+        state.record_edit(if erase { "Erase mask" } else { "Paint mask" });
+    }
 }
@@ -1,6 +1,15 @@
-use lab_core::export::{coco::CocoExporter, voc::VocExporter, yolo::YoloExporter, Exporter};
+use crate::split::Split;
+use lab_core::export::{
+    classification::ClassificationExporter,
+    coco::CocoExporter,
+    segmentation::{render_segmentation, SegmentationOptions},
+    voc::VocExporter,
+    yolo::{YoloClasses, YoloExporter, YoloObbExporter, YoloSegExporter},
+    Exporter,
+};
 use lab_core::{Annotation, Meta, Result};
 use std::fs;
+use std::io::BufWriter;
 use std::path::Path;
 
 /// Export format
@@ -8,10 +17,15 @@ use std::path::Path;
 pub enum ExportFormat {
     Voc,
     Yolo,
+    YoloSeg,
+    YoloObb,
     Coco,
+    Classification,
 }
 
 /// Export a single annotation to a file
+///
+/// YOLO formats number the classes of [`YoloClasses::with_defaults`].
 pub fn export_annotation<P: AsRef<Path>>(
     output_path: P,
     annotation: &Annotation,
@@ -26,8 +40,20 @@ pub fn export_annotation<P: AsRef<Path>>(
             let exporter = VocExporter;
             exporter.export_annotation(annotation, meta, image_path, image_width, image_height)?
         }
-        ExportFormat::Yolo => {
-            let exporter = YoloExporter;
+        ExportFormat::Yolo | ExportFormat::YoloSeg | ExportFormat::YoloObb => {
+            let classes = YoloClasses::with_defaults(meta);
+            return export_yolo_annotation(
+                output_path,
+                annotation,
+                meta,
+                image_width,
+                image_height,
+                format,
+                &classes,
+            );
+        }
+        ExportFormat::Classification => {
+            let exporter = ClassificationExporter;
             exporter.export_annotation(annotation, meta, image_path, image_width, image_height)?
         }
         ExportFormat::Coco => {
@@ -41,6 +67,175 @@ pub fn export_annotation<P: AsRef<Path>>(
     Ok(())
 }
 
+/// Export a single annotation as YOLO labels, numbering categories by `classes`
+///
+/// Objects of categories without a class are left out.
+pub fn export_yolo_annotation<P: AsRef<Path>>(
+    output_path: P,
+    annotation: &Annotation,
+    meta: &Meta,
+    image_width: u32,
+    image_height: u32,
+    format: ExportFormat,
+    classes: &YoloClasses,
+) -> Result<()> {
+    let classes = classes.clone();
+    let exporter: Box<dyn Exporter> = match format {
+        ExportFormat::Yolo => Box::new(YoloExporter { classes }),
+        ExportFormat::YoloSeg => Box::new(YoloSegExporter { classes }),
+        ExportFormat::YoloObb => Box::new(YoloObbExporter { classes }),
+        _ => {
+            return Err(lab_core::Error::Export(format!(
+                "{:?} is not a YOLO format",
+                format
+            )));
+        }
+    };
+    let content = exporter.export_annotation(annotation, meta, "", image_width, image_height)?;
+    fs::write(output_path, content)?;
+    Ok(())
+}
+
+/// Write the `data.yaml` and `classes.txt` of a YOLO dataset
+///
+/// Images are under `images/`, or under `images/<split>/` for each of
+/// `splits` when the dataset is split. Without a val split, training
+/// images are used for validation.
+pub fn export_yolo_classes<P: AsRef<Path>>(
+    output_root: P,
+    classes: &YoloClasses,
+    splits: &[Split],
+) -> Result<()> {
+    let output_root = output_root.as_ref();
+    let folder = |split: Split| format!("images/{}", split.name());
+    let train = match splits.first() {
+        Some(first) if !splits.contains(&Split::Train) => folder(*first),
+        Some(_) => folder(Split::Train),
+        None => "images".to_string(),
+    };
+    let val = if splits.contains(&Split::Val) {
+        folder(Split::Val)
+    } else {
+        train.clone()
+    };
+    let test = splits.contains(&Split::Test).then(|| folder(Split::Test));
+    let data = classes.data_yaml(&train, &val, test.as_deref())?;
+    fs::write(output_root.join("data.yaml"), data)?;
+    fs::write(output_root.join("classes.txt"), classes.classes_txt())?;
+    Ok(())
+}
+
+/// Write the `ImageSets/<task>` lists of a split VOC dataset
+///
+/// `task` is `Main` for detection and `Segmentation` for segmentation
+/// masks; `images` pairs file stems with their split. Besides one list per
+/// split, `trainval.txt` lists the train and val images together, as in VOC.
+pub fn export_voc_image_sets<P: AsRef<Path>>(
+    output_root: P,
+    task: &str,
+    images: &[(String, Split)],
+) -> Result<()> {
+    let sets_dir = output_root.as_ref().join("ImageSets").join(task);
+    fs::create_dir_all(&sets_dir)?;
+    let list = |keep: &dyn Fn(Split) -> bool| {
+        let mut content = String::new();
+        for (stem, split) in images {
+            if keep(*split) {
+                content.push_str(stem);
+                content.push('\n');
+            }
+        }
+        content
+    };
+    for split in Split::ALL {
+        let content = list(&|s| s == split);
+        if !content.is_empty() {
+            fs::write(sets_dir.join(format!("{}.txt", split.name())), content)?;
+        }
+    }
+    fs::write(sets_dir.join("trainval.txt"), list(&|s| s != Split::Test))?;
+    Ok(())
+}
+
+/// Write the class and instance maps of an annotation as paletted PNGs
+///
+/// Pass None for a path to skip that map. Class maps use the category
+/// colors and instance maps the Pascal VOC color map, so the files can be
+/// viewed as they are and read back as indices.
+pub fn export_segmentation(
+    class_path: Option<&Path>,
+    instance_path: Option<&Path>,
+    annotation: &Annotation,
+    meta: &Meta,
+    image_width: u32,
+    image_height: u32,
+    options: &SegmentationOptions,
+) -> Result<()> {
+    let maps = render_segmentation(annotation, meta, image_width, image_height, options)?;
+    if let Some(path) = class_path {
+        let palette = options.class_palette(meta)?;
+        write_indexed_png(path, maps.width, maps.height, &maps.classes, &palette)?;
+    }
+    if let Some(path) = instance_path {
+        let palette = options.instance_palette();
+        write_indexed_png(path, maps.width, maps.height, &maps.instances, &palette)?;
+    }
+    Ok(())
+}
+
+/// Write the `labelmap.txt` of a segmentation dataset
+///
+/// Each line names a value of the class maps with its palette color, in the
+/// format read by CVAT and other tools. Lines follow the values in order:
+/// the classes, the background and the ignore value.
+pub fn export_segmentation_labelmap<P: AsRef<Path>>(
+    output_path: P,
+    meta: &Meta,
+    options: &SegmentationOptions,
+) -> Result<()> {
+    let palette = options.class_palette(meta)?;
+    let mut labels: Vec<(u8, &str)> = options
+        .classes(meta)?
+        .into_iter()
+        .map(|(value, category)| (value, category.name.as_str()))
+        .collect();
+    labels.push((options.background, "background"));
+    if let Some(ignore) = options
+        .ignore
+        .filter(|&ignore| ignore != options.background)
+    {
+        labels.push((ignore, "ignore"));
+    }
+    labels.sort_by_key(|(value, _)| *value);
+
+    let mut content = String::from("# label:color_rgb:parts:actions\n");
+    for (value, name) in labels {
+        let [r, g, b] = palette[value as usize];
+        content.push_str(&format!("{}:{},{},{}::\n", name, r, g, b));
+    }
+    fs::write(output_path, content)?;
+    Ok(())
+}
+
+fn write_indexed_png(
+    path: &Path,
+    width: u32,
+    height: u32,
+    pixels: &[u8],
+    palette: &[[u8; 3]],
+) -> Result<()> {
+    let png_error = |e: png::EncodingError| lab_core::Error::Export(e.to_string());
+    let file = BufWriter::new(fs::File::create(path)?);
+    let mut encoder = png::Encoder::new(file, width, height);
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_palette(palette.concat());
+    let mut writer = encoder.write_header().map_err(png_error)?;
+    writer.write_image_data(pixels).map_err(png_error)?;
+    writer.finish().map_err(png_error)?;
+    Ok(())
+}
+
 /// Export multiple annotations in COCO format
 pub fn export_coco_batch<P: AsRef<Path>>(
     output_path: P,
@@ -53,12 +248,24 @@ pub fn export_coco_batch<P: AsRef<Path>>(
     Ok(())
 }
 
+/// Export the image-level properties of multiple annotations as a classification manifest
+pub fn export_classification_manifest<P: AsRef<Path>>(
+    output_path: P,
+    annotations: &[(String, Annotation, u32, u32)],
+    meta: &Meta,
+) -> Result<()> {
+    let exporter = ClassificationExporter;
+    let content = exporter.export_batch(annotations, meta)?;
+    fs::write(output_path, content)?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_meta;
     use lab_core::annotation::Object;
     use lab_core::geometry::Point;
-    use lab_core::meta::{Category, RoiConfig, ShapeConfig};
 
     #[test]
     fn test_export_yolo() {
@@ -75,30 +282,7 @@ mod tests {
         );
         annotation.add_object(obj);
 
-        let meta = Meta {
-            id: 1,
-            name: "test".to_string(),
-            description: "test".to_string(),
-            shape: ShapeConfig {
-                title_style: 1,
-                thickness: 2,
-                auto_save: true,
-                vertex_radius: 10.0,
-            },
-            roi: RoiConfig {
-                color: "#800080".to_string(),
-            },
-            categories: vec![Category {
-                id: 0,
-                name: "person".to_string(),
-                description: "Person".to_string(),
-                hotkey: "1".to_string(),
-                color: "#FF0000".to_string(),
-                properties: vec![],
-            }],
-            property_types: vec![],
-            property_special_values: vec![],
-        };
+        let meta = test_meta();
 
         let temp_dir = std::env::temp_dir();
         let output_path = temp_dir.join("test_export.txt");
@@ -116,7 +300,142 @@ mod tests {
         assert!(result.is_ok());
         assert!(output_path.exists());
 
+        // Excluded categories are left out
+        let classes = YoloClasses::new(&meta, &[0]);
+        export_yolo_annotation(
+            &output_path,
+            &annotation,
+            &meta,
+            1000,
+            1000,
+            ExportFormat::Yolo,
+            &classes,
+        )
+        .unwrap();
+        assert_eq!(fs::read_to_string(&output_path).unwrap(), "");
+
         // Cleanup
         let _ = fs::remove_file(output_path);
     }
+
+    #[test]
+    fn test_yolo_split_roundtrip() {
+        use crate::split::{assign_splits, SplitConfig, SplitStrategy};
+        use lab_core::import::{yolo::YoloImporter, Importer};
+
+        let meta = test_meta();
+        let mut annotation = Annotation::new("test");
+        annotation.add_object(Object::new(
+            0,
+            0,
+            vec![
+                Point::new(0.25, 0.25),
+                Point::new(0.75, 0.25),
+                Point::new(0.75, 0.75),
+                Point::new(0.25, 0.75),
+            ],
+        ));
+        let names = ["a", "b", "c", "d"];
+        let images: Vec<_> = names.iter().map(|name| (*name, &annotation)).collect();
+        let config = SplitConfig {
+            strategy: SplitStrategy::Ordered,
+            ratios: [0.5, 0.25, 0.25],
+            seed: 0,
+        };
+        let assigned = assign_splits(&images, &meta, &config);
+
+        let root = std::env::temp_dir().join("jlab_test_yolo_split_roundtrip");
+        let _ = fs::remove_dir_all(&root);
+        let classes = YoloClasses::new(&meta, &[]);
+        for (name, split) in names.iter().zip(&assigned) {
+            let images_dir = root.join("images").join(split.name());
+            let labels_dir = root.join("labels").join(split.name());
+            fs::create_dir_all(&images_dir).unwrap();
+            fs::create_dir_all(&labels_dir).unwrap();
+            fs::write(images_dir.join(format!("{}.jpg", name)), b"").unwrap();
+            export_yolo_annotation(
+                labels_dir.join(format!("{}.txt", name)),
+                &annotation,
+                &meta,
+                100,
+                100,
+                ExportFormat::Yolo,
+                &classes,
+            )
+            .unwrap();
+        }
+        export_yolo_classes(&root, &classes, &Split::ALL).unwrap();
+
+        let dataset = YoloImporter.import(&root, &meta, "test").unwrap();
+        assert!(dataset.report.is_complete());
+        let mut imported: Vec<_> = dataset
+            .images
+            .iter()
+            .map(|image| image.file_name.as_str())
+            .collect();
+        imported.sort();
+        assert_eq!(imported, ["a.jpg", "b.jpg", "c.jpg", "d.jpg"]);
+        assert_eq!(dataset.report.objects, 4);
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_export_segmentation() {
+        let mut annotation = Annotation::new("test");
+        annotation.add_object(Object::new(
+            0,
+            0,
+            vec![
+                Point::new(0.0, 0.0),
+                Point::new(0.5, 0.0),
+                Point::new(0.5, 0.5),
+                Point::new(0.0, 0.5),
+            ],
+        ));
+        let meta = test_meta();
+
+        let root = std::env::temp_dir().join("jlab_test_export_segmentation");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let class_path = root.join("a.png");
+        export_segmentation(
+            Some(&class_path),
+            None,
+            &annotation,
+            &meta,
+            4,
+            2,
+            &SegmentationOptions::default(),
+        )
+        .unwrap();
+
+        let decoder = png::Decoder::new(std::io::BufReader::new(
+            fs::File::open(&class_path).unwrap(),
+        ));
+        let mut reader = decoder.read_info().unwrap();
+        assert_eq!(
+            &reader.info().palette.as_ref().unwrap()[..6],
+            [0, 0, 0, 255, 0, 0]
+        );
+        let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+        reader.next_frame(&mut pixels).unwrap();
+        assert_eq!(pixels, [1, 1, 0, 0, 0, 0, 0, 0]);
+
+        export_segmentation_labelmap(root.join("labelmap.txt"), &meta, &Default::default())
+            .unwrap();
+        let labelmap = fs::read_to_string(root.join("labelmap.txt")).unwrap();
+        assert!(labelmap.ends_with("background:0,0,0::\nperson:255,0,0::\nignore:255,255,255::\n"));
+
+        let options = SegmentationOptions {
+            background: 3,
+            ignore: None,
+            ..Default::default()
+        };
+        export_segmentation_labelmap(root.join("labelmap.txt"), &meta, &options).unwrap();
+        let labelmap = fs::read_to_string(root.join("labelmap.txt")).unwrap();
+        assert!(labelmap.ends_with("person:255,0,0::\nbackground:0,0,0::\n"));
+
+        let _ = fs::remove_dir_all(root);
+    }
 }
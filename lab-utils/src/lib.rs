@@ -1,5 +1,46 @@
 pub mod conversion;
+pub mod migration;
+pub mod outline;
 pub mod project;
+pub mod recovery;
+pub mod split;
+pub mod storage;
 pub mod validation;
 
-pub use project::Project;
+pub use project::{Backup, Project};
+
+/// Project metadata with a single `person` category (ID 0, red)
+#[cfg(test)]
+pub(crate) fn test_meta() -> lab_core::Meta {
+    use lab_core::meta::{Category, RoiConfig, ShapeConfig};
+
+    lab_core::Meta {
+        id: 1,
+        name: "test".to_string(),
+        description: "test".to_string(),
+        shape: ShapeConfig {
+            title_style: 1,
+            thickness: 2,
+            auto_save: true,
+            vertex_radius: 10.0,
+        },
+        roi: RoiConfig {
+            color: "#800080".parse().unwrap(),
+        },
+        categories: vec![Category {
+            id: 0,
+            name: "person".to_string(),
+            description: "Person".to_string(),
+            hotkey: "1".to_string(),
+            color: "#FF0000".parse().unwrap(),
+            properties: vec![],
+            keypoints: vec![],
+            skeleton: vec![],
+        }],
+        property_types: vec![],
+        property_special_values: vec![],
+        image_properties: vec![],
+        storage: Default::default(),
+        backups: None,
+    }
+}
@@ -0,0 +1,122 @@
+use crate::Project;
+use lab_core::{Result, StorageFormat};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Outcome of migrating the annotation files of a project
+#[derive(Debug, Clone, Default)]
+pub struct MigrationReport {
+    /// Files rewritten (or to be rewritten) in the current schema version
+    pub migrated: Vec<PathBuf>,
+
+    /// Number of files already in the current version
+    pub up_to_date: usize,
+
+    /// Files that could not be migrated, with the reason
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+/// Rewrite every annotation file of a project in the current schema version
+///
+/// Files written by a newer version are reported as failed and left untouched.
+/// With `dry_run` nothing is written and the report lists what would change.
+/// Files are saved like any edit, see [`Project::save_annotation`]: the
+/// outdated version is kept as a backup and the file is written in the
+/// project's storage format.
+pub fn migrate_project(project: &Project, dry_run: bool) -> Result<MigrationReport> {
+    let mut report = MigrationReport::default();
+    let labels_dir = project.labels_dir();
+    if !labels_dir.exists() {
+        return Ok(report);
+    }
+
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(&labels_dir)? {
+        let path = entry?.path();
+        if path.is_file() && StorageFormat::from_path(&path).is_some() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    for path in paths {
+        match migrate_file(project, &path, dry_run) {
+            Ok(true) => report.migrated.push(path),
+            Ok(false) => report.up_to_date += 1,
+            Err(e) => report.failed.push((path, e.to_string())),
+        }
+    }
+    Ok(report)
+}
+
+/// Migrate one annotation file, returning whether it was outdated
+fn migrate_file(project: &Project, path: &Path, dry_run: bool) -> Result<bool> {
+    let (annotation, migrated) = lab_core::io::load_annotation_with_status(path)?;
+    if migrated && !dry_run {
+        // The label file name stands in for the image name: only its stem is used
+        let name = path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        project.save_annotation(name, &annotation)?;
+    }
+    Ok(migrated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_meta;
+    use lab_core::migrate::ANNOTATION_VERSION;
+    use lab_core::Annotation;
+
+    #[test]
+    fn test_migrate_project() {
+        let root = std::env::temp_dir().join("jlab_test_migrate_project");
+        let _ = fs::remove_dir_all(&root);
+        let project = Project {
+            root: root.clone(),
+            meta: lab_core::Meta {
+                backups: Some(3),
+                ..test_meta()
+            },
+        };
+        let labels_dir = project.labels_dir();
+        fs::create_dir_all(&labels_dir).unwrap();
+        fs::write(
+            labels_dir.join("old.yaml"),
+            "roi: [{x: 0.1, y: 0.1}, {x: 0.2, y: 0.1}, {x: 0.2, y: 0.2}]\nobjects: []\n",
+        )
+        .unwrap();
+        fs::write(
+            labels_dir.join("newer.yaml"),
+            "version: '9.0'\nobjects: []\n",
+        )
+        .unwrap();
+        project
+            .save_annotation("current.jpg", &Annotation::new("test"))
+            .unwrap();
+
+        let report = migrate_project(&project, true).unwrap();
+        assert_eq!(report.migrated.len(), 1);
+        assert_eq!(report.up_to_date, 1);
+        assert_eq!(report.failed.len(), 1);
+        let old = fs::read_to_string(labels_dir.join("old.yaml")).unwrap();
+        assert!(old.starts_with("roi:"));
+
+        migrate_project(&project, false).unwrap();
+        let backups = project.list_backups("old.jpg").unwrap();
+        assert_eq!(backups.len(), 1);
+        assert!(fs::read_to_string(&backups[0].path)
+            .unwrap()
+            .starts_with("roi:"));
+        let migrated = project.load_annotation("old.jpg").unwrap().unwrap();
+        assert_eq!(migrated.version, ANNOTATION_VERSION);
+        assert_eq!(migrated.rois.len(), 1);
+        let report = migrate_project(&project, false).unwrap();
+        assert!(report.migrated.is_empty());
+        assert_eq!(report.up_to_date, 2);
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,182 @@
+use crate::Project;
+use lab_core::Result;
+use std::path::{Path, PathBuf};
+
+/// Vertex operation applied to polygon and polyline outlines
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutlineOperation {
+    /// Remove vertices deviating less than `tolerance` pixels from the outline
+    Simplify { tolerance: f32 },
+
+    /// Round off corners with one smoothing pass
+    Smooth,
+
+    /// Insert vertices so no edge is longer than `max_length` pixels
+    Densify { max_length: f32 },
+}
+
+/// Outcome of processing the outlines of a project
+#[derive(Debug, Clone, Default)]
+pub struct OutlineReport {
+    /// Annotation files changed (or to be changed)
+    pub changed: Vec<PathBuf>,
+
+    /// Number of annotation files left as they were
+    pub unchanged: usize,
+
+    /// Annotation files that could not be processed, with the reason
+    pub failed: Vec<(PathBuf, String)>,
+
+    /// Total vertex count of the changed outlines before processing
+    pub vertices_before: usize,
+
+    /// Total vertex count of the changed outlines after processing
+    pub vertices_after: usize,
+}
+
+/// Apply an outline operation to every annotated image of a project
+///
+/// Pixel tolerances need the image size, which `image_size` reports for an
+/// image path; images it cannot measure are reported as failed. Changed
+/// objects are stamped as modified by `user`. With `dry_run` nothing is
+/// written and the report lists what would change.
+pub fn process_outlines(
+    project: &Project,
+    operation: OutlineOperation,
+    mut image_size: impl FnMut(&Path) -> Option<(u32, u32)>,
+    user: &str,
+    dry_run: bool,
+) -> Result<OutlineReport> {
+    let mut report = OutlineReport::default();
+
+    for image_path in project.list_images()? {
+        let Some(image_name) = image_path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let Some(annotation_path) = project.existing_annotation_path(image_name) else {
+            continue;
+        };
+
+        let size = match operation {
+            OutlineOperation::Smooth => Some((0, 0)),
+            _ => image_size(&image_path),
+        };
+        let Some((width, height)) = size else {
+            report
+                .failed
+                .push((annotation_path, "Cannot read the image size".to_string()));
+            continue;
+        };
+
+        let mut annotation = match project.load_annotation(image_name) {
+            Ok(Some(annotation)) => annotation,
+            Ok(None) => continue,
+            Err(e) => {
+                report.failed.push((annotation_path, e.to_string()));
+                continue;
+            }
+        };
+        let original = annotation.clone();
+
+        let mut changed = false;
+        for obj in &mut annotation.objects {
+            let before = obj.shape.vertices().len();
+            let shape_changed = match operation {
+                OutlineOperation::Simplify { tolerance } => {
+                    obj.shape.simplify(tolerance, width, height)
+                }
+                OutlineOperation::Smooth => obj.shape.smooth(),
+                OutlineOperation::Densify { max_length } => {
+                    obj.shape.densify(max_length, width, height)
+                }
+            };
+            if shape_changed {
+                report.vertices_before += before;
+                report.vertices_after += obj.shape.vertices().len();
+                changed = true;
+            }
+        }
+
+        if !changed {
+            report.unchanged += 1;
+            continue;
+        }
+        if !dry_run {
+            annotation.update_provenance(&original, user);
+            annotation.touch();
+            if let Err(e) = project.save_annotation(image_name, &annotation) {
+                report.failed.push((annotation_path, e.to_string()));
+                continue;
+            }
+        }
+        report.changed.push(annotation_path);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_meta;
+    use lab_core::{Annotation, Object, Point};
+    use std::fs;
+
+    #[test]
+    fn test_process_outlines() {
+        let root = std::env::temp_dir().join("jlab_test_process_outlines");
+        let _ = fs::remove_dir_all(&root);
+        let project = Project {
+            root: root.clone(),
+            meta: test_meta(),
+        };
+        fs::create_dir_all(project.images_dir()).unwrap();
+        for name in ["a.jpg", "b.jpg", "c.jpg"] {
+            fs::write(project.images_dir().join(name), b"").unwrap();
+        }
+
+        // A square with a redundant vertex in the middle of each edge
+        let mut annotation = Annotation::new("test");
+        annotation.objects.push(Object::new(
+            1,
+            1,
+            vec![
+                Point::new(0.1, 0.1),
+                Point::new(0.5, 0.1),
+                Point::new(0.9, 0.1),
+                Point::new(0.9, 0.5),
+                Point::new(0.9, 0.9),
+                Point::new(0.5, 0.9),
+                Point::new(0.1, 0.9),
+                Point::new(0.1, 0.5),
+            ],
+        ));
+        project.save_annotation("a.jpg", &annotation).unwrap();
+        project
+            .save_annotation("b.jpg", &Annotation::new("test"))
+            .unwrap();
+        project.save_annotation("c.jpg", &annotation).unwrap();
+
+        let size = |path: &Path| (!path.ends_with("c.jpg")).then_some((100, 100));
+        let simplify = OutlineOperation::Simplify { tolerance: 1.0 };
+
+        let report = process_outlines(&project, simplify, size, "bob", true).unwrap();
+        assert_eq!(report.changed, vec![project.annotation_path("a.jpg")]);
+        assert_eq!(report.unchanged, 1);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!((report.vertices_before, report.vertices_after), (8, 4));
+        let unchanged = project.load_annotation("a.jpg").unwrap().unwrap();
+        assert_eq!(unchanged.objects[0].shape.vertices().len(), 8);
+
+        process_outlines(&project, simplify, size, "bob", false).unwrap();
+        let simplified = project.load_annotation("a.jpg").unwrap().unwrap();
+        assert_eq!(simplified.objects[0].shape.vertices().len(), 4);
+        assert!(simplified.objects[0].provenance.is_some());
+
+        let report =
+            process_outlines(&project, OutlineOperation::Smooth, size, "bob", false).unwrap();
+        assert_eq!(report.changed.len(), 2);
+        assert!(report.failed.is_empty());
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
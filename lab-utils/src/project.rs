@@ -1,4 +1,5 @@
-use lab_core::{Annotation, Meta, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use lab_core::{Annotation, Diagnostic, Meta, Result, StorageFormat};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -13,15 +14,27 @@ pub struct Project {
 
 impl Project {
     /// Open an existing project from a root directory
+    ///
+    /// Fails with [`lab_core::Error::InvalidMeta`] if `meta.yaml` has fatal
+    /// problems; warnings can be listed with [`Project::meta_warnings`].
     pub fn open<P: AsRef<Path>>(root: P) -> Result<Self> {
         let root = root.as_ref().to_path_buf();
         let meta_path = root.join("meta.yaml");
 
         let meta = lab_core::io::load_meta(&meta_path)?;
+        let diagnostics = meta.validate();
+        if diagnostics.has_errors() {
+            return Err(lab_core::Error::InvalidMeta(diagnostics));
+        }
 
         Ok(Self { root, meta })
     }
 
+    /// Non-fatal problems in the project metadata
+    pub fn meta_warnings(&self) -> Vec<Diagnostic> {
+        self.meta.validate().warnings().cloned().collect()
+    }
+
     /// Get the path to the images directory
     pub fn images_dir(&self) -> PathBuf {
         self.root.join("images")
@@ -32,38 +45,134 @@ impl Project {
         self.root.join("labels")
     }
 
-    /// Get the path to a specific annotation file
+    /// Get the path to a specific annotation file in the project's storage format
     pub fn annotation_path(&self, image_name: &str) -> PathBuf {
-        let stem = Path::new(image_name)
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or(image_name);
+        self.label_path(label_stem(image_name), self.meta.storage)
+    }
 
-        self.labels_dir().join(format!("{}.yaml", stem))
+    /// Path of the annotation file of an image that exists on disk
+    ///
+    /// Files in the project's storage format take precedence; files left in
+    /// another format, e.g. before a conversion finished, are still found.
+    pub fn existing_annotation_path(&self, image_name: &str) -> Option<PathBuf> {
+        self.existing_label_path(label_stem(image_name))
+    }
+
+    fn existing_label_path(&self, stem: &str) -> Option<PathBuf> {
+        std::iter::once(self.meta.storage)
+            .chain(StorageFormat::all())
+            .map(|format| self.label_path(stem, format))
+            .find(|path| path.exists())
     }
 
     /// Load an annotation for a specific image
     pub fn load_annotation(&self, image_name: &str) -> Result<Option<Annotation>> {
-        let path = self.annotation_path(image_name);
-
-        if !path.exists() {
+        let Some(path) = self.existing_annotation_path(image_name) else {
             return Ok(None);
-        }
+        };
 
         let annotation = lab_core::io::load_annotation(&path)?;
         Ok(Some(annotation))
     }
 
     /// Save an annotation for a specific image
+    ///
+    /// The file is replaced atomically, after keeping the previous version as
+    /// a backup if the project asks for backups. Files of the same image in
+    /// other storage formats are removed.
     pub fn save_annotation(&self, image_name: &str, annotation: &Annotation) -> Result<()> {
         let path = self.annotation_path(image_name);
+        let stem = label_stem(image_name);
 
         // Ensure the labels directory exists
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)?;
         }
 
+        if let Some(keep) = self.meta.backups.filter(|&keep| keep > 0) {
+            self.backup_label(stem, keep)?;
+        }
         lab_core::io::save_annotation(&path, annotation)?;
+        self.remove_other_formats(stem)?;
+        Ok(())
+    }
+
+    /// Get the path to the hidden directory with previous annotation versions
+    pub fn backups_dir(&self) -> PathBuf {
+        self.root.join(".jlab").join("backups")
+    }
+
+    /// Previous versions of the annotation of an image, newest first
+    pub fn list_backups(&self, image_name: &str) -> Result<Vec<Backup>> {
+        self.label_backups(label_stem(image_name))
+    }
+
+    fn label_backups(&self, stem: &str) -> Result<Vec<Backup>> {
+        let dir = self.backups_dir().join(stem);
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut backups = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if StorageFormat::from_path(&path).is_none() {
+                continue;
+            }
+            let saved_at = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| NaiveDateTime::parse_from_str(s, BACKUP_TIME_FORMAT).ok());
+            if let Some(saved_at) = saved_at {
+                backups.push(Backup {
+                    path,
+                    saved_at: saved_at.and_utc(),
+                });
+            }
+        }
+        backups.sort_by_key(|backup| std::cmp::Reverse(backup.saved_at));
+        Ok(backups)
+    }
+
+    /// Copy the current annotation file of `stem` into the backups, keeping
+    /// the newest `keep` versions
+    fn backup_label(&self, stem: &str, keep: usize) -> Result<()> {
+        let Some(current) = self.existing_label_path(stem) else {
+            return Ok(());
+        };
+        let saved_at: DateTime<Utc> = fs::metadata(&current)?.modified()?.into();
+        let format = StorageFormat::from_path(&current).unwrap_or_default();
+
+        let dir = self.backups_dir().join(stem);
+        fs::create_dir_all(&dir)?;
+        let backup_path = dir.join(format!(
+            "{}.{}",
+            saved_at.format(BACKUP_TIME_FORMAT),
+            format.extension()
+        ));
+        fs::copy(&current, backup_path)?;
+
+        for old in self.label_backups(stem)?.into_iter().skip(keep) {
+            fs::remove_file(old.path)?;
+        }
+        Ok(())
+    }
+
+    fn label_path(&self, stem: &str, format: StorageFormat) -> PathBuf {
+        self.labels_dir()
+            .join(format!("{}.{}", stem, format.extension()))
+    }
+
+    /// Remove annotation files of `stem` not in the project's storage format
+    pub(crate) fn remove_other_formats(&self, stem: &str) -> Result<()> {
+        for format in StorageFormat::all() {
+            if format != self.meta.storage {
+                let path = self.label_path(stem, format);
+                if path.exists() {
+                    fs::remove_file(path)?;
+                }
+            }
+        }
         Ok(())
     }
 
@@ -97,7 +206,7 @@ impl Project {
 
     /// Check if an image has been annotated
     pub fn is_annotated(&self, image_name: &str) -> bool {
-        self.annotation_path(image_name).exists()
+        self.existing_annotation_path(image_name).is_some()
     }
 
     /// Get annotation progress statistics
@@ -122,6 +231,26 @@ impl Project {
     }
 }
 
+/// File names of backups, the time the backed up version was saved
+const BACKUP_TIME_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+
+/// A previous version of an annotation file
+#[derive(Debug, Clone)]
+pub struct Backup {
+    pub path: PathBuf,
+
+    /// When this version was saved
+    pub saved_at: DateTime<Utc>,
+}
+
+/// Stem of the annotation files of an image
+pub(crate) fn label_stem(image_name: &str) -> &str {
+    Path::new(image_name)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(image_name)
+}
+
 /// Annotation progress statistics
 #[derive(Debug, Clone)]
 pub struct ProgressStats {
@@ -168,6 +297,104 @@ mod tests {
         assert_eq!(project.meta.name, "FDOD");
     }
 
+    #[test]
+    fn test_open_invalid_meta() {
+        let root = std::env::temp_dir().join("jlab_test_open_invalid_meta");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let meta = r##"
+id: 1
+name: test
+description: test
+shape: {title_style: 1, thickness: 2}
+roi: {color: "#800080"}
+categories:
+  - {id: 0, name: person, description: Person, hotkey: "1", color: BROWN}
+  - {id: 1, name: person, description: Cat, hotkey: "1", color: "#00FF00"}
+property_types: []
+property_special_values: []
+"##;
+        fs::write(root.join("meta.yaml"), meta).unwrap();
+        let project = Project::open(&root).unwrap();
+        let warnings = project.meta_warnings();
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].location, "categories[1].name");
+        assert_eq!(warnings[1].location, "categories[1].hotkey");
+
+        fs::write(
+            root.join("meta.yaml"),
+            meta.replace("id: 1, name: person", "id: 0, name: person"),
+        )
+        .unwrap();
+        match Project::open(&root) {
+            Err(lab_core::Error::InvalidMeta(diagnostics)) => {
+                let errors: Vec<_> = diagnostics.errors().collect();
+                assert_eq!(errors.len(), 1);
+                assert_eq!(errors[0].location, "categories[1].id");
+            }
+            other => panic!("expected invalid meta, got {:?}", other.map(|p| p.root)),
+        }
+
+        // A misspelled color is a warning, not a reason to refuse the project
+        fs::write(root.join("meta.yaml"), meta.replace("BROWN", "BRWN")).unwrap();
+        let project = Project::open(&root).unwrap();
+        let warnings = project.meta_warnings();
+        assert_eq!(warnings[2].location, "categories[0].color");
+        assert!(project.meta.categories[0].color.is_unreadable());
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_backups() {
+        let root = std::env::temp_dir().join("jlab_test_backups");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let meta = r##"
+id: 1
+name: test
+description: test
+shape: {title_style: 1, thickness: 2}
+roi: {color: "#800080"}
+categories: []
+property_types: []
+property_special_values: []
+backups: 2
+"##;
+        fs::write(root.join("meta.yaml"), meta).unwrap();
+        let project = Project::open(&root).unwrap();
+        assert_eq!(project.meta.backups, Some(2));
+
+        let start =
+            std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+        for (i, agent) in ["a", "b", "c", "d"].into_iter().enumerate() {
+            let mut annotation = Annotation::new("test");
+            annotation.user_agent = agent.to_string();
+            project.save_annotation("x.y.jpg", &annotation).unwrap();
+            // Distinct modification times, as saves would be apart in practice
+            fs::File::options()
+                .write(true)
+                .open(project.annotation_path("x.y.jpg"))
+                .unwrap()
+                .set_modified(start + std::time::Duration::from_secs(i as u64))
+                .unwrap();
+        }
+
+        let backups = project.list_backups("x.y.jpg").unwrap();
+        assert_eq!(backups.len(), 2);
+        assert!(backups[0].saved_at > backups[1].saved_at);
+        assert!(backups[0]
+            .path
+            .starts_with(project.backups_dir().join("x.y")));
+        let previous = lab_core::io::load_annotation(&backups[0].path).unwrap();
+        assert_eq!(previous.user_agent, "c");
+        let current = project.load_annotation("x.y.jpg").unwrap().unwrap();
+        assert_eq!(current.user_agent, "d");
+        assert!(project.list_backups("other.jpg").unwrap().is_empty());
+
+        let _ = fs::remove_dir_all(root);
+    }
+
     #[test]
     fn test_list_images() {
         let project_path = get_test_project_path();
@@ -193,7 +420,7 @@ mod tests {
         let annotation = project.load_annotation("0001.jpg");
 
         if let Ok(Some(ann)) = annotation {
-            assert_eq!(ann.version, "2.0");
+            assert_eq!(ann.version, lab_core::migrate::ANNOTATION_VERSION);
             assert!(!ann.objects.is_empty());
             assert!(!ann.rois.is_empty());
         }
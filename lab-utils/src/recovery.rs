@@ -0,0 +1,127 @@
+use crate::Project;
+use chrono::{DateTime, Utc};
+use lab_core::{Annotation, Result};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+/// Extension of journal files, after the image file name
+const JOURNAL_EXTENSION: &str = "yaml";
+
+/// Unsaved edits of one image, left in the journal by an earlier session
+#[derive(Debug, Clone)]
+pub struct RecoveredEdit {
+    /// File name of the image the edits belong to
+    pub image_name: String,
+
+    /// Journal file holding the edited annotation
+    pub path: PathBuf,
+
+    /// When the edits were last written to the journal
+    pub journaled_at: DateTime<Utc>,
+}
+
+/// Directory of the recovery journal of a project
+pub fn journal_dir(project: &Project) -> PathBuf {
+    project.root.join(".jlab").join("recovery")
+}
+
+/// Write the unsaved state of an image's annotation to the journal
+pub fn journal_edit(project: &Project, image_name: &str, annotation: &Annotation) -> Result<()> {
+    let dir = journal_dir(project);
+    fs::create_dir_all(&dir)?;
+    lab_core::io::save_annotation(journal_path(project, image_name), annotation)
+}
+
+/// Remove an image from the journal, once its edits are saved or dropped
+pub fn clear_edit(project: &Project, image_name: &str) -> Result<()> {
+    match fs::remove_file(journal_path(project, image_name)) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Images with unsaved edits in the journal, ordered by image name
+pub fn pending_edits(project: &Project) -> Result<Vec<RecoveredEdit>> {
+    let dir = journal_dir(project);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut edits = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+        let Some(image_name) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(&format!(".{}", JOURNAL_EXTENSION)))
+        else {
+            continue;
+        };
+        edits.push(RecoveredEdit {
+            image_name: image_name.to_string(),
+            journaled_at: fs::metadata(&path)?.modified()?.into(),
+            path,
+        });
+    }
+    edits.sort_by(|a, b| a.image_name.cmp(&b.image_name));
+    Ok(edits)
+}
+
+/// Save recovered edits as the image's annotation and remove them from the journal
+pub fn restore_edit(project: &Project, edit: &RecoveredEdit) -> Result<()> {
+    let annotation = lab_core::io::load_annotation(&edit.path)?;
+    project.save_annotation(&edit.image_name, &annotation)?;
+    clear_edit(project, &edit.image_name)
+}
+
+fn journal_path(project: &Project, image_name: &str) -> PathBuf {
+    journal_dir(project).join(format!("{}.{}", image_name, JOURNAL_EXTENSION))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_journal() {
+        let root = std::env::temp_dir().join("jlab_test_recovery_journal");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let meta = r##"
+id: 1
+name: test
+description: test
+shape: {title_style: 1, thickness: 2}
+roi: {color: "#800080"}
+categories: []
+property_types: []
+property_special_values: []
+"##;
+        fs::write(root.join("meta.yaml"), meta).unwrap();
+        let project = Project::open(&root).unwrap();
+        assert!(pending_edits(&project).unwrap().is_empty());
+
+        let mut annotation = Annotation::new("test");
+        project.save_annotation("b.jpg", &annotation).unwrap();
+        annotation.user_agent = "edited".to_string();
+        journal_edit(&project, "b.jpg", &annotation).unwrap();
+        journal_edit(&project, "a.png", &annotation).unwrap();
+        clear_edit(&project, "missing.jpg").unwrap();
+
+        let edits = pending_edits(&project).unwrap();
+        let names: Vec<_> = edits.iter().map(|e| e.image_name.as_str()).collect();
+        assert_eq!(names, ["a.png", "b.jpg"]);
+
+        restore_edit(&project, &edits[1]).unwrap();
+        let restored = project.load_annotation("b.jpg").unwrap().unwrap();
+        assert_eq!(restored.user_agent, "edited");
+        clear_edit(&project, "a.png").unwrap();
+        assert!(pending_edits(&project).unwrap().is_empty());
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
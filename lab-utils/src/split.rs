@@ -0,0 +1,360 @@
+use lab_core::export::property_attributes;
+use lab_core::{Annotation, Meta};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Part of a dataset an image is exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Split {
+    Train,
+    Val,
+    Test,
+}
+
+impl Split {
+    pub const ALL: [Split; 3] = [Split::Train, Split::Val, Split::Test];
+
+    /// Folder and file name of the split in dataset layouts
+    pub fn name(self) -> &'static str {
+        match self {
+            Split::Train => "train",
+            Split::Val => "val",
+            Split::Test => "test",
+        }
+    }
+}
+
+/// How images are assigned to splits
+#[derive(Debug, Clone, PartialEq)]
+pub enum SplitStrategy {
+    /// Shuffle with the seed, then cut by the ratios
+    Random,
+
+    /// Cut by the ratios in file name order, e.g. for frames of one video
+    Ordered,
+
+    /// Give every split about the same share of each category, so rare
+    /// categories are not left out of the smaller splits
+    Stratified,
+
+    /// Keep the images of a group in one split, so near-duplicate frames of
+    /// a sequence or camera do not leak from training into evaluation
+    Grouped(GroupBy),
+}
+
+/// What the images of a group have in common
+#[derive(Debug, Clone, PartialEq)]
+pub enum GroupBy {
+    /// File name up to the first separator, such as `cam1` for
+    /// `cam1_0042.jpg` with `_`
+    Prefix(char),
+
+    /// Value of the named image-level property, such as a camera ID;
+    /// images without a value are groups of their own
+    Property(String),
+}
+
+/// Settings of a dataset split
+#[derive(Debug, Clone, PartialEq)]
+pub struct SplitConfig {
+    pub strategy: SplitStrategy,
+
+    /// Relative sizes of the train, val and test splits
+    pub ratios: [f64; 3],
+
+    /// Seed for shuffling; the same seed gives the same split
+    pub seed: u64,
+}
+
+impl Default for SplitConfig {
+    fn default() -> Self {
+        Self {
+            strategy: SplitStrategy::Random,
+            ratios: [0.8, 0.1, 0.1],
+            seed: 0,
+        }
+    }
+}
+
+/// Assign each image to a split
+///
+/// `images` pairs file names with their annotations and the result follows
+/// the same order. Assignments depend on the names and the seed, not on the
+/// order of `images`, so exporting the same project twice gives the same
+/// split.
+pub fn assign_splits(
+    images: &[(&str, &Annotation)],
+    meta: &Meta,
+    config: &SplitConfig,
+) -> Vec<Split> {
+    let mut order: Vec<usize> = (0..images.len()).collect();
+    order.sort_by_key(|&index| images[index].0);
+
+    let mut rng = SplitRng(config.seed);
+    let mut splits = vec![Split::Train; images.len()];
+    match &config.strategy {
+        SplitStrategy::Random => {
+            rng.shuffle(&mut order);
+            cut(&order, &config.ratios, &mut splits);
+        }
+        SplitStrategy::Ordered => cut(&order, &config.ratios, &mut splits),
+        SplitStrategy::Stratified => {
+            // Each image counts towards the rarest of its categories
+            let categories: Vec<BTreeSet<i32>> = images
+                .iter()
+                .map(|(_, annotation)| annotation.objects.iter().map(|o| o.category).collect())
+                .collect();
+            let mut frequency: BTreeMap<i32, usize> = BTreeMap::new();
+            for category in categories.iter().flatten() {
+                *frequency.entry(*category).or_default() += 1;
+            }
+            let mut strata: BTreeMap<Option<(usize, i32)>, Vec<usize>> = BTreeMap::new();
+            for &index in &order {
+                let rarest = categories[index]
+                    .iter()
+                    .map(|category| (frequency[category], *category))
+                    .min();
+                strata.entry(rarest).or_default().push(index);
+            }
+
+            let mut dealt = Vec::with_capacity(images.len());
+            for mut members in strata.into_values() {
+                rng.shuffle(&mut members);
+                dealt.extend(members);
+            }
+            deal(&dealt, &config.ratios, &mut splits);
+        }
+        SplitStrategy::Grouped(group_by) => {
+            let mut keyed: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+            let mut groups = Vec::new();
+            for &index in &order {
+                let (name, annotation) = images[index];
+                match group_key(name, annotation, group_by, meta) {
+                    Some(key) => keyed.entry(key).or_default().push(index),
+                    None => groups.push(vec![index]),
+                }
+            }
+            groups.extend(keyed.into_values());
+            groups.sort();
+            rng.shuffle(&mut groups);
+
+            // Each group goes to the split furthest below its size
+            let shares = shares(&config.ratios);
+            let targets = split_counts(images.len(), &config.ratios);
+            let mut sizes = [0usize; 3];
+            for members in groups {
+                let k = (0..3)
+                    .filter(|&k| shares[k] > 0.0)
+                    .max_by_key(|&k| (targets[k] as i64 - sizes[k] as i64, std::cmp::Reverse(k)))
+                    .unwrap_or(0);
+                sizes[k] += members.len();
+                for index in members {
+                    splits[index] = Split::ALL[k];
+                }
+            }
+        }
+    }
+    splits
+}
+
+fn group_key(
+    name: &str,
+    annotation: &Annotation,
+    group_by: &GroupBy,
+    meta: &Meta,
+) -> Option<String> {
+    match group_by {
+        GroupBy::Prefix(separator) => name
+            .split_once(*separator)
+            .map(|(prefix, _)| prefix.to_string()),
+        GroupBy::Property(property) => {
+            property_attributes(&annotation.properties, &meta.image_properties, meta)
+                .remove(property)
+                .map(|value| value.to_string())
+        }
+    }
+}
+
+/// Ratios scaled to sum to one; all images go to train if none is positive
+fn shares(ratios: &[f64; 3]) -> [f64; 3] {
+    let ratios = ratios.map(|r| if r.is_finite() { r.max(0.0) } else { 0.0 });
+    let total: f64 = ratios.iter().sum();
+    if total <= 0.0 {
+        return [1.0, 0.0, 0.0];
+    }
+    ratios.map(|r| r / total)
+}
+
+/// Number of images in each split, handing rounding leftovers to the
+/// largest remainders
+fn split_counts(count: usize, ratios: &[f64; 3]) -> [usize; 3] {
+    let exact = shares(ratios).map(|share| count as f64 * share);
+    let mut counts = exact.map(|e| e.floor() as usize);
+    let mut leftover = count.saturating_sub(counts.iter().sum());
+    let mut by_remainder = [0, 1, 2];
+    by_remainder
+        .sort_by(|&a, &b| (exact[b] - exact[b].floor()).total_cmp(&(exact[a] - exact[a].floor())));
+    for k in by_remainder {
+        if leftover == 0 {
+            break;
+        }
+        counts[k] += 1;
+        leftover -= 1;
+    }
+    counts
+}
+
+/// Assign consecutive runs of `indices` to train, val and test
+fn cut(indices: &[usize], ratios: &[f64; 3], splits: &mut [Split]) {
+    let counts = split_counts(indices.len(), ratios);
+    let mut rest = indices;
+    for (k, count) in counts.into_iter().enumerate() {
+        let (run, tail) = rest.split_at(count);
+        for &index in run {
+            splits[index] = Split::ALL[k];
+        }
+        rest = tail;
+    }
+}
+
+/// Deal `indices` out in turn, each to the split furthest behind its share
+fn deal(indices: &[usize], ratios: &[f64; 3], splits: &mut [Split]) {
+    let shares = shares(ratios);
+    let mut dealt = [0usize; 3];
+    for (position, &index) in indices.iter().enumerate() {
+        let deficit = |k: usize| shares[k] * (position + 1) as f64 - dealt[k] as f64;
+        let k = (0..3)
+            .filter(|&k| shares[k] > 0.0)
+            .max_by(|&a, &b| deficit(a).total_cmp(&deficit(b)).then(b.cmp(&a)))
+            .unwrap_or(0);
+        dealt[k] += 1;
+        splits[index] = Split::ALL[k];
+    }
+}
+
+/// Seeded SplitMix64 generator, so splits are reproducible across runs and
+/// platforms
+struct SplitRng(u64);
+
+impl SplitRng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Fisher-Yates shuffle
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = (self.next() % (i as u64 + 1)) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_meta;
+    use lab_core::annotation::Object;
+    use lab_core::geometry::Point;
+
+    fn annotation(categories: &[i32]) -> Annotation {
+        let mut annotation = Annotation::new("test");
+        for (id, category) in categories.iter().enumerate() {
+            annotation.add_object(Object::new(
+                id as i32,
+                *category,
+                vec![
+                    Point::new(0.1, 0.1),
+                    Point::new(0.5, 0.1),
+                    Point::new(0.5, 0.5),
+                ],
+            ));
+        }
+        annotation
+    }
+
+    fn count(splits: &[Split], split: Split) -> usize {
+        splits.iter().filter(|s| **s == split).count()
+    }
+
+    #[test]
+    fn test_random_and_ordered_splits() {
+        let names: Vec<String> = (0..20).map(|i| format!("{:02}.jpg", i)).collect();
+        let empty = annotation(&[]);
+        let images: Vec<(&str, &Annotation)> =
+            names.iter().map(|name| (name.as_str(), &empty)).collect();
+
+        let config = SplitConfig {
+            seed: 7,
+            ..Default::default()
+        };
+        let splits = assign_splits(&images, &test_meta(), &config);
+        assert_eq!(
+            (count(&splits, Split::Train), count(&splits, Split::Val)),
+            (16, 2)
+        );
+
+        // The same seed gives the same split whatever the input order
+        let reversed: Vec<_> = images.iter().rev().copied().collect();
+        let mut again = assign_splits(&reversed, &test_meta(), &config);
+        again.reverse();
+        assert_eq!(again, splits);
+
+        let config = SplitConfig {
+            strategy: SplitStrategy::Ordered,
+            ratios: [3.0, 1.0, 0.0],
+            seed: 0,
+        };
+        let splits = assign_splits(&images, &test_meta(), &config);
+        assert!(splits[..15].iter().all(|s| *s == Split::Train));
+        assert!(splits[15..].iter().all(|s| *s == Split::Val));
+    }
+
+    #[test]
+    fn test_stratified_and_grouped_splits() {
+        // Category 2 is rare: it should still reach val and test
+        let common = annotation(&[1]);
+        let rare = annotation(&[1, 2]);
+        let names: Vec<String> = (0..30)
+            .map(|i| format!("cam{}_{:02}.jpg", i % 5, i))
+            .collect();
+        let images: Vec<(&str, &Annotation)> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.as_str(), if i < 10 { &rare } else { &common }))
+            .collect();
+
+        let config = SplitConfig {
+            strategy: SplitStrategy::Stratified,
+            ratios: [0.6, 0.2, 0.2],
+            seed: 3,
+        };
+        let splits = assign_splits(&images, &test_meta(), &config);
+        for split in Split::ALL {
+            let rare_count = splits[..10].iter().filter(|s| **s == split).count();
+            assert!(
+                rare_count >= 2,
+                "{:?} has {} rare images",
+                split,
+                rare_count
+            );
+        }
+        assert_eq!(count(&splits, Split::Train), 18);
+
+        let config = SplitConfig {
+            strategy: SplitStrategy::Grouped(GroupBy::Prefix('_')),
+            ratios: [0.6, 0.2, 0.2],
+            seed: 3,
+        };
+        let splits = assign_splits(&images, &test_meta(), &config);
+        let mut by_camera: BTreeMap<&str, BTreeSet<Split>> = BTreeMap::new();
+        for ((name, _), split) in images.iter().zip(&splits) {
+            by_camera.entry(&name[..4]).or_default().insert(*split);
+        }
+        assert!(by_camera.values().all(|splits| splits.len() == 1));
+        assert_eq!(Split::ALL.map(|split| count(&splits, split)), [18, 6, 6]);
+    }
+}
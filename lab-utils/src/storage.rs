@@ -0,0 +1,167 @@
+use crate::Project;
+use lab_core::{Result, StorageFormat};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+
+/// Outcome of converting the annotation files of a project
+#[derive(Debug, Clone, Default)]
+pub struct StorageConversionReport {
+    /// Annotation files rewritten (or to be rewritten) in the new format
+    pub converted: Vec<PathBuf>,
+
+    /// Number of files already in the new format
+    pub up_to_date: usize,
+
+    /// Files that could not be converted, with the reason
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+/// Switch the storage format of a project's annotation files
+///
+/// Every annotation is rewritten in `format` (migrating older schema
+/// versions on the way) and the file in the old format is removed; then
+/// `storage` in `meta.yaml` is updated. Files that fail stay as they are and
+/// are still found by [`Project::load_annotation`]. With `dry_run` nothing is
+/// written and the report lists what would change.
+pub fn convert_storage(
+    project: &mut Project,
+    format: StorageFormat,
+    dry_run: bool,
+) -> Result<StorageConversionReport> {
+    let mut report = StorageConversionReport::default();
+
+    let labels_dir = project.labels_dir();
+    let mut stems = BTreeSet::new();
+    if labels_dir.exists() {
+        for entry in fs::read_dir(&labels_dir)? {
+            let path = entry?.path();
+            if !path.is_file() || StorageFormat::from_path(&path).is_none() {
+                continue;
+            }
+            if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                stems.insert(stem.to_string());
+            }
+        }
+    }
+
+    let mut target = Project {
+        root: project.root.clone(),
+        meta: project.meta.clone(),
+    };
+    target.meta.storage = format;
+
+    for stem in stems {
+        // The file the project reads now, named like an image with that stem
+        let name = format!("{}.{}", stem, format.extension());
+        let Some(source) = project.existing_annotation_path(&name) else {
+            continue;
+        };
+        let destination = target.annotation_path(&name);
+        if source == destination {
+            report.up_to_date += 1;
+            if !dry_run {
+                target.remove_other_formats(&stem)?;
+            }
+            continue;
+        }
+
+        if dry_run {
+            report.converted.push(source);
+            continue;
+        }
+        let result = lab_core::io::load_annotation(&source)
+            .and_then(|annotation| target.save_annotation(&name, &annotation));
+        match result {
+            Ok(()) => report.converted.push(source),
+            Err(e) => report.failed.push((source, e.to_string())),
+        }
+    }
+
+    if !dry_run && project.meta.storage != format {
+        let meta_path = project.root.join("meta.yaml");
+        let content = fs::read_to_string(&meta_path)?;
+        lab_core::io::write_atomic(&meta_path, with_storage_line(&content, format).as_bytes())?;
+        project.meta.storage = format;
+    }
+    Ok(report)
+}
+
+/// Set the top-level `storage` key of meta.yaml, keeping everything else as written
+fn with_storage_line(content: &str, format: StorageFormat) -> String {
+    let line = format!("storage: {}", format.as_str());
+    let mut found = false;
+    let mut lines: Vec<String> = content
+        .lines()
+        .map(|l| {
+            if l.starts_with("storage:") {
+                found = true;
+                line.clone()
+            } else {
+                l.to_string()
+            }
+        })
+        .collect();
+    if !found {
+        lines.push(line);
+    }
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_meta;
+    use lab_core::Annotation;
+
+    #[test]
+    fn test_convert_storage() {
+        let root = std::env::temp_dir().join("jlab_test_convert_storage");
+        let _ = fs::remove_dir_all(&root);
+        let mut project = Project {
+            root: root.clone(),
+            meta: test_meta(),
+        };
+        fs::create_dir_all(&root).unwrap();
+        lab_core::io::save_meta(root.join("meta.yaml"), &project.meta).unwrap();
+        fs::write(
+            root.join("meta.yaml"),
+            "# project\n".to_string() + &fs::read_to_string(root.join("meta.yaml")).unwrap(),
+        )
+        .unwrap();
+        let mut annotation = Annotation::new("test");
+        annotation.user_agent = "a".to_string();
+        project.save_annotation("a.jpg", &annotation).unwrap();
+        project
+            .save_annotation("b.c.png", &Annotation::new("test"))
+            .unwrap();
+
+        let report = convert_storage(&mut project, StorageFormat::Binary, true).unwrap();
+        assert_eq!(report.converted.len(), 2);
+        assert_eq!(project.meta.storage, StorageFormat::Yaml);
+        assert!(project.labels_dir().join("a.yaml").exists());
+
+        let report = convert_storage(&mut project, StorageFormat::Binary, false).unwrap();
+        assert_eq!(report.converted.len(), 2);
+        assert!(report.failed.is_empty());
+        assert!(!project.labels_dir().join("a.yaml").exists());
+        assert!(project.labels_dir().join("b.c.msgpack").exists());
+        let loaded = project.load_annotation("a.jpg").unwrap().unwrap();
+        assert_eq!(loaded.user_agent, "a");
+
+        let reopened = Project::open(&root).unwrap();
+        assert_eq!(reopened.meta.storage, StorageFormat::Binary);
+        let meta = fs::read_to_string(root.join("meta.yaml")).unwrap();
+        assert!(meta.starts_with("# project\n"));
+
+        let report = convert_storage(&mut project, StorageFormat::Binary, false).unwrap();
+        assert_eq!(report.up_to_date, 2);
+        let report = convert_storage(&mut project, StorageFormat::Yaml, false).unwrap();
+        assert_eq!(report.converted.len(), 2);
+        assert!(!fs::read_to_string(root.join("meta.yaml"))
+            .unwrap()
+            .contains("storage: binary"));
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
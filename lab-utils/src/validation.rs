@@ -1,4 +1,6 @@
-use lab_core::{Annotation, Meta};
+use lab_core::geometry::has_self_intersections;
+use lab_core::meta::CategoryProperty;
+use lab_core::{Annotation, Meta, PropertyValueWithConfidence};
 
 /// Validation result
 #[derive(Debug, Clone)]
@@ -45,17 +47,36 @@ pub fn validate_annotation(annotation: &Annotation, meta: &Meta) -> ValidationRe
             ));
         }
 
-        // Validate polygon has at least 3 points
-        if obj.polygon.len() < 3 {
+        // Validate shape geometry
+        if matches!(obj.shape, lab_core::Shape::Mask(_)) {
+            if !obj.shape.is_valid() {
+                result.add_error(format!("Object {} has an empty mask", idx));
+            }
+        } else if !obj.shape.is_valid() {
+            let kind = obj.shape.kind();
             result.add_error(format!(
-                "Object {} has invalid polygon with {} points (minimum 3 required)",
+                "Object {} has invalid {} with {} points (minimum {} required)",
                 idx,
-                obj.polygon.len()
+                kind.as_str(),
+                obj.shape.vertices().len(),
+                kind.min_points()
             ));
         }
 
+        if matches!(
+            obj.shape,
+            lab_core::Shape::Polygon { .. } | lab_core::Shape::MultiPolygon { .. }
+        ) && obj
+            .shape
+            .rings()
+            .iter()
+            .any(|ring| has_self_intersections(ring))
+        {
+            result.add_warning(format!("Object {} has a self-intersecting outline", idx));
+        }
+
         // Validate coordinates are normalized (0.0 to 1.0)
-        for (point_idx, point) in obj.polygon.iter().enumerate() {
+        for (point_idx, point) in obj.shape.vertices().iter().enumerate() {
             if point.x < 0.0 || point.x > 1.0 {
                 result.add_error(format!(
                     "Object {} point {} has invalid x coordinate: {} (must be 0.0-1.0)",
@@ -70,73 +91,205 @@ pub fn validate_annotation(annotation: &Annotation, meta: &Meta) -> ValidationRe
             }
         }
 
+        // Validate keypoints against the category definition
+        if let Some(category) = meta.find_category(obj.category) {
+            if obj.keypoints.len() > category.keypoints.len() {
+                result.add_error(format!(
+                    "Object {} has {} keypoints but category {} defines {}",
+                    idx,
+                    obj.keypoints.len(),
+                    category.name,
+                    category.keypoints.len()
+                ));
+            }
+        }
+        for (kp_idx, keypoint) in obj.keypoints.iter().enumerate() {
+            if keypoint.visibility.is_labeled()
+                && !((0.0..=1.0).contains(&keypoint.x) && (0.0..=1.0).contains(&keypoint.y))
+            {
+                result.add_error(format!(
+                    "Object {} keypoint {} is outside the image: ({}, {})",
+                    idx, kp_idx, keypoint.x, keypoint.y
+                ));
+            }
+        }
+
         // Validate properties
         if let Some(category) = meta.find_category(obj.category) {
             for prop in &category.properties {
-                let prop_key = prop.id.to_string();
-
-                if let Some(values) = obj.properties.get(&prop_key) {
-                    // Find the property type
-                    if let Some(prop_type) = meta
-                        .property_types
-                        .iter()
-                        .find(|pt| pt.name == prop.property_type)
-                    {
-                        for value in values {
-                            // Check if value exists in property type
-                            if prop_type.find_value(value.value).is_none()
-                                && !meta
-                                    .property_special_values
-                                    .iter()
-                                    .any(|sv| sv.id == value.value)
-                            {
-                                result.add_error(format!(
-                                    "Object {} has invalid property value: property={}, value={}",
-                                    idx, prop.id, value.value
-                                ));
-                            }
-                        }
+                if let Some(values) = obj.properties.get(&prop.id.to_string()) {
+                    for error in property_errors(meta, prop, values) {
+                        result.add_error(format!("Object {} {}", idx, error));
                     }
                 }
             }
         }
     }
 
+    // Validate image-level properties
+    let mut image_properties: Vec<_> = annotation.properties.iter().collect();
+    image_properties.sort_by(|a, b| a.0.cmp(b.0));
+    for (key, values) in image_properties {
+        let Some(prop) = key
+            .parse::<i32>()
+            .ok()
+            .and_then(|id| meta.find_image_property(id))
+        else {
+            result.add_error(format!("Image has undefined property: {}", key));
+            continue;
+        };
+        for error in property_errors(meta, prop, values) {
+            result.add_error(format!("Image {}", error));
+        }
+    }
+
+    result
+}
+
+/// Check the image size and hash recorded in an annotation against the image file
+///
+/// `content` is the image file as it is on disk and `size` its decoded size,
+/// if the caller could decode it. Annotations without recorded image info
+/// always pass.
+pub fn validate_image(
+    annotation: &Annotation,
+    content: &[u8],
+    size: Option<(u32, u32)>,
+) -> ValidationResult {
+    let mut result = ValidationResult::new();
+    let Some(info) = &annotation.image else {
+        return result;
+    };
+
+    if let Some((width, height)) = size {
+        if (width, height) != (info.width, info.height) {
+            result.add_error(format!(
+                "Image is {}x{} but was annotated at {}x{}",
+                width, height, info.width, info.height
+            ));
+        }
+    }
+    if !info.matches_content(content) {
+        result.add_warning("Image file changed since it was annotated".to_string());
+    }
+
     result
 }
 
+/// Problems with the values stored for one property, phrased after the subject
+///
+/// Checks the cardinality declared by the property, duplicates, special
+/// values mixed with regular ones, confidence range and each value itself.
+fn property_errors(
+    meta: &Meta,
+    prop: &CategoryProperty,
+    values: &[PropertyValueWithConfidence],
+) -> Vec<String> {
+    let mut errors = Vec::new();
+    if !prop.multiple && values.len() > 1 {
+        errors.push(format!(
+            "has {} values for single-value property={}",
+            values.len(),
+            prop.id
+        ));
+    }
+    let is_special = |v: &PropertyValueWithConfidence| {
+        v.data.is_none() && meta.find_special_value(v.value).is_some()
+    };
+    if values.len() > 1 && values.iter().any(is_special) {
+        errors.push(format!(
+            "mixes a special value with other values: property={}",
+            prop.id
+        ));
+    }
+
+    for (index, value) in values.iter().enumerate() {
+        if values[..index]
+            .iter()
+            .any(|v| v.value == value.value && v.data == value.data)
+        {
+            errors.push(format!(
+                "has duplicate property value: property={}",
+                prop.id
+            ));
+        }
+        if !(0.0..=1.0).contains(&value.confidence) {
+            errors.push(format!(
+                "has invalid property confidence: property={}, confidence={} (must be 0.0-1.0)",
+                prop.id, value.confidence
+            ));
+        }
+        if let Some(reason) = property_value_error(meta, prop, value) {
+            errors.push(format!(
+                "has invalid property value: property={}, {}",
+                prop.id, reason
+            ));
+        }
+    }
+    errors
+}
+
+/// Why a stored value does not fit its property type, if it does not
+///
+/// Enumerated properties take a value ID, typed properties take typed data;
+/// both accept special values. Properties whose type is missing from the
+/// metadata are not checked.
+fn property_value_error(
+    meta: &Meta,
+    prop: &CategoryProperty,
+    value: &PropertyValueWithConfidence,
+) -> Option<String> {
+    let prop_type = meta.find_property_type_by_name(&prop.property_type)?;
+    match &value.data {
+        Some(data) => prop_type.check_data(data).err().map(|e| match e {
+            lab_core::Error::InvalidData(reason) => reason,
+            e => e.to_string(),
+        }),
+        None if meta.find_special_value(value.value).is_some() => None,
+        None if prop_type.kind.is_enum() && prop_type.find_value(value.value).is_some() => None,
+        None if prop_type.kind.is_enum() => Some(format!("value={}", value.value)),
+        None => Some(format!("missing {} value", prop_type.kind.as_str())),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_meta;
     use lab_core::annotation::Object;
     use lab_core::geometry::Point;
 
     #[test]
     fn test_validate_empty_annotation() {
         let annotation = Annotation::new("test");
-        let meta = lab_core::Meta {
-            id: 1,
-            name: "test".to_string(),
-            description: "test".to_string(),
-            shape: lab_core::meta::ShapeConfig {
-                title_style: 1,
-                thickness: 2,
-                auto_save: true,
-                vertex_radius: 10.0,
-            },
-            roi: lab_core::meta::RoiConfig {
-                color: "#800080".to_string(),
-            },
-            categories: vec![],
-            property_types: vec![],
-            property_special_values: vec![],
-        };
+        let meta = test_meta();
 
         let result = validate_annotation(&annotation, &meta);
         assert!(result.is_valid);
         assert_eq!(result.warnings.len(), 1);
     }
 
+    #[test]
+    fn test_validate_image() {
+        let mut annotation = Annotation::new("test");
+        let result = validate_image(&annotation, b"pixels", Some((640, 480)));
+        assert!(result.is_valid && result.warnings.is_empty());
+
+        annotation.image = Some(lab_core::ImageInfo::new(640, 480, b"pixels"));
+        let result = validate_image(&annotation, b"pixels", Some((640, 480)));
+        assert!(result.is_valid && result.warnings.is_empty());
+
+        let result = validate_image(&annotation, b"other pixels", None);
+        assert!(result.is_valid);
+        assert_eq!(result.warnings.len(), 1);
+
+        let result = validate_image(&annotation, b"other pixels", Some((480, 640)));
+        assert_eq!(
+            result.errors,
+            vec!["Image is 480x640 but was annotated at 640x480".to_string()]
+        );
+    }
+
     #[test]
     fn test_validate_invalid_category() {
         let mut annotation = Annotation::new("test");
@@ -151,26 +304,171 @@ mod tests {
         );
         annotation.add_object(obj);
 
+        let meta = test_meta();
+
+        let result = validate_annotation(&annotation, &meta);
+        assert!(!result.is_valid);
+        assert!(!result.errors.is_empty());
+        assert!(result.warnings.is_empty());
+
+        // A bow tie is flagged, but still usable
+        annotation.add_object(Object::new(
+            1,
+            999,
+            vec![
+                Point::new(0.0, 0.0),
+                Point::new(1.0, 1.0),
+                Point::new(1.0, 0.0),
+                Point::new(0.0, 1.0),
+            ],
+        ));
+        let result = validate_annotation(&annotation, &meta);
+        assert_eq!(
+            result.warnings,
+            vec!["Object 1 has a self-intersecting outline".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_validate_image_properties() {
         let meta = lab_core::Meta {
-            id: 1,
-            name: "test".to_string(),
-            description: "test".to_string(),
-            shape: lab_core::meta::ShapeConfig {
-                title_style: 1,
-                thickness: 2,
-                auto_save: true,
-                vertex_radius: 10.0,
-            },
-            roi: lab_core::meta::RoiConfig {
-                color: "#800080".to_string(),
-            },
-            categories: vec![],
-            property_types: vec![],
+            property_types: vec![lab_core::PropertyType {
+                id: 0,
+                name: "weather".to_string(),
+                description: "Weather".to_string(),
+                kind: lab_core::PropertyKind::Enum,
+                values: vec![lab_core::PropertyValue {
+                    id: 0,
+                    name: "sunny".to_string(),
+                    description: "Sunny".to_string(),
+                    hotkey: "1".to_string(),
+                    color: "YELLOW".parse().unwrap(),
+                    sign: "S".to_string(),
+                }],
+                pattern: None,
+                min: None,
+                max: None,
+            }],
             property_special_values: vec![],
+            image_properties: vec![CategoryProperty {
+                id: 0,
+                name: "weather".to_string(),
+                property_type: "weather".to_string(),
+                multiple: false,
+            }],
+            ..test_meta()
         };
 
+        let mut annotation = Annotation::new("test");
+        annotation.set_property(0, 0, 1.0);
+        assert!(validate_annotation(&annotation, &meta).is_valid);
+
+        annotation.set_property(0, 7, 1.0);
+        annotation.set_property(3, 0, 1.0);
         let result = validate_annotation(&annotation, &meta);
         assert!(!result.is_valid);
-        assert!(!result.errors.is_empty());
+        assert_eq!(result.errors.len(), 2);
+    }
+
+    #[test]
+    fn test_validate_typed_properties() {
+        let mut meta = lab_core::Meta {
+            property_types: vec![lab_core::PropertyType {
+                id: 0,
+                name: "age".to_string(),
+                description: "Age".to_string(),
+                kind: lab_core::PropertyKind::Integer,
+                values: vec![],
+                pattern: None,
+                min: Some(0.0),
+                max: Some(120.0),
+            }],
+            property_special_values: vec![lab_core::PropertySpecialValue {
+                id: -1,
+                name: "pending".to_string(),
+                description: "Pending".to_string(),
+                hotkey: "q".to_string(),
+                color: "WHITE".parse().unwrap(),
+                sign: "P".to_string(),
+            }],
+            ..test_meta()
+        };
+        meta.categories[0].properties = vec![CategoryProperty {
+            id: 0,
+            name: "age".to_string(),
+            property_type: "age".to_string(),
+            multiple: false,
+        }];
+
+        let mut annotation = Annotation::new("test");
+        let shape = vec![
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 0.0),
+            Point::new(0.5, 1.0),
+        ];
+        let mut valid = Object::new(0, 0, shape.clone());
+        valid.set_property_data(0, lab_core::PropertyData::Int(30), 1.0);
+        let mut pending = Object::new(1, 0, shape.clone());
+        pending.set_property(0, -1, 1.0);
+        annotation.add_object(valid);
+        annotation.add_object(pending);
+        assert!(validate_annotation(&annotation, &meta).is_valid);
+
+        let mut out_of_range = Object::new(2, 0, shape.clone());
+        out_of_range.set_property_data(0, lab_core::PropertyData::Int(200), 1.0);
+        let mut missing = Object::new(3, 0, shape);
+        missing.set_property(0, 5, 1.0);
+        annotation.add_object(out_of_range);
+        annotation.add_object(missing);
+        let result = validate_annotation(&annotation, &meta);
+        assert_eq!(result.errors.len(), 2);
+    }
+
+    #[test]
+    fn test_validate_property_cardinality() {
+        let role = |multiple| CategoryProperty {
+            id: 0,
+            name: "role".to_string(),
+            property_type: "role".to_string(),
+            multiple,
+        };
+        let mut meta = lab_core::Meta {
+            property_types: vec![lab_core::PropertyType {
+                id: 0,
+                name: "role".to_string(),
+                description: "Role".to_string(),
+                kind: lab_core::PropertyKind::Enum,
+                values: (0..2)
+                    .map(|id| lab_core::PropertyValue {
+                        id,
+                        name: format!("role{}", id),
+                        description: String::new(),
+                        hotkey: String::new(),
+                        color: "RED".parse().unwrap(),
+                        sign: String::new(),
+                    })
+                    .collect(),
+                pattern: None,
+                min: None,
+                max: None,
+            }],
+            property_special_values: vec![],
+            image_properties: vec![role(true)],
+            ..test_meta()
+        };
+
+        let mut annotation = Annotation::new("test");
+        annotation.toggle_property_value(0, 0, 1.0);
+        annotation.toggle_property_value(0, 1, 0.5);
+        assert!(validate_annotation(&annotation, &meta).is_valid);
+
+        annotation.properties.get_mut("0").unwrap()[1].confidence = 1.5;
+        assert_eq!(validate_annotation(&annotation, &meta).errors.len(), 1);
+
+        meta.image_properties = vec![role(false)];
+        annotation.properties.get_mut("0").unwrap()[1].confidence = 0.5;
+        let result = validate_annotation(&annotation, &meta);
+        assert_eq!(result.errors.len(), 1);
+        assert!(result.errors[0].contains("single-value"));
     }
 }